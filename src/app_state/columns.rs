@@ -0,0 +1,205 @@
+// Keyboard navigation of the column view. `Workspace::column_path` holds the
+// entry highlighted in each open column, starting with the current
+// directory; a highlighted folder opens the column after it.
+use crate::fs_ops::provider::FileEntry;
+use std::sync::Arc;
+
+/// What an arrow key does in the column view
+#[derive(Clone, Debug)]
+pub enum ColumnStep {
+    /// Close the last column; its folder stays highlighted in the parent
+    Back,
+    /// Highlight the entry in the column, closing the columns after it
+    Select(usize, Box<FileEntry>),
+    /// Nothing to move to
+    Stay,
+}
+
+/// `dy` moves within the last column, `dx` moves to the parent column or into
+/// the highlighted folder. `items` gives a column's entries, None while it is
+/// still loading.
+pub fn step(
+    path: &[FileEntry],
+    items: impl Fn(usize) -> Option<Arc<Vec<FileEntry>>>,
+    dx: i32,
+    dy: i32,
+) -> ColumnStep {
+    let active = path.len().saturating_sub(1);
+    if dx < 0 {
+        if path.len() > 1 {
+            ColumnStep::Back
+        } else {
+            ColumnStep::Stay
+        }
+    } else if dx > 0 {
+        let next = path.len();
+        match path.last() {
+            Some(folder) if folder.is_dir => {
+                match items(next).and_then(|items| items.first().cloned()) {
+                    Some(entry) => ColumnStep::Select(next, Box::new(entry)),
+                    None => ColumnStep::Stay,
+                }
+            }
+            Some(_) => ColumnStep::Stay,
+            // Nothing highlighted yet: start at the top
+            None => step(path, items, 0, 1),
+        }
+    } else {
+        let Some(items) = items(active).filter(|items| !items.is_empty()) else {
+            return ColumnStep::Stay;
+        };
+        let index = path
+            .get(active)
+            .and_then(|entry| items.iter().position(|i| i.path == entry.path));
+        let next = match index {
+            Some(index) => (index as i32 + dy).clamp(0, items.len() as i32 - 1) as usize,
+            None => 0,
+        };
+        ColumnStep::Select(active, Box::new(items[next].clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs_ops::scanner::ScannedFile;
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+    use std::time::SystemTime;
+
+    fn entry(path: &str, is_dir: bool) -> FileEntry {
+        FileEntry::from_scanned(ScannedFile {
+            path: PathBuf::from(path),
+            is_dir,
+            size: 0,
+            modified: SystemTime::UNIX_EPOCH,
+            is_ignored: false,
+            tags: Vec::new(),
+            attributes: Default::default(),
+        })
+    }
+
+    /// /a holds folder b and file c, /a/b holds d and e, and /f is a file
+    struct Columns {
+        path: Vec<FileEntry>,
+        listings: HashMap<PathBuf, Arc<Vec<FileEntry>>>,
+    }
+
+    impl Columns {
+        fn new() -> Self {
+            let listing = |entries: &[(&str, bool)]| {
+                Arc::new(entries.iter().map(|&(p, d)| entry(p, d)).collect())
+            };
+            let mut listings = HashMap::new();
+            listings.insert(PathBuf::from("/"), listing(&[("/a", true), ("/f", false)]));
+            listings.insert(
+                PathBuf::from("/a"),
+                listing(&[("/a/b", true), ("/a/c", false)]),
+            );
+            listings.insert(
+                PathBuf::from("/a/b"),
+                listing(&[("/a/b/d", false), ("/a/b/e", false)]),
+            );
+            Self {
+                path: Vec::new(),
+                listings,
+            }
+        }
+
+        // Like `Workspace::move_in_columns`
+        fn press(&mut self, dx: i32, dy: i32) {
+            let items = |column: usize| {
+                let folder = match column {
+                    0 => Path::new("/"),
+                    _ => self.path.get(column - 1)?.path.as_path(),
+                };
+                self.listings.get(folder).cloned()
+            };
+            match step(&self.path, items, dx, dy) {
+                ColumnStep::Back => {
+                    self.path.pop();
+                }
+                ColumnStep::Select(column, entry) => {
+                    self.path.truncate(column);
+                    self.path.push(*entry);
+                }
+                ColumnStep::Stay => {}
+            }
+        }
+
+        fn highlighted(&self) -> Vec<&str> {
+            self.path
+                .iter()
+                .map(|entry| entry.path.to_str().unwrap())
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_move_down_and_up() {
+        let mut columns = Columns::new();
+        // Nothing highlighted: down starts at the top
+        columns.press(0, 1);
+        assert_eq!(columns.highlighted(), ["/a"]);
+        columns.press(0, 1);
+        assert_eq!(columns.highlighted(), ["/f"]);
+        // Stops at either end
+        columns.press(0, 1);
+        assert_eq!(columns.highlighted(), ["/f"]);
+        columns.press(0, -5);
+        assert_eq!(columns.highlighted(), ["/a"]);
+    }
+
+    #[test]
+    fn test_move_right_and_left() {
+        let mut columns = Columns::new();
+        // Nothing highlighted: right also starts at the top
+        columns.press(1, 0);
+        assert_eq!(columns.highlighted(), ["/a"]);
+        columns.press(1, 0);
+        assert_eq!(columns.highlighted(), ["/a", "/a/b"]);
+        columns.press(1, 0);
+        assert_eq!(columns.highlighted(), ["/a", "/a/b", "/a/b/d"]);
+        // Into a file goes nowhere
+        columns.press(1, 0);
+        assert_eq!(columns.highlighted(), ["/a", "/a/b", "/a/b/d"]);
+
+        // Up and down move within the last column only
+        columns.press(0, 1);
+        assert_eq!(columns.highlighted(), ["/a", "/a/b", "/a/b/e"]);
+
+        // Left closes the last column; the folders stay highlighted
+        columns.press(-1, 0);
+        assert_eq!(columns.highlighted(), ["/a", "/a/b"]);
+        columns.press(-1, 0);
+        assert_eq!(columns.highlighted(), ["/a"]);
+        // The first column stays open
+        columns.press(-1, 0);
+        assert_eq!(columns.highlighted(), ["/a"]);
+    }
+
+    #[test]
+    fn test_moving_closes_later_columns() {
+        let mut columns = Columns::new();
+        columns.press(1, 0);
+        columns.press(1, 0);
+        columns.press(1, 0);
+        columns.press(-1, 0);
+        assert_eq!(columns.highlighted(), ["/a", "/a/b"]);
+        // Moving to another entry of the column closes the ones after it
+        columns.press(0, 1);
+        assert_eq!(columns.highlighted(), ["/a", "/a/c"]);
+        columns.press(1, 0);
+        assert_eq!(columns.highlighted(), ["/a", "/a/c"]);
+    }
+
+    #[test]
+    fn test_loading_column() {
+        let mut columns = Columns::new();
+        columns.press(1, 0);
+        columns.listings.remove(Path::new("/a"));
+        // Its entries aren't in yet
+        columns.press(1, 0);
+        assert_eq!(columns.highlighted(), ["/a"]);
+    }
+}
@@ -1,9 +1,16 @@
 use gpui::{App, AsyncApp, Context, Global};
 use notify::{Config as NotifyConfig, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use crate::app_state::keybindings::KeyBindingValue;
+use crate::fs_ops::sort::SortKey;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
+
+/// How long a change to the recent folders waits before being saved, so
+/// browsing doesn't rewrite (and reload) config.toml on every step
+const RECENT_SAVE_DELAY: Duration = Duration::from_secs(10);
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Config {
@@ -21,6 +28,107 @@ pub struct Config {
     pub file_categories: HashMap<String, Vec<String>>,
     #[serde(default)]
     pub use_dms: bool,
+    #[serde(default)]
+    pub smart_folders: Vec<SmartFolder>,
+    #[serde(default)]
+    pub tags: Vec<TagDef>,
+    /// Keys per action name, replacing the defaults (see `keybindings`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub keybindings: BTreeMap<String, KeyBindingValue>,
+    /// Command palette entries last run, most recent first
+    #[serde(default, skip_serializing_if = "VecDeque::is_empty")]
+    pub recent_commands: VecDeque<String>,
+    #[serde(default)]
+    pub thumbnails: ThumbnailConfig,
+}
+
+/// Limits on the shared thumbnail cache, applied by the cleanup at startup.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ThumbnailConfig {
+    #[serde(default = "default_thumbnail_max_size_mb")]
+    pub max_size_mb: u64,
+    /// Thumbnails unused for this many days are removed; 0 keeps them
+    #[serde(default = "default_thumbnail_max_age_days")]
+    pub max_age_days: u64,
+}
+
+fn default_thumbnail_max_size_mb() -> u64 {
+    512
+}
+
+fn default_thumbnail_max_age_days() -> u64 {
+    180
+}
+
+impl Default for ThumbnailConfig {
+    fn default() -> Self {
+        Self {
+            max_size_mb: default_thumbnail_max_size_mb(),
+            max_age_days: default_thumbnail_max_age_days(),
+        }
+    }
+}
+
+impl ThumbnailConfig {
+    pub fn limits(&self) -> crate::assets::thumbnail_cleanup::CacheLimits {
+        crate::assets::thumbnail_cleanup::CacheLimits {
+            max_bytes: self.max_size_mb * 1024 * 1024,
+            max_age: (self.max_age_days > 0)
+                .then(|| std::time::Duration::from_secs(self.max_age_days * 24 * 60 * 60)),
+        }
+    }
+}
+
+/// A user-defined tag. Files carry the name in their `user.xdg.tags` xattr;
+/// the color only lives here.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct TagDef {
+    pub name: String,
+    /// "#rrggbb"
+    pub color: String,
+}
+
+/// Colors handed out to new tags, in order
+pub const TAG_COLORS: [&str; 8] = [
+    "#e53935", "#fb8c00", "#fdd835", "#43a047", "#00acc1", "#1e88e5", "#8e24aa", "#6d4c41",
+];
+
+impl TagDef {
+    /// Color as 0xRRGGBB, grey if the config holds something unparsable
+    pub fn rgb(&self) -> u32 {
+        u32::from_str_radix(self.color.trim_start_matches('#'), 16).unwrap_or(0x9e9e9e)
+    }
+}
+
+/// A saved search shown as a virtual folder in the sidebar and dashboard.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct SmartFolder {
+    pub name: String,
+    pub root: PathBuf,
+    pub query: String,
+    #[serde(default)]
+    pub options: crate::fs_ops::scanner::SearchOptions,
+}
+
+impl SmartFolder {
+    /// Recursive search for everything carrying `tag` below `root`.
+    pub fn for_tag(tag: &str, root: PathBuf) -> Self {
+        let query = if tag.contains(char::is_whitespace) {
+            format!("tag:\"{}\"", tag)
+        } else {
+            format!("tag:{}", tag)
+        };
+        Self {
+            name: format!("Tag: {}", tag),
+            root,
+            query,
+            options: crate::fs_ops::scanner::SearchOptions {
+                recursive: true,
+                content_search: false,
+                respect_ignore: false,
+            },
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Default)]
@@ -35,12 +143,123 @@ pub struct UiConfig {
     pub icon_size: u32,
     #[serde(default)]
     pub filled_icons: bool,
+    /// How entries excluded by .gitignore/.ignore are shown: "show", "dim" or "hide".
+    #[serde(default = "default_ignored_files")]
+    pub ignored_files: String,
+    /// Sort order of directories without their own view settings
+    #[serde(default)]
+    pub sort: crate::fs_ops::sort::SortOptions,
+    /// Columns of the details view, in display order
+    #[serde(default = "default_details_columns")]
+    pub details_columns: Vec<DetailsColumn>,
+}
+
+/// A details view column: what it shows (and sorts by) and its width in pixels.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub struct DetailsColumn {
+    pub key: SortKey,
+    pub width: f32,
+}
+
+impl DetailsColumn {
+    pub const MIN_WIDTH: f32 = 48.0;
+    pub const MAX_WIDTH: f32 = 800.0;
+
+    /// Every column the details view can show, in the order offered
+    pub const AVAILABLE: [SortKey; 13] = [
+        SortKey::Name,
+        SortKey::Size,
+        SortKey::Modified,
+        SortKey::Created,
+        SortKey::Accessed,
+        SortKey::Type,
+        SortKey::Permissions,
+        SortKey::Owner,
+        SortKey::Group,
+        SortKey::LinkTarget,
+        SortKey::ItemCount,
+        SortKey::Duration,
+        SortKey::Artist,
+    ];
+
+    pub fn new(key: SortKey) -> Self {
+        let width = match key {
+            SortKey::Name => 280.0,
+            SortKey::Modified | SortKey::Created | SortKey::Accessed => 140.0,
+            SortKey::Type | SortKey::LinkTarget | SortKey::Artist => 160.0,
+            SortKey::Permissions => 110.0,
+            _ => 90.0,
+        };
+        Self { key, width }
+    }
+}
+
+pub fn default_details_columns() -> Vec<DetailsColumn> {
+    vec![
+        DetailsColumn::new(SortKey::Name),
+        DetailsColumn::new(SortKey::Size),
+        DetailsColumn::new(SortKey::Modified),
+        DetailsColumn::new(SortKey::Type),
+    ]
+}
+
+impl UiConfig {
+    pub fn details_columns(&self) -> Vec<DetailsColumn> {
+        if self.details_columns.is_empty() {
+            default_details_columns()
+        } else {
+            self.details_columns.clone()
+        }
+    }
+
+    fn details_columns_mut(&mut self) -> &mut Vec<DetailsColumn> {
+        if self.details_columns.is_empty() {
+            self.details_columns = default_details_columns();
+        }
+        &mut self.details_columns
+    }
+
+    /// Show or hide a column. Name can't be hidden.
+    pub fn toggle_details_column(&mut self, key: SortKey) {
+        if key == SortKey::Name {
+            return;
+        }
+        let columns = self.details_columns_mut();
+        if let Some(index) = columns.iter().position(|c| c.key == key) {
+            columns.remove(index);
+        } else {
+            columns.push(DetailsColumn::new(key));
+        }
+    }
+
+    pub fn move_details_column(&mut self, from: usize, to: usize) {
+        let columns = self.details_columns_mut();
+        if from < columns.len() && to < columns.len() && from != to {
+            let column = columns.remove(from);
+            columns.insert(to, column);
+        }
+    }
+
+    pub fn resize_details_column(&mut self, index: usize, width: f32) {
+        if let Some(column) = self.details_columns_mut().get_mut(index) {
+            column.width = width.clamp(DetailsColumn::MIN_WIDTH, DetailsColumn::MAX_WIDTH);
+        }
+    }
+
+    /// Whether listings need to compute the ignored flag at all.
+    pub fn mark_ignored(&self) -> bool {
+        matches!(self.ignored_files.as_str(), "dim" | "hide")
+    }
 }
 
 fn default_view_mode() -> String {
     "grid".to_string()
 }
 
+fn default_ignored_files() -> String {
+    "show".to_string()
+}
+
 fn default_icon_size() -> u32 {
     64
 }
@@ -169,6 +388,11 @@ impl Default for Config {
             group_files_by_type: false,
             file_categories: default_file_categories(),
             use_dms: false,
+            smart_folders: Vec::new(),
+            tags: Vec::new(),
+            keybindings: BTreeMap::new(),
+            recent_commands: VecDeque::new(),
+            thumbnails: ThumbnailConfig::default(),
         }
     }
 }
@@ -214,6 +438,111 @@ impl Config {
         false
     }
 
+    /// Move `path` to the front of the recent folders (max 20 folders).
+    /// Returns false if it already was there.
+    pub fn add_recent_folder(&mut self, path: PathBuf) -> bool {
+        if self.recent_folders.front() == Some(&path) {
+            return false;
+        }
+        self.recent_folders.retain(|p| *p != path);
+        self.recent_folders.push_front(path);
+        self.recent_folders.truncate(20);
+        true
+    }
+
+    /// Move a command palette entry to the front of the recent commands
+    /// (max 20 commands)
+    pub fn record_command(&mut self, id: &str) {
+        self.recent_commands.retain(|c| c != id);
+        self.recent_commands.push_front(id.to_string());
+        self.recent_commands.truncate(20);
+    }
+
+    /// Add or replace a smart folder (matched by name)
+    pub fn add_smart_folder(&mut self, folder: SmartFolder) {
+        if let Some(existing) = self
+            .smart_folders
+            .iter_mut()
+            .find(|f| f.name == folder.name)
+        {
+            *existing = folder;
+        } else {
+            self.smart_folders.push(folder);
+        }
+    }
+
+    /// Why `name` can't be given to a smart folder. `renaming` is the
+    /// current name of the folder being renamed, which it may keep.
+    pub fn smart_folder_name_error(&self, name: &str, renaming: Option<&str>) -> Option<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Some("The name can't be empty".to_string());
+        }
+        let taken = self
+            .smart_folders
+            .iter()
+            .any(|f| f.name == name && Some(f.name.as_str()) != renaming);
+        taken.then(|| format!("There is already a smart folder named \"{}\"", name))
+    }
+
+    /// Give a smart folder a new name, keeping its place in the list.
+    /// Returns false if there is none by that name or the new one is taken.
+    pub fn rename_smart_folder(&mut self, name: &str, new_name: &str) -> bool {
+        if self.smart_folder_name_error(new_name, Some(name)).is_some() {
+            return false;
+        }
+        match self.smart_folders.iter_mut().find(|f| f.name == name) {
+            Some(folder) => {
+                folder.name = new_name.trim().to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove a smart folder by name
+    pub fn remove_smart_folder(&mut self, name: &str) -> bool {
+        let before = self.smart_folders.len();
+        self.smart_folders.retain(|f| f.name != name);
+        self.smart_folders.len() != before
+    }
+
+    /// Define a new tag with the next preset color. Returns false if it exists.
+    pub fn add_tag(&mut self, name: &str) -> bool {
+        let name = name.trim();
+        if name.is_empty() || name.contains(',') || self.tag(name).is_some() {
+            return false;
+        }
+        let color = TAG_COLORS[self.tags.len() % TAG_COLORS.len()];
+        self.tags.push(TagDef {
+            name: name.to_string(),
+            color: color.to_string(),
+        });
+        true
+    }
+
+    /// Remove a tag definition. Files keep the xattr; the tag just shows uncolored.
+    pub fn remove_tag(&mut self, name: &str) -> bool {
+        let before = self.tags.len();
+        self.tags.retain(|t| t.name != name);
+        self.tags.len() != before
+    }
+
+    /// Switch a tag to the next preset color
+    pub fn cycle_tag_color(&mut self, name: &str) {
+        if let Some(tag) = self.tags.iter_mut().find(|t| t.name == name) {
+            let next = TAG_COLORS
+                .iter()
+                .position(|c| c.eq_ignore_ascii_case(&tag.color))
+                .map_or(0, |i| (i + 1) % TAG_COLORS.len());
+            tag.color = TAG_COLORS[next].to_string();
+        }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&TagDef> {
+        self.tags.iter().find(|t| t.name == name)
+    }
+
     /// Save config to file
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(config_path) = ConfigManager::get_config_path() {
@@ -226,6 +555,11 @@ impl Config {
 
 pub struct ConfigManager {
     pub config: Config, // Public for trait access
+    /// Problems in [keybindings] found by the last reload, until a window
+    /// shows them
+    pub keybinding_problems: Vec<String>,
+    /// Recent folders changed since the config was last saved
+    recent_unsaved: bool,
     _watcher: Option<RecommendedWatcher>,
 }
 
@@ -262,10 +596,24 @@ impl ConfigManager {
                         let async_cx_clone = async_cx.clone();
                         cx.spawn(move |_: &mut AsyncApp| async move {
                             while let Ok(_) = rx.recv_async().await {
-                                async_cx_clone.update_global::<ConfigManager, _>(|manager, cx| {
-                                    let new_config = Self::load_config();
-                                    if manager.config != new_config {
-                                        manager.config = new_config;
+                                let _ = async_cx_clone.update(|cx| {
+                                    let mut new_config = Self::load_config();
+                                    let manager = cx.global::<ConfigManager>();
+                                    // Recent folders not saved yet are newer than the file's
+                                    if manager.recent_unsaved {
+                                        new_config.recent_folders =
+                                            manager.config.recent_folders.clone();
+                                    }
+                                    let config = &manager.config;
+                                    if *config != new_config {
+                                        let rebind = config.keybindings != new_config.keybindings;
+                                        cx.global_mut::<ConfigManager>().config = new_config;
+                                        if rebind {
+                                            let problems =
+                                                crate::app_state::keybindings::apply(cx);
+                                            cx.global_mut::<ConfigManager>()
+                                                .keybinding_problems = problems;
+                                        }
                                         cx.refresh_windows();
                                     }
                                 });
@@ -280,8 +628,41 @@ impl ConfigManager {
 
         cx.set_global(ConfigManager {
             config,
+            keybinding_problems: Vec::new(),
+            recent_unsaved: false,
             _watcher: watcher,
         });
+        cx.on_app_quit(|cx| {
+            Self::save_recent_folders(cx);
+            async {}
+        })
+        .detach();
+    }
+
+    /// Moves `path` to the front of the recent folders, which are saved a
+    /// little later along with any other changes made meanwhile
+    pub fn add_recent_folder(path: PathBuf, cx: &mut App) {
+        let manager = cx.global_mut::<ConfigManager>();
+        if !manager.config.add_recent_folder(path) || manager.recent_unsaved {
+            return;
+        }
+        manager.recent_unsaved = true;
+        let executor = cx.background_executor().clone();
+        cx.spawn(move |cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                executor.timer(RECENT_SAVE_DELAY).await;
+                let _ = cx.update(Self::save_recent_folders);
+            }
+        })
+        .detach();
+    }
+
+    fn save_recent_folders(cx: &mut App) {
+        let manager = cx.global_mut::<ConfigManager>();
+        if std::mem::take(&mut manager.recent_unsaved) {
+            manager.save_config();
+        }
     }
 
     fn get_config_path() -> Option<PathBuf> {
@@ -392,6 +773,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_recent_folders() {
+        let mut config = Config::default();
+        for i in 0..25 {
+            assert!(config.add_recent_folder(PathBuf::from(format!("/path/{}", i))));
+        }
+        assert_eq!(config.recent_folders.len(), 20);
+        assert_eq!(config.recent_folders[0], PathBuf::from("/path/24"));
+
+        // Revisiting moves a folder to the front instead of duplicating it
+        assert!(config.add_recent_folder(PathBuf::from("/path/10")));
+        assert!(!config.add_recent_folder(PathBuf::from("/path/10")));
+        assert_eq!(config.recent_folders.len(), 20);
+        assert_eq!(config.recent_folders[0], PathBuf::from("/path/10"));
+        assert_eq!(config.recent_folders[1], PathBuf::from("/path/24"));
+    }
+
+    #[test]
+    fn test_record_command() {
+        let mut config = Config::default();
+        config.record_command("action:Reload");
+        config.record_command("action:NewTab");
+        config.record_command("action:Reload");
+        assert_eq!(
+            config.recent_commands,
+            VecDeque::from(["action:Reload".to_string(), "action:NewTab".to_string()])
+        );
+    }
+
     #[test]
     fn test_pinned_folders_limit() {
         let mut config = Config::default();
@@ -418,4 +828,157 @@ mod tests {
         assert_eq!(config.pinned_folders.len(), 10);
         assert_eq!(config.pinned_folders[9], PathBuf::from("/path/10")); // config didn't change order if duplicate
     }
+
+    #[test]
+    fn test_smart_folders_roundtrip() {
+        let mut config = Config::default();
+        config.add_smart_folder(SmartFolder {
+            name: "Work PDFs".to_string(),
+            root: PathBuf::from("/home/user/work"),
+            query: ".pdf".to_string(),
+            options: crate::fs_ops::scanner::SearchOptions::default(),
+        });
+
+        // Same name replaces instead of duplicating
+        config.add_smart_folder(SmartFolder {
+            name: "Work PDFs".to_string(),
+            root: PathBuf::from("/home/user/work"),
+            query: "report".to_string(),
+            options: crate::fs_ops::scanner::SearchOptions::default(),
+        });
+        assert_eq!(config.smart_folders.len(), 1);
+        assert_eq!(config.smart_folders[0].query, "report");
+
+        let toml_str = toml::to_string_pretty(&config).unwrap();
+        let parsed: Config = toml::from_str(&toml_str).unwrap();
+        assert_eq!(parsed.smart_folders, config.smart_folders);
+
+        // Options may be omitted in hand-written configs
+        let parsed: Config = toml::from_str(
+            r#"
+[[smart_folders]]
+name = "Logs"
+root = "/var/log"
+query = "log"
+"#,
+        )
+        .unwrap();
+        assert!(parsed.smart_folders[0].options.recursive);
+
+        // Renaming keeps the folder's place; names stay unique
+        config.add_smart_folder(SmartFolder {
+            name: "Logs".to_string(),
+            root: PathBuf::from("/var/log"),
+            query: "log".to_string(),
+            options: crate::fs_ops::scanner::SearchOptions::default(),
+        });
+        assert!(config.smart_folder_name_error("Logs", None).is_some());
+        assert!(config.smart_folder_name_error("Logs", Some("Logs")).is_none());
+        assert!(config.smart_folder_name_error("  ", None).is_some());
+        assert!(!config.rename_smart_folder("Work PDFs", "Logs"));
+        assert!(config.rename_smart_folder("Work PDFs", " Reports "));
+        assert_eq!(config.smart_folders[0].name, "Reports");
+        assert!(!config.rename_smart_folder("Work PDFs", "Other"));
+        assert!(config.remove_smart_folder("Logs"));
+
+        assert!(config.remove_smart_folder("Reports"));
+        assert!(!config.remove_smart_folder("Reports"));
+        assert!(config.smart_folders.is_empty());
+    }
+
+    #[test]
+    fn test_tag_definitions() {
+        let mut config = Config::default();
+        assert!(config.add_tag("work"));
+        assert!(config.add_tag(" urgent "));
+        assert!(!config.add_tag("work"), "Duplicates are rejected");
+        assert!(!config.add_tag("a,b"), "Commas would break the xattr format");
+        assert!(!config.add_tag("  "));
+
+        assert_eq!(config.tags[0].color, TAG_COLORS[0]);
+        assert_eq!(config.tags[1].name, "urgent");
+        assert_eq!(config.tag("work").unwrap().rgb(), 0xe53935);
+
+        config.cycle_tag_color("work");
+        assert_eq!(config.tag("work").unwrap().color, TAG_COLORS[1]);
+
+        let bad = TagDef {
+            name: "x".to_string(),
+            color: "nope".to_string(),
+        };
+        assert_eq!(bad.rgb(), 0x9e9e9e);
+
+        assert!(config.remove_tag("work"));
+        assert!(config.tag("work").is_none());
+    }
+
+    #[test]
+    fn test_sort_options_serde() {
+        use crate::fs_ops::sort::{SortKey, SortOptions};
+
+        let mut config = Config::default();
+        config.ui.sort = SortOptions {
+            key: SortKey::Modified,
+            descending: true,
+            folders_first: false,
+        };
+        let toml_str = toml::to_string_pretty(&config).unwrap();
+        let parsed: Config = toml::from_str(&toml_str).unwrap();
+        assert_eq!(parsed.ui.sort, config.ui.sort);
+
+        // Partial tables fall back to defaults per field
+        let parsed: Config = toml::from_str(
+            r#"
+[ui]
+view_mode = "list"
+
+[ui.sort]
+key = "size"
+"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.ui.sort.key, SortKey::Size);
+        assert!(parsed.ui.sort.folders_first);
+    }
+
+    #[test]
+    fn test_details_columns() {
+        let mut ui = UiConfig::default();
+        // An empty list (e.g. Default) means the default layout
+        assert_eq!(ui.details_columns(), default_details_columns());
+
+        ui.toggle_details_column(SortKey::Owner);
+        ui.toggle_details_column(SortKey::Name);
+        let keys: Vec<SortKey> = ui.details_columns().iter().map(|c| c.key).collect();
+        assert_eq!(
+            keys,
+            vec![
+                SortKey::Name,
+                SortKey::Size,
+                SortKey::Modified,
+                SortKey::Type,
+                SortKey::Owner
+            ]
+        );
+
+        ui.move_details_column(4, 1);
+        ui.move_details_column(0, 99);
+        assert_eq!(ui.details_columns()[1].key, SortKey::Owner);
+        assert_eq!(ui.details_columns()[0].key, SortKey::Name);
+
+        ui.resize_details_column(0, 10.0);
+        assert_eq!(ui.details_columns()[0].width, DetailsColumn::MIN_WIDTH);
+        ui.toggle_details_column(SortKey::Owner);
+        assert!(!ui.details_columns().iter().any(|c| c.key == SortKey::Owner));
+
+        let parsed: Config = toml::from_str(
+            r#"
+[[ui.details_columns]]
+key = "linktarget"
+width = 200.0
+"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.ui.details_columns()[0].key, SortKey::LinkTarget);
+    }
 }
@@ -0,0 +1,395 @@
+// Keyboard shortcuts. Every command is a gpui action with default keys;
+// `[keybindings]` in config.toml replaces an action's keys by name:
+//
+//     [keybindings]
+//     ToggleHidden = "ctrl-."
+//     GoToParent = ["backspace", "alt-up"]
+//     DeletePermanently = ""   # unbound
+//
+// Actions in the "FileList" context only fire while a file view has focus;
+// the others work anywhere in the window.
+use crate::app_state::config::ConfigManager;
+use gpui::{actions, App, KeyBinding, Keystroke};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Key context set by the file views
+pub const FILE_LIST_CONTEXT: &str = "FileList";
+const FILE_LIST: Option<&str> = Some(FILE_LIST_CONTEXT);
+
+/// Keys for one action in `[keybindings]`: a keystroke or a list of them.
+/// Empty strings unbind.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum KeyBindingValue {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyBindingValue {
+    pub fn keystrokes(&self) -> Vec<&str> {
+        match self {
+            Self::One(key) => vec![key.as_str()],
+            Self::Many(keys) => keys.iter().map(String::as_str).collect(),
+        }
+        .into_iter()
+        .filter(|key| !key.trim().is_empty())
+        .collect()
+    }
+}
+
+/// A bindable action as listed for users
+pub struct ActionDef {
+    /// Name used in `[keybindings]`
+    pub name: &'static str,
+    pub label: &'static str,
+    pub context: Option<&'static str>,
+    pub defaults: &'static [&'static str],
+}
+
+macro_rules! key_actions {
+    ($($name:ident, $label:expr, $context:expr, [$($key:expr),*];)*) => {
+        actions!(file_manager, [$($name),*]);
+
+        /// Every bindable action with its default keys
+        pub const ACTIONS: &[ActionDef] = &[$(ActionDef {
+            name: stringify!($name),
+            label: $label,
+            context: $context,
+            defaults: &[$($key),*],
+        }),*];
+
+        fn key_binding(name: &str, keystrokes: &str, context: Option<&str>) -> Option<KeyBinding> {
+            match name {
+                $(stringify!($name) => Some(KeyBinding::new(keystrokes, $name, context)),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+key_actions! {
+    MoveUp, "Move Up", FILE_LIST, ["up"];
+    MoveDown, "Move Down", FILE_LIST, ["down"];
+    MoveLeft, "Move Left", FILE_LIST, ["left"];
+    MoveRight, "Move Right", FILE_LIST, ["right"];
+    Open, "Open", FILE_LIST, ["enter"];
+    GoToParent, "Go to Parent Folder", FILE_LIST, ["backspace", "alt-up"];
+    GoBack, "Go Back", None, ["alt-left"];
+    GoForward, "Go Forward", None, ["alt-right"];
+    Rename, "Rename", FILE_LIST, ["f2"];
+    Delete, "Move to Trash", FILE_LIST, ["delete"];
+    DeletePermanently, "Delete Permanently", FILE_LIST, ["shift-delete"];
+    SelectAll, "Select All", FILE_LIST, ["ctrl-a"];
+    Copy, "Copy", FILE_LIST, ["ctrl-c"];
+    Cut, "Cut", FILE_LIST, ["ctrl-x"];
+    Paste, "Paste", FILE_LIST, ["ctrl-v"];
+    FocusLocation, "Edit Location", None, ["ctrl-l"];
+    Search, "Search", None, ["ctrl-f"];
+    NewTab, "New Tab", None, ["ctrl-t"];
+    CloseTab, "Close Tab", None, ["ctrl-w"];
+    ToggleHidden, "Show Hidden Files", None, ["ctrl-h"];
+    ToggleGrouping, "Group by Type", None, ["ctrl-g"];
+    Reload, "Reload", None, ["ctrl-r"];
+    OpenSettings, "Settings", None, ["ctrl-,"];
+    ToggleSplit, "Split View", None, ["ctrl-\\"];
+    SwitchPane, "Switch Pane", FILE_LIST, ["tab"];
+    CopyToOtherPane, "Copy to Other Pane", FILE_LIST, ["f5"];
+    MoveToOtherPane, "Move to Other Pane", FILE_LIST, ["f6"];
+    SyncPanes, "Sync Panes", None, ["alt-i"];
+    CommandPalette, "Command Palette", None, ["ctrl-shift-p"];
+}
+
+/// A keystroke bound to an action after applying the user's overrides
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolvedBinding {
+    pub action: &'static str,
+    /// Normalized, see `normalize_keystroke`
+    pub keystroke: String,
+    pub context: Option<&'static str>,
+}
+
+/// Canonical form of a keystroke such as "Shift-Ctrl-K" ("ctrl-shift-k"),
+/// so differently written bindings of the same keys compare equal. Chords
+/// are separated by spaces. `None` if a chord has no key or an unknown
+/// modifier.
+pub fn normalize_keystroke(keystroke: &str) -> Option<String> {
+    const MODIFIERS: [&str; 5] = ["ctrl", "alt", "shift", "super", "fn"];
+
+    let chords = keystroke.split_whitespace().map(|chord| {
+        let (modifiers, key) = split_chord(chord);
+        if key.is_empty() {
+            return None;
+        }
+
+        let mut held = [false; 5];
+        for modifier in modifiers.split('-').filter(|m| !m.is_empty()) {
+            let canonical = match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" | "secondary" => "ctrl",
+                "alt" | "option" => "alt",
+                "shift" => "shift",
+                "super" | "cmd" | "win" | "platform" => "super",
+                "fn" | "function" => "fn",
+                _ => return None,
+            };
+            held[MODIFIERS.iter().position(|m| *m == canonical)?] = true;
+        }
+        // Uppercase letters imply shift, as gpui parses them
+        let key = if key.len() == 1 && key.as_bytes()[0].is_ascii_uppercase() {
+            held[MODIFIERS.iter().position(|m| *m == "shift")?] = true;
+            key.to_ascii_lowercase()
+        } else {
+            key.to_lowercase()
+        };
+
+        let mut parts: Vec<&str> = MODIFIERS
+            .iter()
+            .zip(held)
+            .filter(|(_, held)| *held)
+            .map(|(modifier, _)| *modifier)
+            .collect();
+        parts.push(&key);
+        Some(parts.join("-"))
+    });
+    let chords: Option<Vec<String>> = chords.collect();
+    chords
+        .filter(|chords| !chords.is_empty())
+        .map(|c| c.join(" "))
+}
+
+/// Modifiers and key of one chord. A trailing "-" is the minus key itself,
+/// as in "ctrl--".
+fn split_chord(chord: &str) -> (&str, &str) {
+    match chord.strip_suffix("--") {
+        Some(modifiers) => (modifiers, "-"),
+        None if chord == "-" => ("", "-"),
+        None => chord.rsplit_once('-').unwrap_or(("", chord)),
+    }
+}
+
+/// A normalized keystroke as shown to users: "ctrl-shift-p" becomes
+/// "Ctrl+Shift+P".
+pub fn display_keystroke(keystroke: &str) -> String {
+    let capitalize = |part: &str| {
+        let mut chars = part.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    };
+    keystroke
+        .split_whitespace()
+        .map(|chord| {
+            let (modifiers, key) = split_chord(chord);
+            modifiers
+                .split('-')
+                .filter(|m| !m.is_empty())
+                .chain(std::iter::once(key))
+                .map(capitalize)
+                .collect::<Vec<String>>()
+                .join("+")
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Default keys with `overrides` applied, plus a description of every
+/// problem found: unknown actions, invalid keystrokes and keys bound to two
+/// actions that can fire in the same place. Overrides win conflicts with
+/// defaults; otherwise the action listed first keeps the key.
+pub fn resolve(
+    overrides: &BTreeMap<String, KeyBindingValue>,
+) -> (Vec<ResolvedBinding>, Vec<String>) {
+    let mut problems = Vec::new();
+    for name in overrides.keys() {
+        if !ACTIONS.iter().any(|action| action.name == name) {
+            problems.push(format!("Unknown action \"{}\"", name));
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for from_config in [true, false] {
+        for action in ACTIONS {
+            let keys = match overrides.get(action.name) {
+                Some(value) if from_config => value.keystrokes(),
+                None if !from_config => action.defaults.to_vec(),
+                _ => continue,
+            };
+            for key in keys {
+                match normalize_keystroke(key) {
+                    Some(keystroke) => candidates.push((action, keystroke)),
+                    None => {
+                        problems.push(format!("Invalid keystroke \"{}\" for {}", key, action.name))
+                    }
+                }
+            }
+        }
+    }
+
+    let mut bindings: Vec<ResolvedBinding> = Vec::new();
+    for (action, keystroke) in candidates {
+        // Window-wide bindings overlap with every context
+        let existing = bindings.iter().find(|binding| {
+            binding.keystroke == keystroke
+                && (binding.context.is_none()
+                    || action.context.is_none()
+                    || binding.context == action.context)
+        });
+        if let Some(existing) = existing {
+            if existing.action != action.name {
+                problems.push(format!(
+                    "\"{}\" is bound to both {} and {}; keeping {}",
+                    keystroke, existing.action, action.name, existing.action
+                ));
+            }
+            continue;
+        }
+        bindings.push(ResolvedBinding {
+            action: action.name,
+            keystroke,
+            context: action.context,
+        });
+    }
+    (bindings, problems)
+}
+
+/// Replaces the app's key bindings with the configured ones and returns the
+/// problems found, which are also logged.
+pub fn apply(cx: &mut App) -> Vec<String> {
+    let (bindings, mut problems) = resolve(&cx.global::<ConfigManager>().config.keybindings);
+
+    let mut key_bindings = Vec::new();
+    for binding in bindings {
+        // gpui panics on keystrokes it can't parse, so check them first
+        if let Some(Err(e)) = binding
+            .keystroke
+            .split(' ')
+            .map(Keystroke::parse)
+            .find(|parsed| parsed.is_err())
+        {
+            problems.push(format!("Invalid keystroke for {}: {}", binding.action, e));
+            continue;
+        }
+        key_bindings.extend(key_binding(
+            binding.action,
+            &binding.keystroke,
+            binding.context,
+        ));
+    }
+
+    cx.clear_key_bindings();
+    cx.bind_keys(key_bindings);
+    for problem in &problems {
+        eprintln!("[DEBUG] Keybindings: {}", problem);
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrides(entries: &[(&str, KeyBindingValue)]) -> BTreeMap<String, KeyBindingValue> {
+        entries
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_normalize_keystroke() {
+        assert_eq!(
+            normalize_keystroke("Shift-Ctrl-K"),
+            Some("ctrl-shift-k".into())
+        );
+        assert_eq!(normalize_keystroke("ctrl-K"), Some("ctrl-shift-k".into()));
+        assert_eq!(
+            normalize_keystroke("cmd-alt-up"),
+            Some("alt-super-up".into())
+        );
+        assert_eq!(normalize_keystroke("ctrl--"), Some("ctrl--".into()));
+        assert_eq!(normalize_keystroke("-"), Some("-".into()));
+        assert_eq!(
+            normalize_keystroke("ctrl-k  ctrl-s"),
+            Some("ctrl-k ctrl-s".into())
+        );
+        assert_eq!(normalize_keystroke("hyper-k"), None);
+        assert_eq!(normalize_keystroke("ctrl-"), None);
+        assert_eq!(normalize_keystroke(""), None);
+    }
+
+    #[test]
+    fn test_display_keystroke() {
+        assert_eq!(display_keystroke("ctrl-shift-p"), "Ctrl+Shift+P");
+        assert_eq!(display_keystroke("f2"), "F2");
+        assert_eq!(display_keystroke("ctrl--"), "Ctrl+-");
+        assert_eq!(display_keystroke("ctrl-k ctrl-s"), "Ctrl+K Ctrl+S");
+    }
+
+    #[test]
+    fn test_defaults_have_no_conflicts() {
+        let (bindings, problems) = resolve(&BTreeMap::new());
+        assert!(problems.is_empty(), "{:?}", problems);
+        let default_count: usize = ACTIONS.iter().map(|a| a.defaults.len()).sum();
+        assert_eq!(bindings.len(), default_count);
+    }
+
+    #[test]
+    fn test_overrides_replace_defaults() {
+        let (bindings, problems) = resolve(&overrides(&[
+            ("ToggleHidden", KeyBindingValue::One("ctrl-.".into())),
+            ("GoToParent", KeyBindingValue::Many(vec!["alt-up".into()])),
+            ("DeletePermanently", KeyBindingValue::One(String::new())),
+        ]));
+        assert!(problems.is_empty(), "{:?}", problems);
+        let keys = |action: &str| -> Vec<&str> {
+            bindings
+                .iter()
+                .filter(|b| b.action == action)
+                .map(|b| b.keystroke.as_str())
+                .collect()
+        };
+        assert_eq!(keys("ToggleHidden"), vec!["ctrl-."]);
+        assert_eq!(keys("GoToParent"), vec!["alt-up"]);
+        assert!(keys("DeletePermanently").is_empty());
+        assert_eq!(keys("Open"), vec!["enter"]);
+    }
+
+    #[test]
+    fn test_conflicts_are_reported() {
+        // A window-wide binding overlaps with the file list's; the override
+        // keeps the key
+        let (bindings, problems) = resolve(&overrides(&[(
+            "Reload",
+            KeyBindingValue::One("Ctrl-a".into()),
+        )]));
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("Reload and SelectAll"));
+        assert!(bindings
+            .iter()
+            .any(|b| b.action == "Reload" && b.keystroke == "ctrl-a"));
+        assert!(!bindings.iter().any(|b| b.action == "SelectAll"));
+
+        // Two file list actions on one key: the first listed wins
+        let (bindings, problems) = resolve(&overrides(&[
+            ("Rename", KeyBindingValue::One("f9".into())),
+            ("Open", KeyBindingValue::One("f9".into())),
+        ]));
+        assert_eq!(problems.len(), 1);
+        assert!(bindings
+            .iter()
+            .any(|b| b.action == "Open" && b.keystroke == "f9"));
+        assert!(!bindings.iter().any(|b| b.action == "Rename"));
+    }
+
+    #[test]
+    fn test_unknown_and_invalid_are_reported() {
+        let (_, problems) = resolve(&overrides(&[
+            ("Explode", KeyBindingValue::One("ctrl-e".into())),
+            ("Reload", KeyBindingValue::One("hyper-r".into())),
+        ]));
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].contains("Explode"));
+        assert!(problems[1].contains("hyper-r"));
+    }
+}
@@ -1,2 +1,7 @@
+pub mod columns;
 pub mod config;
+pub mod keybindings;
+pub mod panes;
+pub mod tree;
+pub mod view_settings;
 pub mod workspace;
@@ -0,0 +1,82 @@
+// Dual-pane mode: F5 and F6 copy or move the active pane's selection into the
+// folder the other pane shows.
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// The selected paths of the pane showing `from` to copy or move into `to`,
+/// in name order, or why they can't go there.
+pub fn transfer_sources(
+    from: &Path,
+    to: &Path,
+    selection: &HashSet<PathBuf>,
+) -> Result<Vec<PathBuf>, String> {
+    if from == to {
+        return Err("Both panes show the same folder".to_string());
+    }
+    if let Some(folder) = selection.iter().find(|path| to.starts_with(path)) {
+        return Err(format!(
+            "Can't put \"{}\" inside itself",
+            folder.file_name().unwrap_or_default().to_string_lossy()
+        ));
+    }
+    let mut sources: Vec<PathBuf> = selection.iter().cloned().collect();
+    sources.sort();
+    Ok(sources)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection(paths: &[&str]) -> HashSet<PathBuf> {
+        paths.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn test_transfer_to_other_pane() {
+        let sources = transfer_sources(
+            Path::new("/home/a"),
+            Path::new("/home/b"),
+            &selection(&["/home/a/y", "/home/a/x"]),
+        );
+        assert_eq!(
+            sources,
+            Ok(vec![PathBuf::from("/home/a/x"), PathBuf::from("/home/a/y")])
+        );
+        // Into a folder next to or above the selection
+        let sources = transfer_sources(
+            Path::new("/home/a"),
+            Path::new("/home/a/x"),
+            &selection(&["/home/a/y"]),
+        );
+        assert_eq!(sources, Ok(vec![PathBuf::from("/home/a/y")]));
+        let sources = transfer_sources(Path::new("/home/a"), Path::new("/"), &selection(&[]));
+        assert_eq!(sources, Ok(vec![]));
+    }
+
+    #[test]
+    fn test_transfer_refused() {
+        let sources = transfer_sources(
+            Path::new("/home/a"),
+            Path::new("/home/a"),
+            &selection(&["/home/a/x"]),
+        );
+        assert_eq!(sources, Err("Both panes show the same folder".to_string()));
+        // The other pane shows a selected folder or one inside it
+        for to in ["/home/a/x", "/home/a/x/deeper"] {
+            let sources = transfer_sources(
+                Path::new("/home/a"),
+                Path::new(to),
+                &selection(&["/home/a/x"]),
+            );
+            assert_eq!(sources, Err("Can't put \"x\" inside itself".to_string()));
+        }
+        // Only whole path components count
+        let sources = transfer_sources(
+            Path::new("/home/a"),
+            Path::new("/home/a/xy"),
+            &selection(&["/home/a/x"]),
+        );
+        assert!(sources.is_ok());
+    }
+}
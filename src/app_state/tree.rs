@@ -0,0 +1,118 @@
+// The list view's tree: the children of expanded folders are shown inline
+// below their rows, one level further in.
+use crate::fs_ops::provider::FileEntry;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Insert the children of expanded folders after their rows, depth first,
+/// so the visible order is also the selection order. `children` gives a
+/// folder's entries in the order they're shown, None until it is listed.
+pub fn flatten(
+    items: Vec<FileEntry>,
+    expanded: &HashSet<PathBuf>,
+    mut children: impl FnMut(&Path) -> Option<Vec<FileEntry>>,
+) -> Vec<FileEntry> {
+    let mut rows = Vec::with_capacity(items.len());
+    let mut stack: Vec<FileEntry> = items.into_iter().rev().collect();
+    while let Some(entry) = stack.pop() {
+        if entry.is_dir && expanded.contains(&entry.path) {
+            if let Some(children) = children(&entry.path) {
+                stack.extend(children.into_iter().rev().map(|mut child| {
+                    child.depth = entry.depth + 1;
+                    child
+                }));
+            }
+        }
+        rows.push(entry);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs_ops::scanner::ScannedFile;
+    use std::collections::HashMap;
+    use std::time::SystemTime;
+
+    fn entry(path: &str, is_dir: bool) -> FileEntry {
+        FileEntry::from_scanned(ScannedFile {
+            path: PathBuf::from(path),
+            is_dir,
+            size: 0,
+            modified: SystemTime::UNIX_EPOCH,
+            is_ignored: false,
+            tags: Vec::new(),
+            attributes: Default::default(),
+        })
+    }
+
+    /// The rows for `/a` (holding folder b and file c, b holding d and e),
+    /// `/f` and `/g` with the given folders expanded, as (path, depth)
+    fn rows(expanded: &[&str]) -> Vec<(String, usize)> {
+        let mut listings = HashMap::new();
+        listings.insert(
+            PathBuf::from("/a"),
+            vec![entry("/a/b", true), entry("/a/c", false)],
+        );
+        listings.insert(
+            PathBuf::from("/a/b"),
+            vec![entry("/a/b/d", false), entry("/a/b/e", false)],
+        );
+        listings.insert(PathBuf::from("/f"), vec![entry("/f/h", false)]);
+        let items = vec![entry("/a", true), entry("/f", false), entry("/g", true)];
+        let expanded = expanded.iter().map(PathBuf::from).collect();
+
+        flatten(items, &expanded, |folder| listings.get(folder).cloned())
+            .into_iter()
+            .map(|row| (row.path.to_string_lossy().to_string(), row.depth))
+            .collect()
+    }
+
+    fn row(path: &str, depth: usize) -> (String, usize) {
+        (path.to_string(), depth)
+    }
+
+    #[test]
+    fn test_collapsed() {
+        assert_eq!(rows(&[]), [row("/a", 0), row("/f", 0), row("/g", 0)]);
+    }
+
+    #[test]
+    fn test_expanded_children_follow_their_folder() {
+        assert_eq!(
+            rows(&["/a"]),
+            [
+                row("/a", 0),
+                row("/a/b", 1),
+                row("/a/c", 1),
+                row("/f", 0),
+                row("/g", 0)
+            ]
+        );
+        // Nested folders open depth first, before their parent's next entry
+        assert_eq!(
+            rows(&["/a", "/a/b"]),
+            [
+                row("/a", 0),
+                row("/a/b", 1),
+                row("/a/b/d", 2),
+                row("/a/b/e", 2),
+                row("/a/c", 1),
+                row("/f", 0),
+                row("/g", 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_only_listed_folders_open() {
+        // A folder below a collapsed one stays hidden even when expanded
+        assert_eq!(rows(&["/a/b"]), [row("/a", 0), row("/f", 0), row("/g", 0)]);
+        // Files don't open, and folders still loading show no children yet
+        assert_eq!(
+            rows(&["/f", "/g"]),
+            [row("/a", 0), row("/f", 0), row("/g", 0)]
+        );
+    }
+}
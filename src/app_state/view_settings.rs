@@ -0,0 +1,277 @@
+// Per-directory view settings (view mode, zoom, sort, grouping, hidden files).
+// Stored in view_settings.toml next to config.toml; directories without an
+// entry use the nearest ancestor that shares its settings with subfolders,
+// or the global defaults from config.toml.
+use crate::app_state::config::Config;
+use crate::fs_ops::sort::SortOptions;
+use gpui::{App, Global};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Zoom levels, as grid icon sizes in pixels
+pub const ICON_SIZES: [u32; 4] = [48, 64, 80, 96];
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ViewSettings {
+    /// "grid", "list", "details" or "columns"
+    pub view_mode: String,
+    pub icon_size: u32,
+    #[serde(default)]
+    pub sort: SortOptions,
+    #[serde(default)]
+    pub group_by_type: bool,
+    #[serde(default)]
+    pub show_hidden: bool,
+}
+
+impl ViewSettings {
+    /// The global defaults from config.toml
+    pub fn defaults(config: &Config) -> Self {
+        Self {
+            view_mode: config.ui.view_mode.clone(),
+            icon_size: config.ui.icon_size,
+            sort: config.ui.sort,
+            group_by_type: config.group_files_by_type,
+            show_hidden: config.ui.show_hidden,
+        }
+    }
+
+    /// Make these the global defaults
+    pub fn apply_to_config(&self, config: &mut Config) {
+        config.ui.view_mode = self.view_mode.clone();
+        config.ui.icon_size = self.icon_size;
+        config.ui.sort = self.sort;
+        config.group_files_by_type = self.group_by_type;
+        config.ui.show_hidden = self.show_hidden;
+    }
+
+    pub fn is_grid(&self) -> bool {
+        !matches!(self.view_mode.as_str(), "list" | "details" | "columns")
+    }
+
+    pub fn is_list(&self) -> bool {
+        self.view_mode == "list"
+    }
+
+    pub fn is_details(&self) -> bool {
+        self.view_mode == "details"
+    }
+
+    /// Miller columns
+    pub fn is_columns(&self) -> bool {
+        self.view_mode == "columns"
+    }
+
+    /// Step through `ICON_SIZES`; `steps` may be negative to zoom out.
+    pub fn zoom(&mut self, steps: i32) {
+        let current = ICON_SIZES
+            .iter()
+            .position(|&size| size >= self.icon_size)
+            .unwrap_or(ICON_SIZES.len() - 1) as i32;
+        let next = (current + steps).clamp(0, ICON_SIZES.len() as i32 - 1);
+        self.icon_size = ICON_SIZES[next as usize];
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct DirectoryView {
+    #[serde(flatten)]
+    pub settings: ViewSettings,
+    /// Subfolders without their own entry use these settings too
+    #[serde(default)]
+    pub inherit: bool,
+}
+
+/// Where the settings of a directory come from
+#[derive(Clone, Debug, PartialEq)]
+pub enum ViewSource {
+    Defaults,
+    Own,
+    Inherited(PathBuf),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct ViewSettingsStore {
+    #[serde(default)]
+    pub directories: BTreeMap<String, DirectoryView>,
+}
+
+impl Global for ViewSettingsStore {}
+
+impl ViewSettingsStore {
+    pub fn init(cx: &mut App) {
+        cx.set_global(Self::load());
+    }
+
+    fn get_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("m3fm").join("view_settings.toml"))
+    }
+
+    fn load() -> Self {
+        if let Some(path) = Self::get_path() {
+            if let Ok(contents) = fs::read_to_string(path) {
+                match toml::from_str(&contents) {
+                    Ok(store) => return store,
+                    Err(e) => eprintln!("Failed to parse view_settings.toml: {}", e),
+                }
+            }
+        }
+        Self::default()
+    }
+
+    /// Writes view_settings.toml on the background executor, as every view
+    /// change saves. Of saves made in quick succession only the latest is
+    /// written.
+    pub fn save(&self, cx: &App) {
+        static LATEST: AtomicU64 = AtomicU64::new(0);
+        static WRITING: Mutex<()> = Mutex::new(());
+        let Some(path) = Self::get_path() else {
+            return;
+        };
+        let Ok(toml_str) = toml::to_string_pretty(self) else {
+            return;
+        };
+        let generation = LATEST.fetch_add(1, Ordering::SeqCst) + 1;
+        cx.background_executor()
+            .spawn(async move {
+                let _writing = WRITING.lock().unwrap_or_else(|e| e.into_inner());
+                if LATEST.load(Ordering::SeqCst) != generation {
+                    // A newer save is waiting its turn
+                    return;
+                }
+                if let Some(parent) = path.parent() {
+                    let _ = fs::create_dir_all(parent);
+                }
+                if let Err(e) = fs::write(path, toml_str) {
+                    eprintln!("Failed to save view settings: {:?}", e);
+                }
+            })
+            .detach();
+    }
+
+    fn key(path: &Path) -> String {
+        path.to_string_lossy().to_string()
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&DirectoryView> {
+        self.directories.get(&Self::key(path))
+    }
+
+    /// Effective settings for `path` and where they come from.
+    pub fn resolve(&self, path: &Path, defaults: ViewSettings) -> (ViewSettings, ViewSource) {
+        if let Some(view) = self.get(path) {
+            return (view.settings.clone(), ViewSource::Own);
+        }
+        for ancestor in path.ancestors().skip(1) {
+            if let Some(view) = self.get(ancestor).filter(|v| v.inherit) {
+                return (
+                    view.settings.clone(),
+                    ViewSource::Inherited(ancestor.to_path_buf()),
+                );
+            }
+        }
+        (defaults, ViewSource::Defaults)
+    }
+
+    /// Remember settings for `path`, keeping its inheritance choice.
+    pub fn set(&mut self, path: &Path, settings: ViewSettings) {
+        let inherit = self.get(path).is_some_and(|v| v.inherit);
+        self.directories
+            .insert(Self::key(path), DirectoryView { settings, inherit });
+    }
+
+    pub fn set_inherit(&mut self, path: &Path, settings: ViewSettings, inherit: bool) {
+        self.directories
+            .insert(Self::key(path), DirectoryView { settings, inherit });
+    }
+
+    /// Forget the settings of `path`; returns whether it had any.
+    pub fn reset(&mut self, path: &Path) -> bool {
+        self.directories.remove(&Self::key(path)).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.directories.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs_ops::sort::SortKey;
+
+    fn settings(view_mode: &str, icon_size: u32) -> ViewSettings {
+        ViewSettings {
+            view_mode: view_mode.to_string(),
+            icon_size,
+            sort: SortOptions::default(),
+            group_by_type: false,
+            show_hidden: false,
+        }
+    }
+
+    #[test]
+    fn test_resolve_and_inherit() {
+        let defaults = settings("grid", 64);
+        let mut store = ViewSettingsStore::default();
+        let pictures = Path::new("/home/user/Pictures");
+        let holiday = Path::new("/home/user/Pictures/2024/holiday");
+
+        store.set(pictures, settings("grid", 96));
+        assert_eq!(
+            store.resolve(pictures, defaults.clone()),
+            (settings("grid", 96), ViewSource::Own)
+        );
+        // Not shared with subfolders unless asked
+        assert_eq!(
+            store.resolve(holiday, defaults.clone()),
+            (defaults.clone(), ViewSource::Defaults)
+        );
+
+        store.set_inherit(pictures, settings("grid", 96), true);
+        assert_eq!(
+            store.resolve(holiday, defaults.clone()),
+            (
+                settings("grid", 96),
+                ViewSource::Inherited(pictures.to_path_buf())
+            )
+        );
+
+        // Changing settings later keeps the inheritance choice
+        store.set(pictures, settings("list", 96));
+        assert!(store.get(pictures).unwrap().inherit);
+
+        assert!(store.reset(pictures));
+        assert!(!store.reset(pictures));
+        assert_eq!(
+            store.resolve(holiday, defaults.clone()).1,
+            ViewSource::Defaults
+        );
+    }
+
+    #[test]
+    fn test_zoom_and_roundtrip() {
+        let mut view = settings("grid", 64);
+        view.zoom(1);
+        assert_eq!(view.icon_size, 80);
+        view.zoom(5);
+        assert_eq!(view.icon_size, 96);
+        view.zoom(-10);
+        assert_eq!(view.icon_size, 48);
+
+        let mut store = ViewSettingsStore::default();
+        view.sort.key = SortKey::Size;
+        store.set_inherit(Path::new("/srv/data"), view.clone(), true);
+        let toml_str = toml::to_string_pretty(&store).unwrap();
+        let parsed: ViewSettingsStore = toml::from_str(&toml_str).unwrap();
+        assert_eq!(parsed, store);
+
+        let mut config = Config::default();
+        view.apply_to_config(&mut config);
+        assert_eq!(ViewSettings::defaults(&config), view);
+    }
+}
@@ -1,8 +1,20 @@
-use crate::app_state::config::ConfigContext;
+use crate::app_state::columns::{self, ColumnStep};
+use crate::app_state::config::{ConfigContext, SmartFolder};
+use crate::app_state::tree;
+use crate::app_state::view_settings::{ViewSettings, ViewSettingsStore, ViewSource};
 use crate::assets::app_cache::AppCache;
+use crate::fs_ops::git_status::GitStatus;
+use crate::fs_ops::image_metadata::{self, PhotoMetadata, StripMode};
+use crate::fs_ops::media::{self, MediaInfo, MediaSummary};
+use crate::fs_ops::names;
 use crate::fs_ops::provider::{FileEntry, FileSystemProvider, LocalFs};
 use crate::fs_ops::scanner::SearchOptions;
+use crate::fs_ops::sort::{SortKey, SortOptions};
 use crate::fs_ops::watcher::FsWatcher;
+use crate::ui_components::drag_drop::DropOp;
+use crate::ui_components::hex_view::HexPreview;
+use crate::ui_components::markdown_view::MarkdownPreview;
+use crate::ui_components::name_dialog::{NameDialog, NameDialogEvent};
 use crate::ui_components::open_with_dialog::{OpenWithDialog, OpenWithEvent};
 use crate::ui_components::toast::{Toast, ToastKind};
 use crate::ui_components::universal_picker_modal::{FilePickerEvent, UniversalPickerModal};
@@ -10,17 +22,29 @@ use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 use gpui::prelude::*;
 use gpui::*;
-use std::collections::HashSet;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
 #[derive(Clone, Debug)]
 pub enum WorkspaceEvent {
     PathChanged(PathBuf),
+    /// Dual-pane requests, handled by the window that owns both panes
+    Pane(PaneAction),
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaneAction {
+    ToggleSplit,
+    SwitchPane,
+    /// Copy or move the selection to the other pane's directory
+    TransferToOther(ClipboardOp),
+    /// Show this pane's directory in the other pane too
+    SyncPanes,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ClipboardOp {
     Copy,
     Cut,
@@ -35,6 +59,10 @@ pub enum ActiveOverlay {
     Settings,
     FolderPicker,
     OpenWith(PathBuf),
+    MarkdownPreview(PathBuf),
+    HexPreview(PathBuf),
+    /// Asking for a name, such as a smart folder's
+    NameDialog,
 }
 
 #[derive(Clone, Debug)]
@@ -43,6 +71,11 @@ pub struct ExtendedMetadata {
     pub modified: std::time::SystemTime,
     pub mime_type: String,
     pub image_dimensions: Option<(u32, u32)>,
+    pub tags: Vec<String>,
+    /// Stream properties and tags of audio and video files
+    pub media: Option<Arc<MediaInfo>>,
+    /// Camera, exposure and location of photos, from EXIF and XMP
+    pub photo: Option<PhotoMetadata>,
 }
 
 impl Default for ExtendedMetadata {
@@ -52,8 +85,128 @@ impl Default for ExtendedMetadata {
             modified: std::time::SystemTime::now(),
             mime_type: "application/octet-stream".to_string(),
             image_dimensions: None,
+            tags: Vec::new(),
+            media: None,
+            photo: None,
+        }
+    }
+}
+
+/// Wait for a double-click before a second click on an entry renames it
+const SLOW_CLICK_DELAY: Duration = Duration::from_millis(500);
+
+/// An entry whose name is being edited in place
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenameState {
+    pub path: PathBuf,
+    pub text: String,
+    /// Caret position in bytes; the selection runs from `anchor` to here
+    pub cursor: usize,
+    pub anchor: usize,
+    /// Why `text` can't be used as the new name, shown under the editor
+    pub error: Option<String>,
+}
+
+impl RenameState {
+    /// Edits the current name with everything but the extension selected
+    pub fn new(path: PathBuf) -> Self {
+        let text = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let base_len = names::base_name_len(&text, path.is_dir());
+        Self {
+            path,
+            text,
+            cursor: base_len,
+            anchor: 0,
+            error: None,
+        }
+    }
+
+    /// Edits a name that isn't a file's, such as a smart folder's, with all
+    /// of it selected
+    pub fn for_text(text: String) -> Self {
+        Self {
+            path: PathBuf::new(),
+            cursor: text.len(),
+            anchor: 0,
+            text,
+            error: None,
         }
     }
+
+    /// Selected byte range, empty when only the caret shows
+    pub fn selection(&self) -> std::ops::Range<usize> {
+        self.anchor.min(self.cursor)..self.anchor.max(self.cursor)
+    }
+
+    pub fn selected_text(&self) -> &str {
+        &self.text[self.selection()]
+    }
+
+    fn prev_boundary(&self, ix: usize) -> usize {
+        self.text[..ix].char_indices().next_back().map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self, ix: usize) -> usize {
+        self.text[ix..].chars().next().map_or(ix, |c| ix + c.len_utf8())
+    }
+
+    /// Replaces the selection (or inserts at the caret)
+    pub fn insert(&mut self, text: &str) {
+        let range = self.selection();
+        self.text.replace_range(range.clone(), text);
+        self.cursor = range.start + text.len();
+        self.anchor = self.cursor;
+    }
+
+    pub fn backspace(&mut self) {
+        if self.selection().is_empty() {
+            self.anchor = self.prev_boundary(self.cursor);
+        }
+        self.insert("");
+    }
+
+    pub fn delete(&mut self) {
+        if self.selection().is_empty() {
+            self.anchor = self.next_boundary(self.cursor);
+        }
+        self.insert("");
+    }
+
+    /// Moves the caret, growing the selection if `extend`
+    pub fn move_to(&mut self, ix: usize, extend: bool) {
+        self.cursor = ix;
+        if !extend {
+            self.anchor = ix;
+        }
+    }
+
+    pub fn move_left(&mut self, extend: bool) {
+        let range = self.selection();
+        let ix = if !extend && !range.is_empty() {
+            range.start
+        } else {
+            self.prev_boundary(self.cursor)
+        };
+        self.move_to(ix, extend);
+    }
+
+    pub fn move_right(&mut self, extend: bool) {
+        let range = self.selection();
+        let ix = if !extend && !range.is_empty() {
+            range.end
+        } else {
+            self.next_boundary(self.cursor)
+        };
+        self.move_to(ix, extend);
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = 0;
+        self.cursor = self.text.len();
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -79,19 +232,45 @@ pub struct Workspace {
     pub history_index: usize,
     pub filter_query: String,
     pub search_options: SearchOptions,
+    /// View mode, zoom, sort, grouping and hidden files for `current_path`
+    pub view: ViewSettings,
+    pub view_source: ViewSource,
     pub is_searching: bool,
     pub details_metadata: Option<ExtendedMetadata>,
     pub search_results: Option<Vec<FileEntry>>,
     pub clipboard_state: Option<(ClipboardOp, Vec<PathBuf>)>,
     pub is_dashboard: bool,
-    pub group_by_type: bool,
     pub grouped_files: std::collections::HashMap<String, Vec<FileEntry>>,
     pub folder_picker: Option<Entity<UniversalPickerModal>>,
     pub open_with_dialog: Option<Entity<OpenWithDialog>>,
+    pub name_dialog: Option<Entity<NameDialog>>,
+    pub markdown_preview: Option<Entity<MarkdownPreview>>,
+    pub hex_preview: Option<Entity<HexPreview>>,
     pub app_cache: Entity<AppCache>,
     pub pending_portal_response:
         Option<tokio::sync::oneshot::Sender<crate::fs_ops::portal::PortalResponse>>,
     pub watcher: Option<FsWatcher>,
+    /// Saved search currently presented in place of the directory listing
+    pub active_smart_folder: Option<SmartFolder>,
+    /// Git status of the repository containing `current_path`, if any
+    pub git_status: Option<Arc<GitStatus>>,
+    /// Column view: the entry highlighted in each column, starting with the
+    /// current directory. Folders among them open the next column.
+    pub column_path: Vec<FileEntry>,
+    /// Listings of the folders opened as columns
+    pub column_listings: HashMap<PathBuf, Arc<Vec<FileEntry>>>,
+    /// List view tree: folders expanded inline, kept across reloads
+    pub expanded_folders: HashSet<PathBuf>,
+    /// Children of the expanded folders, as listed
+    tree_children: HashMap<PathBuf, Vec<FileEntry>>,
+    /// Shown as one of two panes of a tab; set by the window
+    pub in_split: bool,
+    /// Text of the location bar while it is being edited
+    pub location_input: Option<String>,
+    pub renaming: Option<RenameState>,
+    /// Entry that was already the only selection when pressed; releasing
+    /// the click renames it unless a double-click follows
+    rename_on_click: Option<PathBuf>,
 }
 
 impl Workspace {
@@ -107,6 +286,13 @@ impl Workspace {
             w.watch(&initial_path);
         }
 
+        let (view, view_source) = cx.global::<ViewSettingsStore>().resolve(
+            &initial_path,
+            ViewSettings::defaults(
+                &cx.global::<crate::app_state::config::ConfigManager>()
+                    .config,
+            ),
+        );
         let ws_entity = cx.new(|_cx| Self {
             app_cache,
             current_path: initial_path.clone(),
@@ -124,17 +310,31 @@ impl Workspace {
             history_index: 0,
             filter_query: String::new(),
             search_options: SearchOptions::default(),
+            view,
+            view_source,
             is_searching: false,
             details_metadata: None,
             search_results: None,
             clipboard_state: None,
             is_dashboard: false,
-            group_by_type: false,
             grouped_files: std::collections::HashMap::new(),
             folder_picker: None,
             open_with_dialog: None,
+            name_dialog: None,
+            markdown_preview: None,
+            hex_preview: None,
             pending_portal_response: None,
             watcher,
+            active_smart_folder: None,
+            git_status: None,
+            column_path: Vec::new(),
+            column_listings: HashMap::new(),
+            expanded_folders: HashSet::new(),
+            tree_children: HashMap::new(),
+            in_split: false,
+            location_input: None,
+            renaming: None,
+            rename_on_click: None,
         });
 
         let weak_ws_watcher = ws_entity.downgrade();
@@ -226,11 +426,26 @@ impl Workspace {
                                 }
                             }
 
+                            let media_info = if !meta.is_dir() && media::is_media(&path) {
+                                media::read(&path).ok().map(Arc::new)
+                            } else {
+                                None
+                            };
+
+                            let photo = if !meta.is_dir() && image_metadata::is_supported(&path) {
+                                image_metadata::read(&path).ok().filter(|p| !p.is_empty())
+                            } else {
+                                None
+                            };
+
                             Ok(ExtendedMetadata {
                                 size,
                                 modified: meta.modified().unwrap_or(std::time::SystemTime::now()),
                                 mime_type,
                                 image_dimensions,
+                                tags: crate::fs_ops::tags::read_tags(&path),
+                                media: media_info,
+                                photo,
                             })
                         } else {
                             Err(anyhow::anyhow!("Could not get metadata"))
@@ -252,6 +467,81 @@ impl Workspace {
         .detach();
     }
 
+    /// Add or remove a tag on a file, then refresh every cached copy of its tags
+    pub fn toggle_tag(&mut self, path: PathBuf, tag: String, cx: &mut Context<Self>) {
+        let executor = cx.background_executor().clone();
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                let target = path.clone();
+                let result = executor
+                    .spawn(async move { crate::fs_ops::tags::toggle_tag(&target, &tag) })
+                    .await;
+
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |ws, cx| match result {
+                        Ok(tags) => {
+                            for entry in ws.items.iter_mut().filter(|e| e.path == path) {
+                                entry.tags = tags.clone();
+                            }
+                            if let Some(results) = &mut ws.search_results {
+                                for entry in results.iter_mut().filter(|e| e.path == path) {
+                                    entry.tags = tags.clone();
+                                }
+                            }
+                            if let Some(meta) = &mut ws.details_metadata {
+                                meta.tags = tags;
+                            }
+                            ws.filter_items(cx);
+                            cx.notify();
+                        }
+                        Err(e) => {
+                            ws.show_toast(
+                                format!("Failed to update tags: {}", e),
+                                ToastKind::Error,
+                                cx,
+                            );
+                        }
+                    });
+                });
+            }
+        })
+        .detach();
+    }
+
+    /// Re-read git status for the current directory in the background.
+    /// Also watches the repository's .git directory so staging, commits and
+    /// fetches refresh the badges.
+    pub fn refresh_git_status(&mut self, cx: &mut Context<Self>) {
+        let path = self.current_path.clone();
+        let executor = cx.background_executor().clone();
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                let target = path.clone();
+                let status = executor
+                    .spawn(async move { crate::fs_ops::git_status::read_status(&target) })
+                    .await;
+
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |ws, cx| {
+                        // A newer navigation has already started its own refresh
+                        if ws.current_path != path {
+                            return;
+                        }
+                        if let Some(w) = &mut ws.watcher {
+                            let git_dirs = status.iter().map(|s| s.git_dir.clone()).collect();
+                            w.set_extra("git", git_dirs);
+                        }
+                        ws.git_status = status.map(Arc::new);
+                        cx.notify();
+                    });
+                });
+            }
+        })
+        .detach();
+    }
+
     pub fn show_toast(&mut self, message: String, kind: ToastKind, cx: &mut Context<Self>) {
         let id = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -283,19 +573,52 @@ impl Workspace {
     }
 
     pub fn navigate(&mut self, path: PathBuf, cx: &mut Context<Self>) {
-        if self.current_path != path {
+        // Navigating anywhere (even to the same root) leaves a smart folder
+        let left_smart_folder = self.active_smart_folder.take().is_some();
+        if left_smart_folder {
+            self.filter_query.clear();
+            self.search_results = None;
+            self.is_searching = false;
+        }
+
+        let path_changed = self.current_path != path;
+        if path_changed {
             self.history.push(self.current_path.clone());
             self.history_index = self.history.len() - 1;
             self.current_path = path.clone();
+            crate::app_state::config::ConfigManager::add_recent_folder(path.clone(), cx);
+        }
 
+        if path_changed || left_smart_folder {
             if let Some(w) = &mut self.watcher {
                 w.watch(&path);
             }
+            self.close_columns();
+            self.collapse_all();
+            self.renaming = None;
+        }
+
+        // Badges from another repository must not linger while we refresh
+        if self
+            .git_status
+            .as_ref()
+            .is_some_and(|status| !path.starts_with(&status.root))
+        {
+            self.git_status = None;
         }
+        self.refresh_git_status(cx);
 
+        self.resolve_view_settings(cx);
         self.is_loading = true;
         self.selection.clear();
         self.last_selected = None;
+        // A reload keeps the open columns and their highlighted entry
+        if let Some(entry) = self.column_path.last() {
+            self.selection.insert(entry.path.clone());
+            self.last_selected = Some(entry.path.clone());
+        }
+        self.reload_columns(cx);
+        self.reload_tree(cx);
 
         cx.emit(WorkspaceEvent::PathChanged(path.clone()));
         cx.notify();
@@ -303,19 +626,25 @@ impl Workspace {
         let fs = LocalFs;
         let path_clone = path.clone();
         let executor = cx.background_executor().clone();
-        let show_hidden = cx.config().ui.show_hidden;
+        let show_hidden = self.view.show_hidden;
+        let mark_ignored = cx.config().ui.mark_ignored();
 
         cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
             let cx = cx.clone();
             async move {
-                match fs.list_directory(executor, path_clone, show_hidden).await {
+                match fs
+                    .list_directory(executor, path_clone, show_hidden, mark_ignored)
+                    .await
+                {
                     Ok(entries) => {
                         let _ = cx.update(|cx| {
                             let _ = this.update(cx, |ws, cx| {
                                 ws.items = entries;
                                 ws.filter_items(cx);
                                 ws.is_loading = false;
-                                ws.compute_grouped_files(cx);
+                                ws.load_child_counts(cx);
+                                ws.load_media_summaries(cx);
+                                ws.prune_columns();
                                 cx.notify();
                             });
                         });
@@ -339,7 +668,7 @@ impl Workspace {
     }
 
     fn compute_grouped_files(&mut self, cx: &mut Context<Self>) {
-        if !self.group_by_type {
+        if !self.view.group_by_type {
             self.grouped_files.clear();
             return;
         }
@@ -350,7 +679,8 @@ impl Workspace {
         let mut folders: Vec<FileEntry> = Vec::new();
         let mut other_files: Vec<FileEntry> = Vec::new();
 
-        for entry in &self.items {
+        // Group what the list would show, so hidden/ignored rules still apply
+        for entry in self.filtered_items.iter() {
             if entry.is_dir {
                 folders.push(entry.clone());
             } else {
@@ -376,16 +706,521 @@ impl Workspace {
         self.grouped_files = groups;
     }
 
+    /// Group names in display order: folders first, then categories, then
+    /// everything else
+    pub fn group_names(&self) -> Vec<String> {
+        let mut groups: Vec<String> = self.grouped_files.keys().cloned().collect();
+        groups.sort_by(|a, b| {
+            if *a == "Folders" {
+                std::cmp::Ordering::Less
+            } else if *b == "Folders" {
+                std::cmp::Ordering::Greater
+            } else if *a == "Other" {
+                std::cmp::Ordering::Greater
+            } else if *b == "Other" {
+                std::cmp::Ordering::Less
+            } else {
+                a.cmp(b)
+            }
+        });
+        groups
+    }
+
+    /// Whether the file view shows `grouped_files` rather than a flat list
+    pub fn shows_groups(&self) -> bool {
+        self.view.group_by_type
+            && !self.view.is_details()
+            && !self.view.is_columns()
+            && !self.grouped_files.is_empty()
+    }
+
+    /// Entries in the order the file view shows them
+    pub fn visible_items(&self) -> Vec<&FileEntry> {
+        if self.shows_groups() {
+            self.group_names()
+                .iter()
+                .flat_map(|group| self.grouped_files[group].iter())
+                .collect()
+        } else {
+            self.filtered_items.iter().collect()
+        }
+    }
+
+    fn resolve_view_settings(&mut self, cx: &mut Context<Self>) {
+        let defaults = ViewSettings::defaults(cx.config());
+        let (view, source) = cx
+            .global::<ViewSettingsStore>()
+            .resolve(&self.current_path, defaults);
+        self.view = view;
+        self.view_source = source;
+    }
+
+    /// Change the view settings of the current directory and remember them
+    /// for it. Hidden files need a fresh listing, everything else only a
+    /// re-filter.
+    pub fn update_view(&mut self, update: impl FnOnce(&mut ViewSettings), cx: &mut Context<Self>) {
+        let show_hidden = self.view.show_hidden;
+        update(&mut self.view);
+        let path = self.current_path.clone();
+        let view = self.view.clone();
+        cx.update_global::<ViewSettingsStore, _>(|store, cx| {
+            store.set(&path, view);
+            store.save(cx);
+        });
+        self.view_source = ViewSource::Own;
+
+        if self.view.show_hidden != show_hidden {
+            self.reload(cx);
+        } else {
+            self.filter_items(cx);
+        }
+        cx.notify();
+    }
+
+    pub fn set_sort(&mut self, options: SortOptions, cx: &mut Context<Self>) {
+        self.update_view(|view| view.sort = options, cx);
+    }
+
     pub fn toggle_grouping(&mut self, cx: &mut Context<Self>) {
-        self.group_by_type = !self.group_by_type;
-        cx.update_global::<crate::app_state::config::ConfigManager, _>(|mgr, _cx| {
-            mgr.config.group_files_by_type = self.group_by_type;
-            let _ = mgr.config.save();
+        self.update_view(|view| view.group_by_type = !view.group_by_type, cx);
+    }
+
+    /// Cycle list -> details -> columns -> grid
+    pub fn toggle_view_mode(&mut self, cx: &mut Context<Self>) {
+        let next = match self.view.view_mode.as_str() {
+            "list" => "details",
+            "details" => "columns",
+            "columns" => "grid",
+            _ => "list",
+        };
+        self.set_view_mode(next, cx);
+    }
+
+    /// Switch to "grid", "list", "details" or "columns"
+    pub fn set_view_mode(&mut self, mode: &str, cx: &mut Context<Self>) {
+        self.update_view(|view| view.view_mode = mode.to_string(), cx);
+        if !self.view.is_columns() {
+            self.close_columns();
+        }
+        self.load_child_counts(cx);
+        self.load_media_summaries(cx);
+    }
+
+    /// Entries shown in column `column` of the column view; column 0 is the
+    /// current directory. None while the folder is still loading.
+    pub fn column_items(&self, column: usize) -> Option<Arc<Vec<FileEntry>>> {
+        if column == 0 {
+            return Some(self.filtered_items.clone());
+        }
+        let folder = self.column_path.get(column - 1)?;
+        self.column_listings.get(&folder.path).cloned()
+    }
+
+    /// Highlight `entry` in column `column`, closing the columns after it and
+    /// opening it as the next column if it's a folder. The highlighted entry
+    /// is also the selection, so the preview and file operations follow it.
+    pub fn select_in_column(&mut self, column: usize, entry: FileEntry, cx: &mut Context<Self>) {
+        self.column_path.truncate(column);
+        self.set_selection(entry.path.clone(), cx);
+        if entry.is_dir {
+            self.load_column(entry.path.clone(), cx);
+        }
+        self.column_path.push(entry);
+        self.watch_columns();
+        cx.notify();
+    }
+
+    /// Keyboard navigation in the column view: `dy` moves within the last
+    /// column, `dx` moves to the parent column or into the highlighted folder.
+    pub fn move_in_columns(&mut self, dx: i32, dy: i32, cx: &mut Context<Self>) {
+        match columns::step(&self.column_path, |c| self.column_items(c), dx, dy) {
+            ColumnStep::Back => {
+                self.column_path.pop();
+                if let Some(parent) = self.column_path.last() {
+                    self.set_selection(parent.path.clone(), cx);
+                }
+                self.watch_columns();
+            }
+            ColumnStep::Select(column, entry) => self.select_in_column(column, *entry, cx),
+            ColumnStep::Stay => {}
+        }
+        cx.notify();
+    }
+
+    fn close_columns(&mut self) {
+        self.column_path.clear();
+        self.column_listings.clear();
+        self.watch_columns();
+    }
+
+    /// Open folders are watched so their columns update live
+    fn watch_columns(&mut self) {
+        let folders = self
+            .column_path
+            .iter()
+            .filter(|entry| entry.is_dir)
+            .map(|entry| entry.path.clone())
+            .collect();
+        if let Some(w) = &mut self.watcher {
+            w.set_extra("columns", folders);
+        }
+    }
+
+    fn reload_columns(&mut self, cx: &mut Context<Self>) {
+        let folders: Vec<PathBuf> = self
+            .column_path
+            .iter()
+            .filter(|entry| entry.is_dir)
+            .map(|entry| entry.path.clone())
+            .collect();
+        for folder in folders {
+            self.load_column(folder, cx);
+        }
+    }
+
+    fn load_column(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        let executor = cx.background_executor().clone();
+        let show_hidden = self.view.show_hidden;
+        let mark_ignored = cx.config().ui.mark_ignored();
+
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                let result = LocalFs
+                    .list_directory(executor, path.clone(), show_hidden, mark_ignored)
+                    .await;
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |ws, cx| {
+                        match result {
+                            Ok(entries) => {
+                                let entries = ws.arrange_entries(entries, cx);
+                                ws.column_listings.insert(path, Arc::new(entries));
+                                ws.prune_columns();
+                            }
+                            Err(e) => {
+                                eprintln!("[DEBUG] Failed to list column {:?}: {}", path, e)
+                            }
+                        }
+                        cx.notify();
+                    });
+                });
+            }
+        })
+        .detach();
+    }
+
+    /// Hide and sort the entries of a nested listing like the main listing
+    fn arrange_entries(&self, entries: Vec<FileEntry>, cx: &mut Context<Self>) -> Vec<FileEntry> {
+        let config = cx.config();
+        let hide_ignored = config.ui.ignored_files == "hide";
+        let mut entries: Vec<FileEntry> = entries
+            .into_iter()
+            .filter(|e| self.view.show_hidden || !e.name.starts_with('.'))
+            .filter(|e| !(hide_ignored && e.is_ignored))
+            .collect();
+        crate::fs_ops::sort::sort_entries(&mut entries, &self.view.sort, |path| {
+            config.get_file_category(path)
         });
-        self.compute_grouped_files(cx);
+        entries
+    }
+
+    /// After a reload, close the columns of entries that are gone and pick
+    /// up fresh metadata for the rest.
+    fn prune_columns(&mut self) {
+        for column in 0..self.column_path.len() {
+            let Some(items) = self.column_items(column) else {
+                continue;
+            };
+            match items
+                .iter()
+                .find(|i| i.path == self.column_path[column].path)
+            {
+                Some(entry) => self.column_path[column] = entry.clone(),
+                None => {
+                    self.column_path.truncate(column);
+                    break;
+                }
+            }
+        }
+        self.column_listings
+            .retain(|path, _| self.column_path.iter().any(|e| &e.path == path));
+        self.watch_columns();
+    }
+
+    /// List view: show or hide the children of `path` below its row.
+    pub fn toggle_expanded(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        if self.expanded_folders.contains(&path) {
+            // Collapsing also forgets expanded folders below it
+            self.expanded_folders.retain(|p| !p.starts_with(&path));
+            self.tree_children.retain(|p, _| !p.starts_with(&path));
+            self.watch_tree();
+            self.filter_items(cx);
+        } else {
+            self.expanded_folders.insert(path.clone());
+            self.load_tree_children(path, cx);
+        }
         cx.notify();
     }
 
+    fn collapse_all(&mut self) {
+        self.expanded_folders.clear();
+        self.tree_children.clear();
+        self.watch_tree();
+    }
+
+    /// Expanded folders are watched so their rows update live
+    fn watch_tree(&mut self) {
+        let folders = self.expanded_folders.iter().cloned().collect();
+        if let Some(w) = &mut self.watcher {
+            w.set_extra("tree", folders);
+        }
+    }
+
+    fn reload_tree(&mut self, cx: &mut Context<Self>) {
+        let folders: Vec<PathBuf> = self.expanded_folders.iter().cloned().collect();
+        for folder in folders {
+            self.load_tree_children(folder, cx);
+        }
+    }
+
+    fn load_tree_children(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        let executor = cx.background_executor().clone();
+        let show_hidden = self.view.show_hidden;
+        let mark_ignored = cx.config().ui.mark_ignored();
+
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                let result = LocalFs
+                    .list_directory(executor, path.clone(), show_hidden, mark_ignored)
+                    .await;
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |ws, cx| {
+                        // Collapsed while loading
+                        if !ws.expanded_folders.contains(&path) {
+                            return;
+                        }
+                        match result {
+                            Ok(entries) => {
+                                ws.tree_children.insert(path, entries);
+                            }
+                            Err(e) => {
+                                // Gone or unreadable: drop it from the tree
+                                eprintln!("[DEBUG] Failed to expand {:?}: {}", path, e);
+                                ws.expanded_folders.retain(|p| !p.starts_with(&path));
+                                ws.tree_children.retain(|p, _| !p.starts_with(&path));
+                            }
+                        }
+                        ws.watch_tree();
+                        ws.filter_items(cx);
+                        cx.notify();
+                    });
+                });
+            }
+        })
+        .detach();
+    }
+
+    /// The listing with the children of expanded folders below their rows
+    fn flatten_tree(&self, items: Vec<FileEntry>, cx: &mut Context<Self>) -> Vec<FileEntry> {
+        tree::flatten(items, &self.expanded_folders, |folder| {
+            let children = self.tree_children.get(folder)?;
+            Some(self.arrange_entries(children.clone(), cx))
+        })
+    }
+
+    /// Look up a listed entry by path, wherever it is shown.
+    pub fn find_entry(&self, path: &Path) -> Option<&FileEntry> {
+        self.items
+            .iter()
+            .chain(self.search_results.iter().flatten())
+            .chain(self.column_listings.values().flat_map(|items| items.iter()))
+            .chain(self.tree_children.values().flatten())
+            .find(|entry| entry.path == path)
+    }
+
+    /// Count the entries of listed folders in the background, when the
+    /// details view shows the item count column.
+    pub fn load_child_counts(&mut self, cx: &mut Context<Self>) {
+        let shows_counts = self.view.is_details()
+            && cx
+                .config()
+                .ui
+                .details_columns()
+                .iter()
+                .any(|c| c.key == SortKey::ItemCount);
+        if !shows_counts {
+            return;
+        }
+
+        let folders: Vec<PathBuf> = self
+            .items
+            .iter()
+            .chain(self.search_results.iter().flatten())
+            .filter(|e| e.is_dir && e.child_count.is_none())
+            .map(|e| e.path.clone())
+            .collect();
+        if folders.is_empty() {
+            return;
+        }
+
+        let executor = cx.background_executor().clone();
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                let counts: std::collections::HashMap<PathBuf, usize> = executor
+                    .spawn(async move {
+                        folders
+                            .into_iter()
+                            .filter_map(|folder| {
+                                let count = crate::fs_ops::file_info::child_count(&folder)?;
+                                Some((folder, count))
+                            })
+                            .collect()
+                    })
+                    .await;
+
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |ws, cx| {
+                        let entries = ws
+                            .items
+                            .iter_mut()
+                            .chain(ws.search_results.iter_mut().flatten());
+                        for entry in entries {
+                            if let Some(count) = counts.get(&entry.path) {
+                                entry.child_count = Some(*count);
+                            }
+                        }
+                        ws.filter_items(cx);
+                        cx.notify();
+                    });
+                });
+            }
+        })
+        .detach();
+    }
+
+    /// Read the duration and artist of listed audio and video files in the
+    /// background, when the details view shows either column.
+    pub fn load_media_summaries(&mut self, cx: &mut Context<Self>) {
+        let shows_media = self.view.is_details()
+            && cx
+                .config()
+                .ui
+                .details_columns()
+                .iter()
+                .any(|c| matches!(c.key, SortKey::Duration | SortKey::Artist));
+        if !shows_media {
+            return;
+        }
+
+        let files: Vec<PathBuf> = self
+            .items
+            .iter()
+            .chain(self.search_results.iter().flatten())
+            .filter(|e| !e.is_dir && e.media.is_none() && media::is_media(&e.path))
+            .map(|e| e.path.clone())
+            .collect();
+        if files.is_empty() {
+            return;
+        }
+
+        let executor = cx.background_executor().clone();
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                let summaries: std::collections::HashMap<PathBuf, MediaSummary> = executor
+                    .spawn(async move {
+                        files
+                            .into_iter()
+                            .map(|file| {
+                                let summary = media::summary(&file);
+                                (file, summary)
+                            })
+                            .collect()
+                    })
+                    .await;
+
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |ws, cx| {
+                        let entries = ws
+                            .items
+                            .iter_mut()
+                            .chain(ws.search_results.iter_mut().flatten());
+                        for entry in entries {
+                            if let Some(summary) = summaries.get(&entry.path) {
+                                entry.media = Some(summary.clone());
+                            }
+                        }
+                        ws.filter_items(cx);
+                        cx.notify();
+                    });
+                });
+            }
+        })
+        .detach();
+    }
+
+    pub fn zoom(&mut self, steps: i32, cx: &mut Context<Self>) {
+        self.update_view(|view| view.zoom(steps), cx);
+    }
+
+    pub fn toggle_hidden(&mut self, cx: &mut Context<Self>) {
+        self.update_view(|view| view.show_hidden = !view.show_hidden, cx);
+    }
+
+    /// Share the current directory's settings with its subfolders (or stop)
+    pub fn toggle_view_inheritance(&mut self, cx: &mut Context<Self>) {
+        let path = self.current_path.clone();
+        let view = self.view.clone();
+        let inherit = cx.update_global::<ViewSettingsStore, _>(|store, cx| {
+            let inherit = !store.get(&path).is_some_and(|v| v.inherit);
+            store.set_inherit(&path, view, inherit);
+            store.save(cx);
+            inherit
+        });
+        self.view_source = ViewSource::Own;
+        let message = if inherit {
+            "Subfolders now use this view"
+        } else {
+            "Subfolders no longer use this view"
+        };
+        self.show_toast(message.to_string(), ToastKind::Info, cx);
+    }
+
+    /// Drop the current directory's own settings
+    pub fn reset_view_settings(&mut self, cx: &mut Context<Self>) {
+        let path = self.current_path.clone();
+        cx.update_global::<ViewSettingsStore, _>(|store, cx| {
+            if store.reset(&path) {
+                store.save(cx);
+            }
+        });
+        self.show_toast("View reset to defaults".to_string(), ToastKind::Info, cx);
+        self.reload(cx);
+    }
+
+    /// Make the current settings the global defaults and forget every
+    /// per-directory override
+    pub fn use_view_settings_everywhere(&mut self, cx: &mut Context<Self>) {
+        let view = self.view.clone();
+        cx.update_global::<crate::app_state::config::ConfigManager, _>(|manager, _cx| {
+            view.apply_to_config(&mut manager.config);
+            manager.save_config();
+        });
+        cx.update_global::<ViewSettingsStore, _>(|store, cx| {
+            store.clear();
+            store.save(cx);
+        });
+        self.show_toast(
+            "View settings now used everywhere".to_string(),
+            ToastKind::Info,
+            cx,
+        );
+        self.reload(cx);
+        cx.refresh_windows();
+    }
+
     pub fn toggle_search_recursive(&mut self, cx: &mut Context<Self>) {
         self.search_options.recursive = !self.search_options.recursive;
         if !self.filter_query.is_empty() {
@@ -406,6 +1241,16 @@ impl Workspace {
         }
     }
 
+    pub fn toggle_search_ignore(&mut self, cx: &mut Context<Self>) {
+        self.search_options.respect_ignore = !self.search_options.respect_ignore;
+        if !self.filter_query.is_empty() {
+            let query = self.filter_query.clone();
+            self.perform_search(query, cx);
+        } else {
+            cx.notify();
+        }
+    }
+
     pub fn open(&mut self, path: PathBuf, cx: &mut Context<Self>) {
         if path.is_dir() {
             if self.history_index < self.history.len() - 1 {
@@ -441,26 +1286,183 @@ impl Workspace {
                 }
             }
         } else {
-            if let Err(e) = open::that(&path) {
-                self.show_toast(format!("Failed to open file: {}", e), ToastKind::Error, cx);
-            }
+            if let Err(e) = open::that(&path) {
+                self.show_toast(format!("Failed to open file: {}", e), ToastKind::Error, cx);
+            }
+        }
+    }
+
+    pub fn go_back(&mut self, cx: &mut Context<Self>) {
+        if self.history_index > 0 {
+            self.history_index -= 1;
+            let path = self.history[self.history_index].clone();
+            self.navigate(path, cx);
+        }
+    }
+
+    pub fn go_forward(&mut self, cx: &mut Context<Self>) {
+        if self.history_index < self.history.len() - 1 {
+            self.history_index += 1;
+            let path = self.history[self.history_index].clone();
+            self.navigate(path, cx);
+        }
+    }
+
+    /// Ctrl+L: edit the current path as text
+    pub fn begin_location_edit(&mut self, cx: &mut Context<Self>) {
+        let mut text = self.current_path.to_string_lossy().to_string();
+        if !text.ends_with('/') {
+            text.push('/');
+        }
+        self.location_input = Some(text);
+        cx.notify();
+    }
+
+    pub fn set_location_input(&mut self, text: String, cx: &mut Context<Self>) {
+        self.location_input = Some(text);
+        cx.notify();
+    }
+
+    pub fn cancel_location_edit(&mut self, cx: &mut Context<Self>) {
+        self.location_input = None;
+        cx.notify();
+    }
+
+    /// Opens the typed folder; `~` stands for the home directory
+    pub fn commit_location(&mut self, cx: &mut Context<Self>) {
+        let Some(text) = self.location_input.take() else {
+            return;
+        };
+        let text = text.trim();
+        let path = match text.strip_prefix('~') {
+            Some(rest) => dirs::home_dir()
+                .unwrap_or_default()
+                .join(rest.trim_start_matches('/')),
+            None => PathBuf::from(text),
+        };
+        if path.is_dir() {
+            self.open(path, cx);
+        } else {
+            self.show_toast(format!("Not a folder: {}", text), ToastKind::Error, cx);
         }
+        cx.notify();
     }
 
-    pub fn go_back(&mut self, cx: &mut Context<Self>) {
-        if self.history_index > 0 {
-            self.history_index -= 1;
-            let path = self.history[self.history_index].clone();
-            self.navigate(path, cx);
+    /// Starts editing the name of the entry under the cursor
+    pub fn begin_rename(&mut self, cx: &mut Context<Self>) {
+        let Some(path) = self
+            .last_selected
+            .clone()
+            .filter(|path| self.selection.contains(path))
+        else {
+            return;
+        };
+        self.rename_on_click = None;
+        self.renaming = Some(RenameState::new(path));
+        cx.notify();
+    }
+
+    /// A press on an entry; a slow second click on the only selected
+    /// entry renames it
+    pub fn arm_click_rename(&mut self, path: &Path) {
+        let sole = self.selection.len() == 1 && self.selection.contains(path);
+        self.rename_on_click = (sole && self.renaming.is_none()).then(|| path.to_path_buf());
+    }
+
+    /// A released click; `click_count` above one is a double-click, which
+    /// opens the entry instead of renaming it
+    pub fn click_released(&mut self, path: &Path, click_count: usize, cx: &mut Context<Self>) {
+        if click_count > 1 || self.rename_on_click.as_deref() != Some(path) {
+            self.rename_on_click = None;
+            return;
         }
+        let path = path.to_path_buf();
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                cx.background_executor().timer(SLOW_CLICK_DELAY).await;
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |ws, cx| {
+                        if ws.rename_on_click.as_ref() == Some(&path) {
+                            ws.begin_rename(cx);
+                        }
+                    });
+                });
+            }
+        })
+        .detach();
     }
 
-    pub fn go_forward(&mut self, cx: &mut Context<Self>) {
-        if self.history_index < self.history.len() - 1 {
-            self.history_index += 1;
-            let path = self.history[self.history_index].clone();
-            self.navigate(path, cx);
+    /// Applies an edit to the name being renamed and checks the result
+    pub fn edit_rename(&mut self, edit: impl FnOnce(&mut RenameState), cx: &mut Context<Self>) {
+        if let Some(rename) = &mut self.renaming {
+            edit(rename);
+            // Checked as typed: spaces at either end are part of the name
+            let name = rename.text.as_str();
+            let unchanged = rename.path.file_name() == Some(std::ffi::OsStr::new(name));
+            rename.error = if unchanged {
+                None
+            } else {
+                names::validate_rename(&rename.path, name).err()
+            };
+            cx.notify();
+        }
+    }
+
+    pub fn cancel_rename(&mut self, cx: &mut Context<Self>) {
+        self.renaming = None;
+        cx.notify();
+    }
+
+    /// Renames the entry, or keeps the editor open with the reason it can't
+    pub fn commit_rename(&mut self, cx: &mut Context<Self>) {
+        let Some(rename) = self.renaming.clone() else {
+            return;
+        };
+        let name = rename.text.clone();
+        if rename.path.file_name() == Some(std::ffi::OsStr::new(&name)) {
+            self.cancel_rename(cx);
+            return;
+        }
+        if let Err(error) = names::validate_rename(&rename.path, &name) {
+            self.renaming = Some(RenameState {
+                error: Some(error),
+                ..rename
+            });
+            cx.notify();
+            return;
         }
+        self.renaming = None;
+        cx.notify();
+
+        let path = rename.path.clone();
+        let target = path.with_file_name(&name);
+        let fs = LocalFs;
+        let executor = cx.background_executor().clone();
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                let result = fs.rename(executor, path, target.clone()).await;
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |ws, cx| match result {
+                        Ok(()) => {
+                            // Reloading clears the selection
+                            ws.reload(cx);
+                            ws.set_selection(target, cx);
+                        }
+                        Err(e) => {
+                            // Back to the editor, with the typed name intact
+                            ws.renaming = Some(RenameState {
+                                error: Some(format!("Failed to rename: {}", e)),
+                                ..rename
+                            });
+                            cx.notify();
+                        }
+                    });
+                });
+            }
+        })
+        .detach();
     }
 
     pub fn can_go_back(&self) -> bool {
@@ -499,15 +1501,16 @@ impl Workspace {
 
     pub fn select_range(&mut self, target: PathBuf, cx: &mut Context<Self>) {
         if let Some(start) = &self.last_selected {
-            let start_idx = self.items.iter().position(|i| i.path == *start);
-            let end_idx = self.items.iter().position(|i| i.path == target);
+            // Visible order, which includes nested tree rows
+            let start_idx = self.filtered_items.iter().position(|i| i.path == *start);
+            let end_idx = self.filtered_items.iter().position(|i| i.path == target);
 
             if let (Some(s), Some(e)) = (start_idx, end_idx) {
                 let (min, max) = if s < e { (s, e) } else { (e, s) };
 
                 self.selection.clear();
                 for i in min..=max {
-                    if let Some(item) = self.items.get(i) {
+                    if let Some(item) = self.filtered_items.get(i) {
                         self.selection.insert(item.path.clone());
                     }
                 }
@@ -518,7 +1521,115 @@ impl Workspace {
         cx.notify();
     }
 
-    pub fn delete_selection(&mut self, cx: &mut Context<Self>) {
+    /// Position of the keyboard cursor (the last selected entry) among the
+    /// visible entries
+    pub fn cursor_index(&self) -> Option<usize> {
+        let cursor = self.last_selected.as_ref()?;
+        self.visible_items()
+            .iter()
+            .position(|item| item.path == *cursor)
+    }
+
+    /// Moves the cursor `delta` entries through the visible order, selecting
+    /// only the entry it lands on, and returns its position. Without a
+    /// cursor it starts at the first or last entry.
+    pub fn move_cursor(&mut self, delta: isize, cx: &mut Context<Self>) -> Option<usize> {
+        let count = self.visible_items().len();
+        if count == 0 {
+            return None;
+        }
+        let target = match self.cursor_index() {
+            Some(ix) => (ix as isize + delta).clamp(0, count as isize - 1) as usize,
+            None if delta < 0 => count - 1,
+            None => 0,
+        };
+        let path = self.visible_items()[target].path.clone();
+        self.set_selection(path, cx);
+        Some(target)
+    }
+
+    /// Type-ahead find: selects the next entry whose name starts with
+    /// `prefix` (ignoring case), from the cursor on. A single repeated
+    /// letter cycles through the matches instead.
+    pub fn select_by_prefix(&mut self, prefix: &str, cx: &mut Context<Self>) -> Option<usize> {
+        let prefix = prefix.to_lowercase();
+        let items = self.visible_items();
+        let start = match self.cursor_index() {
+            Some(ix) if prefix.chars().count() == 1 => ix + 1,
+            Some(ix) => ix,
+            None => 0,
+        };
+        let target = (0..items.len())
+            .map(|offset| (start + offset) % items.len())
+            .find(|ix| items[*ix].name.to_lowercase().starts_with(&prefix))?;
+        let path = items[target].path.clone();
+        self.set_selection(path, cx);
+        Some(target)
+    }
+
+    pub fn select_all(&mut self, cx: &mut Context<Self>) {
+        self.selection = self
+            .visible_items()
+            .iter()
+            .map(|item| item.path.clone())
+            .collect();
+        cx.notify();
+    }
+
+    /// Replaces the selection, e.g. with the entries under a rubber band,
+    /// moving the cursor to `cursor` if given
+    pub fn select_paths(
+        &mut self,
+        paths: HashSet<PathBuf>,
+        cursor: Option<PathBuf>,
+        cx: &mut Context<Self>,
+    ) {
+        if self.selection == paths {
+            return;
+        }
+        self.selection = paths;
+        if cursor.is_some() {
+            self.last_selected = cursor;
+        }
+        cx.notify();
+    }
+
+    /// Opens the entry under the cursor
+    pub fn open_selection(&mut self, cx: &mut Context<Self>) {
+        if let Some(path) = self.last_selected.clone() {
+            if self.selection.contains(&path) {
+                self.open(path, cx);
+            }
+        }
+    }
+
+    /// Selects `path`, opening its folder first unless it's already shown,
+    /// e.g. among the search results. Folders are opened instead.
+    pub fn reveal(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        if path.is_dir() {
+            self.open(path, cx);
+            return;
+        }
+        if !self.visible_items().iter().any(|item| item.path == path) {
+            if let Some(parent) = path.parent() {
+                self.open(parent.to_path_buf(), cx);
+            }
+        }
+        self.set_selection(path, cx);
+    }
+
+    pub fn go_to_parent(&mut self, cx: &mut Context<Self>) {
+        if let Some(parent) = self.current_path.parent() {
+            let child = self.current_path.clone();
+            self.open(parent.to_path_buf(), cx);
+            // Keep the folder we came from under the cursor
+            self.last_selected = Some(child.clone());
+            self.selection.insert(child);
+        }
+    }
+
+    /// Moves the selection to the trash, or removes it for good
+    pub fn delete_selection(&mut self, permanent: bool, cx: &mut Context<Self>) {
         if self.selection.is_empty() {
             return;
         }
@@ -534,7 +1645,12 @@ impl Workspace {
             let cx = cx.clone();
             async move {
                 for path in paths {
-                    if let Err(e) = fs.delete(executor.clone(), path).await {
+                    let result = if permanent {
+                        fs.delete_permanently(executor.clone(), path).await
+                    } else {
+                        fs.delete(executor.clone(), path).await
+                    };
+                    if let Err(e) = result {
                         let _ = cx.update(|cx| {
                             let _ = this.update(cx, |ws, cx| {
                                 ws.show_toast(
@@ -549,7 +1665,12 @@ impl Workspace {
 
                 let _ = cx.update(|cx| {
                     let _ = this.update(cx, |ws, cx| {
-                        ws.show_toast("Deleted items".to_string(), ToastKind::Info, cx);
+                        let message = if permanent {
+                            "Deleted items"
+                        } else {
+                            "Moved to trash"
+                        };
+                        ws.show_toast(message.to_string(), ToastKind::Info, cx);
                         ws.is_loading = false;
                         ws.reload(cx);
                     });
@@ -616,6 +1737,33 @@ impl Workspace {
         cx.notify();
     }
 
+    /// Properties dialog of `path`
+    pub fn show_properties(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        self.active_overlay = Some(ActiveOverlay::DetailsDialog(path.clone()));
+        self.details_metadata = None;
+        self.load_details_metadata(path, cx);
+        cx.notify();
+    }
+
+    /// Rendered Markdown of `path` in the larger preview overlay
+    pub fn show_markdown_preview(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        let workspace_entity = cx.entity().clone();
+        let preview_path = path.clone();
+        self.markdown_preview =
+            Some(cx.new(|cx| MarkdownPreview::new(workspace_entity, preview_path, cx)));
+        self.active_overlay = Some(ActiveOverlay::MarkdownPreview(path));
+        cx.notify();
+    }
+
+    /// Hex dump of `path` in the larger preview overlay
+    pub fn show_hex_preview(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        let workspace_entity = cx.entity().clone();
+        let preview_path = path.clone();
+        self.hex_preview = Some(cx.new(|cx| HexPreview::new(workspace_entity, preview_path, cx)));
+        self.active_overlay = Some(ActiveOverlay::HexPreview(path));
+        cx.notify();
+    }
+
     pub fn open_settings(&mut self, cx: &mut Context<Self>) {
         self.active_overlay = Some(ActiveOverlay::Settings);
         cx.notify();
@@ -793,33 +1941,57 @@ impl Workspace {
             .global::<crate::app_state::config::ConfigManager>()
             .config
             .clone();
-        let show_hidden = config.ui.show_hidden;
+        let show_hidden = self.view.show_hidden;
+        let hide_ignored = config.ui.ignored_files == "hide";
         let matcher = SkimMatcherV2::default();
 
-        if let Some(global_results) = &self.search_results {
-            self.filtered_items = std::sync::Arc::new(global_results.clone());
+        let mut items: Vec<FileEntry> = if let Some(global_results) = &self.search_results {
+            global_results.clone()
         } else {
-            let query = self.filter_query.clone();
-            self.filtered_items = std::sync::Arc::new(
-                self.items
-                    .iter()
-                    .filter(|item| {
-                        let is_hidden = item.name.starts_with(".");
-                        if is_hidden && !show_hidden {
-                            return false;
-                        }
-                        if !query.is_empty() {
-                            return matcher.fuzzy_match(&item.name, &query).is_some();
-                        }
-                        true
-                    })
-                    .cloned()
-                    .collect(),
-            );
+            let query = crate::fs_ops::scanner::SearchQuery::parse(&self.filter_query);
+            self.items
+                .iter()
+                .filter(|item| {
+                    let is_hidden = item.name.starts_with(".");
+                    if is_hidden && !show_hidden {
+                        return false;
+                    }
+                    if item.is_ignored && hide_ignored {
+                        return false;
+                    }
+                    if !query.matches_tags(&item.tags) {
+                        return false;
+                    }
+                    if !query.text.is_empty() {
+                        return matcher.fuzzy_match(&item.name, &query.text).is_some();
+                    }
+                    true
+                })
+                .cloned()
+                .collect()
+        };
+        crate::fs_ops::sort::sort_entries(&mut items, &self.view.sort, |path| {
+            config.get_file_category(path)
+        });
+        let shows_tree = self.view.is_list()
+            && !self.view.group_by_type
+            && self.search_results.is_none()
+            && !self.expanded_folders.is_empty();
+        if shows_tree {
+            items = self.flatten_tree(items, cx);
         }
+        self.filtered_items = std::sync::Arc::new(items);
+        self.compute_grouped_files(cx);
     }
 
     pub fn reload(&mut self, cx: &mut Context<Self>) {
+        if let Some(folder) = self.active_smart_folder.clone() {
+            // Smart folders stay live by re-running their search
+            self.perform_search(folder.query, cx);
+            self.refresh_git_status(cx);
+            self.details_metadata = None;
+            return;
+        }
         let path = self.current_path.clone();
         self.navigate(path, cx);
         self.details_metadata = None;
@@ -901,6 +2073,63 @@ impl Workspace {
         .detach();
     }
 
+    /// Removes the location, or all metadata, from the photos in `paths`
+    pub fn strip_metadata(&mut self, paths: Vec<PathBuf>, mode: StripMode, cx: &mut Context<Self>) {
+        let executor = cx.background_executor().clone();
+
+        self.is_loading = true;
+        cx.notify();
+
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                let (stripped, errors) = executor
+                    .spawn(async move {
+                        let mut stripped = 0;
+                        let mut errors = Vec::new();
+                        for path in paths {
+                            match image_metadata::strip(&path, mode) {
+                                Ok(true) => stripped += 1,
+                                Ok(false) => {}
+                                Err(e) => errors.push(format!(
+                                    "{}: {}",
+                                    path.file_name().unwrap_or_default().to_string_lossy(),
+                                    e
+                                )),
+                            }
+                        }
+                        (stripped, errors)
+                    })
+                    .await;
+
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |ws, cx| {
+                        for error in errors {
+                            ws.show_toast(
+                                format!("Failed to remove metadata: {}", error),
+                                ToastKind::Error,
+                                cx,
+                            );
+                        }
+                        let what = match mode {
+                            StripMode::Location => "location",
+                            StripMode::All => "metadata",
+                        };
+                        let message = match stripped {
+                            0 => format!("No {} to remove", what),
+                            1 => format!("Removed {} from 1 photo", what),
+                            n => format!("Removed {} from {} photos", what, n),
+                        };
+                        ws.show_toast(message, ToastKind::Info, cx);
+                        ws.is_loading = false;
+                        ws.reload(cx);
+                    });
+                });
+            }
+        })
+        .detach();
+    }
+
     pub fn perform_search(&mut self, query: String, cx: &mut Context<Self>) {
         if query.is_empty() {
             return;
@@ -926,6 +2155,8 @@ impl Workspace {
                             ws.is_loading = false;
                             ws.search_results = Some(entries);
                             ws.filter_items(cx);
+                            ws.load_child_counts(cx);
+                            ws.load_media_summaries(cx);
                             cx.notify();
                         });
                     }
@@ -942,7 +2173,186 @@ impl Workspace {
         .detach();
     }
 
+    /// Open a saved search: jump to its root and present the results as the
+    /// listing. Watcher events re-run the search (see `reload`).
+    pub fn open_smart_folder(&mut self, folder: SmartFolder, cx: &mut Context<Self>) {
+        self.navigate(folder.root.clone(), cx);
+        self.filter_query = folder.query.clone();
+        self.search_options = folder.options;
+        self.watch_smart_folder(&folder);
+        self.active_smart_folder = Some(folder.clone());
+        self.perform_search(folder.query, cx);
+    }
+
+    /// Asks for a name in a dialog starting out with `name`; `confirmed` is
+    /// called with the one given
+    fn prompt_name(
+        &mut self,
+        title: &'static str,
+        confirm_label: &'static str,
+        name: String,
+        validate: impl Fn(&str, &App) -> Option<String> + 'static,
+        confirmed: impl Fn(&mut Self, String, &mut Context<Self>) + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        let dialog = cx.new(|cx| NameDialog::new(title, confirm_label, name, validate, cx));
+        cx.subscribe(&dialog, move |ws, _dialog, event: &NameDialogEvent, cx| {
+            ws.name_dialog = None;
+            ws.dismiss_overlay(cx);
+            if let NameDialogEvent::Confirm(name) = event {
+                confirmed(ws, name.clone(), cx);
+            }
+        })
+        .detach();
+        self.name_dialog = Some(dialog);
+        self.active_overlay = Some(ActiveOverlay::NameDialog);
+        cx.notify();
+    }
+
+    /// Save the current query, root and options as a smart folder in
+    /// config.toml, under a name asked for
+    pub fn save_current_search(&mut self, cx: &mut Context<Self>) {
+        if self.filter_query.is_empty() {
+            return;
+        }
+
+        let root_name = self
+            .current_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "/".to_string());
+        let folder = SmartFolder {
+            name: format!("{} in {}", self.filter_query, root_name),
+            root: self.current_path.clone(),
+            query: self.filter_query.clone(),
+            options: self.search_options,
+        };
+        self.prompt_name(
+            "Save Smart Folder",
+            "Save",
+            folder.name.clone(),
+            |name, cx| cx.config().smart_folder_name_error(name, None),
+            move |ws, name, cx| {
+                let folder = SmartFolder {
+                    name,
+                    ..folder.clone()
+                };
+                ws.save_smart_folder(folder, cx);
+            },
+            cx,
+        );
+    }
+
+    fn save_smart_folder(&mut self, folder: SmartFolder, cx: &mut Context<Self>) {
+        cx.update_global::<crate::app_state::config::ConfigManager, _>(|mgr, _cx| {
+            mgr.config.add_smart_folder(folder.clone());
+            mgr.save_config();
+        });
+
+        self.show_toast(
+            format!("Saved smart folder \"{}\"", folder.name),
+            ToastKind::Success,
+            cx,
+        );
+        self.watch_smart_folder(&folder);
+        self.active_smart_folder = Some(folder);
+        // Tab title and sidebar reflect the smart folder now
+        cx.emit(WorkspaceEvent::PathChanged(self.current_path.clone()));
+        if self.search_results.is_none() {
+            let query = self.filter_query.clone();
+            self.perform_search(query, cx);
+        }
+        cx.notify();
+    }
+
+    /// Forget the saved smart folder `name`; showing it stays a plain search
+    pub fn remove_smart_folder(&mut self, name: &str, cx: &mut Context<Self>) {
+        cx.update_global::<crate::app_state::config::ConfigManager, _>(|mgr, cx| {
+            if mgr.config.remove_smart_folder(name) {
+                mgr.save_config();
+                cx.refresh_windows();
+            }
+        });
+        if self
+            .active_smart_folder
+            .as_ref()
+            .is_some_and(|folder| folder.name == name)
+        {
+            self.active_smart_folder = None;
+            cx.emit(WorkspaceEvent::PathChanged(self.current_path.clone()));
+        }
+        self.show_toast(
+            format!("Removed smart folder \"{}\"", name),
+            ToastKind::Success,
+            cx,
+        );
+        cx.notify();
+    }
+
+    /// Asks for a new name for the saved smart folder `name`
+    pub fn rename_smart_folder(&mut self, name: String, cx: &mut Context<Self>) {
+        let current = name.clone();
+        let old_name = name.clone();
+        self.prompt_name(
+            "Rename Smart Folder",
+            "Rename",
+            name,
+            move |new_name, cx| cx.config().smart_folder_name_error(new_name, Some(&current)),
+            move |ws, new_name, cx| {
+                if new_name == old_name {
+                    return;
+                }
+                let renamed = cx.update_global::<crate::app_state::config::ConfigManager, _>(
+                    |mgr, cx| {
+                        let renamed = mgr.config.rename_smart_folder(&old_name, &new_name);
+                        if renamed {
+                            mgr.save_config();
+                            cx.refresh_windows();
+                        }
+                        renamed
+                    },
+                );
+                if !renamed {
+                    return;
+                }
+                if let Some(folder) = ws
+                    .active_smart_folder
+                    .as_mut()
+                    .filter(|folder| folder.name == old_name)
+                {
+                    folder.name = new_name.clone();
+                    // Tab title and sidebar show the new name
+                    cx.emit(WorkspaceEvent::PathChanged(ws.current_path.clone()));
+                }
+                ws.show_toast(
+                    format!("Renamed smart folder to \"{}\"", new_name),
+                    ToastKind::Success,
+                    cx,
+                );
+                cx.notify();
+            },
+            cx,
+        );
+    }
+
+    fn watch_smart_folder(&mut self, folder: &SmartFolder) {
+        // Recursive searches need to hear about changes anywhere below the root
+        if let Some(w) = &mut self.watcher {
+            if folder.options.recursive {
+                w.watch_recursive(&folder.root);
+            } else {
+                w.watch(&folder.root);
+            }
+        }
+    }
+
     pub fn clear_search(&mut self, cx: &mut Context<Self>) {
+        if self.active_smart_folder.take().is_some() {
+            if let Some(w) = &mut self.watcher {
+                w.watch(&self.current_path);
+            }
+            cx.emit(WorkspaceEvent::PathChanged(self.current_path.clone()));
+        }
         self.filter_query.clear();
         self.search_results = None;
         self.is_searching = false;
@@ -978,12 +2388,77 @@ impl Workspace {
         let Some((op, sources)) = self.clipboard_state.clone() else {
             return;
         };
+        let target_dir = self.current_path.clone();
+        self.transfer(op, sources, target_dir, cx);
+    }
+
+    /// Ask the window for a dual-pane action
+    pub fn pane_action(&mut self, action: PaneAction, cx: &mut Context<Self>) {
+        cx.emit(WorkspaceEvent::Pane(action));
+    }
+
+    /// Entries dropped on `target_dir`, from this window or another
+    /// application.
+    pub fn drop_paths(
+        &mut self,
+        paths: Vec<PathBuf>,
+        target_dir: PathBuf,
+        op: DropOp,
+        cx: &mut Context<Self>,
+    ) {
+        match op {
+            DropOp::Move => self.transfer(ClipboardOp::Cut, paths, target_dir, cx),
+            DropOp::Copy => self.transfer(ClipboardOp::Copy, paths, target_dir, cx),
+            DropOp::Link => self.link_paths(paths, target_dir, cx),
+        }
+    }
+
+    /// Symlinks to `sources` in `target_dir`, named like them.
+    pub fn link_paths(
+        &mut self,
+        sources: Vec<PathBuf>,
+        target_dir: PathBuf,
+        cx: &mut Context<Self>,
+    ) {
+        let mut error_count = 0;
+        for source in &sources {
+            let Some(file_name) = source.file_name() else {
+                continue;
+            };
+            let dest = target_dir.join(file_name);
+            if dest.symlink_metadata().is_ok()
+                || std::os::unix::fs::symlink(source, &dest).is_err()
+            {
+                error_count += 1;
+            }
+        }
+
+        let linked = sources.len() - error_count;
+        if error_count > 0 {
+            self.show_toast(
+                format!("Linked {} items. {} failed.", linked, error_count),
+                ToastKind::Error,
+                cx,
+            );
+        } else {
+            self.show_toast(format!("Linked {} items.", linked), ToastKind::Success, cx);
+        }
+        self.reload(cx);
+    }
 
+    /// Copy or move `sources` into `target_dir`, then reload.
+    pub fn transfer(
+        &mut self,
+        op: ClipboardOp,
+        sources: Vec<PathBuf>,
+        target_dir: PathBuf,
+        cx: &mut Context<Self>,
+    ) {
         if sources.is_empty() {
             return;
         }
 
-        let target_dir = self.current_path.clone();
+        let moved = sources.clone();
         let fs = LocalFs;
         let executor = cx.background_executor().clone();
 
@@ -1062,7 +2537,12 @@ impl Workspace {
                             );
                         }
 
-                        if op == ClipboardOp::Cut && error_count == 0 {
+                        // Pasted a cut: the clipboard entries are gone now
+                        let from_clipboard = ws
+                            .clipboard_state
+                            .as_ref()
+                            .is_some_and(|(_, paths)| *paths == moved);
+                        if op == ClipboardOp::Cut && error_count == 0 && from_clipboard {
                             ws.clipboard_state = None;
                         }
 
@@ -1137,11 +2617,16 @@ impl Workspace {
                                     // Check for cross-device link error (EXDEV / OS error 18)
                                     // or any error
                                     // Fallback using fs_extra for directories or copy+delete
+                                    // Copy, carry the tags over, then drop the source:
+                                    // the copies don't preserve xattrs on their own
                                     let move_result = if source.is_dir() {
                                         let mut options = fs_extra::dir::CopyOptions::new();
                                         options.copy_inside = true;
-                                        match fs_extra::dir::move_dir(&source, &dest, &options) {
-                                            Ok(_) => Ok(()),
+                                        match fs_extra::dir::copy(&source, &dest, &options) {
+                                            Ok(_) => {
+                                                crate::fs_ops::tags::copy_tags(&source, &dest);
+                                                std::fs::remove_dir_all(&source)
+                                            }
                                             Err(e) => Err(std::io::Error::new(
                                                 std::io::ErrorKind::Other,
                                                 e.to_string(),
@@ -1151,6 +2636,7 @@ impl Workspace {
                                         // File fallback
                                         match std::fs::copy(&source, &dest) {
                                             Ok(_) => {
+                                                crate::fs_ops::tags::copy_tags(&source, &dest);
                                                 let _ = std::fs::remove_file(&source);
                                                 Ok(())
                                             }
@@ -81,8 +81,10 @@ pub fn icon(name: &str) -> Svg {
         "close" | "items/x-mark.svg" | "icons/x-mark.svg" => "close.svg",
         "add" => "add.svg",
         "remove" => "remove.svg",
+        "edit" => "edit.svg",
         "grid" => "grid.svg",
         "list" => "list.svg",
+        "view_column" => "view_column.svg",
         "chevron_right" => "chevron_right.svg",
         "magnifying-glass" | "icons/magnifying-glass.svg" | "search" => "search.svg",
         // File Types
@@ -116,8 +118,10 @@ pub fn icon_img(name: &str) -> Img {
         "close" | "items/x-mark.svg" | "icons/x-mark.svg" => "close.svg",
         "add" => "add.svg",
         "remove" => "remove.svg",
+        "edit" => "edit.svg",
         "grid" => "grid.svg",
         "list" => "list.svg",
+        "view_column" => "view_column.svg",
         "chevron_right" => "chevron_right.svg",
         "magnifying-glass" | "icons/magnifying-glass.svg" | "search" => "search.svg",
         "folder" => "folder.svg",
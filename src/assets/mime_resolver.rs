@@ -9,6 +9,14 @@ impl MimeResolver {
         mime_guess::from_path(path).first_or_octet_stream()
     }
 
+    /// What the Type column shows and sorts by: the MIME type, or Folder
+    pub fn type_label(path: &Path, is_dir: bool) -> String {
+        if is_dir {
+            return "Folder".to_string();
+        }
+        Self::get_mime(path).essence_str().to_string()
+    }
+
     pub fn get_icon_name(path: &Path, is_dir: bool) -> String {
         if is_dir {
             return "folder".to_string();
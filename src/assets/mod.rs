@@ -4,4 +4,8 @@ pub mod fonts;
 pub mod icon_cache;
 pub mod icons;
 pub mod mime_resolver;
+pub mod thumbnail_cache;
+pub mod thumbnail_cleanup;
+pub mod thumbnail_pool;
 pub mod thumbnail_worker;
+pub mod thumbnailers;
@@ -0,0 +1,357 @@
+// The thumbnail cache shared with other desktop applications, as laid out
+// by the freedesktop.org Thumbnail Managing Standard: PNGs under
+// $XDG_CACHE_HOME/thumbnails/<size>/ named by the MD5 of the source's URI,
+// with tEXt chunks recording which version of the source they were made
+// from, and empty PNGs under fail/<app>/ for sources that can't be
+// thumbnailed.
+use image::RgbaImage;
+use md5::{Digest, Md5};
+use std::collections::HashMap;
+use std::fs::{self, DirBuilder, File, FileTimes, OpenOptions};
+use std::io::{self, BufReader, BufWriter};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const KEY_URI: &str = "Thumb::URI";
+const KEY_MTIME: &str = "Thumb::MTime";
+const KEY_SIZE: &str = "Thumb::Size";
+
+/// Where this application records its failures, per the spec's
+/// fail/<appname>-<version> convention
+const FAIL_DIR: &str = concat!(env!("CARGO_PKG_NAME"), "-", env!("CARGO_PKG_VERSION"));
+
+/// Size buckets of the spec, each with its own directory
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ThumbnailSize {
+    Normal,
+    Large,
+    XLarge,
+}
+
+impl ThumbnailSize {
+    pub const ALL: [ThumbnailSize; 3] = [Self::Normal, Self::Large, Self::XLarge];
+
+    /// Longest edge in pixels
+    pub fn pixels(self) -> u32 {
+        match self {
+            Self::Normal => 128,
+            Self::Large => 256,
+            Self::XLarge => 512,
+        }
+    }
+
+    pub fn dir_name(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Large => "large",
+            Self::XLarge => "x-large",
+        }
+    }
+
+    /// Smallest bucket that covers a preview `pixels` across
+    pub fn for_pixels(pixels: f32) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|size| size.pixels() as f32 >= pixels)
+            .unwrap_or(Self::XLarge)
+    }
+}
+
+/// What a thumbnail of a source file must match to be current.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceInfo {
+    pub uri: String,
+    /// Modification time in whole seconds since the epoch
+    pub mtime: u64,
+    pub size: u64,
+}
+
+impl SourceInfo {
+    pub fn for_path(path: &Path) -> Option<Self> {
+        let uri = file_uri(path)?;
+        let meta = fs::metadata(path).ok()?;
+        let mtime = meta
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_secs();
+        Some(Self {
+            uri,
+            mtime,
+            size: meta.len(),
+        })
+    }
+
+    fn text_chunks(&self) -> [(&'static str, String); 3] {
+        [
+            (KEY_URI, self.uri.clone()),
+            (KEY_MTIME, self.mtime.to_string()),
+            (KEY_SIZE, self.size.to_string()),
+        ]
+    }
+
+    /// Whether chunks read from a thumbnail describe this version of the
+    /// source. Thumb::Size is optional in the spec, so only checked if set.
+    fn matches(&self, chunks: &HashMap<String, String>) -> bool {
+        chunks.get(KEY_URI) == Some(&self.uri)
+            && chunks.get(KEY_MTIME).and_then(|m| m.trim().parse().ok()) == Some(self.mtime)
+            && chunks
+                .get(KEY_SIZE)
+                .is_none_or(|s| s.trim().parse().ok() == Some(self.size))
+    }
+}
+
+/// Absolute file:// URI of `path`, escaped the way other implementations do
+pub fn file_uri(path: &Path) -> Option<String> {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().ok()?.join(path)
+    };
+    url::Url::from_file_path(path).ok().map(String::from)
+}
+
+/// File name of the thumbnail for `uri`
+pub fn thumbnail_name(uri: &str) -> String {
+    format!("{:x}.png", Md5::digest(uri.as_bytes()))
+}
+
+/// tEXt chunks of a PNG, read from the header up to the image data.
+pub fn read_text_chunks(path: &Path) -> Option<HashMap<String, String>> {
+    let file = File::open(path).ok()?;
+    let reader = png::Decoder::new(BufReader::new(file)).read_info().ok()?;
+    let info = reader.info();
+    Some(
+        info.uncompressed_latin1_text
+            .iter()
+            .map(|chunk| (chunk.keyword.clone(), chunk.text.clone()))
+            .chain(
+                info.utf8_text
+                    .iter()
+                    .filter_map(|chunk| Some((chunk.keyword.clone(), chunk.get_text().ok()?))),
+            )
+            .collect(),
+    )
+}
+
+/// A thumbnail directory tree, normally the user's.
+#[derive(Clone, Debug)]
+pub struct ThumbnailCache {
+    root: PathBuf,
+}
+
+impl ThumbnailCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// $XDG_CACHE_HOME/thumbnails, shared with other applications
+    pub fn user() -> Self {
+        Self::new(
+            dirs::cache_dir()
+                .unwrap_or(PathBuf::from("/tmp"))
+                .join("thumbnails"),
+        )
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn thumbnail_path(&self, uri: &str, size: ThumbnailSize) -> PathBuf {
+        self.root.join(size.dir_name()).join(thumbnail_name(uri))
+    }
+
+    /// This application's failure directories: the current version's and
+    /// those older versions left. Other applications' are theirs to manage.
+    pub fn own_failure_dirs(&self) -> Vec<PathBuf> {
+        let prefix = concat!(env!("CARGO_PKG_NAME"), "-");
+        fs::read_dir(self.root.join("fail"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| {
+                let name = entry.file_name();
+                let version = name.to_str().and_then(|name| name.strip_prefix(prefix));
+                version.is_some_and(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+            })
+            .map(|entry| entry.path())
+            .collect()
+    }
+
+    pub fn failure_path(&self, uri: &str) -> PathBuf {
+        self.root
+            .join("fail")
+            .join(FAIL_DIR)
+            .join(thumbnail_name(uri))
+    }
+
+    /// A current thumbnail of `source` at least `size` across. Larger ones,
+    /// possibly made by other applications, are as good as an exact match.
+    /// Its access time is set, as the cleanup evicts the least recently used.
+    pub fn lookup(&self, source: &SourceInfo, size: ThumbnailSize) -> Option<PathBuf> {
+        let found = ThumbnailSize::ALL
+            .into_iter()
+            .filter(|candidate| candidate.pixels() >= size.pixels())
+            .map(|candidate| self.thumbnail_path(&source.uri, candidate))
+            .find(|path| read_text_chunks(path).is_some_and(|chunks| source.matches(&chunks)))?;
+        let _ = OpenOptions::new()
+            .write(true)
+            .open(&found)
+            .and_then(|file| file.set_times(FileTimes::new().set_accessed(SystemTime::now())));
+        Some(found)
+    }
+
+    /// Whether thumbnailing this version of `source` has already failed
+    pub fn has_failed(&self, source: &SourceInfo) -> bool {
+        read_text_chunks(&self.failure_path(&source.uri))
+            .is_some_and(|chunks| source.matches(&chunks))
+    }
+
+    /// Scales `image` down to `size` if needed and stores it as the
+    /// thumbnail of `source`.
+    pub fn store(
+        &self,
+        source: &SourceInfo,
+        size: ThumbnailSize,
+        image: &RgbaImage,
+    ) -> io::Result<PathBuf> {
+        let max = size.pixels();
+        let scaled;
+        let image = if image.width() > max || image.height() > max {
+            scaled = image::DynamicImage::ImageRgba8(image.clone())
+                .thumbnail(max, max)
+                .into_rgba8();
+            &scaled
+        } else {
+            image
+        };
+        let path = self.thumbnail_path(&source.uri, size);
+        write_png(&path, image, source)?;
+        Ok(path)
+    }
+
+    /// Notes that `source` can't be thumbnailed until it changes
+    pub fn record_failure(&self, source: &SourceInfo) -> io::Result<()> {
+        write_png(
+            &self.failure_path(&source.uri),
+            &RgbaImage::new(1, 1),
+            source,
+        )
+    }
+}
+
+/// Writes `image` with the source's chunks to a temporary file next to
+/// `path` and renames it into place, so readers never see a partial PNG.
+/// The spec asks for private permissions, the source may be private too.
+fn write_png(path: &Path, image: &RgbaImage, source: &SourceInfo) -> io::Result<()> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no parent directory"))?;
+    DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+
+    let tmp = dir.join(format!(
+        ".{}.{}.tmp",
+        path.file_name().unwrap_or_default().to_string_lossy(),
+        std::process::id()
+    ));
+    let result = (|| {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&tmp)?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), image.width(), image.height());
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let chunks = source
+            .text_chunks()
+            .into_iter()
+            .chain([("Software", env!("CARGO_PKG_NAME").to_string())]);
+        for (key, value) in chunks {
+            encoder
+                .add_text_chunk(key.to_string(), value)
+                .map_err(io::Error::other)?;
+        }
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer
+            .write_image_data(image.as_raw())
+            .map_err(io::Error::other)?;
+        writer.finish().map_err(io::Error::other)?;
+        fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(uri: &str, mtime: u64) -> SourceInfo {
+        SourceInfo {
+            uri: uri.to_string(),
+            mtime,
+            size: 42,
+        }
+    }
+
+    #[test]
+    fn test_thumbnail_name() {
+        // The example from the spec
+        assert_eq!(
+            thumbnail_name("file:///home/jens/photos/me.png"),
+            "c6ee772d9e49320e97ec29a7eb5b1697.png"
+        );
+        assert_eq!(
+            file_uri(Path::new("/home/a/My Photos/me.png")).as_deref(),
+            Some("file:///home/a/My%20Photos/me.png")
+        );
+        assert_eq!(ThumbnailSize::for_pixels(96.0), ThumbnailSize::Normal);
+        assert_eq!(ThumbnailSize::for_pixels(200.0), ThumbnailSize::Large);
+        assert_eq!(ThumbnailSize::for_pixels(1024.0), ThumbnailSize::XLarge);
+    }
+
+    #[test]
+    fn test_store_and_revalidate() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ThumbnailCache::new(dir.path().to_path_buf());
+        let photo = source("file:///photo.jpg", 1_700_000_000);
+
+        let stored = cache
+            .store(&photo, ThumbnailSize::Large, &RgbaImage::new(1000, 500))
+            .unwrap();
+        assert_eq!(image::image_dimensions(&stored).unwrap(), (256, 128));
+        assert_eq!(
+            cache.lookup(&photo, ThumbnailSize::Normal),
+            Some(stored.clone())
+        );
+        assert_eq!(cache.lookup(&photo, ThumbnailSize::Large), Some(stored));
+        assert_eq!(cache.lookup(&photo, ThumbnailSize::XLarge), None);
+        // Edited since
+        assert_eq!(
+            cache.lookup(
+                &source("file:///photo.jpg", 1_700_000_001),
+                ThumbnailSize::Large
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_record_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ThumbnailCache::new(dir.path().to_path_buf());
+        let broken = source("file:///broken.jpg", 5);
+
+        assert!(!cache.has_failed(&broken));
+        cache.record_failure(&broken).unwrap();
+        assert!(cache.has_failed(&broken));
+        assert!(!cache.has_failed(&source("file:///broken.jpg", 6)));
+    }
+}
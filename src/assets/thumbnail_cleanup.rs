@@ -0,0 +1,259 @@
+// Keeping the shared thumbnail cache in bounds. Thumbnails of files that
+// no longer exist are pruned, then those unused for longer than the
+// configured age, then the least recently used until the cache fits its
+// size limit. A thumbnail's access time is its last use: lookups set it
+// explicitly, since relatime and noatime mounts rarely update it.
+use crate::assets::thumbnail_cache::{read_text_chunks, ThumbnailCache, ThumbnailSize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// Wait after startup before cleaning, to keep out of the first folder load
+const STARTUP_DELAY: Duration = Duration::from_secs(30);
+
+/// How big and old the cache may get.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CacheLimits {
+    pub max_bytes: u64,
+    /// Thumbnails unused for longer are removed; `None` keeps them
+    pub max_age: Option<Duration>,
+}
+
+/// Thumbnails, and the disk space they take up.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CacheUsage {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+impl CacheUsage {
+    fn add(&mut self, bytes: u64) {
+        self.files += 1;
+        self.bytes += bytes;
+    }
+}
+
+struct CachedFile {
+    path: PathBuf,
+    bytes: u64,
+    last_used: SystemTime,
+}
+
+/// Every file in the size directories and in this application's failure
+/// directories. Other applications' failures are left to them: removing
+/// those would make them retry every broken file.
+fn cached_files(cache: &ThumbnailCache) -> Vec<CachedFile> {
+    let dirs = ThumbnailSize::ALL
+        .into_iter()
+        .map(|size| cache.root().join(size.dir_name()))
+        .chain(cache.own_failure_dirs());
+
+    dirs.filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok().filter(|meta| meta.is_file())?;
+            let last_used = meta
+                .accessed()
+                .or_else(|_| meta.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            Some(CachedFile {
+                path: entry.path(),
+                bytes: meta.len(),
+                last_used,
+            })
+        })
+        .collect()
+}
+
+pub fn usage(cache: &ThumbnailCache) -> CacheUsage {
+    let mut usage = CacheUsage::default();
+    for file in cached_files(cache) {
+        usage.add(file.bytes);
+    }
+    usage
+}
+
+/// Whether the thumbnail at `file` is of a local file that is gone.
+/// Thumbnails of other URIs are left alone.
+fn source_is_gone(file: &CachedFile) -> bool {
+    let uri = read_text_chunks(&file.path)
+        .and_then(|chunks| url::Url::parse(chunks.get("Thumb::URI")?).ok());
+    // Reading it counts as a use under relatime; put its last use back
+    let _ = fs::File::options()
+        .write(true)
+        .open(&file.path)
+        .and_then(|f| f.set_times(fs::FileTimes::new().set_accessed(file.last_used)));
+    uri.filter(|uri| uri.scheme() == "file")
+        .and_then(|uri| uri.to_file_path().ok())
+        .is_some_and(|source| !source.exists())
+}
+
+/// Applies `limits` as of `now`; returns what was removed.
+pub fn clean(cache: &ThumbnailCache, limits: &CacheLimits, now: SystemTime) -> CacheUsage {
+    let mut removed = CacheUsage::default();
+    let mut remove = |file: &CachedFile| {
+        if fs::remove_file(&file.path).is_ok() {
+            removed.add(file.bytes);
+        }
+    };
+
+    let mut kept = Vec::new();
+    for file in cached_files(cache) {
+        let expired = limits.max_age.is_some_and(|age| {
+            now.duration_since(file.last_used)
+                .is_ok_and(|unused| unused > age)
+        });
+        if expired || source_is_gone(&file) {
+            remove(&file);
+        } else {
+            kept.push(file);
+        }
+    }
+
+    // Least recently used first
+    kept.sort_by_key(|file| file.last_used);
+    let mut total: u64 = kept.iter().map(|file| file.bytes).sum();
+    for file in &kept {
+        if total <= limits.max_bytes {
+            break;
+        }
+        remove(file);
+        total -= file.bytes;
+    }
+    removed
+}
+
+/// Removes every thumbnail; returns what was removed.
+pub fn clear(cache: &ThumbnailCache) -> CacheUsage {
+    let mut removed = CacheUsage::default();
+    for file in cached_files(cache) {
+        if fs::remove_file(&file.path).is_ok() {
+            removed.add(file.bytes);
+        }
+    }
+    removed
+}
+
+/// Cleans the user's cache on a thread of its own, a while after startup
+/// and at the lowest CPU priority.
+pub fn spawn_startup_cleanup(limits: CacheLimits) {
+    let spawned = std::thread::Builder::new()
+        .name("thumbnail-cleanup".to_string())
+        .spawn(move || {
+            std::thread::sleep(STARTUP_DELAY);
+            // SAFETY: nice() has no memory effects; on Linux it only lowers
+            // the priority of the calling thread
+            unsafe {
+                nix::libc::nice(19);
+            }
+            let removed = clean(&ThumbnailCache::user(), &limits, SystemTime::now());
+            if removed.files > 0 {
+                println!(
+                    "ThumbnailCleanup: Removed {} thumbnails ({} bytes)",
+                    removed.files, removed.bytes
+                );
+            }
+        });
+    if let Err(e) = spawned {
+        eprintln!("ThumbnailCleanup: Failed to start: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::thumbnail_cache::SourceInfo;
+    use image::RgbaImage;
+    use std::fs::{File, FileTimes};
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// Stores a thumbnail of `source`, last used `days_ago`
+    fn thumbnail(cache: &ThumbnailCache, source: &std::path::Path, now: SystemTime, days_ago: u32) {
+        let info = SourceInfo::for_path(source).unwrap_or(SourceInfo {
+            uri: url::Url::from_file_path(source).unwrap().to_string(),
+            mtime: 0,
+            size: 0,
+        });
+        let thumb = cache
+            .store(&info, ThumbnailSize::Normal, &RgbaImage::new(64, 64))
+            .unwrap();
+        let used = now - DAY * days_ago;
+        File::options()
+            .write(true)
+            .open(thumb)
+            .unwrap()
+            .set_times(FileTimes::new().set_accessed(used))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_clean() {
+        let sources = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ThumbnailCache::new(dir.path().to_path_buf());
+        let now = SystemTime::now();
+        let source = |name: &str| {
+            let path = sources.path().join(name);
+            fs::write(&path, name).unwrap();
+            path
+        };
+
+        thumbnail(&cache, &source("recent.png"), now, 1);
+        thumbnail(&cache, &source("older.png"), now, 20);
+        thumbnail(&cache, &source("ancient.png"), now, 400);
+        thumbnail(&cache, &sources.path().join("deleted.png"), now, 1);
+        let before = usage(&cache);
+        assert_eq!(before.files, 4);
+
+        let unlimited = CacheLimits {
+            max_bytes: u64::MAX,
+            max_age: Some(DAY * 180),
+        };
+        assert_eq!(clean(&cache, &unlimited, now).files, 2);
+        assert_eq!(usage(&cache).files, 2);
+
+        // Over the size limit: the least recently used one goes
+        let recent = SourceInfo::for_path(&sources.path().join("recent.png")).unwrap();
+        let recent_thumb = cache.thumbnail_path(&recent.uri, ThumbnailSize::Normal);
+        let one_file = CacheLimits {
+            max_bytes: fs::metadata(&recent_thumb).unwrap().len(),
+            max_age: None,
+        };
+        assert_eq!(clean(&cache, &one_file, now).files, 1);
+        assert_eq!(
+            cache.lookup(&recent, ThumbnailSize::Normal),
+            Some(recent_thumb)
+        );
+
+        assert_eq!(clear(&cache).files, 1);
+        assert_eq!(usage(&cache), CacheUsage::default());
+    }
+
+    #[test]
+    fn test_other_applications_failures_are_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ThumbnailCache::new(dir.path().to_path_buf());
+        let broken = SourceInfo {
+            uri: "file:///broken.jpg".to_string(),
+            mtime: 5,
+            size: 0,
+        };
+        cache.record_failure(&broken).unwrap();
+        let old_version = cache
+            .root()
+            .join(concat!("fail/", env!("CARGO_PKG_NAME"), "-0.0.1"));
+        let foreign = cache.root().join("fail/gnome-thumbnail-factory");
+        for failures in [&old_version, &foreign] {
+            fs::create_dir_all(failures).unwrap();
+            fs::write(failures.join("0123.png"), b"").unwrap();
+        }
+
+        assert_eq!(usage(&cache).files, 2);
+        assert_eq!(clear(&cache).files, 2);
+        assert!(!cache.has_failed(&broken));
+        assert!(!old_version.join("0123.png").exists());
+        assert!(foreign.join("0123.png").exists());
+    }
+}
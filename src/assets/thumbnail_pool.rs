@@ -0,0 +1,284 @@
+// Thumbnails for the file views, made by a small pool of background
+// workers. Views ask for the thumbnails of the rows they are showing on
+// each render and get back what is already known; everything else is
+// queued behind the most recently shown rows, and rows that scrolled away
+// before a worker got to them are dropped from the queue. What's known is
+// kept in memory, so rendering never waits on the disk.
+use crate::assets::thumbnail_cache::ThumbnailSize;
+use crate::assets::thumbnail_worker::ThumbnailWorker;
+use gpui::{AppContext, AsyncApp, Context, Entity, WeakEntity};
+use lru::LruCache;
+use std::collections::{HashSet, VecDeque};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Most thumbnails made at once
+const MAX_WORKERS: usize = 4;
+/// Thumbnail states remembered, across all folders visited
+const STATE_CAPACITY: usize = 8192;
+
+/// One thumbnail of one version of a file.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ThumbnailKey {
+    pub path: PathBuf,
+    pub size: ThumbnailSize,
+    /// Source modification time, so edited files get a new thumbnail
+    pub modified: SystemTime,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum ThumbnailState {
+    /// Queued or being made
+    Pending,
+    Ready(PathBuf),
+    Failed,
+}
+
+/// A job waiting for a worker, and the view that wants it.
+#[derive(Debug)]
+struct Job {
+    key: ThumbnailKey,
+    owner: u64,
+}
+
+/// Bookkeeping of the pool, apart from the workers themselves.
+pub struct ThumbnailQueue {
+    states: LruCache<ThumbnailKey, ThumbnailState>,
+    /// Waiting jobs, most urgent first
+    queue: VecDeque<Job>,
+    running: HashSet<ThumbnailKey>,
+}
+
+impl Default for ThumbnailQueue {
+    fn default() -> Self {
+        Self {
+            states: LruCache::new(NonZeroUsize::new(STATE_CAPACITY).unwrap()),
+            queue: VecDeque::new(),
+            running: HashSet::new(),
+        }
+    }
+}
+
+impl ThumbnailQueue {
+    /// Thumbnails already made for `wanted`, the entries `owner` is showing
+    /// in display order. The rest go to the front of the queue, and the
+    /// owner's queued jobs that aren't wanted any more are cancelled.
+    pub fn request(&mut self, owner: u64, wanted: &[ThumbnailKey]) -> Vec<Option<PathBuf>> {
+        let wanted_set: HashSet<&ThumbnailKey> = wanted.iter().collect();
+        let mut others = VecDeque::with_capacity(self.queue.len());
+        for job in self.queue.drain(..) {
+            if job.owner != owner {
+                others.push_back(job);
+            } else if !wanted_set.contains(&job.key) {
+                // Forgotten, so it's queued again if it comes back into view
+                self.states.pop(&job.key);
+            }
+        }
+
+        let mut urgent = Vec::new();
+        let found = wanted
+            .iter()
+            .map(|key| match self.states.get(key) {
+                Some(ThumbnailState::Ready(thumb)) => Some(thumb.clone()),
+                Some(ThumbnailState::Failed) => None,
+                Some(ThumbnailState::Pending) => {
+                    if !self.running.contains(key) {
+                        // Another view may have queued it; it moves up
+                        // rather than being made twice
+                        if let Some(index) = others.iter().position(|job| &job.key == key) {
+                            others.remove(index);
+                        }
+                        urgent.push(key.clone());
+                    }
+                    None
+                }
+                None => {
+                    self.states.put(key.clone(), ThumbnailState::Pending);
+                    urgent.push(key.clone());
+                    None
+                }
+            })
+            .collect();
+
+        self.queue = urgent
+            .into_iter()
+            .map(|key| Job { key, owner })
+            .chain(others)
+            .collect();
+        found
+    }
+
+    /// Takes the most urgent job, if a worker is free for it
+    pub fn next_job(&mut self) -> Option<ThumbnailKey> {
+        if self.running.len() >= MAX_WORKERS {
+            return None;
+        }
+        let job = self.queue.pop_front()?;
+        self.running.insert(job.key.clone());
+        Some(job.key)
+    }
+
+    pub fn finish(&mut self, key: ThumbnailKey, thumb: Option<PathBuf>) {
+        self.running.remove(&key);
+        let state = match thumb {
+            Some(thumb) => ThumbnailState::Ready(thumb),
+            None => ThumbnailState::Failed,
+        };
+        self.states.put(key, state);
+    }
+
+    /// Forgets every thumbnail, for when the cache has been cleared. Jobs
+    /// already running still report back.
+    pub fn reset(&mut self) {
+        let running = std::mem::take(&mut self.running);
+        *self = Self {
+            running,
+            ..Self::default()
+        };
+    }
+}
+
+pub struct ThumbnailPool {
+    queue: ThumbnailQueue,
+}
+
+impl ThumbnailPool {
+    pub fn new<T: AppContext>(cx: &mut T) -> Entity<Self> {
+        cx.new(|cx| {
+            // Scan for external thumbnailers before the first render needs them
+            cx.background_executor()
+                .spawn(async { crate::assets::thumbnailers::load() })
+                .detach();
+            Self {
+                queue: ThumbnailQueue::default(),
+            }
+        })
+    }
+
+    /// See `ThumbnailQueue::request`. Views observe the pool to re-render
+    /// as thumbnails come in.
+    pub fn request(
+        &mut self,
+        owner: u64,
+        wanted: &[ThumbnailKey],
+        cx: &mut Context<Self>,
+    ) -> Vec<Option<PathBuf>> {
+        let found = self.queue.request(owner, wanted);
+        self.start_jobs(cx);
+        found
+    }
+
+    pub fn reset(&mut self, cx: &mut Context<Self>) {
+        self.queue.reset();
+        cx.notify();
+    }
+
+    fn start_jobs(&mut self, cx: &mut Context<Self>) {
+        while let Some(key) = self.queue.next_job() {
+            let executor = cx.background_executor().clone();
+            cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+                let cx = cx.clone();
+                async move {
+                    let path = key.path.clone();
+                    let size = key.size;
+                    let thumb = executor
+                        .spawn(async move { ThumbnailWorker::generate_thumbnail(path, size) })
+                        .await;
+                    let _ = cx.update(|cx| {
+                        let _ = this.update(cx, |pool, cx| {
+                            pool.queue.finish(key, thumb);
+                            pool.start_jobs(cx);
+                            cx.notify();
+                        });
+                    });
+                }
+            })
+            .detach();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str) -> ThumbnailKey {
+        ThumbnailKey {
+            path: PathBuf::from(format!("/photos/{}", name)),
+            size: ThumbnailSize::Large,
+            modified: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn test_visible_rows_first() {
+        let mut queue = ThumbnailQueue::default();
+        let page1: Vec<_> = (0..3).map(|i| key(&format!("a{}", i))).collect();
+        let page2: Vec<_> = (0..3).map(|i| key(&format!("b{}", i))).collect();
+
+        assert_eq!(queue.request(1, &page1), vec![None, None, None]);
+        // Scrolled on: the first page's queued jobs are dropped
+        queue.request(1, &page2);
+        let order: Vec<_> = std::iter::from_fn(|| queue.next_job()).collect();
+        assert_eq!(order, page2);
+
+        // Another view's jobs stay queued, behind the latest request
+        let mut queue = ThumbnailQueue::default();
+        queue.request(2, &page1[..1]);
+        queue.request(1, &page2[..1]);
+        assert_eq!(queue.next_job(), Some(page2[0].clone()));
+        assert_eq!(queue.next_job(), Some(page1[0].clone()));
+    }
+
+    #[test]
+    fn test_repeated_request_keeps_jobs() {
+        // A grouped grid asks for all its groups at once; asking again on
+        // the next render keeps every group's jobs, in order
+        let mut queue = ThumbnailQueue::default();
+        let groups: Vec<_> = ["images/a", "images/b", "videos/a", "videos/b"]
+            .iter()
+            .map(|name| key(name))
+            .collect();
+        queue.request(1, &groups);
+        queue.request(1, &groups);
+        let order: Vec<_> = std::iter::from_fn(|| queue.next_job()).collect();
+        assert_eq!(order, groups);
+    }
+
+    #[test]
+    fn test_shared_key_made_once() {
+        // Two views showing the same file
+        let mut queue = ThumbnailQueue::default();
+        let (shared, other) = (key("shared"), key("other"));
+        queue.request(2, &[other.clone(), shared.clone()]);
+        queue.request(1, std::slice::from_ref(&shared));
+        let order: Vec<_> = std::iter::from_fn(|| queue.next_job()).collect();
+        assert_eq!(order, [shared.clone(), other]);
+
+        // Once it's running, neither view queues it again
+        queue.request(2, std::slice::from_ref(&shared));
+        assert_eq!(queue.next_job(), None);
+        queue.finish(shared.clone(), Some(PathBuf::from("/cache/shared.png")));
+        let found = queue.request(2, std::slice::from_ref(&shared));
+        assert_eq!(found, [Some(PathBuf::from("/cache/shared.png"))]);
+    }
+
+    #[test]
+    fn test_worker_cap_and_results() {
+        let mut queue = ThumbnailQueue::default();
+        let keys: Vec<_> = (0..MAX_WORKERS + 2).map(|i| key(&i.to_string())).collect();
+        queue.request(1, &keys);
+
+        let started: Vec<_> = std::iter::from_fn(|| queue.next_job()).collect();
+        assert_eq!(started.len(), MAX_WORKERS);
+        queue.finish(started[0].clone(), Some(PathBuf::from("/cache/0.png")));
+        queue.finish(started[1].clone(), None);
+        assert!(queue.next_job().is_some());
+
+        let found = queue.request(1, &keys[..2]);
+        assert_eq!(found, vec![Some(PathBuf::from("/cache/0.png")), None]);
+        // Failures aren't retried for the same version of the file
+        assert_eq!(queue.next_job(), None);
+    }
+}
@@ -1,62 +1,68 @@
-#![allow(dead_code)]
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
-use std::path::PathBuf;
+use crate::assets::thumbnail_cache::{SourceInfo, ThumbnailCache, ThumbnailSize};
+use crate::assets::thumbnailers;
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use std::path::{Path, PathBuf};
 
 pub struct ThumbnailWorker;
 
 impl ThumbnailWorker {
-    pub fn new() -> Self {
-        Self
+    /// Whether `path` can get a thumbnail: an image the `image` crate
+    /// decodes, or a type an installed external thumbnailer declares.
+    pub fn can_thumbnail(path: &Path) -> bool {
+        image::ImageFormat::from_path(path).is_ok() || thumbnailers::for_path(path).is_some()
     }
 
-    fn cache_path(path: &PathBuf) -> PathBuf {
-        let mut hasher = DefaultHasher::new();
-        path.hash(&mut hasher);
-        let hash = hasher.finish();
-        dirs::cache_dir()
-            .unwrap_or(PathBuf::from("/tmp"))
-            .join("material-3-file-manager/thumbnails")
-            .join(format!("{:x}.jpg", hash))
-    }
-
-    pub fn get_cached_path(path: &PathBuf) -> Option<PathBuf> {
-        let p = Self::cache_path(path);
-        if p.exists() {
-            Some(p)
-        } else {
-            None
+    /// Makes a thumbnail of `path` unless a current one exists or an
+    /// earlier attempt on the same version of the file failed.
+    pub fn generate_thumbnail(path: PathBuf, size: ThumbnailSize) -> Option<PathBuf> {
+        let cache = ThumbnailCache::user();
+        let source = SourceInfo::for_path(&path)?;
+        if let Some(cached) = cache.lookup(&source, size) {
+            return Some(cached);
         }
-    }
-
-    pub fn generate_thumbnail(path: PathBuf) -> Option<PathBuf> {
-        let cache_path = Self::cache_path(&path);
-        // println!("ThumbnailWorker: Check cache for {:?} -> {:?}", path, cache_path);
-
-        if cache_path.exists() {
-            return Some(cache_path);
+        if cache.has_failed(&source) {
+            return None;
         }
 
         println!("ThumbnailWorker: Generating for {:?}", path);
 
-        if let Some(parent) = cache_path.parent() {
-            let _ = std::fs::create_dir_all(parent);
-        }
-
-        match image::open(&path) {
-            Ok(img) => {
-                let thumb = img.thumbnail(256, 256);
-                if let Err(e) = thumb.save(&cache_path) {
-                    println!("ThumbnailWorker: Failed to save thumb: {}", e);
-                    return None;
+        let decoded = if image::ImageFormat::from_path(&path).is_ok() {
+            decode_upright(&path)
+                .map(|img| img.into_rgba8())
+                .map_err(|e| e.to_string())
+        } else if let Some(thumbnailer) = thumbnailers::for_path(&path) {
+            thumbnailer.run(&path, &source, size)
+        } else {
+            return None;
+        };
+        let img = match decoded {
+            Ok(img) => img,
+            Err(e) => {
+                println!("ThumbnailWorker: Failed to thumbnail {:?}: {}", path, e);
+                if let Err(e) = cache.record_failure(&source) {
+                    println!("ThumbnailWorker: Failed to record failure: {}", e);
                 }
-                println!("ThumbnailWorker: Generated {:?}", cache_path);
-                Some(cache_path)
+                return None;
             }
+        };
+        match cache.store(&source, size, &img) {
+            Ok(thumb) => Some(thumb),
             Err(e) => {
-                println!("ThumbnailWorker: Failed to open image {:?}: {}", path, e);
+                println!("ThumbnailWorker: Failed to save thumb: {}", e);
                 None
             }
         }
     }
 }
+
+/// Decodes an image turned the way its EXIF orientation says, as photos
+/// are usually stored the way the camera was held
+fn decode_upright(path: &Path) -> image::ImageResult<DynamicImage> {
+    let mut decoder = ImageReader::open(path)?
+        .with_guessed_format()?
+        .into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut img = DynamicImage::from_decoder(decoder)?;
+    img.apply_orientation(orientation);
+    Ok(img)
+}
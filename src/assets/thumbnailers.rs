@@ -0,0 +1,315 @@
+// External thumbnailers installed by other packages (ffmpegthumbnailer,
+// evince, gnome-font-viewer, ...), registered as *.thumbnailer key files in
+// the thumbnailers directory of each XDG data dir:
+//
+//   [Thumbnailer Entry]
+//   TryExec=evince-thumbnailer
+//   Exec=evince-thumbnailer -s %s %u %o
+//   MimeType=application/pdf;application/x-bzpdf;
+//
+// Each one runs without a shell, with its output going to a private
+// temporary directory that is removed afterwards, and is killed if it
+// doesn't finish in time.
+use crate::assets::mime_resolver::MimeResolver;
+use crate::assets::thumbnail_cache::{SourceInfo, ThumbnailSize};
+use image::RgbaImage;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// How long a thumbnailer may run before it's killed
+pub const THUMBNAILER_TIMEOUT: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// One *.thumbnailer entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Thumbnailer {
+    pub exec: String,
+    pub try_exec: Option<String>,
+    pub mime_types: Vec<String>,
+}
+
+impl Thumbnailer {
+    /// Reads the [Thumbnailer Entry] group of a key file
+    pub fn parse(content: &str) -> Option<Self> {
+        let mut in_entry = false;
+        let mut exec = None;
+        let mut try_exec = None;
+        let mut mime_types = Vec::new();
+        for line in content.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_entry = line == "[Thumbnailer Entry]";
+                continue;
+            }
+            if !in_entry || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key.trim() {
+                "Exec" => exec = Some(value.trim().to_string()),
+                "TryExec" => try_exec = Some(value.trim().to_string()),
+                "MimeType" => {
+                    mime_types = value
+                        .split(';')
+                        .map(|mime| mime.trim().to_lowercase())
+                        .filter(|mime| !mime.is_empty())
+                        .collect()
+                }
+                _ => {}
+            }
+        }
+        Some(Self {
+            exec: exec.filter(|exec| !exec.is_empty())?,
+            try_exec,
+            mime_types,
+        })
+    }
+
+    /// Whether the program it needs is installed
+    fn is_installed(&self) -> bool {
+        let program = match &self.try_exec {
+            Some(program) => program.clone(),
+            None => match split_exec(&self.exec).into_iter().next() {
+                Some(program) => program,
+                None => return false,
+            },
+        };
+        find_program(&program).is_some()
+    }
+
+    /// Runs it on `path` and decodes what it wrote.
+    pub fn run(
+        &self,
+        path: &Path,
+        source: &SourceInfo,
+        size: ThumbnailSize,
+    ) -> Result<RgbaImage, String> {
+        let dir = tempfile::Builder::new()
+            .prefix("m3fm-thumbnailer-")
+            .tempdir()
+            .map_err(|e| format!("No temporary directory: {}", e))?;
+        let output = dir.path().join("thumbnail.png");
+        let args = expand_exec(&self.exec, path, &source.uri, &output, size.pixels());
+        let (program, args) = args.split_first().ok_or("Empty Exec line")?;
+
+        let mut child = Command::new(program)
+            .args(args)
+            .current_dir(dir.path())
+            .env("TMPDIR", dir.path())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+
+        let deadline = Instant::now() + THUMBNAILER_TIMEOUT;
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(format!("{} timed out", program));
+                }
+                Ok(None) => std::thread::sleep(POLL_INTERVAL),
+                Err(e) => return Err(e.to_string()),
+            }
+        };
+        if !status.success() {
+            return Err(format!("{} failed ({})", program, status));
+        }
+        image::open(&output)
+            .map(|img| img.into_rgba8())
+            .map_err(|e| format!("Unreadable output from {}: {}", program, e))
+    }
+}
+
+/// Directories searched, most important first: $XDG_DATA_HOME, then each
+/// of $XDG_DATA_DIRS.
+fn thumbnailer_dirs() -> Vec<PathBuf> {
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or("/usr/local/share:/usr/share".to_string());
+    dirs::data_dir()
+        .into_iter()
+        .chain(std::env::split_paths(&data_dirs))
+        .map(|dir| dir.join("thumbnailers"))
+        .collect()
+}
+
+/// Installed thumbnailers by MIME type, scanned once. An entry in a more
+/// important directory wins over others for the same type.
+fn registry() -> &'static HashMap<String, Thumbnailer> {
+    static REGISTRY: OnceLock<HashMap<String, Thumbnailer>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut registry = HashMap::new();
+        for dir in thumbnailer_dirs() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut files: Vec<PathBuf> = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "thumbnailer"))
+                .collect();
+            files.sort();
+            for file in files {
+                let Some(thumbnailer) = std::fs::read_to_string(&file)
+                    .ok()
+                    .and_then(|content| Thumbnailer::parse(&content))
+                    .filter(Thumbnailer::is_installed)
+                else {
+                    continue;
+                };
+                for mime in &thumbnailer.mime_types {
+                    registry
+                        .entry(mime.clone())
+                        .or_insert_with(|| thumbnailer.clone());
+                }
+            }
+        }
+        registry
+    })
+}
+
+/// Scans for thumbnailers ahead of the first lookup
+pub fn load() {
+    registry();
+}
+
+/// The installed thumbnailer for `path`'s type, if any
+pub fn for_path(path: &Path) -> Option<&'static Thumbnailer> {
+    let mime = MimeResolver::get_mime(path);
+    registry().get(mime.essence_str())
+}
+
+/// Splits an Exec line into arguments, with the key file quoting rules:
+/// double quotes group, and backslash escapes the next character in them.
+pub fn split_exec(exec: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quoted = false;
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_arg = true;
+            }
+            '\\' if quoted => current.extend(chars.next()),
+            c if c.is_whitespace() && !quoted => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// Arguments of `exec` with its field codes filled in: %i the input path,
+/// %u its URI, %o the output path, %s the size and %% a percent sign.
+pub fn expand_exec(exec: &str, input: &Path, uri: &str, output: &Path, size: u32) -> Vec<String> {
+    split_exec(exec)
+        .into_iter()
+        .map(|arg| {
+            let mut expanded = String::new();
+            let mut chars = arg.chars();
+            while let Some(c) = chars.next() {
+                if c != '%' {
+                    expanded.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some('i') => expanded.push_str(&input.to_string_lossy()),
+                    Some('u') => expanded.push_str(uri),
+                    Some('o') => expanded.push_str(&output.to_string_lossy()),
+                    Some('s') => expanded.push_str(&size.to_string()),
+                    Some('%') => expanded.push('%'),
+                    // Unknown codes are dropped, as launchers do
+                    _ => {}
+                }
+            }
+            expanded
+        })
+        .collect()
+}
+
+fn find_program(program: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        return Some(PathBuf::from(program)).filter(|path| path.is_file());
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_thumbnailer() {
+        let entry = Thumbnailer::parse(
+            "[Thumbnailer Entry]\n\
+             TryExec=evince-thumbnailer\n\
+             Exec=evince-thumbnailer -s %s %u %o\n\
+             MimeType=application/pdf;application/x-bzpdf;\n\
+             \n\
+             [Other Group]\n\
+             Exec=ignored\n",
+        )
+        .unwrap();
+        assert_eq!(entry.exec, "evince-thumbnailer -s %s %u %o");
+        assert_eq!(entry.try_exec.as_deref(), Some("evince-thumbnailer"));
+        assert_eq!(
+            entry.mime_types,
+            vec!["application/pdf", "application/x-bzpdf"]
+        );
+        assert_eq!(
+            Thumbnailer::parse("[Thumbnailer Entry]\nMimeType=a/b;\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_expand_exec() {
+        let args = expand_exec(
+            "ffmpegthumbnailer -i %i -o %o -s %s -c png \"--label=%% done\"",
+            Path::new("/videos/my clip.mp4"),
+            "file:///videos/my%20clip.mp4",
+            Path::new("/tmp/out.png"),
+            256,
+        );
+        assert_eq!(
+            args,
+            vec![
+                "ffmpegthumbnailer",
+                "-i",
+                "/videos/my clip.mp4",
+                "-o",
+                "/tmp/out.png",
+                "-s",
+                "256",
+                "-c",
+                "png",
+                "--label=% done",
+            ]
+        );
+        assert_eq!(split_exec(r#"a "b \"c\"" d"#), vec!["a", "b \"c\"", "d"]);
+    }
+}
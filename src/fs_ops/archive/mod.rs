@@ -0,0 +1,234 @@
+// Archive contents for the preview: the top-level entries, totals and
+// compression of ZIP and tar files, plain or compressed. ZIP files are
+// read from their central directory; tar files header by header, stopping
+// after `MAX_ENTRIES` entries or `MAX_UNPACKED` bytes of decompressed data.
+mod tar;
+mod zip;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Entries beyond this many aren't read
+const MAX_ENTRIES: usize = 50_000;
+
+/// How much of a compressed tar is decompressed before giving up
+const MAX_UNPACKED: u64 = 256 * 1024 * 1024;
+
+/// A top-level file or folder; a folder's size is that of its contents
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    /// Whether it is, or contains, an encrypted file
+    pub encrypted: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ArchiveSummary {
+    pub format: &'static str,
+    /// Folders first, then by name
+    pub entries: Vec<ArchiveEntry>,
+    pub entry_count: usize,
+    /// Uncompressed size of all files
+    pub total_size: u64,
+    /// Size of the compressed data; `None` for uncompressed tar files
+    pub compressed_size: Option<u64>,
+    pub encrypted_count: usize,
+    /// Whether reading stopped at a limit, so the totals are a lower bound
+    pub truncated: bool,
+}
+
+impl ArchiveSummary {
+    /// Uncompressed size over compressed size
+    pub fn compression_ratio(&self) -> Option<f64> {
+        let compressed = self.compressed_size.filter(|&size| size > 0)?;
+        (!self.truncated).then(|| self.total_size as f64 / compressed as f64)
+    }
+}
+
+/// Collects entries as they're read and groups them by their first path
+/// component
+#[derive(Default)]
+struct Builder {
+    top_level: HashMap<String, ArchiveEntry>,
+    entry_count: usize,
+    total_size: u64,
+    encrypted_count: usize,
+}
+
+impl Builder {
+    fn add(&mut self, path: &str, is_dir: bool, size: u64, encrypted: bool) {
+        self.entry_count += 1;
+        // Sizes come from the file, so may be anything
+        self.total_size = self.total_size.saturating_add(size);
+        self.encrypted_count += encrypted as usize;
+        let mut components = path
+            .split(['/', '\\'])
+            .filter(|c| !c.is_empty() && *c != ".");
+        let Some(first) = components.next() else {
+            return;
+        };
+        let entry = self
+            .top_level
+            .entry(first.to_string())
+            .or_insert_with(|| ArchiveEntry {
+                name: first.to_string(),
+                is_dir: false,
+                size: 0,
+                encrypted: false,
+            });
+        entry.is_dir |= is_dir || components.next().is_some();
+        entry.size = entry.size.saturating_add(size);
+        entry.encrypted |= encrypted;
+    }
+
+    fn finish(
+        self,
+        format: &'static str,
+        compressed_size: Option<u64>,
+        truncated: bool,
+    ) -> ArchiveSummary {
+        let mut entries: Vec<_> = self.top_level.into_values().collect();
+        entries.sort_by(|a, b| {
+            b.is_dir
+                .cmp(&a.is_dir)
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+        ArchiveSummary {
+            format,
+            entries,
+            entry_count: self.entry_count,
+            total_size: self.total_size,
+            compressed_size,
+            encrypted_count: self.encrypted_count,
+            truncated,
+        }
+    }
+}
+
+/// Whether `path` has the extension of an archive `read` understands
+pub fn is_archive(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let ext = name.rsplit('.').next().unwrap_or_default();
+    matches!(
+        ext,
+        "zip" | "jar" | "apk" | "epub" | "cbz" | "tar" | "tgz" | "tbz2" | "txz" | "tzst"
+    ) || [".tar.gz", ".tar.bz2", ".tar.xz", ".tar.zst"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+}
+
+/// Lists an archive, telling the format by its first bytes
+pub fn read(path: &Path) -> Result<ArchiveSummary, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+    let head = read_at(&mut file, 0, 512).map_err(|e| e.to_string())?;
+
+    if head.starts_with(b"PK\x03\x04") || head.starts_with(b"PK\x05\x06") {
+        zip::read(&mut file, size).map_err(|e| e.to_string())
+    } else if head.starts_with(&[0x1f, 0x8b]) {
+        tar::read_gzip(file, size).map_err(|e| e.to_string())
+    } else if head.get(257..262) == Some(b"ustar") || tar::is_header(&head) {
+        // Before the other compressions' magic, which a name could start with
+        tar::read(file).map_err(|e| e.to_string())
+    } else if head.starts_with(b"BZh") {
+        tar::read_bzip2(file, size).map_err(|e| e.to_string())
+    } else if head.starts_with(b"\xfd7zXZ\0") {
+        tar::read_xz(file, size).map_err(|e| e.to_string())
+    } else if head.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        tar::read_zstd(file, size).map_err(|e| e.to_string())
+    } else {
+        Err("Not a supported archive format".to_string())
+    }
+}
+
+/// Labelled values for display, in a fixed order, leaving out unknowns
+pub fn describe(summary: &ArchiveSummary) -> Vec<(&'static str, String)> {
+    // Counts and sizes are at least this much when reading stopped early
+    let plus = if summary.truncated { "+" } else { "" };
+    let rows = [
+        ("Format", Some(summary.format.to_string())),
+        ("Entries", Some(format!("{}{}", summary.entry_count, plus))),
+        (
+            "Unpacked",
+            Some(format!(
+                "{}{}",
+                human_bytes::human_bytes(summary.total_size as f64),
+                plus
+            )),
+        ),
+        (
+            "Ratio",
+            summary
+                .compression_ratio()
+                .map(|ratio| format!("{:.1}:1", ratio)),
+        ),
+        (
+            "Encrypted",
+            (summary.encrypted_count > 0).then(|| match summary.encrypted_count {
+                1 => "1 entry".to_string(),
+                n => format!("{} entries", n),
+            }),
+        ),
+    ];
+    rows.into_iter()
+        .filter_map(|(label, value)| Some((label, value?)))
+        .collect()
+}
+
+/// Up to `len` bytes from `offset`; fewer at the end of the file
+fn read_at(file: &mut File, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buf = Vec::new();
+    file.take(len).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let mut builder = Builder::default();
+        builder.add("./src/", true, 0, false);
+        builder.add("./src/main.rs", false, 3000, false);
+        builder.add("README", false, 1100, false);
+        builder.add("secret/key", false, 96, true);
+        let summary = builder.finish("ZIP", Some(1024), false);
+
+        let names: Vec<_> = summary
+            .entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.is_dir, e.size))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("secret", true, 96),
+                ("src", true, 3000),
+                ("README", false, 1100)
+            ]
+        );
+        assert!(summary.entries[0].encrypted);
+        assert_eq!(
+            describe(&summary),
+            vec![
+                ("Format", "ZIP".to_string()),
+                ("Entries", "4".to_string()),
+                ("Unpacked", "4.1 KiB".to_string()),
+                ("Ratio", "4.1:1".to_string()),
+                ("Encrypted", "1 entry".to_string()),
+            ]
+        );
+        assert!(is_archive(Path::new("a.tar.gz")) && is_archive(Path::new("b.ZIP")));
+        assert!(is_archive(Path::new("c.tar.xz")) && is_archive(Path::new("d.TZST")));
+        assert!(!is_archive(Path::new("notes.gz")) && !is_archive(Path::new("e.xz")));
+    }
+}
@@ -0,0 +1,286 @@
+// Tar, plain or compressed with gzip, bzip2, xz or Zstandard: 512-byte
+// headers, each followed by the entry's data padded to a whole block. Plain
+// files skip the data by seeking; compressed ones have to decompress it, up
+// to `MAX_UNPACKED`.
+// GNU long names and PAX path and size records are followed.
+use super::{ArchiveSummary, Builder, MAX_ENTRIES, MAX_UNPACKED};
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use xz2::bufread::XzDecoder;
+
+const BLOCK: u64 = 512;
+
+/// GNU long names and PAX records longer than this are skipped
+const MAX_NAME: u64 = 64 * 1024;
+
+pub fn read(mut file: File) -> io::Result<ArchiveSummary> {
+    file.rewind()?;
+    let skip = |file: &mut File, len: u64| {
+        let len = i64::try_from(len).unwrap_or(i64::MAX);
+        file.seek(SeekFrom::Current(len)).map(|_| ())
+    };
+    let (builder, truncated) = list(&mut file, skip, None)?;
+    Ok(builder.finish("Tar", None, truncated))
+}
+
+pub fn read_gzip(mut file: File, size: u64) -> io::Result<ArchiveSummary> {
+    file.rewind()?;
+    let decoder = MultiGzDecoder::new(BufReader::new(file));
+    read_compressed(decoder, "Tar (gzip)", size)
+}
+
+pub fn read_bzip2(mut file: File, size: u64) -> io::Result<ArchiveSummary> {
+    file.rewind()?;
+    let decoder = MultiBzDecoder::new(BufReader::new(file));
+    read_compressed(decoder, "Tar (bzip2)", size)
+}
+
+pub fn read_xz(mut file: File, size: u64) -> io::Result<ArchiveSummary> {
+    file.rewind()?;
+    let decoder = XzDecoder::new_multi_decoder(BufReader::new(file));
+    read_compressed(decoder, "Tar (xz)", size)
+}
+
+pub fn read_zstd(mut file: File, size: u64) -> io::Result<ArchiveSummary> {
+    file.rewind()?;
+    let decoder = zstd::stream::read::Decoder::new(file)?;
+    read_compressed(decoder, "Tar (Zstandard)", size)
+}
+
+/// `size` is that of the compressed file
+fn read_compressed<R: Read>(
+    mut decoder: R,
+    format: &'static str,
+    size: u64,
+) -> io::Result<ArchiveSummary> {
+    let skip =
+        |decoder: &mut R, len: u64| io::copy(&mut decoder.take(len), &mut io::sink()).map(|_| ());
+    let (builder, truncated) = list(&mut decoder, skip, Some(MAX_UNPACKED))?;
+    Ok(builder.finish(format, Some(size), truncated))
+}
+
+/// Whether `block` is a tar header, by its checksum: older tar files have
+/// no magic
+pub fn is_header(block: &[u8]) -> bool {
+    let Some(block) = block.get(..BLOCK as usize) else {
+        return false;
+    };
+    let sum: u64 = block
+        .iter()
+        .enumerate()
+        .map(|(i, &b)| if (148..156).contains(&i) { b' ' } else { b } as u64)
+        .sum();
+    octal(&block[148..156]) == Some(sum)
+}
+
+/// A numeric field: octal text, or big endian binary when the top bit of
+/// the first byte is set
+fn octal(field: &[u8]) -> Option<u64> {
+    if field.first()? & 0x80 != 0 {
+        let value = field[1..]
+            .iter()
+            .fold(0u64, |value, &b| value << 8 | b as u64);
+        return Some(value);
+    }
+    let text = std::str::from_utf8(field).ok()?;
+    let text = text.trim_matches(|c: char| c == '\0' || c == ' ');
+    u64::from_str_radix(text, 8).ok()
+}
+
+fn text(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// The entries of a tar stream, and whether reading stopped at a limit.
+/// `limit` caps the bytes read from `reader`, headers and data together.
+fn list<R: Read>(
+    reader: &mut R,
+    skip: impl Fn(&mut R, u64) -> io::Result<()>,
+    limit: Option<u64>,
+) -> io::Result<(Builder, bool)> {
+    let mut builder = Builder::default();
+    let mut position = 0u64;
+    // Set by a GNU long name or PAX header for the entry after it
+    let mut next_name = None;
+    let mut next_size = None;
+    let mut header = [0u8; BLOCK as usize];
+    loop {
+        if builder.entry_count == MAX_ENTRIES || limit.is_some_and(|limit| position > limit) {
+            return Ok((builder, true));
+        }
+        if let Err(e) = reader.read_exact(&mut header) {
+            // Some writers leave out the end-of-archive blocks
+            return match e.kind() {
+                io::ErrorKind::UnexpectedEof if position > 0 => Ok((builder, false)),
+                _ => Err(e),
+            };
+        }
+        position = position.saturating_add(BLOCK);
+        if header.iter().all(|&b| b == 0) {
+            return Ok((builder, false));
+        }
+        if !is_header(&header) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Damaged tar header",
+            ));
+        }
+
+        let header_size = octal(&header[124..136]).unwrap_or(0);
+        // A PAX size record stands in for the size of the entry after it
+        let size = match header[156] {
+            b'L' | b'K' | b'x' | b'g' => header_size,
+            _ => next_size.take().unwrap_or(header_size),
+        };
+        let padded = size.div_ceil(BLOCK).saturating_mul(BLOCK);
+        position = position.saturating_add(padded);
+        match header[156] {
+            // Long name, PAX record for the next entry, PAX global record
+            kind @ (b'L' | b'x') if size <= MAX_NAME => {
+                let mut data = vec![0; padded as usize];
+                reader.read_exact(&mut data)?;
+                data.truncate(size as usize);
+                if kind == b'L' {
+                    next_name = Some(text(&data));
+                } else {
+                    for (key, value) in pax_records(&data) {
+                        match key {
+                            "path" => next_name = Some(value.to_string()),
+                            "size" => next_size = value.parse().ok(),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            b'L' | b'K' | b'x' | b'g' => skip(reader, padded)?,
+            kind => {
+                let mut name = text(&header[..100]);
+                if &header[257..262] == b"ustar" {
+                    let prefix = text(&header[345..500]);
+                    if !prefix.is_empty() {
+                        name = format!("{}/{}", prefix, name);
+                    }
+                }
+                let name = next_name.take().unwrap_or(name);
+                let is_dir = kind == b'5' || name.ends_with('/');
+                // Only regular files have their size counted
+                let counted = matches!(kind, b'0' | b'\0' | b'7');
+                builder.add(&name, is_dir, if counted { size } else { 0 }, false);
+                skip(reader, padded)?;
+            }
+        }
+    }
+}
+
+/// The "length key=value\n" records of a PAX header
+fn pax_records(data: &[u8]) -> Vec<(&str, &str)> {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter_map(|line| {
+            let (_, record) = line.split_once(' ')?;
+            record.split_once('=')
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fs_ops::archive;
+    use bzip2::write::BzEncoder;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    use xz2::write::XzEncoder;
+
+    fn tar_entry(out: &mut Vec<u8>, name: &str, kind: u8, data: &[u8]) {
+        let mut header = [0u8; 512];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..107].copy_from_slice(b"0000644");
+        header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+        header[156] = kind;
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[148..156].fill(b' ');
+        let sum: u32 = header.iter().map(|&b| b as u32).sum();
+        header[148..155].copy_from_slice(format!("{:06o}\0", sum).as_bytes());
+        out.extend(header);
+        out.extend(data);
+        out.resize(out.len().div_ceil(512) * 512, 0);
+    }
+
+    #[test]
+    fn test_read_tar() {
+        let long_name = format!("project/{}.txt", "n".repeat(120));
+        let mut tar = Vec::new();
+        tar_entry(&mut tar, "project/", b'5', b"");
+        tar_entry(&mut tar, "././@LongLink", b'L', long_name.as_bytes());
+        tar_entry(&mut tar, "project/nnnn", b'0', &[7; 700]);
+        tar_entry(&mut tar, "notes.txt", b'0', b"hello");
+        tar.extend([0; 1024]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("a.tar");
+        std::fs::write(&plain, &tar).unwrap();
+        let summary = archive::read(&plain).unwrap();
+        assert_eq!(summary.format, "Tar");
+        assert_eq!((summary.entry_count, summary.total_size), (3, 705));
+        assert_eq!(summary.compression_ratio(), None);
+        let top: Vec<_> = summary
+            .entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.is_dir, e.size))
+            .collect();
+        assert_eq!(top, vec![("project", true, 700), ("notes.txt", false, 5)]);
+
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&tar).unwrap();
+        let gzip = dir.path().join("a.tar.gz");
+        std::fs::write(&gzip, encoder.finish().unwrap()).unwrap();
+        let summary = archive::read(&gzip).unwrap();
+        assert_eq!(summary.format, "Tar (gzip)");
+        assert_eq!(summary.total_size, 705);
+        assert!(summary.compression_ratio().unwrap() > 1.0);
+
+        let compressed = [
+            ("a.tar.bz2", "Tar (bzip2)", {
+                let mut encoder = BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                encoder.write_all(&tar).unwrap();
+                encoder.finish().unwrap()
+            }),
+            ("a.tar.xz", "Tar (xz)", {
+                let mut encoder = XzEncoder::new(Vec::new(), 6);
+                encoder.write_all(&tar).unwrap();
+                encoder.finish().unwrap()
+            }),
+            (
+                "a.tar.zst",
+                "Tar (Zstandard)",
+                zstd::encode_all(&tar[..], 0).unwrap(),
+            ),
+        ];
+        for (name, format, data) in compressed {
+            let path = dir.path().join(name);
+            std::fs::write(&path, data).unwrap();
+            assert!(archive::is_archive(&path));
+            let summary = archive::read(&path).unwrap();
+            assert_eq!(summary.format, format);
+            assert_eq!((summary.entry_count, summary.total_size), (3, 705));
+            assert_eq!(summary.entries.len(), 2);
+            assert!(summary.compression_ratio().unwrap() > 1.0);
+        }
+
+        // A large file's size is in its PAX record, not its header
+        let mut tar = Vec::new();
+        tar_entry(&mut tar, "PaxHeaders/big.bin", b'x', b"13 size=1000\n");
+        tar_entry(&mut tar, "big.bin", b'0', b"");
+        tar.extend([9; 1024]);
+        tar_entry(&mut tar, "after.txt", b'0', b"hello");
+        tar.extend([0; 1024]);
+        std::fs::write(&plain, &tar).unwrap();
+        let summary = archive::read(&plain).unwrap();
+        assert_eq!((summary.entry_count, summary.total_size), (2, 1005));
+    }
+}
@@ -0,0 +1,204 @@
+// ZIP and the formats built on it (JAR, APK, EPUB, CBZ): the end of central
+// directory record, found by scanning back over the comment, then the
+// central directory itself. ZIP64 sizes and offsets are followed.
+use super::{read_at, ArchiveSummary, Builder, MAX_ENTRIES};
+use std::fs::File;
+use std::io;
+
+const END_SIGNATURE: u32 = 0x0605_4b50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const ZIP64_END_SIGNATURE: u32 = 0x0606_4b50;
+const ENTRY_SIGNATURE: u32 = 0x0201_4b50;
+
+/// The end record and the longest comment it may be followed by
+const END_SEARCH: u64 = 22 + 0xffff;
+
+/// Central directories larger than this are read only this far
+const MAX_DIRECTORY: u64 = 64 * 1024 * 1024;
+
+fn le_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn le_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le_u64(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(at..at + 8)?.try_into().ok()?))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+pub fn read(file: &mut File, size: u64) -> io::Result<ArchiveSummary> {
+    let tail_start = size.saturating_sub(END_SEARCH);
+    let tail = read_at(file, tail_start, END_SEARCH)?;
+    let end = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|&i| le_u32(&tail, i) == Some(END_SIGNATURE))
+        .ok_or_else(|| invalid("No ZIP central directory"))?;
+    let record = &tail[end..];
+    let mut count = le_u16(record, 10).unwrap_or(0) as u64;
+    let mut directory_size = le_u32(record, 12).unwrap_or(0) as u64;
+    let mut directory_offset = le_u32(record, 16).unwrap_or(0) as u64;
+
+    // Values that don't fit are in the ZIP64 record the locator points at
+    let zip64 = count == 0xffff || directory_size == 0xffff_ffff || directory_offset == 0xffff_ffff;
+    if zip64 && end >= 20 && le_u32(&tail, end - 20) == Some(ZIP64_LOCATOR_SIGNATURE) {
+        let record_offset = le_u64(&tail, end - 12).unwrap_or(0);
+        let record = read_at(file, record_offset, 56)?;
+        if le_u32(&record, 0) == Some(ZIP64_END_SIGNATURE) {
+            count = le_u64(&record, 32).unwrap_or(count);
+            directory_size = le_u64(&record, 40).unwrap_or(directory_size);
+            directory_offset = le_u64(&record, 48).unwrap_or(directory_offset);
+        }
+    }
+
+    let directory = read_at(file, directory_offset, directory_size.min(MAX_DIRECTORY))?;
+    let mut builder = Builder::default();
+    let mut compressed = 0u64;
+    let mut pos = 0;
+    let mut truncated = directory_size > MAX_DIRECTORY;
+    for _ in 0..count {
+        if builder.entry_count == MAX_ENTRIES {
+            truncated = true;
+            break;
+        }
+        let Some(entry) = parse_entry(&directory[pos.min(directory.len())..]) else {
+            // Cut off at MAX_DIRECTORY, or damaged
+            truncated = true;
+            break;
+        };
+        builder.add(&entry.name, entry.is_dir, entry.size, entry.encrypted);
+        compressed = compressed.saturating_add(entry.compressed_size);
+        pos += entry.len;
+    }
+    Ok(builder.finish("ZIP", Some(compressed), truncated))
+}
+
+struct Entry {
+    name: String,
+    is_dir: bool,
+    size: u64,
+    compressed_size: u64,
+    encrypted: bool,
+    /// Length of the header with its name, extra field and comment
+    len: usize,
+}
+
+fn parse_entry(data: &[u8]) -> Option<Entry> {
+    if le_u32(data, 0)? != ENTRY_SIGNATURE {
+        return None;
+    }
+    let flags = le_u16(data, 8)?;
+    let mut compressed_size = le_u32(data, 20)? as u64;
+    let mut size = le_u32(data, 24)? as u64;
+    let name_len = le_u16(data, 28)? as usize;
+    let extra_len = le_u16(data, 30)? as usize;
+    let comment_len = le_u16(data, 32)? as usize;
+    let name = data.get(46..46 + name_len)?;
+    let extra = data.get(46 + name_len..46 + name_len + extra_len)?;
+
+    // The ZIP64 extra field holds the sizes that didn't fit, in order
+    let mut pos = 0;
+    while let (Some(id), Some(len)) = (le_u16(extra, pos), le_u16(extra, pos + 2)) {
+        let field = extra.get(pos + 4..pos + 4 + len as usize)?;
+        if id == 1 {
+            let mut values = field.chunks_exact(8).map(|v| le_u64(v, 0));
+            if size == 0xffff_ffff {
+                size = values.next().flatten()?;
+            }
+            if compressed_size == 0xffff_ffff {
+                compressed_size = values.next().flatten()?;
+            }
+        }
+        pos += 4 + len as usize;
+    }
+
+    // Names are UTF-8 when flag 11 says so, and usually ASCII otherwise
+    let name = String::from_utf8_lossy(name).into_owned();
+    Some(Entry {
+        is_dir: name.ends_with('/'),
+        name,
+        size,
+        compressed_size,
+        encrypted: flags & 1 != 0,
+        len: 46 + name_len + extra_len + comment_len,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fs_ops::archive;
+
+    /// A stored file's local header and data, and its central directory
+    /// entry for `offset`
+    fn zip_entry(name: &str, data: &[u8], flags: u16, offset: u32) -> (Vec<u8>, Vec<u8>) {
+        let fields = |out: &mut Vec<u8>| {
+            out.extend(flags.to_le_bytes());
+            // Stored, no time, no CRC
+            out.extend([0; 10]);
+            out.extend((data.len() as u32).to_le_bytes());
+            out.extend((data.len() as u32).to_le_bytes());
+            out.extend((name.len() as u16).to_le_bytes());
+            out.extend([0, 0]);
+        };
+        let mut local = b"PK\x03\x04\x14\x00".to_vec();
+        fields(&mut local);
+        local.extend(name.as_bytes());
+        local.extend(data);
+
+        let mut central = b"PK\x01\x02\x14\x00\x14\x00".to_vec();
+        fields(&mut central);
+        // Comment length, disk, attributes
+        central.extend([0; 10]);
+        central.extend(offset.to_le_bytes());
+        central.extend(name.as_bytes());
+        (local, central)
+    }
+
+    #[test]
+    fn test_read_zip() {
+        let mut file = Vec::new();
+        let mut directory = Vec::new();
+        for (name, data, flags) in [
+            ("docs/", &b""[..], 0),
+            ("docs/a.txt", b"hello", 0),
+            ("locked.bin", b"0123456789", 1),
+        ] {
+            let (local, central) = zip_entry(name, data, flags, file.len() as u32);
+            file.extend(local);
+            directory.extend(central);
+        }
+        let directory_offset = file.len() as u32;
+        file.extend(&directory);
+        file.extend(b"PK\x05\x06\0\0\0\0\x03\0\x03\0");
+        file.extend((directory.len() as u32).to_le_bytes());
+        file.extend(directory_offset.to_le_bytes());
+        file.extend(7u16.to_le_bytes());
+        file.extend(b"comment");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.zip");
+        std::fs::write(&path, file).unwrap();
+        let summary = archive::read(&path).unwrap();
+
+        assert_eq!(summary.format, "ZIP");
+        assert_eq!(summary.entry_count, 3);
+        assert_eq!(summary.total_size, 15);
+        assert_eq!(summary.encrypted_count, 1);
+        assert!(!summary.truncated);
+        assert_eq!(summary.compression_ratio(), Some(1.0));
+        let top: Vec<_> = summary
+            .entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.is_dir, e.encrypted))
+            .collect();
+        assert_eq!(
+            top,
+            vec![("docs", true, false), ("locked.bin", false, true)]
+        );
+    }
+}
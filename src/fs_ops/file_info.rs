@@ -0,0 +1,162 @@
+// Extra file attributes shown by the details view: timestamps, permissions,
+// ownership and symlink targets. Captured from the metadata the scanner
+// already reads, so listing a directory doesn't cost extra syscalls.
+use nix::unistd::{Gid, Group, Uid, User};
+use std::collections::BTreeMap;
+use std::fs::Metadata;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FileAttributes {
+    /// Not every filesystem records a birth time
+    pub created: Option<SystemTime>,
+    pub accessed: Option<SystemTime>,
+    /// st_mode, including the file type bits
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    /// Set for symlinks
+    pub link_target: Option<PathBuf>,
+}
+
+impl FileAttributes {
+    /// `metadata` follows symlinks; `is_symlink` tells whether `path` itself is one.
+    pub fn from_metadata(path: &Path, metadata: &Metadata, is_symlink: bool) -> Self {
+        Self {
+            created: metadata.created().ok(),
+            accessed: metadata.accessed().ok(),
+            mode: metadata.mode(),
+            uid: metadata.uid(),
+            gid: metadata.gid(),
+            link_target: if is_symlink {
+                std::fs::read_link(path).ok()
+            } else {
+                None
+            },
+        }
+    }
+
+    /// `ls -l` style, e.g. "drwxr-xr-x" or "lrwxrwxrwx"
+    pub fn permissions_string(&self, is_dir: bool) -> String {
+        let kind = if self.link_target.is_some() {
+            'l'
+        } else if is_dir {
+            'd'
+        } else {
+            '-'
+        };
+        let mut out = String::with_capacity(10);
+        out.push(kind);
+        for shift in [6, 3, 0] {
+            let bits = (self.mode >> shift) & 0o7;
+            out.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+            out.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+            out.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+        }
+        out
+    }
+
+    pub fn owner_name(&self) -> &'static str {
+        static USERS: IdNames = Mutex::new(BTreeMap::new());
+        id_name(&USERS, self.uid, |uid| {
+            User::from_uid(Uid::from_raw(uid))
+                .ok()
+                .flatten()
+                .map(|u| u.name)
+        })
+    }
+
+    pub fn group_name(&self) -> &'static str {
+        static GROUPS: IdNames = Mutex::new(BTreeMap::new());
+        id_name(&GROUPS, self.gid, |gid| {
+            Group::from_gid(Gid::from_raw(gid))
+                .ok()
+                .flatten()
+                .map(|g| g.name)
+        })
+    }
+}
+
+/// Names looked up so far. A system has few accounts, so they're kept for
+/// good and can be handed out without copying.
+type IdNames = Mutex<BTreeMap<u32, &'static str>>;
+
+/// The account name of `id`, asking NSS (so LDAP etc. users show too) the
+/// first time. Unknown ids show as the number.
+fn id_name(names: &IdNames, id: u32, lookup: impl FnOnce(u32) -> Option<String>) -> &'static str {
+    let mut names = names.lock().unwrap_or_else(|e| e.into_inner());
+    names.entry(id).or_insert_with(|| {
+        let name = lookup(id).unwrap_or_else(|| id.to_string());
+        Box::leak(name.into_boxed_str())
+    })
+}
+
+/// Number of entries in a directory, None if it can't be read
+pub fn child_count(path: &Path) -> Option<usize> {
+    std::fs::read_dir(path).ok().map(|entries| entries.count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_permissions_string() {
+        let attributes = FileAttributes {
+            mode: 0o100644,
+            ..Default::default()
+        };
+        assert_eq!(attributes.permissions_string(false), "-rw-r--r--");
+        let attributes = FileAttributes {
+            mode: 0o40755,
+            ..Default::default()
+        };
+        assert_eq!(attributes.permissions_string(true), "drwxr-xr-x");
+    }
+
+    #[test]
+    fn test_id_names() {
+        let attributes = FileAttributes {
+            uid: 0,
+            gid: 0,
+            ..Default::default()
+        };
+        assert_eq!(attributes.owner_name(), "root");
+        assert_eq!(attributes.group_name(), "root");
+
+        // Looked up once, then the same name every time
+        let names = Mutex::new(BTreeMap::new());
+        let first = id_name(&names, 42, |_| Some("alice".to_string()));
+        let again = id_name(&names, 42, |_| unreachable!());
+        assert!(std::ptr::eq(first, again));
+        assert_eq!(id_name(&names, 43, |_| None), "43");
+    }
+
+    #[test]
+    fn test_from_metadata() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("script.sh");
+        std::fs::write(&file, "#!/bin/sh").unwrap();
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o750)).unwrap();
+        let link = temp_dir.path().join("link");
+        std::os::unix::fs::symlink(&file, &link).unwrap();
+
+        let metadata = std::fs::metadata(&file).unwrap();
+        let attributes = FileAttributes::from_metadata(&file, &metadata, false);
+        assert_eq!(attributes.permissions_string(false), "-rwxr-x---");
+        assert!(attributes.link_target.is_none());
+
+        let metadata = std::fs::metadata(&link).unwrap();
+        let attributes = FileAttributes::from_metadata(&link, &metadata, true);
+        assert_eq!(attributes.link_target.as_deref(), Some(file.as_path()));
+        assert!(attributes.permissions_string(false).starts_with('l'));
+
+        assert_eq!(child_count(temp_dir.path()), Some(2));
+        assert_eq!(child_count(&file), None);
+    }
+}
@@ -0,0 +1,523 @@
+// What a file is, from its signature rather than its name: executables
+// (ELF, PE, Mach-O) with their architecture, PNG images with their chunks,
+// and the magic numbers of common archive, document and media formats.
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// How much of the file's start is looked at
+const HEAD_LEN: u64 = 64 * 1024;
+
+/// PNG chunks beyond this many aren't listed
+const MAX_PNG_CHUNKS: usize = 1000;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Signature {
+    /// What the file is, e.g. "ELF executable"
+    pub kind: String,
+    /// Labelled details, in the order they're shown
+    pub details: Vec<(&'static str, String)>,
+}
+
+impl Signature {
+    fn new(kind: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            details: Vec::new(),
+        }
+    }
+
+    fn with(mut self, label: &'static str, value: impl Into<String>) -> Self {
+        self.details.push((label, value.into()));
+        self
+    }
+}
+
+/// The signature of the file at `path`; `None` if it isn't recognised
+pub fn detect(path: &Path) -> io::Result<Option<Signature>> {
+    let mut file = File::open(path)?;
+    let head = read_at(&mut file, 0, HEAD_LEN)?;
+    if head.starts_with(b"\x7fELF") {
+        return Ok(elf(&head));
+    }
+    if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return png(&mut file).map(Some);
+    }
+    if head.starts_with(b"MZ") {
+        return Ok(Some(
+            pe(&head).unwrap_or_else(|| Signature::new("MS-DOS executable")),
+        ));
+    }
+    if let Some(signature) = mach_o(&head) {
+        return Ok(Some(signature));
+    }
+    Ok(simple(&head))
+}
+
+/// Labelled values for display: the kind, then its details
+pub fn describe(signature: &Signature) -> Vec<(&'static str, String)> {
+    std::iter::once(("Type", signature.kind.clone()))
+        .chain(signature.details.iter().cloned())
+        .collect()
+}
+
+/// Reads integers of either byte order, bounds-checked
+#[derive(Clone, Copy)]
+struct Reader<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl Reader<'_> {
+    fn u16(&self, at: usize) -> Option<u16> {
+        let bytes = self.data.get(at..at + 2)?.try_into().ok()?;
+        Some(match self.big_endian {
+            true => u16::from_be_bytes(bytes),
+            false => u16::from_le_bytes(bytes),
+        })
+    }
+
+    fn u32(&self, at: usize) -> Option<u32> {
+        let bytes = self.data.get(at..at + 4)?.try_into().ok()?;
+        Some(match self.big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        })
+    }
+
+    fn u64(&self, at: usize) -> Option<u64> {
+        let bytes = self.data.get(at..at + 8)?.try_into().ok()?;
+        Some(match self.big_endian {
+            true => u64::from_be_bytes(bytes),
+            false => u64::from_le_bytes(bytes),
+        })
+    }
+}
+
+fn elf_machine(machine: u16) -> String {
+    match machine {
+        0x02 => "SPARC",
+        0x03 => "x86",
+        0x08 => "MIPS",
+        0x14 => "PowerPC",
+        0x15 => "PowerPC64",
+        0x16 => "IBM S/390",
+        0x28 => "ARM",
+        0x2b => "SPARC V9",
+        0x32 => "IA-64",
+        0x3e => "x86-64",
+        0xb7 => "AArch64",
+        0xf3 => "RISC-V",
+        0xf7 => "BPF",
+        0x102 => "LoongArch",
+        other => return format!("Unknown ({:#x})", other),
+    }
+    .to_string()
+}
+
+fn elf(head: &[u8]) -> Option<Signature> {
+    let is_64 = match head.get(4)? {
+        1 => false,
+        2 => true,
+        _ => return None,
+    };
+    let big_endian = *head.get(5)? == 2;
+    let r = Reader {
+        data: head,
+        big_endian,
+    };
+    let word = |at32: usize, at64: usize| match is_64 {
+        true => r.u64(at64),
+        false => r.u32(at32).map(u64::from),
+    };
+
+    // Program headers: an interpreter makes a shared object a PIE executable
+    let ph_offset = word(28, 32)? as usize;
+    let (ph_size, ph_count) = match is_64 {
+        true => (r.u16(54)?, r.u16(56)?),
+        false => (r.u16(42)?, r.u16(44)?),
+    };
+    let interpreter = (0..ph_count as usize).find_map(|i| {
+        let header = ph_offset + i * ph_size as usize;
+        // PT_INTERP
+        if r.u32(header)? != 3 {
+            return None;
+        }
+        let (offset, size) = match is_64 {
+            true => (r.u64(header + 8)?, r.u64(header + 32)?),
+            false => (r.u32(header + 4)? as u64, r.u32(header + 16)? as u64),
+        };
+        let path = head.get(offset as usize..(offset + size) as usize)?;
+        Some(
+            String::from_utf8_lossy(path)
+                .trim_end_matches('\0')
+                .to_string(),
+        )
+    });
+
+    let kind = match (r.u16(16)?, &interpreter) {
+        (1, _) => "ELF relocatable object",
+        (2, _) => "ELF executable",
+        (3, Some(_)) => "ELF position-independent executable",
+        (3, None) => "ELF shared object",
+        (4, _) => "ELF core dump",
+        _ => "ELF file",
+    };
+    let mut signature = Signature::new(kind)
+        .with("Architecture", elf_machine(r.u16(18)?))
+        .with("Class", if is_64 { "64-bit" } else { "32-bit" })
+        .with(
+            "Byte order",
+            if big_endian {
+                "Big endian"
+            } else {
+                "Little endian"
+            },
+        );
+    let entry = word(24, 24)?;
+    if entry != 0 {
+        signature = signature.with("Entry point", format!("{:#x}", entry));
+    }
+    if let Some(interpreter) = interpreter {
+        signature = signature.with("Interpreter", interpreter);
+    }
+    Some(signature)
+}
+
+/// Header details and the chunk types in order, with runs of the same type
+/// counted, e.g. "IHDR, IDAT ×12, IEND"
+fn png(file: &mut File) -> io::Result<Signature> {
+    let mut chunks: Vec<([u8; 4], usize)> = Vec::new();
+    let mut header = None;
+    let mut animated = false;
+    let mut offset = 8;
+    let mut complete = false;
+    while chunks.len() < MAX_PNG_CHUNKS {
+        let bytes = read_at(file, offset, 8 + 13)?;
+        let (Some(len), Some(kind)) = (bytes.get(..4), bytes.get(4..8)) else {
+            break;
+        };
+        let len = u32::from_be_bytes(len.try_into().unwrap_or_default()) as u64;
+        let kind: [u8; 4] = kind.try_into().unwrap_or_default();
+        match &kind {
+            b"IHDR" => header = bytes.get(8..21).map(<[u8]>::to_vec),
+            b"acTL" => animated = true,
+            _ => {}
+        }
+        match chunks.last_mut() {
+            Some((last, count)) if *last == kind => *count += 1,
+            _ => chunks.push((kind, 1)),
+        }
+        if &kind == b"IEND" {
+            complete = true;
+            break;
+        }
+        // Length, type, data and CRC
+        offset += 12 + len;
+    }
+
+    let mut signature = Signature::new(if animated {
+        "Animated PNG image"
+    } else {
+        "PNG image"
+    });
+    if let Some(ihdr) = header {
+        let r = Reader {
+            data: &ihdr,
+            big_endian: true,
+        };
+        let color = match ihdr[9] {
+            0 => "Grayscale",
+            2 => "RGB",
+            3 => "Indexed",
+            4 => "Grayscale with alpha",
+            6 => "RGBA",
+            _ => "Unknown",
+        };
+        signature = signature
+            .with(
+                "Dimensions",
+                format!("{}x{}", r.u32(0).unwrap_or(0), r.u32(4).unwrap_or(0)),
+            )
+            .with("Color", format!("{}, {}-bit", color, ihdr[8]))
+            .with("Interlaced", if ihdr[12] == 1 { "Yes" } else { "No" });
+    }
+    let mut list = chunks
+        .iter()
+        .map(|(kind, count)| {
+            let kind = String::from_utf8_lossy(kind);
+            match count {
+                1 => kind.into_owned(),
+                n => format!("{} ×{}", kind, n),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !complete {
+        list.push_str(", …");
+    }
+    Ok(signature.with("Chunks", list))
+}
+
+fn pe(head: &[u8]) -> Option<Signature> {
+    let r = Reader {
+        data: head,
+        big_endian: false,
+    };
+    let pe = r.u32(0x3c)? as usize;
+    if head.get(pe..pe + 4)? != b"PE\0\0" {
+        return None;
+    }
+    let machine = match r.u16(pe + 4)? {
+        0x014c => "x86".to_string(),
+        0x8664 => "x86-64".to_string(),
+        0x01c4 => "ARM".to_string(),
+        0xaa64 => "AArch64".to_string(),
+        0x0200 => "IA-64".to_string(),
+        other => format!("Unknown ({:#x})", other),
+    };
+    let characteristics = r.u16(pe + 22)?;
+    let kind = if characteristics & 0x2000 != 0 {
+        "Windows DLL"
+    } else {
+        "Windows executable"
+    };
+    let class = match r.u16(pe + 24)? {
+        0x20b => "64-bit",
+        _ => "32-bit",
+    };
+    let subsystem = match r.u16(pe + 24 + 68)? {
+        1 => Some("Native"),
+        2 => Some("GUI"),
+        3 => Some("Console"),
+        10..=13 => Some("EFI"),
+        _ => None,
+    };
+    let signature = Signature::new(kind)
+        .with("Architecture", machine)
+        .with("Class", class);
+    Some(match subsystem {
+        Some(subsystem) => signature.with("Subsystem", subsystem),
+        None => signature,
+    })
+}
+
+fn mach_o_cpu(cpu: u32) -> String {
+    match cpu {
+        7 => "x86",
+        0x0100_0007 => "x86-64",
+        12 => "ARM",
+        0x0100_000c => "ARM64",
+        18 => "PowerPC",
+        0x0100_0012 => "PowerPC64",
+        other => return format!("Unknown ({:#x})", other),
+    }
+    .to_string()
+}
+
+fn mach_o(head: &[u8]) -> Option<Signature> {
+    let magic = u32::from_be_bytes(head.get(..4)?.try_into().ok()?);
+    let (big_endian, class) = match magic {
+        0xfeed_face => (true, "32-bit"),
+        0xfeed_facf => (true, "64-bit"),
+        0xcefa_edfe => (false, "32-bit"),
+        0xcffa_edfe => (false, "64-bit"),
+        0xcafe_babe => {
+            // Shared with Java classes, whose version is where a universal
+            // binary has its small architecture count
+            let r = Reader {
+                data: head,
+                big_endian: true,
+            };
+            let count = r.u32(4)?;
+            if count >= 30 {
+                return Some(
+                    Signature::new("Java class file")
+                        .with("Version", format!("{}.{}", r.u16(6)?, r.u16(4)?)),
+                );
+            }
+            let cpus: Vec<_> = (0..count as usize)
+                .filter_map(|i| r.u32(8 + i * 20).map(mach_o_cpu))
+                .collect();
+            return Some(
+                Signature::new("Mach-O universal binary").with("Architectures", cpus.join(", ")),
+            );
+        }
+        _ => return None,
+    };
+    let r = Reader {
+        data: head,
+        big_endian,
+    };
+    let kind = match r.u32(12)? {
+        1 => "Mach-O object",
+        2 => "Mach-O executable",
+        6 => "Mach-O dynamic library",
+        8 => "Mach-O bundle",
+        _ => "Mach-O file",
+    };
+    Some(
+        Signature::new(kind)
+            .with("Architecture", mach_o_cpu(r.u32(4)?))
+            .with("Class", class),
+    )
+}
+
+/// Formats told apart by their first bytes alone
+fn simple(head: &[u8]) -> Option<Signature> {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"%PDF-", "PDF document"),
+        (b"GIF87a", "GIF image"),
+        (b"GIF89a", "GIF image"),
+        (b"\xff\xd8\xff", "JPEG image"),
+        (b"BM", "BMP image"),
+        (b"II*\0", "TIFF image"),
+        (b"MM\0*", "TIFF image"),
+        (b"PK\x03\x04", "ZIP archive"),
+        (b"PK\x05\x06", "ZIP archive (empty)"),
+        (b"\x1f\x8b", "gzip compressed data"),
+        (b"BZh", "bzip2 compressed data"),
+        (b"\xfd7zXZ\0", "xz compressed data"),
+        (b"\x28\xb5\x2f\xfd", "Zstandard compressed data"),
+        (b"7z\xbc\xaf\x27\x1c", "7-Zip archive"),
+        (b"Rar!\x1a\x07", "RAR archive"),
+        (b"SQLite format 3\0", "SQLite database"),
+        (b"\0asm", "WebAssembly module"),
+        (b"ID3", "MP3 audio"),
+        (b"fLaC", "FLAC audio"),
+        (b"OggS", "Ogg media"),
+        (b"\x1a\x45\xdf\xa3", "Matroska or WebM media"),
+        (b"LUKS\xba\xbe", "LUKS encrypted volume"),
+        (b"-----BEGIN ", "PEM encoded data"),
+        (
+            b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1",
+            "Microsoft Office document (legacy)",
+        ),
+        (b"wOFF", "WOFF font"),
+        (b"wOF2", "WOFF2 font"),
+        (b"OTTO", "OpenType font"),
+        (b"\0\x01\0\0", "TrueType font"),
+    ];
+    if let Some(&(_, kind)) = MAGIC.iter().find(|(magic, _)| head.starts_with(magic)) {
+        let signature = Signature::new(kind);
+        return Some(if kind == "PDF document" {
+            let version = head[5..].iter().take_while(|b| !b.is_ascii_whitespace());
+            signature.with(
+                "Version",
+                String::from_utf8_lossy(&version.copied().collect::<Vec<_>>()),
+            )
+        } else if kind == "GIF image" {
+            let r = Reader {
+                data: head,
+                big_endian: false,
+            };
+            signature.with("Dimensions", format!("{}x{}", r.u16(6)?, r.u16(8)?))
+        } else if kind == "SQLite database" {
+            let page_size = u16::from_be_bytes(head.get(16..18)?.try_into().ok()?);
+            // 1 stands for 65536
+            let page_size = if page_size == 1 {
+                65536
+            } else {
+                page_size as u32
+            };
+            signature.with("Page size", page_size.to_string())
+        } else {
+            signature
+        });
+    }
+    if head.starts_with(b"#!") {
+        let line = head[2..].split(|&b| b == b'\n').next()?;
+        return Some(
+            Signature::new("Script").with("Interpreter", String::from_utf8_lossy(line).trim()),
+        );
+    }
+    if head.starts_with(b"RIFF") {
+        let kind = match head.get(8..12)? {
+            b"WAVE" => "WAV audio",
+            b"AVI " => "AVI video",
+            b"WEBP" => "WebP image",
+            _ => "RIFF data",
+        };
+        return Some(Signature::new(kind));
+    }
+    if head.get(4..8) == Some(b"ftyp") {
+        let brand = String::from_utf8_lossy(head.get(8..12)?).trim().to_string();
+        return Some(Signature::new("ISO media (MP4, MOV, HEIF)").with("Brand", brand));
+    }
+    if head.get(257..262) == Some(b"ustar") {
+        return Some(Signature::new("Tar archive"));
+    }
+    None
+}
+
+/// Up to `len` bytes from `offset`; fewer at the end of the file
+fn read_at(file: &mut File, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buf = Vec::new();
+    file.take(len).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // A 64-bit x86-64 PIE: the ELF header, one PT_INTERP program header
+        // and the interpreter path after it
+        let mut elf = vec![0; 64 + 56];
+        elf[..7].copy_from_slice(b"\x7fELF\x02\x01\x01");
+        elf[16..18].copy_from_slice(&3u16.to_le_bytes());
+        elf[18..20].copy_from_slice(&0x3eu16.to_le_bytes());
+        elf[24..32].copy_from_slice(&0x1040u64.to_le_bytes());
+        elf[32..40].copy_from_slice(&64u64.to_le_bytes());
+        elf[54..56].copy_from_slice(&56u16.to_le_bytes());
+        elf[56..58].copy_from_slice(&1u16.to_le_bytes());
+        elf[64..68].copy_from_slice(&3u32.to_le_bytes());
+        elf[72..80].copy_from_slice(&120u64.to_le_bytes());
+        elf[96..104].copy_from_slice(&28u64.to_le_bytes());
+        elf.extend(b"/lib64/ld-linux-x86-64.so.2\0");
+        let path = dir.path().join("program");
+        std::fs::write(&path, elf).unwrap();
+        let signature = detect(&path).unwrap().unwrap();
+        assert_eq!(
+            describe(&signature),
+            vec![
+                ("Type", "ELF position-independent executable".to_string()),
+                ("Architecture", "x86-64".to_string()),
+                ("Class", "64-bit".to_string()),
+                ("Byte order", "Little endian".to_string()),
+                ("Entry point", "0x1040".to_string()),
+                ("Interpreter", "/lib64/ld-linux-x86-64.so.2".to_string()),
+            ]
+        );
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let mut chunk = |kind: &[u8], data: &[u8]| {
+            png.extend((data.len() as u32).to_be_bytes());
+            png.extend(kind);
+            png.extend(data);
+            png.extend([0; 4]);
+        };
+        chunk(b"IHDR", &[0, 0, 0, 64, 0, 0, 0, 32, 8, 6, 0, 0, 0]);
+        chunk(b"IDAT", &[1, 2, 3]);
+        chunk(b"IDAT", &[4, 5]);
+        chunk(b"IEND", &[]);
+        let path = dir.path().join("mystery");
+        std::fs::write(&path, png).unwrap();
+        let signature = detect(&path).unwrap().unwrap();
+        assert_eq!(signature.kind, "PNG image");
+        assert_eq!(
+            signature.details,
+            vec![
+                ("Dimensions", "64x32".to_string()),
+                ("Color", "RGBA, 8-bit".to_string()),
+                ("Interlaced", "No".to_string()),
+                ("Chunks", "IHDR, IDAT ×2, IEND".to_string()),
+            ]
+        );
+    }
+}
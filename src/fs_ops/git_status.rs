@@ -0,0 +1,323 @@
+// Git working tree status for the current directory.
+// Uses the `git` CLI (porcelain v2) rather than linking libgit2; runs with
+// --no-optional-locks so a status refresh never touches .git/index and
+// re-triggers the watcher.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Per-file status, ordered by how loudly it should be reported: folders
+/// show the highest status of anything below them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GitFileStatus {
+    Ignored,
+    Untracked,
+    Staged,
+    Modified,
+    Conflicted,
+}
+
+impl GitFileStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            GitFileStatus::Ignored => "I",
+            GitFileStatus::Untracked => "U",
+            GitFileStatus::Staged => "S",
+            GitFileStatus::Modified => "M",
+            GitFileStatus::Conflicted => "C",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            GitFileStatus::Ignored => "Ignored",
+            GitFileStatus::Untracked => "Untracked",
+            GitFileStatus::Staged => "Staged",
+            GitFileStatus::Modified => "Modified",
+            GitFileStatus::Conflicted => "Conflicted",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GitBranchInfo {
+    /// None when HEAD is detached
+    pub branch: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: u32,
+    pub behind: u32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GitStatus {
+    /// Working tree root
+    pub root: PathBuf,
+    /// The .git directory, watched so staging/commits refresh the badges
+    pub git_dir: PathBuf,
+    pub branch: GitBranchInfo,
+    files: HashMap<PathBuf, GitFileStatus>,
+    folders: HashMap<PathBuf, GitFileStatus>,
+}
+
+impl GitStatus {
+    /// Build from `git status --porcelain=v2 --branch -z` output. Paths in
+    /// porcelain output are always relative to the working tree root.
+    pub fn parse(root: &Path, git_dir: &Path, output: &[u8]) -> Self {
+        let mut status = GitStatus {
+            root: root.to_path_buf(),
+            git_dir: git_dir.to_path_buf(),
+            ..Default::default()
+        };
+
+        let text = String::from_utf8_lossy(output);
+        let mut records = text.split('\0');
+        while let Some(record) = records.next() {
+            if let Some(header) = record.strip_prefix("# ") {
+                status.parse_header(header);
+                continue;
+            }
+            let entry = match record.chars().next() {
+                Some('1') => {
+                    let fields: Vec<_> = record.splitn(9, ' ').collect();
+                    fields.get(8).map(|path| (xy_status(fields[1]), *path))
+                }
+                Some('2') => {
+                    // Renames carry the original path as the next record
+                    let fields: Vec<_> = record.splitn(10, ' ').collect();
+                    records.next();
+                    fields.get(9).map(|path| (xy_status(fields[1]), *path))
+                }
+                Some('u') => record
+                    .splitn(11, ' ')
+                    .nth(10)
+                    .map(|path| (GitFileStatus::Conflicted, path)),
+                Some('?') => record.get(2..).map(|path| (GitFileStatus::Untracked, path)),
+                Some('!') => record.get(2..).map(|path| (GitFileStatus::Ignored, path)),
+                _ => None,
+            };
+            if let Some((file_status, path)) = entry {
+                status.insert(path.trim_end_matches('/'), file_status);
+            }
+        }
+        status
+    }
+
+    fn parse_header(&mut self, header: &str) {
+        let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+        match key {
+            "branch.head" if value != "(detached)" => {
+                self.branch.branch = Some(value.to_string());
+            }
+            "branch.upstream" => self.branch.upstream = Some(value.to_string()),
+            "branch.ab" => {
+                for part in value.split_whitespace() {
+                    if let Some(n) = part.strip_prefix('+') {
+                        self.branch.ahead = n.parse().unwrap_or(0);
+                    } else if let Some(n) = part.strip_prefix('-') {
+                        self.branch.behind = n.parse().unwrap_or(0);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn insert(&mut self, relative: &str, file_status: GitFileStatus) {
+        let path = self.root.join(relative);
+        // Ignored content doesn't make its parents interesting
+        if file_status != GitFileStatus::Ignored {
+            for folder in path.ancestors().skip(1) {
+                if !folder.starts_with(&self.root) || folder == self.root {
+                    break;
+                }
+                let entry = self
+                    .folders
+                    .entry(folder.to_path_buf())
+                    .or_insert(file_status);
+                *entry = (*entry).max(file_status);
+            }
+        }
+        self.files.insert(path, file_status);
+    }
+
+    /// Status of a file, or the rolled-up status of a folder. Entries inside
+    /// an untracked or ignored folder inherit its status.
+    pub fn status_for(&self, path: &Path) -> Option<GitFileStatus> {
+        if let Some(status) = self.files.get(path) {
+            return Some(*status);
+        }
+        if let Some(status) = self.folders.get(path) {
+            return Some(*status);
+        }
+        path.ancestors()
+            .skip(1)
+            .take_while(|folder| folder.starts_with(&self.root) && *folder != self.root)
+            .find_map(|folder| self.files.get(folder).copied())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Map a porcelain v2 XY field to a single status. Unstaged changes win
+/// over staged ones since they are what still needs attention.
+fn xy_status(xy: &str) -> GitFileStatus {
+    let mut chars = xy.chars();
+    let index = chars.next().unwrap_or('.');
+    let worktree = chars.next().unwrap_or('.');
+    if worktree != '.' {
+        GitFileStatus::Modified
+    } else if index != '.' {
+        GitFileStatus::Staged
+    } else {
+        GitFileStatus::Modified
+    }
+}
+
+fn git_output(dir: &Path, args: &[&str]) -> Option<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("--no-optional-locks")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if output.status.success() {
+        Some(output.stdout)
+    } else {
+        None
+    }
+}
+
+/// Status of everything below `path`, or None when it isn't inside a git
+/// working tree (or git isn't installed). Blocking: call from the
+/// background executor.
+pub fn read_status(path: &Path) -> Option<GitStatus> {
+    let paths = git_output(
+        path,
+        &["rev-parse", "--show-toplevel", "--absolute-git-dir"],
+    )?;
+    let paths = String::from_utf8_lossy(&paths);
+    let mut lines = paths.lines();
+    let root = PathBuf::from(lines.next()?);
+    let git_dir = PathBuf::from(lines.next()?);
+
+    // Limit the scan to the directory being browsed
+    let pathspec = path
+        .strip_prefix(&root)
+        .ok()
+        .filter(|p| !p.as_os_str().is_empty())
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| ".".to_string());
+
+    let output = git_output(
+        &root,
+        &[
+            "status",
+            "--porcelain=v2",
+            "--branch",
+            "-z",
+            "--ignored",
+            "--",
+            &pathspec,
+        ],
+    )?;
+    Some(GitStatus::parse(&root, &git_dir, &output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_porcelain() {
+        let output = [
+            "# branch.oid 1234567890abcdef",
+            "# branch.head main",
+            "# branch.upstream origin/main",
+            "# branch.ab +2 -1",
+            "1 .M N... 100644 100644 100644 abc abc src/main.rs",
+            "1 A. N... 000000 100644 100644 000 abc src/new file.rs",
+            "2 R. N... 100644 100644 100644 abc abc R100 docs/renamed.md",
+            "docs/old.md",
+            "u UU N... 100644 100644 100644 100644 a b c conflict.txt",
+            "? notes/",
+            "! target/",
+            "",
+        ]
+        .join("\0");
+        let root = Path::new("/repo");
+        let status = GitStatus::parse(root, Path::new("/repo/.git"), output.as_bytes());
+
+        assert_eq!(
+            status.branch,
+            GitBranchInfo {
+                branch: Some("main".to_string()),
+                upstream: Some("origin/main".to_string()),
+                ahead: 2,
+                behind: 1,
+            }
+        );
+        let at = |p: &str| status.status_for(&root.join(p));
+        assert_eq!(at("src/main.rs"), Some(GitFileStatus::Modified));
+        assert_eq!(at("src/new file.rs"), Some(GitFileStatus::Staged));
+        assert_eq!(at("docs/renamed.md"), Some(GitFileStatus::Staged));
+        assert_eq!(at("docs/old.md"), None);
+        assert_eq!(at("conflict.txt"), Some(GitFileStatus::Conflicted));
+
+        // Folders roll up the highest status below them
+        assert_eq!(at("src"), Some(GitFileStatus::Modified));
+        assert_eq!(at("docs"), Some(GitFileStatus::Staged));
+
+        // Untracked/ignored folders are reported whole
+        assert_eq!(at("notes"), Some(GitFileStatus::Untracked));
+        assert_eq!(at("notes/deep/todo.md"), Some(GitFileStatus::Untracked));
+        assert_eq!(at("target/debug"), Some(GitFileStatus::Ignored));
+        assert_eq!(at("README.md"), None);
+    }
+
+    #[test]
+    fn test_detached_head() {
+        let output = "# branch.oid abc\0# branch.head (detached)\0";
+        let status = GitStatus::parse(Path::new("/r"), Path::new("/r/.git"), output.as_bytes());
+        assert_eq!(status.branch.branch, None);
+        assert!(status.is_empty());
+    }
+
+    #[test]
+    fn test_read_status() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        assert!(read_status(&root).is_none());
+
+        // git may be missing from minimal build environments
+        if git_output(&root, &["init", "-q", "-b", "main"]).is_none() {
+            return;
+        }
+        std::fs::create_dir(root.join("sub")).unwrap();
+        std::fs::write(root.join("sub/new.txt"), "x").unwrap();
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        std::fs::write(root.join("debug.log"), "x").unwrap();
+
+        let status = read_status(&root).unwrap();
+        assert_eq!(status.root, root);
+        assert_eq!(status.branch.branch.as_deref(), Some("main"));
+        assert_eq!(
+            status.status_for(&root.join("sub/new.txt")),
+            Some(GitFileStatus::Untracked)
+        );
+        assert_eq!(
+            status.status_for(&root.join("sub")),
+            Some(GitFileStatus::Untracked)
+        );
+        assert_eq!(
+            status.status_for(&root.join("debug.log")),
+            Some(GitFileStatus::Ignored)
+        );
+
+        // Scoped to a subdirectory, entries elsewhere are not reported
+        let status = read_status(&root.join("sub")).unwrap();
+        assert_eq!(status.status_for(&root.join("debug.log")), None);
+    }
+}
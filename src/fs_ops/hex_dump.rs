@@ -0,0 +1,126 @@
+// Paged hex dumps of binary files. Only the page being shown is read, so
+// any file can be browsed however large it is.
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::path::Path;
+
+/// One page of a file
+#[derive(Clone, Debug, PartialEq)]
+pub struct HexPage {
+    /// Zero-based page number
+    pub number: u64,
+    /// Offset of the first byte
+    pub offset: u64,
+    pub data: Vec<u8>,
+    pub file_size: u64,
+    pub page_size: NonZeroU64,
+}
+
+impl HexPage {
+    pub fn page_count(&self) -> u64 {
+        self.file_size.div_ceil(self.page_size.get()).max(1)
+    }
+}
+
+/// A row of the dump: offset, hex bytes and their printable characters
+#[derive(Clone, Debug, PartialEq)]
+pub struct HexRow {
+    pub offset: String,
+    pub hex: String,
+    pub ascii: String,
+}
+
+/// Reads page `number` of `page_size` bytes; past the end gives the last
+/// page
+pub fn read_page(path: &Path, number: u64, page_size: NonZeroU64) -> io::Result<HexPage> {
+    let mut file = File::open(path)?;
+    let file_size = file.metadata()?.len();
+    let last = file_size.saturating_sub(1) / page_size;
+    let number = number.min(last);
+    let offset = number * page_size.get();
+    file.seek(SeekFrom::Start(offset))?;
+    let mut data = Vec::with_capacity(page_size.get() as usize);
+    file.take(page_size.get()).read_to_end(&mut data)?;
+    Ok(HexPage {
+        number,
+        offset,
+        data,
+        file_size,
+        page_size,
+    })
+}
+
+/// The page's rows, `width` bytes each. Offsets have as many digits as the
+/// file's last offset needs, at least eight.
+pub fn rows(page: &HexPage, width: NonZeroUsize) -> Vec<HexRow> {
+    let width = width.get();
+    let digits = format!("{:x}", page.file_size.saturating_sub(1))
+        .len()
+        .max(8);
+    page.data
+        .chunks(width)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex = (0..width)
+                .map(|column| match chunk.get(column) {
+                    Some(byte) => format!("{:02x}", byte),
+                    None => "  ".to_string(),
+                })
+                .collect::<Vec<_>>()
+                // An extra space between the halves of each row
+                .chunks(8)
+                .map(|half| half.join(" "))
+                .collect::<Vec<_>>()
+                .join("  ");
+            let ascii = chunk
+                .iter()
+                .map(|&b| match b {
+                    0x20..=0x7e => b as char,
+                    _ => '.',
+                })
+                .collect();
+            HexRow {
+                offset: format!("{:0digits$x}", page.offset + (i * width) as u64),
+                hex,
+                ascii,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        let data: Vec<u8> = (0..=255u8).cycle().take(600).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let page_size = NonZeroU64::new(256).unwrap();
+        let page = read_page(&path, 1, page_size).unwrap();
+        assert_eq!(
+            (page.offset, page.data.len(), page.page_count()),
+            (256, 256, 3)
+        );
+        // Asking past the end gives the last, partial page
+        let page = read_page(&path, 9, page_size).unwrap();
+        assert_eq!((page.number, page.offset, page.data.len()), (2, 512, 88));
+
+        let rows = rows(&page, NonZeroUsize::new(16).unwrap());
+        assert_eq!(rows.len(), 6);
+        assert_eq!(rows[0].offset, "00000200");
+        assert_eq!(
+            rows[0].hex,
+            "00 01 02 03 04 05 06 07  08 09 0a 0b 0c 0d 0e 0f"
+        );
+        assert_eq!(rows[0].ascii, "................");
+        assert_eq!(rows[4].ascii, "@ABCDEFGHIJKLMNO");
+        // The last row is padded so the columns line up
+        assert_eq!(rows[5].hex.len(), rows[0].hex.len());
+        assert_eq!(rows[5].ascii, "PQRSTUVW");
+    }
+}
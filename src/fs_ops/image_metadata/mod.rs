@@ -0,0 +1,572 @@
+// Photo metadata: camera, exposure, capture time, orientation and location
+// from the EXIF and XMP blocks of JPEG, PNG, WebP and TIFF-based raw files.
+// The blocks are found by walking the file's segments or chunks, so the
+// image data itself is never read.
+mod strip;
+mod tiff;
+mod xmp;
+
+pub use strip::{strip, StripMode};
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Metadata blocks larger than this are skipped rather than read
+const MAX_BLOCK: u64 = 16 * 1024 * 1024;
+
+/// How much of a TIFF-based file is read for its IFDs
+const TIFF_HEAD: u64 = 1024 * 1024;
+
+const EXIF_PREFIX: &[u8] = b"Exif\0\0";
+const XMP_PREFIX: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+const XMP_KEYWORD: &[u8] = b"XML:com.adobe.xmp";
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PhotoMetadata {
+    pub make: Option<String>,
+    pub model: Option<String>,
+    pub lens: Option<String>,
+    /// Seconds, as numerator and denominator
+    pub exposure_time: Option<(u32, u32)>,
+    pub f_number: Option<f64>,
+    pub iso: Option<u32>,
+    /// Millimetres
+    pub focal_length: Option<f64>,
+    pub focal_length_35mm: Option<u32>,
+    /// "2024-05-01 14:03:22", as the camera recorded it
+    pub captured: Option<String>,
+    /// The EXIF orientation, 1 to 8
+    pub orientation: Option<u16>,
+    /// Latitude and longitude in degrees
+    pub gps: Option<(f64, f64)>,
+    /// Metres above sea level
+    pub altitude: Option<f64>,
+}
+
+impl PhotoMetadata {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Jpeg,
+    Png,
+    WebP,
+    Tiff,
+}
+
+impl Format {
+    fn detect(head: &[u8]) -> Option<Self> {
+        if head.starts_with(&[0xff, 0xd8]) {
+            Some(Self::Jpeg)
+        } else if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WEBP") {
+            Some(Self::WebP)
+        } else if tiff::Tiff::new(head).is_some() {
+            Some(Self::Tiff)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether `path` has the extension of a format `read` understands
+pub fn is_supported(path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    matches!(
+        ext.as_str(),
+        "jpg"
+            | "jpeg"
+            | "png"
+            | "webp"
+            | "tif"
+            | "tiff"
+            | "dng"
+            | "cr2"
+            | "nef"
+            | "arw"
+            | "orf"
+            | "rw2"
+    )
+}
+
+/// Reads the EXIF and XMP metadata of a photo. A file without either gives
+/// empty metadata rather than an error.
+pub fn read(path: &Path) -> Result<PhotoMetadata, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let head = read_at(&mut file, 0, 12).map_err(|e| e.to_string())?;
+    let format = Format::detect(&head).ok_or("Not a supported image format")?;
+    let (exif, xmp) = blocks(&mut file, format).map_err(|e| e.to_string())?;
+
+    let mut metadata = PhotoMetadata::default();
+    if let Some(exif) = exif {
+        from_exif(&exif, &mut metadata);
+    }
+    if let Some(xmp) = xmp {
+        from_xmp(&String::from_utf8_lossy(&xmp), &mut metadata);
+    }
+    Ok(metadata)
+}
+
+/// The EXIF block, as a TIFF structure, and the XMP packet of a file
+type Blocks = (Option<Vec<u8>>, Option<Vec<u8>>);
+
+fn blocks(file: &mut File, format: Format) -> io::Result<Blocks> {
+    let (mut exif, mut xmp) = (None, None);
+    match format {
+        Format::Jpeg => {
+            for (marker, offset, len) in jpeg_segments(file)? {
+                if marker != 0xe1 || (exif.is_some() && xmp.is_some()) {
+                    continue;
+                }
+                let body = read_at(file, offset, len)?;
+                if let Some(tiff) = body.strip_prefix(EXIF_PREFIX) {
+                    exif.get_or_insert_with(|| tiff.to_vec());
+                } else if let Some(packet) = body.strip_prefix(XMP_PREFIX) {
+                    xmp.get_or_insert_with(|| packet.to_vec());
+                }
+            }
+        }
+        Format::Png => {
+            for (kind, offset, len) in png_chunks(file)? {
+                match &kind {
+                    b"eXIf" if len <= MAX_BLOCK => exif = Some(read_at(file, offset, len)?),
+                    b"iTXt" if len <= MAX_BLOCK => {
+                        let chunk = read_at(file, offset, len)?;
+                        if let Some(start) = png_xmp_start(&chunk) {
+                            xmp = Some(chunk[start..].to_vec());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Format::WebP => {
+            for (kind, offset, len) in webp_chunks(file)? {
+                match &kind {
+                    b"EXIF" if len <= MAX_BLOCK => {
+                        let chunk = read_at(file, offset, len)?;
+                        // Some writers keep the JPEG prefix
+                        exif = Some(match chunk.strip_prefix(EXIF_PREFIX) {
+                            Some(tiff) => tiff.to_vec(),
+                            None => chunk,
+                        });
+                    }
+                    b"XMP " if len <= MAX_BLOCK => xmp = Some(read_at(file, offset, len)?),
+                    _ => {}
+                }
+            }
+        }
+        Format::Tiff => {
+            let data = read_at(file, 0, TIFF_HEAD)?;
+            if let Some(tiff) = tiff::Tiff::new(&data) {
+                let packet = tiff
+                    .first_ifd()
+                    .and_then(|ifd0| tiff.find(ifd0, tiff::XMP))
+                    .and_then(|entry| tiff.bytes(&entry));
+                xmp = packet.map(<[u8]>::to_vec);
+            }
+            exif = Some(data);
+        }
+    }
+    Ok((exif, xmp))
+}
+
+/// Marker, body offset and body length of each JPEG segment before the
+/// image data
+fn jpeg_segments(file: &mut File) -> io::Result<Vec<(u8, u64, u64)>> {
+    let mut segments = Vec::new();
+    let mut offset = 2;
+    loop {
+        let header = read_at(file, offset, 4)?;
+        let &[0xff, marker, hi, lo] = header.as_slice() else {
+            break;
+        };
+        // Start of scan and end of image
+        if marker == 0xda || marker == 0xd9 {
+            break;
+        }
+        let len = u16::from_be_bytes([hi, lo]) as u64;
+        if len < 2 {
+            break;
+        }
+        segments.push((marker, offset + 4, len - 2));
+        offset += 2 + len;
+    }
+    Ok(segments)
+}
+
+/// Type, data offset and data length of each PNG chunk
+fn png_chunks(file: &mut File) -> io::Result<Vec<([u8; 4], u64, u64)>> {
+    let mut chunks = Vec::new();
+    let mut offset = 8;
+    loop {
+        let header = read_at(file, offset, 8)?;
+        let Some((len, kind)) = header.split_first_chunk::<4>() else {
+            break;
+        };
+        let Ok(kind) = <[u8; 4]>::try_from(kind) else {
+            break;
+        };
+        let len = u32::from_be_bytes(*len) as u64;
+        chunks.push((kind, offset + 8, len));
+        if &kind == b"IEND" {
+            break;
+        }
+        // Length, type, data and CRC
+        offset += 12 + len;
+    }
+    Ok(chunks)
+}
+
+/// Type, data offset and data length of each chunk of a WebP file
+fn webp_chunks(file: &mut File) -> io::Result<Vec<([u8; 4], u64, u64)>> {
+    let mut chunks = Vec::new();
+    let mut offset = 12;
+    loop {
+        let header = read_at(file, offset, 8)?;
+        let Some((kind, len)) = header.split_first_chunk::<4>() else {
+            break;
+        };
+        let Ok(len) = <[u8; 4]>::try_from(len) else {
+            break;
+        };
+        let len = u32::from_le_bytes(len) as u64;
+        chunks.push((*kind, offset + 8, len));
+        // Chunks are padded to an even length
+        offset += 8 + len + (len & 1);
+    }
+    Ok(chunks)
+}
+
+/// Where the XMP packet starts in a PNG `iTXt` chunk, if it holds one
+/// uncompressed
+fn png_xmp_start(chunk: &[u8]) -> Option<usize> {
+    let rest = chunk.strip_prefix(XMP_KEYWORD)?.strip_prefix(b"\0")?;
+    // Compression flag and method, then language and translated keyword
+    if *rest.first()? != 0 {
+        return None;
+    }
+    let mut nuls = rest.iter().enumerate().skip(2).filter(|(_, &b)| b == 0);
+    nuls.next()?;
+    let (end, _) = nuls.next()?;
+    Some(XMP_KEYWORD.len() + 1 + end + 1)
+}
+
+fn from_exif(data: &[u8], metadata: &mut PhotoMetadata) {
+    let Some(tiff) = tiff::Tiff::new(data) else {
+        return;
+    };
+    let Some(ifd0) = tiff.first_ifd() else {
+        return;
+    };
+    let ascii = |ifd, tag| tiff.find(ifd, tag).and_then(|e| tiff.ascii(&e));
+    let uint = |ifd, tag| tiff.find(ifd, tag).and_then(|e| tiff.uint(&e, 0));
+    let float = |ifd, tag| tiff.find(ifd, tag).and_then(|e| tiff.float(&e, 0));
+
+    metadata.make = ascii(ifd0, 0x010f);
+    metadata.model = ascii(ifd0, 0x0110);
+    metadata.orientation = uint(ifd0, tiff::ORIENTATION).map(|o| o as u16);
+    metadata.captured = ascii(ifd0, 0x0132);
+
+    if let Some(exif) = tiff.sub_ifd(ifd0, tiff::EXIF_IFD) {
+        metadata.exposure_time = tiff
+            .find(exif, 0x829a)
+            .and_then(|e| tiff.rational(&e, 0))
+            .filter(|&(_, den)| den != 0);
+        metadata.f_number = float(exif, 0x829d);
+        metadata.iso = uint(exif, 0x8827);
+        metadata.focal_length = float(exif, 0x920a);
+        metadata.focal_length_35mm = uint(exif, 0xa405).filter(|&f| f > 0);
+        metadata.lens = ascii(exif, 0xa434);
+        if let Some(original) = ascii(exif, 0x9003) {
+            metadata.captured = Some(original);
+        }
+    }
+    metadata.captured = metadata.captured.take().map(|date| {
+        // EXIF separates the date with colons
+        match date.get(..10) {
+            Some(day) => format!("{}{}", day.replace(':', "-"), &date[10..]),
+            None => date,
+        }
+    });
+
+    if let Some(gps) = tiff.sub_ifd(ifd0, tiff::GPS_IFD) {
+        let degrees = |tag| {
+            let entry = tiff.find(gps, tag)?;
+            let parts = [0, 1, 2].map(|i| tiff.float(&entry, i).unwrap_or(0.0));
+            Some(parts[0] + parts[1] / 60.0 + parts[2] / 3600.0)
+        };
+        let sign = |tag, negative: &str| match ascii(gps, tag) {
+            Some(r) if r == negative => -1.0,
+            _ => 1.0,
+        };
+        if let (Some(lat), Some(lon)) = (degrees(2), degrees(4)) {
+            metadata.gps = Some((lat * sign(1, "S"), lon * sign(3, "W")));
+        }
+        metadata.altitude = float(gps, 6).map(|alt| match uint(gps, 5) {
+            Some(1) => -alt,
+            _ => alt,
+        });
+    }
+}
+
+/// Fills in what the EXIF block didn't have from an XMP packet
+fn from_xmp(packet: &str, metadata: &mut PhotoMetadata) {
+    let value = |names: &[&str]| names.iter().find_map(|name| xmp::value(packet, name));
+    let rational = |text: String| -> Option<(u32, u32)> {
+        let (num, den) = text.split_once('/').unwrap_or((&text, "1"));
+        Some((num.trim().parse().ok()?, den.trim().parse().ok()?)).filter(|&(_, den)| den != 0)
+    };
+    let number = |text: String| rational(text).map(|(num, den)| num as f64 / den as f64);
+
+    let m = metadata;
+    m.make = m.make.take().or_else(|| value(&["tiff:Make"]));
+    m.model = m.model.take().or_else(|| value(&["tiff:Model"]));
+    m.lens = m
+        .lens
+        .take()
+        .or_else(|| value(&["exifEX:LensModel", "aux:Lens"]));
+    m.exposure_time = m
+        .exposure_time
+        .or_else(|| value(&["exif:ExposureTime"]).and_then(rational));
+    m.f_number = m
+        .f_number
+        .or_else(|| value(&["exif:FNumber"]).and_then(number));
+    m.iso = m.iso.or_else(|| {
+        value(&["exifEX:PhotographicSensitivity", "exif:ISOSpeedRatings"])?
+            .parse()
+            .ok()
+    });
+    m.focal_length = m
+        .focal_length
+        .or_else(|| value(&["exif:FocalLength"]).and_then(number));
+    m.focal_length_35mm = m
+        .focal_length_35mm
+        .or_else(|| value(&["exif:FocalLengthIn35mmFilm"])?.parse().ok());
+    m.captured = m.captured.take().or_else(|| {
+        // ISO 8601, shown without the fraction and zone
+        let date = value(&["exif:DateTimeOriginal", "xmp:CreateDate"])?;
+        Some(date.chars().take(19).collect::<String>().replace('T', " "))
+    });
+    m.orientation = m
+        .orientation
+        .or_else(|| value(&["tiff:Orientation"])?.parse().ok());
+    m.gps = m.gps.or_else(|| {
+        let lat = xmp::coordinate(&value(&["exif:GPSLatitude"])?)?;
+        let lon = xmp::coordinate(&value(&["exif:GPSLongitude"])?)?;
+        Some((lat, lon))
+    });
+    m.altitude = m.altitude.or_else(|| {
+        let altitude = value(&["exif:GPSAltitude"]).and_then(number)?;
+        Some(match value(&["exif:GPSAltitudeRef"]).as_deref() {
+            Some("1") => -altitude,
+            _ => altitude,
+        })
+    });
+}
+
+/// "1/125 s", or "2.5 s" from a second up
+fn format_exposure((num, den): (u32, u32)) -> String {
+    if num >= den || num == 0 {
+        let secs = format!("{:.1}", num as f64 / den as f64);
+        format!("{} s", secs.trim_end_matches(".0"))
+    } else {
+        format!("1/{} s", (den as f64 / num as f64).round())
+    }
+}
+
+fn format_orientation(orientation: u16) -> Option<&'static str> {
+    Some(match orientation {
+        1 => "Normal",
+        2 => "Flipped horizontally",
+        3 => "Rotated 180°",
+        4 => "Flipped vertically",
+        5 => "Transposed",
+        6 => "Rotated 90° clockwise",
+        7 => "Transversed",
+        8 => "Rotated 90° counter-clockwise",
+        _ => return None,
+    })
+}
+
+/// Labelled values for display, in a fixed order, leaving out unknowns
+pub fn describe(metadata: &PhotoMetadata) -> Vec<(&'static str, String)> {
+    let camera = match (&metadata.make, &metadata.model) {
+        // Most models already start with the make
+        (Some(make), Some(model)) if model.starts_with(make.as_str()) => Some(model.clone()),
+        (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+        (make, model) => make.clone().or_else(|| model.clone()),
+    };
+    let focal_length = metadata.focal_length.map(|f| {
+        let mm = format!("{:.1}", f);
+        let mm = mm.trim_end_matches(".0");
+        match metadata.focal_length_35mm {
+            Some(equiv) => format!("{} mm ({} mm equiv.)", mm, equiv),
+            None => format!("{} mm", mm),
+        }
+    });
+    let rows = [
+        ("Camera", camera),
+        ("Lens", metadata.lens.clone()),
+        ("Exposure", metadata.exposure_time.map(format_exposure)),
+        ("Aperture", metadata.f_number.map(|f| format!("f/{:.1}", f))),
+        ("ISO", metadata.iso.map(|iso| iso.to_string())),
+        ("Focal length", focal_length),
+        ("Taken", metadata.captured.clone()),
+        (
+            "Orientation",
+            metadata
+                .orientation
+                .and_then(format_orientation)
+                .map(str::to_string),
+        ),
+        (
+            "Coordinates",
+            metadata
+                .gps
+                .map(|(lat, lon)| format!("{:.5}, {:.5}", lat, lon)),
+        ),
+        (
+            "Altitude",
+            metadata.altitude.map(|alt| format!("{:.0} m", alt)),
+        ),
+    ];
+    rows.into_iter()
+        .filter_map(|(label, value)| Some((label, value?)))
+        .collect()
+}
+
+/// Up to `len` bytes from `offset`; fewer at the end of the file
+fn read_at(file: &mut File, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buf = Vec::new();
+    file.take(len).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A little endian TIFF block with `ifd0` and an Exif and a GPS IFD
+    pub(super) fn exif_block() -> Vec<u8> {
+        let mut data = b"II*\0".to_vec();
+        data.extend(8u32.to_le_bytes());
+        let mut values: Vec<u8> = Vec::new();
+        // IFD0 at 8 with five entries, the Exif IFD after it and the GPS
+        // IFD after that, then the values that don't fit inline
+        let ifd0_len = 2 + 5 * 12 + 4;
+        let exif_at = 8 + ifd0_len;
+        let exif_len = 2 + 4 * 12 + 4;
+        let gps_at = exif_at + exif_len;
+        let gps_len = 2 + 4 * 12 + 4;
+        let values_at = gps_at + gps_len;
+        let mut entry = |out: &mut Vec<u8>, tag: u16, kind: u16, count: u32, value: &[u8]| {
+            out.extend(tag.to_le_bytes());
+            out.extend(kind.to_le_bytes());
+            out.extend(count.to_le_bytes());
+            if value.len() <= 4 {
+                let mut inline = value.to_vec();
+                inline.resize(4, 0);
+                out.extend(inline);
+            } else {
+                out.extend(((values_at + values.len()) as u32).to_le_bytes());
+                values.extend(value);
+            }
+        };
+        let rational = |parts: &[(u32, u32)]| -> Vec<u8> {
+            parts
+                .iter()
+                .flat_map(|(n, d)| [n.to_le_bytes(), d.to_le_bytes()].concat())
+                .collect()
+        };
+
+        data.extend(5u16.to_le_bytes());
+        entry(&mut data, 0x010f, 2, 6, b"Canon\0");
+        entry(&mut data, 0x0110, 2, 13, b"Canon EOS R6\0");
+        entry(&mut data, tiff::ORIENTATION, 3, 1, &6u16.to_le_bytes());
+        entry(
+            &mut data,
+            tiff::EXIF_IFD,
+            4,
+            1,
+            &(exif_at as u32).to_le_bytes(),
+        );
+        entry(
+            &mut data,
+            tiff::GPS_IFD,
+            4,
+            1,
+            &(gps_at as u32).to_le_bytes(),
+        );
+        data.extend(0u32.to_le_bytes());
+
+        data.extend(4u16.to_le_bytes());
+        entry(&mut data, 0x829a, 5, 1, &rational(&[(1, 250)]));
+        entry(&mut data, 0x829d, 5, 1, &rational(&[(28, 10)]));
+        entry(&mut data, 0x8827, 3, 1, &400u16.to_le_bytes());
+        entry(&mut data, 0x9003, 2, 20, b"2024:05:01 14:03:22\0");
+        data.extend(0u32.to_le_bytes());
+
+        data.extend(4u16.to_le_bytes());
+        entry(&mut data, 1, 2, 2, b"N\0");
+        entry(
+            &mut data,
+            2,
+            5,
+            3,
+            &rational(&[(48, 1), (51, 1), (3012, 100)]),
+        );
+        entry(&mut data, 3, 2, 2, b"W\0");
+        entry(&mut data, 4, 5, 3, &rational(&[(2, 1), (30, 1), (0, 1)]));
+        data.extend(0u32.to_le_bytes());
+
+        data.extend(values);
+        data
+    }
+
+    #[test]
+    fn test_read_jpeg() {
+        let exif = exif_block();
+        let xmp = br#"<x:xmpmeta><rdf:Description aux:Lens="RF24-105mm F4 L IS USM"
+            exif:FocalLength="50/1" exif:FNumber="40/10"/></x:xmpmeta>"#;
+        let mut file = vec![0xff, 0xd8];
+        for body in [[EXIF_PREFIX, &exif].concat(), [XMP_PREFIX, xmp].concat()] {
+            file.extend([0xff, 0xe1]);
+            file.extend(((body.len() + 2) as u16).to_be_bytes());
+            file.extend(body);
+        }
+        file.extend([0xff, 0xda, 0, 2, 1, 2, 3, 0xff, 0xd9]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        std::fs::write(&path, file).unwrap();
+        let metadata = read(&path).unwrap();
+
+        let (lat, lon) = metadata.gps.unwrap();
+        assert!((lat - 48.85837).abs() < 1e-5 && (lon + 2.5).abs() < 1e-9);
+        assert_eq!(
+            describe(&metadata),
+            vec![
+                ("Camera", "Canon EOS R6".to_string()),
+                ("Lens", "RF24-105mm F4 L IS USM".to_string()),
+                ("Exposure", "1/250 s".to_string()),
+                // EXIF wins over XMP
+                ("Aperture", "f/2.8".to_string()),
+                ("ISO", "400".to_string()),
+                ("Focal length", "50 mm".to_string()),
+                ("Taken", "2024-05-01 14:03:22".to_string()),
+                ("Orientation", "Rotated 90° clockwise".to_string()),
+                ("Coordinates", "48.85837, -2.50000".to_string()),
+            ]
+        );
+    }
+}
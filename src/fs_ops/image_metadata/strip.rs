@@ -0,0 +1,414 @@
+// Removing metadata from photos before sharing them. The file is rewritten
+// block by block, copying the image data untouched, and replaced
+// atomically, keeping its tags and other xattrs, permissions and owner.
+// Colour profiles are kept, as is the orientation, so the photo still looks
+// the same afterwards.
+use super::{png_xmp_start, tiff, xmp, Format, EXIF_PREFIX, XMP_PREFIX};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+const XMP_EXTENSION_PREFIX: &[u8] = b"http://ns.adobe.com/xmp/extension/\0";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StripMode {
+    /// Just the GPS coordinates, from both EXIF and XMP
+    Location,
+    /// EXIF, XMP, IPTC, comments and text chunks
+    All,
+}
+
+/// Removes metadata from the photo at `path`. Returns whether there was any
+/// to remove; the file is left alone if not.
+pub fn strip(path: &Path, mode: StripMode) -> Result<bool, String> {
+    let data = fs::read(path).map_err(|e| e.to_string())?;
+    let stripped = match Format::detect(&data) {
+        Some(Format::Jpeg) => jpeg(&data, mode),
+        Some(Format::Png) => png(&data, mode),
+        Some(Format::WebP) => webp(&data, mode),
+        Some(Format::Tiff) if mode == StripMode::Location => tiff_location(&data),
+        Some(Format::Tiff) => {
+            return Err("Only the location can be removed from TIFF and raw files".to_string())
+        }
+        None => return Err("Not a supported image format".to_string()),
+    };
+    match stripped.filter(|stripped| *stripped != data) {
+        Some(stripped) => {
+            replace(path, &stripped).map_err(|e| e.to_string())?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Writes `data` to a temporary file next to `path`, with its permissions,
+/// owner and xattrs, and renames it into place. A file with other hard
+/// links is rewritten in place instead, so they see the change too.
+fn replace(path: &Path, data: &[u8]) -> io::Result<()> {
+    let metadata = fs::metadata(path)?;
+    if metadata.nlink() > 1 {
+        return fs::write(path, data);
+    }
+    let dir = path
+        .parent()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no parent directory"))?;
+    let tmp = dir.join(format!(
+        ".{}.{}.tmp",
+        path.file_name().unwrap_or_default().to_string_lossy(),
+        std::process::id()
+    ));
+    let result = (|| {
+        let mut file = OpenOptions::new().write(true).create_new(true).open(&tmp)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        copy_xattrs(path, &tmp);
+        // Only allowed for another owner when running as root
+        let _ = std::os::unix::fs::chown(&tmp, Some(metadata.uid()), Some(metadata.gid()));
+        fs::set_permissions(&tmp, metadata.permissions())?;
+        fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Copies every xattr, tags included. Best effort: the filesystem may not
+/// support them, or refuse some namespaces.
+fn copy_xattrs(from: &Path, to: &Path) {
+    let Ok(names) = xattr::list(from) else {
+        return;
+    };
+    for name in names {
+        if let Ok(Some(value)) = xattr::get(from, &name) {
+            let _ = xattr::set(to, &name, &value);
+        }
+    }
+}
+
+/// The orientation tag of an EXIF block, unless it's the default
+fn orientation(exif: &[u8]) -> Option<u16> {
+    let tiff = tiff::Tiff::new(exif)?;
+    let entry = tiff.find(tiff.first_ifd()?, tiff::ORIENTATION)?;
+    Some(tiff.uint(&entry, 0)? as u16).filter(|&o| o != 1)
+}
+
+/// An XMP packet without its location, if it had one
+fn xmp_without_gps(packet: &[u8]) -> Option<String> {
+    let packet = std::str::from_utf8(packet).ok()?;
+    let cleaned = xmp::remove_gps(packet);
+    (cleaned != packet).then_some(cleaned)
+}
+
+/// Marker, whole segment and body of a JPEG segment
+type Segment<'a> = (u8, &'a [u8], &'a [u8]);
+
+/// The segments of a JPEG file before the image data, and where that starts
+fn jpeg_segments(data: &[u8]) -> (Vec<Segment<'_>>, usize) {
+    let mut segments = Vec::new();
+    let mut pos = 2;
+    while let Some(&[0xff, marker, hi, lo]) = data.get(pos..pos + 4) {
+        if marker == 0xda || marker == 0xd9 {
+            break;
+        }
+        let end = pos + 2 + u16::from_be_bytes([hi, lo]) as usize;
+        let Some(body) = data.get(pos + 4..end) else {
+            break;
+        };
+        segments.push((marker, &data[pos..end], body));
+        pos = end;
+    }
+    (segments, pos)
+}
+
+fn push_jpeg_segment(out: &mut Vec<u8>, marker: u8, parts: &[&[u8]]) {
+    let len: usize = parts.iter().map(|part| part.len()).sum();
+    out.extend([0xff, marker]);
+    out.extend(((len + 2) as u16).to_be_bytes());
+    for part in parts {
+        out.extend(*part);
+    }
+}
+
+fn jpeg(data: &[u8], mode: StripMode) -> Option<Vec<u8>> {
+    let (segments, image_data) = jpeg_segments(data);
+    let mut out = data.get(..2)?.to_vec();
+    for (marker, segment, body) in segments {
+        let exif = (marker == 0xe1)
+            .then(|| body.strip_prefix(EXIF_PREFIX))
+            .flatten();
+        let packet = (marker == 0xe1)
+            .then(|| body.strip_prefix(XMP_PREFIX))
+            .flatten();
+        match mode {
+            StripMode::Location => {
+                if let Some(exif) = exif {
+                    let mut exif = exif.to_vec();
+                    tiff::remove_gps(&mut exif);
+                    push_jpeg_segment(&mut out, marker, &[EXIF_PREFIX, &exif]);
+                } else if let Some(cleaned) = packet.and_then(xmp_without_gps) {
+                    push_jpeg_segment(&mut out, marker, &[XMP_PREFIX, cleaned.as_bytes()]);
+                } else {
+                    out.extend(segment);
+                }
+            }
+            StripMode::All => {
+                if let Some(orientation) = exif.and_then(orientation) {
+                    let exif = tiff::orientation_only(orientation);
+                    push_jpeg_segment(&mut out, marker, &[EXIF_PREFIX, &exif]);
+                } else {
+                    // APP1 holds EXIF and XMP, APP13 IPTC, COM comments
+                    let metadata = marker == 0xe1
+                        && (exif.is_some()
+                            || packet.is_some()
+                            || body.starts_with(XMP_EXTENSION_PREFIX))
+                        || marker == 0xed
+                        || marker == 0xfe;
+                    if !metadata {
+                        out.extend(segment);
+                    }
+                }
+            }
+        }
+    }
+    out.extend(&data[image_data..]);
+    Some(out)
+}
+
+/// Type, whole chunk and data of a PNG or WebP chunk
+type Chunk<'a> = (&'a [u8], &'a [u8], &'a [u8]);
+
+/// The chunks of a PNG file, and where they end
+fn png_chunks(data: &[u8]) -> (Vec<Chunk<'_>>, usize) {
+    let mut chunks = Vec::new();
+    let mut pos = 8;
+    while let Some(header) = data.get(pos..pos + 8) {
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let kind = &header[4..];
+        let Some(chunk) = data.get(pos..pos + 12 + len) else {
+            break;
+        };
+        chunks.push((kind, chunk, &chunk[8..8 + len]));
+        pos += 12 + len;
+        if kind == b"IEND" {
+            break;
+        }
+    }
+    (chunks, pos)
+}
+
+fn push_png_chunk(out: &mut Vec<u8>, kind: &[u8], parts: &[&[u8]]) {
+    let len: usize = parts.iter().map(|part| part.len()).sum();
+    out.extend((len as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    for part in parts {
+        out.extend(*part);
+    }
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+fn png(data: &[u8], mode: StripMode) -> Option<Vec<u8>> {
+    let (chunks, end) = png_chunks(data);
+    let kept_orientation = chunks
+        .iter()
+        .find(|(kind, _, _)| *kind == b"eXIf")
+        .and_then(|(_, _, body)| orientation(body));
+    let mut out = data.get(..8)?.to_vec();
+    for (kind, chunk, body) in chunks {
+        match (mode, kind) {
+            (StripMode::Location, b"eXIf") => {
+                let mut exif = body.to_vec();
+                tiff::remove_gps(&mut exif);
+                push_png_chunk(&mut out, kind, &[&exif]);
+            }
+            (StripMode::Location, b"iTXt") => {
+                let cleaned = png_xmp_start(body)
+                    .and_then(|start| Some((start, xmp_without_gps(&body[start..])?)));
+                match cleaned {
+                    Some((start, cleaned)) => {
+                        push_png_chunk(&mut out, kind, &[&body[..start], cleaned.as_bytes()])
+                    }
+                    None => out.extend(chunk),
+                }
+            }
+            (StripMode::All, b"eXIf" | b"tEXt" | b"zTXt" | b"iTXt" | b"tIME") => {}
+            (StripMode::All, b"IHDR") => {
+                out.extend(chunk);
+                // eXIf has to come before the image data
+                if let Some(orientation) = kept_orientation {
+                    push_png_chunk(&mut out, b"eXIf", &[&tiff::orientation_only(orientation)]);
+                }
+            }
+            _ => out.extend(chunk),
+        }
+    }
+    out.extend(&data[end..]);
+    Some(out)
+}
+
+/// The chunks of a WebP file
+fn webp_chunks(data: &[u8]) -> Vec<Chunk<'_>> {
+    let mut chunks = Vec::new();
+    let mut pos = 12;
+    while let Some(header) = data.get(pos..pos + 8) {
+        let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        // Chunks are padded to an even length
+        let end = (pos + 8 + len + (len & 1)).min(data.len());
+        let Some(body) = data.get(pos + 8..pos + 8 + len) else {
+            break;
+        };
+        chunks.push((&header[..4], &data[pos..end], body));
+        pos = end;
+    }
+    chunks
+}
+
+fn push_webp_chunk(out: &mut Vec<u8>, kind: &[u8], body: &[u8]) {
+    out.extend(kind);
+    out.extend((body.len() as u32).to_le_bytes());
+    out.extend(body);
+    if body.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+fn webp(data: &[u8], mode: StripMode) -> Option<Vec<u8>> {
+    let chunks = webp_chunks(data);
+    let kept_orientation = chunks
+        .iter()
+        .find(|(kind, _, _)| *kind == b"EXIF")
+        .and_then(|(_, _, body)| orientation(body.strip_prefix(EXIF_PREFIX).unwrap_or(body)));
+    let mut out = data.get(..12)?.to_vec();
+    for (kind, chunk, body) in chunks {
+        match (mode, kind) {
+            (StripMode::Location, b"EXIF") => {
+                let mut exif = body.to_vec();
+                let prefix = if exif.starts_with(EXIF_PREFIX) {
+                    EXIF_PREFIX.len()
+                } else {
+                    0
+                };
+                tiff::remove_gps(&mut exif[prefix..]);
+                push_webp_chunk(&mut out, kind, &exif);
+            }
+            (StripMode::Location, b"XMP ") => match xmp_without_gps(body) {
+                Some(cleaned) => push_webp_chunk(&mut out, kind, cleaned.as_bytes()),
+                None => out.extend(chunk),
+            },
+            (StripMode::All, b"EXIF") => {
+                if let Some(orientation) = kept_orientation {
+                    push_webp_chunk(&mut out, kind, &tiff::orientation_only(orientation));
+                }
+            }
+            (StripMode::All, b"XMP ") => {}
+            (StripMode::All, b"VP8X") if !body.is_empty() => {
+                // Clear the XMP flag, and the EXIF flag unless it's kept
+                let mut header = body.to_vec();
+                header[0] &= !0x04;
+                if kept_orientation.is_none() {
+                    header[0] &= !0x08;
+                }
+                push_webp_chunk(&mut out, kind, &header);
+            }
+            _ => out.extend(chunk),
+        }
+    }
+    let riff_len = (out.len() - 8) as u32;
+    out[4..8].copy_from_slice(&riff_len.to_le_bytes());
+    Some(out)
+}
+
+/// Blanks the GPS IFD and the location in the XMP packet in place, padding
+/// the packet with spaces as XMP allows
+fn tiff_location(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = data.to_vec();
+    tiff::remove_gps(&mut out);
+    let tiff = tiff::Tiff::new(data)?;
+    let packet = tiff
+        .first_ifd()
+        .and_then(|ifd0| tiff.find(ifd0, tiff::XMP))
+        .and_then(|entry| tiff.value_range(&entry));
+    if let Some(range) = packet {
+        if let Some(cleaned) = xmp_without_gps(&data[range.clone()]) {
+            let mut cleaned = cleaned.into_bytes();
+            cleaned.resize(range.len(), b' ');
+            out[range].copy_from_slice(&cleaned);
+        }
+    }
+    Some(out)
+}
+
+/// The CRC-32 PNG chunks end with
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs_ops::image_metadata::{read, tests::exif_block};
+    use crate::fs_ops::tags;
+
+    #[test]
+    fn test_strip() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+
+        let exif = exif_block();
+        let xmp = br#"<rdf:Description tiff:Make="Canon" exif:GPSLatitude="48,51.5N"/>"#;
+        let mut photo = vec![0xff, 0xd8];
+        push_jpeg_segment(&mut photo, 0xe1, &[EXIF_PREFIX, &exif]);
+        push_jpeg_segment(&mut photo, 0xe1, &[XMP_PREFIX, xmp]);
+        push_jpeg_segment(&mut photo, 0xe2, &[b"ICC_PROFILE\0data"]);
+        push_jpeg_segment(&mut photo, 0xfe, &[b"comment"]);
+        photo.extend([0xff, 0xda, 0, 2, 1, 2, 3, 0xff, 0xd9]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        std::fs::write(&path, &photo).unwrap();
+
+        assert_eq!(strip(&path, StripMode::Location), Ok(true));
+        let metadata = read(&path).unwrap();
+        assert_eq!(metadata.gps, None);
+        assert_eq!(metadata.model.as_deref(), Some("Canon EOS R6"));
+        assert_eq!(metadata.iso, Some(400));
+        assert_eq!(strip(&path, StripMode::Location), Ok(false));
+
+        assert_eq!(strip(&path, StripMode::All), Ok(true));
+        let stripped = std::fs::read(&path).unwrap();
+        let metadata = read(&path).unwrap();
+        assert_eq!(metadata.orientation, Some(6));
+        assert_eq!(metadata.make, None);
+        assert!(stripped.windows(11).any(|w| w == b"ICC_PROFILE"));
+        assert!(!stripped.windows(7).any(|w| w == b"comment"));
+        assert!(stripped.ends_with(&[0xff, 0xda, 0, 2, 1, 2, 3, 0xff, 0xd9]));
+        assert_eq!(strip(&path, StripMode::All), Ok(false));
+
+        // Tags survive the file being replaced, where xattrs are supported
+        std::fs::write(&path, &photo).unwrap();
+        if tags::write_tags(&path, &["holiday".to_string()]).is_ok() {
+            assert_eq!(strip(&path, StripMode::All), Ok(true));
+            assert_eq!(tags::read_tags(&path), vec!["holiday".to_string()]);
+        }
+
+        // Other hard links see the stripped photo too
+        std::fs::write(&path, &photo).unwrap();
+        let link = dir.path().join("link.jpg");
+        std::fs::hard_link(&path, &link).unwrap();
+        assert_eq!(strip(&path, StripMode::All), Ok(true));
+        assert_eq!(read(&link).unwrap().make, None);
+    }
+}
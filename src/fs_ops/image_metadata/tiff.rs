@@ -0,0 +1,195 @@
+// The TIFF structure EXIF is stored in: a byte order mark, then chains of
+// IFDs whose entries hold small values inline and point at larger ones.
+// Parsing is bounds-checked throughout, so a truncated block just yields
+// fewer tags.
+
+use std::ops::Range;
+
+pub const ORIENTATION: u16 = 0x0112;
+pub const EXIF_IFD: u16 = 0x8769;
+pub const GPS_IFD: u16 = 0x8825;
+pub const XMP: u16 = 0x02bc;
+
+/// One IFD entry; `offset` is where its value is, inline or not
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Entry {
+    pub tag: u16,
+    kind: u16,
+    count: u32,
+    /// Position of the entry itself
+    position: usize,
+    offset: usize,
+}
+
+impl Entry {
+    fn len(&self) -> Option<usize> {
+        let size = match self.kind {
+            1 | 2 | 6 | 7 => 1,
+            3 | 8 => 2,
+            4 | 9 | 11 => 4,
+            5 | 10 | 12 => 8,
+            _ => return None,
+        };
+        (self.count as usize).checked_mul(size)
+    }
+}
+
+pub struct Tiff<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        let big_endian = match data.get(..4)? {
+            b"II*\0" => false,
+            b"MM\0*" => true,
+            _ => return None,
+        };
+        Some(Self { data, big_endian })
+    }
+
+    fn u16_at(&self, at: usize) -> Option<u16> {
+        let bytes = [*self.data.get(at)?, *self.data.get(at + 1)?];
+        Some(match self.big_endian {
+            true => u16::from_be_bytes(bytes),
+            false => u16::from_le_bytes(bytes),
+        })
+    }
+
+    fn u32_at(&self, at: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(at..at + 4)?.try_into().ok()?;
+        Some(match self.big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        })
+    }
+
+    /// Offset of IFD0
+    pub fn first_ifd(&self) -> Option<usize> {
+        Some(self.u32_at(4)? as usize)
+    }
+
+    /// The entries of the IFD at `ifd`
+    pub fn entries(&self, ifd: usize) -> Vec<Entry> {
+        let count = self.u16_at(ifd).unwrap_or(0) as usize;
+        (0..count)
+            .map_while(|i| {
+                let position = ifd + 2 + i * 12;
+                let mut entry = Entry {
+                    tag: self.u16_at(position)?,
+                    kind: self.u16_at(position + 2)?,
+                    count: self.u32_at(position + 4)?,
+                    position,
+                    offset: position + 8,
+                };
+                if entry.len().is_some_and(|len| len > 4) {
+                    entry.offset = self.u32_at(position + 8)? as usize;
+                }
+                Some(entry)
+            })
+            .collect()
+    }
+
+    /// The entry for `tag` in the IFD at `ifd`
+    pub fn find(&self, ifd: usize, tag: u16) -> Option<Entry> {
+        self.entries(ifd).into_iter().find(|e| e.tag == tag)
+    }
+
+    /// The IFD a pointer tag such as `EXIF_IFD` in IFD0 leads to
+    pub fn sub_ifd(&self, ifd: usize, tag: u16) -> Option<usize> {
+        Some(self.uint(&self.find(ifd, tag)?, 0)? as usize)
+    }
+
+    /// Where the value of `entry` is in the data
+    pub fn value_range(&self, entry: &Entry) -> Option<Range<usize>> {
+        let range = entry.offset..entry.offset.checked_add(entry.len()?)?;
+        (range.end <= self.data.len()).then_some(range)
+    }
+
+    pub fn bytes(&self, entry: &Entry) -> Option<&'a [u8]> {
+        self.data.get(self.value_range(entry)?)
+    }
+
+    pub fn ascii(&self, entry: &Entry) -> Option<String> {
+        let bytes = self.bytes(entry)?;
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        let text = String::from_utf8_lossy(&bytes[..end]).trim().to_string();
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// Value `i` of a BYTE, SHORT or LONG entry
+    pub fn uint(&self, entry: &Entry, i: usize) -> Option<u32> {
+        if i >= entry.count as usize {
+            return None;
+        }
+        match entry.kind {
+            1 | 7 => self.data.get(entry.offset + i).map(|&b| b as u32),
+            3 => self.u16_at(entry.offset + i * 2).map(u32::from),
+            4 => self.u32_at(entry.offset + i * 4),
+            _ => None,
+        }
+    }
+
+    /// Value `i` of a RATIONAL entry, as numerator and denominator
+    pub fn rational(&self, entry: &Entry, i: usize) -> Option<(u32, u32)> {
+        if entry.kind != 5 || i >= entry.count as usize {
+            return None;
+        }
+        let at = entry.offset + i * 8;
+        Some((self.u32_at(at)?, self.u32_at(at + 4)?))
+    }
+
+    pub fn float(&self, entry: &Entry, i: usize) -> Option<f64> {
+        let (num, den) = self.rational(entry, i)?;
+        (den != 0).then(|| num as f64 / den as f64)
+    }
+}
+
+/// Blanks the GPS IFD in place: its values and entries are zeroed and its
+/// count set to zero, leaving every other offset valid. Returns whether
+/// there was one.
+pub fn remove_gps(data: &mut [u8]) -> bool {
+    let Some(tiff) = Tiff::new(data) else {
+        return false;
+    };
+    let Some(gps) = tiff
+        .first_ifd()
+        .and_then(|ifd0| tiff.sub_ifd(ifd0, GPS_IFD))
+    else {
+        return false;
+    };
+    let entries = tiff.entries(gps);
+    if entries.is_empty() {
+        return false;
+    }
+    for entry in &entries {
+        if let Some(len) = entry.len().filter(|&len| len > 4) {
+            if let Some(value) = data.get_mut(entry.offset..entry.offset + len) {
+                value.fill(0);
+            }
+        }
+        if let Some(bytes) = data.get_mut(entry.position..entry.position + 12) {
+            bytes.fill(0);
+        }
+    }
+    data[gps..gps + 2].fill(0);
+    true
+}
+
+/// A little endian TIFF block with just an orientation tag, kept when
+/// everything else is stripped so photos still display upright
+pub fn orientation_only(orientation: u16) -> Vec<u8> {
+    let mut data = b"II*\0".to_vec();
+    data.extend(8u32.to_le_bytes());
+    data.extend(1u16.to_le_bytes());
+    data.extend(ORIENTATION.to_le_bytes());
+    // One SHORT, padded to the four bytes of the value field
+    data.extend(3u16.to_le_bytes());
+    data.extend(1u32.to_le_bytes());
+    data.extend(orientation.to_le_bytes());
+    data.extend([0, 0]);
+    // No further IFDs
+    data.extend(0u32.to_le_bytes());
+    data
+}
@@ -0,0 +1,123 @@
+// Just enough of XMP, the RDF/XML packet editors write, to look up a
+// property and to take the location out. Properties come either as
+// attributes (`exif:FNumber="28/10"`) or as elements, sometimes wrapping
+// an `rdf:Seq`/`rdf:Alt` list whose first item is used.
+
+/// The value of property `name`, e.g. "tiff:Model"
+pub fn value(xmp: &str, name: &str) -> Option<String> {
+    let attribute = format!("{}=\"", name);
+    if let Some(start) = xmp.find(&attribute).map(|i| i + attribute.len()) {
+        let end = xmp[start..].find('"')? + start;
+        return clean(&xmp[start..end]);
+    }
+    let open = format!("<{}>", name);
+    let start = xmp.find(&open)? + open.len();
+    let end = xmp[start..].find(&format!("</{}>", name))? + start;
+    let inner = &xmp[start..end];
+    match inner.find("<rdf:li") {
+        Some(item) => {
+            let text_start = inner[item..].find('>')? + item + 1;
+            let text_end = inner[text_start..].find('<')? + text_start;
+            clean(&inner[text_start..text_end])
+        }
+        None => clean(inner),
+    }
+}
+
+fn clean(text: &str) -> Option<String> {
+    let text = text
+        .trim()
+        .replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"");
+    (!text.is_empty()).then_some(text)
+}
+
+/// A coordinate such as "48,51.5022N" or "48,51,30.13N" in degrees,
+/// negative to the south and west
+pub fn coordinate(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let direction = text.chars().last()?;
+    let sign = match direction {
+        'N' | 'E' => 1.0,
+        'S' | 'W' => -1.0,
+        _ => return None,
+    };
+    let parts: Vec<f64> = text[..text.len() - 1]
+        .split(',')
+        .map(|part| part.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    let degrees = parts
+        .iter()
+        .zip([1.0, 60.0, 3600.0])
+        .map(|(value, scale)| value / scale)
+        .sum::<f64>();
+    Some(sign * degrees)
+}
+
+/// The packet without its `exif:GPS…` properties, in either form
+pub fn remove_gps(xmp: &str) -> String {
+    const PREFIX: &str = "exif:GPS";
+    let mut out = String::with_capacity(xmp.len());
+    let mut rest = xmp;
+    while let Some(found) = rest.find(PREFIX) {
+        let name_len = rest[found..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == ':'))
+            .unwrap_or(rest.len() - found);
+        let name = &rest[found..found + name_len];
+        let after = &rest[found + name_len..];
+
+        if found > 0 && rest[..found].ends_with('<') {
+            // An element: drop it through its closing tag
+            out.push_str(&rest[..found - 1]);
+            let close = format!("</{}>", name);
+            rest = match (after.find("/>"), after.find('>'), after.find(&close)) {
+                (Some(self_closing), Some(gt), _) if self_closing + 1 == gt => &after[gt + 1..],
+                (_, _, Some(end)) => &after[end + close.len()..],
+                _ => after,
+            };
+        } else if let Some(value) = after.strip_prefix("=\"") {
+            // An attribute: drop it with its value and the space before it
+            out.push_str(rest[..found].trim_end());
+            rest = match value.find('"') {
+                Some(end) => &value[end + 1..],
+                None => "",
+            };
+        } else {
+            // Mentioned in some other way, e.g. in a closing tag
+            out.push_str(&rest[..found + name_len]);
+            rest = after;
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xmp() {
+        let xmp = r#"<x:xmpmeta><rdf:RDF><rdf:Description
+            tiff:Make="Canon" exif:GPSLatitude="48,51.5022N"
+            exif:GPSLongitude="2,17.6688E">
+            <exif:ISOSpeedRatings><rdf:Seq><rdf:li>200</rdf:li></rdf:Seq></exif:ISOSpeedRatings>
+            <aux:Lens>EF 50mm</aux:Lens>
+            <exif:GPSAltitude>35/1</exif:GPSAltitude>
+            </rdf:Description></rdf:RDF></x:xmpmeta>"#;
+        assert_eq!(value(xmp, "tiff:Make").as_deref(), Some("Canon"));
+        assert_eq!(value(xmp, "exif:ISOSpeedRatings").as_deref(), Some("200"));
+        assert_eq!(value(xmp, "aux:Lens").as_deref(), Some("EF 50mm"));
+        assert_eq!(value(xmp, "tiff:Model"), None);
+        let latitude = coordinate(&value(xmp, "exif:GPSLatitude").unwrap()).unwrap();
+        assert!((latitude - 48.85837).abs() < 1e-5);
+        assert_eq!(coordinate("2,30,0W"), Some(-2.5));
+
+        let stripped = remove_gps(xmp);
+        assert!(!stripped.contains("GPS"));
+        assert!(stripped.contains(r#"tiff:Make="Canon">"#));
+        assert_eq!(value(&stripped, "aux:Lens").as_deref(), Some("EF 50mm"));
+    }
+}
@@ -0,0 +1,198 @@
+// FLAC metadata blocks: STREAMINFO, the Vorbis comment and pictures. The
+// comment and picture layouts are shared with Ogg Vorbis and Opus.
+use super::{clean, image_mime, read_at, Bytes, Cover, MediaInfo, MAX_BLOCK};
+use std::fs::File;
+use std::io;
+use std::time::Duration;
+
+const STREAMINFO: u8 = 0;
+const VORBIS_COMMENT: u8 = 4;
+const PICTURE: u8 = 6;
+
+pub fn read(file: &mut File) -> io::Result<MediaInfo> {
+    let mut info = MediaInfo {
+        codec: Some("FLAC".to_string()),
+        ..Default::default()
+    };
+    let mut covers = Vec::new();
+    let mut offset = 4;
+    loop {
+        let header = read_at(file, offset, 4)?;
+        let &[kind, a, b, c] = header.as_slice() else {
+            break;
+        };
+        let len = u32::from_be_bytes([0, a, b, c]) as u64;
+        let block_type = kind & 0x7f;
+        if matches!(block_type, STREAMINFO | VORBIS_COMMENT | PICTURE) && len <= MAX_BLOCK {
+            let block = read_at(file, offset + 4, len)?;
+            match block_type {
+                STREAMINFO => stream_info(&block, &mut info),
+                VORBIS_COMMENT => comments(&block, &mut info, &mut covers),
+                _ => covers.extend(picture(&block)),
+            }
+        }
+        if kind & 0x80 != 0 {
+            break;
+        }
+        offset += 4 + len;
+    }
+    set_cover(&mut info, covers);
+    Ok(info)
+}
+
+fn stream_info(block: &[u8], info: &mut MediaInfo) {
+    // 20 bits of sample rate, 3 of channels, 5 of bits per sample and 36
+    // of total samples, after the block and frame sizes
+    let Some(bits) = block.get(10..18) else {
+        return;
+    };
+    let packed = u64::from_be_bytes(bits.try_into().unwrap_or_default());
+    let sample_rate = (packed >> 44) as u32;
+    let channels = ((packed >> 41) & 0x7) as u16 + 1;
+    let total = packed & 0xf_ffff_ffff;
+    info.sample_rate = Some(sample_rate);
+    info.channels = Some(channels);
+    if sample_rate > 0 && total > 0 {
+        info.duration = Some(Duration::from_secs_f64(total as f64 / sample_rate as f64));
+    }
+}
+
+/// Reads a Vorbis comment block (little endian lengths, "KEY=value"
+/// entries) into the tags, collecting any base64 encoded pictures
+pub fn comments(block: &[u8], info: &mut MediaInfo, covers: &mut Vec<(u32, Cover)>) {
+    let mut bytes = Bytes::new(block);
+    let Some(vendor) = bytes.le_u32() else {
+        return;
+    };
+    if bytes.skip(vendor as usize).is_none() {
+        return;
+    }
+    let count = bytes.le_u32().unwrap_or(0);
+    for _ in 0..count {
+        let Some(entry) = bytes.le_u32().and_then(|len| bytes.take(len as usize)) else {
+            break;
+        };
+        let entry = String::from_utf8_lossy(entry);
+        let Some((key, value)) = entry.split_once('=') else {
+            continue;
+        };
+        let tags = &mut info.tags;
+        let field = match key.to_ascii_uppercase().as_str() {
+            "TITLE" => &mut tags.title,
+            "ARTIST" => &mut tags.artist,
+            "ALBUM" => &mut tags.album,
+            "TRACKNUMBER" => &mut tags.track,
+            "METADATA_BLOCK_PICTURE" => {
+                covers.extend(base64_decode(value).and_then(|data| picture(&data)));
+                continue;
+            }
+            _ => continue,
+        };
+        if field.is_none() {
+            *field = clean(value.to_string());
+        }
+    }
+}
+
+/// A FLAC picture block (big endian) and its picture type
+pub fn picture(block: &[u8]) -> Option<(u32, Cover)> {
+    let mut bytes = Bytes::new(block);
+    let kind = bytes.be_u32()?;
+    let mime_len = bytes.be_u32()? as usize;
+    let mime = String::from_utf8_lossy(bytes.take(mime_len)?).into_owned();
+    let description_len = bytes.be_u32()? as usize;
+    // Description, then width, height, depth and palette size
+    bytes.skip(description_len + 16)?;
+    let data_len = bytes.be_u32()? as usize;
+    let data = bytes.take(data_len)?.to_vec();
+    let mime = match mime.as_str() {
+        "" | "image/jpg" => image_mime(&data).to_string(),
+        _ => mime,
+    };
+    Some((kind, Cover { mime, data }))
+}
+
+/// Keeps the front cover, or else the first picture
+pub fn set_cover(info: &mut MediaInfo, mut covers: Vec<(u32, Cover)>) {
+    if info.cover.is_none() && !covers.is_empty() {
+        let front = covers.iter().position(|(kind, _)| *kind == 3).unwrap_or(0);
+        info.cover = Some(covers.swap_remove(front).1);
+    }
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in text
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace() && *c != b'=')
+    {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs_ops::media;
+
+    #[test]
+    fn test_read_flac() {
+        let mut file = b"fLaC".to_vec();
+        // STREAMINFO: 48 kHz, stereo, 16 bit, 96000 samples
+        let packed: u64 = (48000 << 44) | (1 << 41) | (15 << 36) | 96000;
+        let mut streaminfo = vec![0; 10];
+        streaminfo.extend(packed.to_be_bytes());
+        streaminfo.extend([0; 16]);
+        file.extend([STREAMINFO, 0, 0, streaminfo.len() as u8]);
+        file.extend(streaminfo);
+
+        let mut comment = Vec::new();
+        comment.extend(6u32.to_le_bytes());
+        comment.extend(b"vendor");
+        comment.extend(3u32.to_le_bytes());
+        // A front cover picture block, "image/png" with the data "PNG"
+        let picture = "AAAAAwAAAAlpbWFnZS9wbmcAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAANQTkc=";
+        let entries = [
+            "title=Tïtle".to_string(),
+            "ARTIST=Band".to_string(),
+            format!("METADATA_BLOCK_PICTURE={}", picture),
+        ];
+        for entry in entries {
+            comment.extend((entry.len() as u32).to_le_bytes());
+            comment.extend(entry.as_bytes());
+        }
+        file.extend([0x80 | VORBIS_COMMENT, 0, 0, comment.len() as u8]);
+        file.extend(comment);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("song.flac");
+        std::fs::write(&path, file).unwrap();
+        let info = media::read(&path).unwrap();
+
+        assert_eq!(info.duration, Some(Duration::from_secs(2)));
+        assert_eq!((info.sample_rate, info.channels), (Some(48000), Some(2)));
+        assert_eq!(info.tags.title.as_deref(), Some("Tïtle"));
+        assert_eq!(info.tags.artist.as_deref(), Some("Band"));
+        let cover = info.cover.unwrap();
+        assert_eq!(
+            (cover.mime.as_str(), cover.data.as_slice()),
+            ("image/png", &b"PNG"[..])
+        );
+    }
+}
@@ -0,0 +1,221 @@
+// ID3v2.2-2.4 tags, as found at the start of MP3 files and in WAV chunks,
+// and the older fixed-size ID3v1 tag at the end of MP3 files.
+use super::{clean, image_mime, Bytes, Cover, MediaInfo};
+use std::time::Duration;
+
+/// Whole size of the tag whose 10-byte header starts `header`
+pub fn tag_size(header: &[u8]) -> Option<u64> {
+    if header.len() < 10 || !header.starts_with(b"ID3") {
+        return None;
+    }
+    let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+    Some(10 + syncsafe(&header[6..10]) as u64 + footer)
+}
+
+/// Fills in the tags and cover of `info` from a whole ID3v2 tag
+pub fn parse(tag: &[u8], info: &mut MediaInfo) {
+    let Some(size) = tag_size(tag) else {
+        return;
+    };
+    let (version, flags) = (tag[3], tag[5]);
+    let end = (size as usize).min(tag.len());
+    let mut body = tag[10..end].to_vec();
+    // v2.4 unsynchronises frame by frame instead
+    if flags & 0x80 != 0 && version < 4 {
+        body = resynchronise(&body);
+    }
+
+    let mut frames = Bytes::new(&body);
+    if flags & 0x40 != 0 && version >= 3 {
+        let Some(extended) = frames.take(4) else {
+            return;
+        };
+        // v2.3 counts the size bytes as part of the header, v2.4 doesn't
+        let skip = match version {
+            3 => u32::from_be_bytes([extended[0], extended[1], extended[2], extended[3]]),
+            _ => syncsafe(extended).saturating_sub(4),
+        };
+        if frames.skip(skip as usize).is_none() {
+            return;
+        }
+    }
+
+    let mut covers = Vec::new();
+    loop {
+        let (id, format, data) = if version == 2 {
+            let Some(header) = frames.take(6) else { break };
+            let size = u32::from_be_bytes([0, header[3], header[4], header[5]]);
+            (&header[..3], 0, frames.take(size as usize))
+        } else {
+            let Some(header) = frames.take(10) else { break };
+            let size = match version {
+                3 => u32::from_be_bytes([header[4], header[5], header[6], header[7]]),
+                _ => syncsafe(&header[4..8]),
+            };
+            (&header[..4], header[9], frames.take(size as usize))
+        };
+        // Padding, or a frame running past the end
+        let Some(mut data) = data.filter(|_| id[0] != 0) else {
+            break;
+        };
+        let (compressed_or_encrypted, unsynchronised, length_prefixed) = match version {
+            3 => (format & 0xc0 != 0, false, false),
+            4 => (format & 0x0c != 0, format & 0x02 != 0, format & 0x01 != 0),
+            _ => (false, false, false),
+        };
+        if compressed_or_encrypted {
+            continue;
+        }
+        if length_prefixed {
+            data = data.get(4..).unwrap_or_default();
+        }
+        let resynchronised;
+        if unsynchronised {
+            resynchronised = resynchronise(data);
+            data = &resynchronised;
+        }
+
+        let tags = &mut info.tags;
+        match id {
+            b"TIT2" | b"TT2" => tags.title = tags.title.take().or_else(|| text(data)),
+            b"TPE1" | b"TP1" => tags.artist = tags.artist.take().or_else(|| text(data)),
+            b"TALB" | b"TAL" => tags.album = tags.album.take().or_else(|| text(data)),
+            b"TRCK" | b"TRK" => tags.track = tags.track.take().or_else(|| text(data)),
+            b"TLEN" | b"TLE" => {
+                let millis = text(data).and_then(|ms| ms.trim().parse::<u64>().ok());
+                info.duration = info.duration.or(millis.map(Duration::from_millis));
+            }
+            b"APIC" => covers.extend(picture(data, false)),
+            b"PIC" => covers.extend(picture(data, true)),
+            _ => {}
+        }
+    }
+    // The front cover if it's marked, the first picture otherwise
+    if info.cover.is_none() && !covers.is_empty() {
+        let front = covers.iter().position(|(kind, _)| *kind == 3).unwrap_or(0);
+        info.cover = Some(covers.swap_remove(front).1);
+    }
+}
+
+/// Title, artist, album and track from a 128-byte ID3v1 tag, for those the
+/// ID3v2 tag didn't have
+pub fn parse_v1(tag: &[u8], info: &mut MediaInfo) {
+    if tag.len() != 128 || !tag.starts_with(b"TAG") {
+        return;
+    }
+    let field = |range: std::ops::Range<usize>| {
+        let bytes = &tag[range];
+        let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        clean(latin1(&bytes[..len]))
+    };
+    let tags = &mut info.tags;
+    tags.title = tags.title.take().or_else(|| field(3..33));
+    tags.artist = tags.artist.take().or_else(|| field(33..63));
+    tags.album = tags.album.take().or_else(|| field(63..93));
+    // ID3v1.1 keeps the track in the last byte of the comment
+    if tag[125] == 0 && tag[126] != 0 {
+        tags.track = tags.track.take().or_else(|| Some(tag[126].to_string()));
+    }
+}
+
+/// A 28-bit integer stored 7 bits to a byte
+fn syncsafe(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .take(4)
+        .fold(0, |n, &b| (n << 7) | (b & 0x7f) as u32)
+}
+
+/// Undoes unsynchronisation: the 0x00 inserted after every 0xFF
+fn resynchronise(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut after_ff = false;
+    for &b in data {
+        if !(after_ff && b == 0) {
+            out.push(b);
+        }
+        after_ff = b == 0xff;
+    }
+    out
+}
+
+/// The first value of a text frame
+fn text(data: &[u8]) -> Option<String> {
+    let (&encoding, rest) = data.split_first()?;
+    let (value, _) = terminated(encoding, rest);
+    clean(value)
+}
+
+/// Decodes a string up to its terminator in the frame's text encoding,
+/// returning it and the bytes after the terminator
+fn terminated(encoding: u8, data: &[u8]) -> (String, &[u8]) {
+    let wide = matches!(encoding, 1 | 2);
+    let end = if wide {
+        data.chunks_exact(2)
+            .position(|pair| pair == [0, 0])
+            .map(|i| i * 2)
+    } else {
+        data.iter().position(|&b| b == 0)
+    };
+    let (value, rest) = match end {
+        Some(end) => (&data[..end], &data[end + if wide { 2 } else { 1 }..]),
+        None => (data, &data[data.len()..]),
+    };
+    let decoded = match encoding {
+        0 => latin1(value),
+        1 | 2 => {
+            let (big_endian, value) = match value {
+                [0xfe, 0xff, rest @ ..] => (true, rest),
+                [0xff, 0xfe, rest @ ..] => (false, rest),
+                _ => (encoding == 2, value),
+            };
+            let units: Vec<u16> = value
+                .chunks_exact(2)
+                .map(|pair| match big_endian {
+                    true => u16::from_be_bytes([pair[0], pair[1]]),
+                    false => u16::from_le_bytes([pair[0], pair[1]]),
+                })
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => String::from_utf8_lossy(value).into_owned(),
+    };
+    (decoded, rest)
+}
+
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+/// An attached picture and its picture type; v2.2 names the format with
+/// three letters rather than a mime type
+fn picture(data: &[u8], v22: bool) -> Option<(u8, Cover)> {
+    let (&encoding, rest) = data.split_first()?;
+    let (mime, rest) = if v22 {
+        let format = rest.get(..3)?;
+        let mime = match format.to_ascii_uppercase().as_slice() {
+            b"PNG" => "image/png",
+            _ => "image/jpeg",
+        };
+        (mime.to_string(), &rest[3..])
+    } else {
+        terminated(0, rest)
+    };
+    let (&kind, rest) = rest.split_first()?;
+    let (_description, image) = terminated(encoding, rest);
+    if image.is_empty() {
+        return None;
+    }
+    let mime = match mime.as_str() {
+        "" | "image/jpg" => image_mime(image).to_string(),
+        _ if !mime.contains('/') => format!("image/{}", mime.to_lowercase()),
+        _ => mime,
+    };
+    Some((
+        kind,
+        Cover {
+            mime,
+            data: image.to_vec(),
+        },
+    ))
+}
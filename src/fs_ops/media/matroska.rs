@@ -0,0 +1,430 @@
+// Matroska and WebM: the segment's Info, Tracks, Tags and Attachments
+// elements. Clusters of media data are never read; elements after them are
+// found through the SeekHead index.
+use super::{clean, join_codecs, read_at, Bytes, Cover, MediaInfo, MAX_BLOCK};
+use std::fs::File;
+use std::io;
+use std::time::Duration;
+
+pub const EBML_MAGIC: [u8; 4] = [0x1a, 0x45, 0xdf, 0xa3];
+
+const SEGMENT: u32 = 0x1853_8067;
+const SEEK_HEAD: u32 = 0x114d_9b74;
+const INFO: u32 = 0x1549_a966;
+const TRACKS: u32 = 0x1654_ae6b;
+const TAGS: u32 = 0x1254_c367;
+const ATTACHMENTS: u32 = 0x1941_a469;
+const CLUSTER: u32 = 0x1f43_b675;
+
+/// An element ID, keeping its length marker as Matroska's IDs are written
+fn element_id(bytes: &mut Bytes) -> Option<u32> {
+    let first = bytes.u8()?;
+    let len = first.leading_zeros() as usize + 1;
+    if len > 4 {
+        return None;
+    }
+    let rest = bytes.take(len - 1)?;
+    Some(
+        rest.iter()
+            .fold(first as u32, |id, &b| (id << 8) | b as u32),
+    )
+}
+
+/// An element size with its length marker removed; `None` inside the
+/// option for the "unknown" size of live streams
+fn element_size(bytes: &mut Bytes) -> Option<Option<u64>> {
+    let first = bytes.u8()?;
+    let len = first.leading_zeros() as usize + 1;
+    if len > 8 {
+        return None;
+    }
+    let rest = bytes.take(len - 1)?;
+    let mask = (1u64 << (7 * len)) - 1;
+    let size = rest
+        .iter()
+        .fold((first as u64) & (0xff >> len), |size, &b| {
+            (size << 8) | b as u64
+        });
+    Some((size != mask).then_some(size))
+}
+
+/// The elements in `data`, as ID and contents
+fn elements(data: &[u8]) -> impl Iterator<Item = (u32, &[u8])> {
+    let mut bytes = Bytes::new(data);
+    std::iter::from_fn(move || {
+        let id = element_id(&mut bytes)?;
+        let body = match element_size(&mut bytes)? {
+            Some(size) => bytes.take(usize::try_from(size).ok()?)?,
+            None => bytes.rest(),
+        };
+        Some((id, body))
+    })
+}
+
+fn uint(data: &[u8]) -> u64 {
+    data.iter().take(8).fold(0, |n, &b| (n << 8) | b as u64)
+}
+
+fn float(data: &[u8]) -> Option<f64> {
+    match data.len() {
+        4 => Some(f32::from_be_bytes(data.try_into().ok()?) as f64),
+        8 => Some(f64::from_be_bytes(data.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+fn string(data: &[u8]) -> Option<String> {
+    clean(String::from_utf8_lossy(data).into_owned())
+}
+
+pub fn read(file: &mut File, size: u64) -> io::Result<MediaInfo> {
+    let head = read_at(file, 0, 256)?;
+    let segment_start = segment_start(&head)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "No Matroska segment"))?;
+
+    let mut found: Vec<(u32, Vec<u8>)> = Vec::new();
+    let mut seek_positions = Vec::new();
+    let mut offset = segment_start;
+    while offset < size {
+        let header = read_at(file, offset, 12)?;
+        let mut bytes = Bytes::new(&header);
+        let (Some(id), Some(Some(len))) = (element_id(&mut bytes), element_size(&mut bytes)) else {
+            break;
+        };
+        let body_start = offset + bytes.pos as u64;
+        match id {
+            // Without an index the clusters are skipped one by one
+            CLUSTER if !seek_positions.is_empty() => break,
+            SEEK_HEAD | INFO | TRACKS | TAGS | ATTACHMENTS if len <= MAX_BLOCK => {
+                let body = read_at(file, body_start, len)?;
+                if id == SEEK_HEAD {
+                    seek_positions.extend(seek_head(&body));
+                } else {
+                    found.push((id, body));
+                }
+            }
+            _ => {}
+        }
+        offset = body_start + len;
+    }
+
+    // Elements the SeekHead points to past the clusters
+    for (id, position) in seek_positions {
+        if found.iter().any(|(found_id, _)| *found_id == id)
+            || !matches!(id, INFO | TRACKS | TAGS | ATTACHMENTS)
+        {
+            continue;
+        }
+        let header = read_at(file, segment_start + position, 12)?;
+        let mut bytes = Bytes::new(&header);
+        if element_id(&mut bytes) != Some(id) {
+            continue;
+        }
+        if let Some(Some(len)) = element_size(&mut bytes) {
+            if len <= MAX_BLOCK {
+                let start = segment_start + position + bytes.pos as u64;
+                found.push((id, read_at(file, start, len)?));
+            }
+        }
+    }
+
+    let mut info = MediaInfo::default();
+    for id in [INFO, TRACKS, TAGS, ATTACHMENTS] {
+        let Some((_, body)) = found.iter().find(|(found_id, _)| *found_id == id) else {
+            continue;
+        };
+        match id {
+            INFO => segment_info(body, &mut info),
+            TRACKS => tracks(body, &mut info),
+            TAGS => tags(body, &mut info),
+            _ => attachments(body, &mut info),
+        }
+    }
+    Ok(info)
+}
+
+/// Where the segment's data starts, after the EBML header
+fn segment_start(head: &[u8]) -> Option<u64> {
+    let mut bytes = Bytes::new(head);
+    element_id(&mut bytes)?;
+    let header_len = element_size(&mut bytes)??;
+    bytes.skip(header_len as usize)?;
+    if element_id(&mut bytes)? != SEGMENT {
+        return None;
+    }
+    element_size(&mut bytes)?;
+    Some(bytes.pos as u64)
+}
+
+/// IDs and positions, relative to the segment's data, from a SeekHead
+fn seek_head(body: &[u8]) -> Vec<(u32, u64)> {
+    elements(body)
+        .filter(|(id, _)| *id == 0x4dbb)
+        .filter_map(|(_, seek)| {
+            let mut id = None;
+            let mut position = None;
+            for (child, data) in elements(seek) {
+                match child {
+                    0x53ab => id = Some(uint(data) as u32),
+                    0x53ac => position = Some(uint(data)),
+                    _ => {}
+                }
+            }
+            id.zip(position)
+        })
+        .collect()
+}
+
+fn segment_info(body: &[u8], info: &mut MediaInfo) {
+    let mut scale = 1_000_000;
+    let mut duration = None;
+    for (id, data) in elements(body) {
+        match id {
+            0x2ad7b1 => scale = uint(data),
+            0x4489 => duration = float(data),
+            0x7ba9 => info.tags.title = info.tags.title.take().or_else(|| string(data)),
+            _ => {}
+        }
+    }
+    info.duration = duration
+        .filter(|d| *d > 0.0)
+        .map(|d| Duration::from_secs_f64(d * scale as f64 / 1e9));
+}
+
+fn tracks(body: &[u8], info: &mut MediaInfo) {
+    let (mut video, mut audio) = (None, None);
+    for (_, entry) in elements(body).filter(|(id, _)| *id == 0xae) {
+        let mut kind = 0;
+        let mut codec = None;
+        let mut frame_duration = None;
+        let mut settings = None;
+        for (id, data) in elements(entry) {
+            match id {
+                0x83 => kind = uint(data),
+                0x86 => codec = Some(String::from_utf8_lossy(data).into_owned()),
+                0x23e383 => frame_duration = Some(uint(data)),
+                0xe0 | 0xe1 => settings = Some(data),
+                _ => {}
+            }
+        }
+        let settings = settings.unwrap_or_default();
+        match kind {
+            1 if video.is_none() => {
+                video = codec.as_deref().map(codec_name);
+                let (mut width, mut height) = (0, 0);
+                for (id, data) in elements(settings) {
+                    match id {
+                        0xb0 => width = uint(data) as u32,
+                        0xba => height = uint(data) as u32,
+                        _ => {}
+                    }
+                }
+                info.resolution = Some((width, height)).filter(|&(w, h)| w > 0 && h > 0);
+                info.frame_rate = frame_duration
+                    .filter(|&ns| ns > 0)
+                    .map(|ns| 1e9 / ns as f64);
+            }
+            2 if audio.is_none() => {
+                audio = codec.as_deref().map(codec_name);
+                info.sample_rate = Some(8000);
+                info.channels = Some(1);
+                for (id, data) in elements(settings) {
+                    match id {
+                        0xb5 => info.sample_rate = float(data).map(|rate| rate as u32),
+                        0x9f => info.channels = Some(uint(data) as u16),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    info.codec = join_codecs(video, audio);
+}
+
+/// Tags for the whole file (a target type of 50 names the album) or its
+/// first track
+fn tags(body: &[u8], info: &mut MediaInfo) {
+    for (_, tag) in elements(body).filter(|(id, _)| *id == 0x7373) {
+        let mut target = 50;
+        let mut simple_tags = Vec::new();
+        for (id, data) in elements(tag) {
+            match id {
+                0x63c0 => {
+                    for (id, data) in elements(data) {
+                        if id == 0x68ca {
+                            target = uint(data);
+                        }
+                    }
+                }
+                0x67c8 => simple_tags.push(data),
+                _ => {}
+            }
+        }
+        for simple in simple_tags {
+            let mut name = String::new();
+            let mut value = None;
+            for (id, data) in elements(simple) {
+                match id {
+                    0x45a3 => name = String::from_utf8_lossy(data).to_uppercase(),
+                    0x4487 => value = string(data),
+                    _ => {}
+                }
+            }
+            let tags = &mut info.tags;
+            let field = match (name.as_str(), target) {
+                ("TITLE", 50) => &mut tags.album,
+                ("TITLE", _) => &mut tags.title,
+                ("ARTIST", _) => &mut tags.artist,
+                ("PART_NUMBER", 30) => &mut tags.track,
+                _ => continue,
+            };
+            if field.is_none() {
+                *field = value;
+            }
+        }
+    }
+}
+
+/// An attached picture named "cover", or else the first attached image
+fn attachments(body: &[u8], info: &mut MediaInfo) {
+    let mut covers = Vec::new();
+    for (_, file) in elements(body).filter(|(id, _)| *id == 0x61a7) {
+        let (mut name, mut mime, mut data) = (String::new(), String::new(), None);
+        for (id, value) in elements(file) {
+            match id {
+                0x466e => name = String::from_utf8_lossy(value).to_lowercase(),
+                0x4660 => mime = String::from_utf8_lossy(value).into_owned(),
+                0x465c => data = Some(value),
+                _ => {}
+            }
+        }
+        if let Some(data) = data.filter(|_| mime.starts_with("image/")) {
+            covers.push((
+                name.starts_with("cover"),
+                Cover {
+                    mime,
+                    data: data.to_vec(),
+                },
+            ));
+        }
+    }
+    let front = covers
+        .iter()
+        .position(|(is_cover, _)| *is_cover)
+        .unwrap_or(0);
+    if info.cover.is_none() && !covers.is_empty() {
+        info.cover = Some(covers.swap_remove(front).1);
+    }
+}
+
+fn codec_name(id: &str) -> String {
+    let name = match id {
+        "V_MPEG4/ISO/AVC" => "H.264",
+        "V_MPEGH/ISO/HEVC" => "H.265",
+        "V_AV1" => "AV1",
+        "V_VP8" => "VP8",
+        "V_VP9" => "VP9",
+        "V_THEORA" => "Theora",
+        "A_OPUS" => "Opus",
+        "A_VORBIS" => "Vorbis",
+        "A_FLAC" => "FLAC",
+        "A_AC3" => "AC-3",
+        "A_EAC3" => "E-AC-3",
+        "A_DTS" => "DTS",
+        "A_MPEG/L3" => "MP3",
+        "A_TRUEHD" => "TrueHD",
+        id if id.starts_with("A_AAC") => "AAC",
+        id if id.starts_with("A_PCM") => "PCM",
+        id if id.starts_with("V_MPEG4") => "MPEG-4 Visual",
+        id => id.get(2..).unwrap_or(id),
+    };
+    name.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs_ops::media;
+
+    /// An element with a one-byte size
+    fn element(id: u32, parts: &[&[u8]]) -> Vec<u8> {
+        let body = parts.concat();
+        let id_bytes = id.to_be_bytes();
+        let skip = id_bytes.iter().position(|&b| b != 0).unwrap_or(3);
+        let mut out = id_bytes[skip..].to_vec();
+        out.push(0x80 | body.len() as u8);
+        out.extend(body);
+        out
+    }
+
+    #[test]
+    fn test_read_webm() {
+        let header = element(0x1a45dfa3, &[&element(0x4282, &[b"webm"])]);
+        // 12.5 seconds with the default timestamp scale
+        let info = element(INFO, &[&element(0x4489, &[&12500f64.to_be_bytes()])]);
+        let video = element(
+            0xae,
+            &[
+                &element(0x83, &[&[1]]),
+                &element(0x86, &[b"V_VP9"]),
+                &element(0x23e383, &[&40_000_000u32.to_be_bytes()]),
+                &element(
+                    0xe0,
+                    &[&element(0xb0, &[&[2, 128]]), &element(0xba, &[&[1, 104]])],
+                ),
+            ],
+        );
+        let audio = element(
+            0xae,
+            &[
+                &element(0x83, &[&[2]]),
+                &element(0x86, &[b"A_OPUS"]),
+                &element(
+                    0xe1,
+                    &[
+                        &element(0xb5, &[&48000f32.to_be_bytes()]),
+                        &element(0x9f, &[&[2]]),
+                    ],
+                ),
+            ],
+        );
+        let tracks = element(TRACKS, &[&video, &audio]);
+        let simple = |name: &[u8], value: &[u8]| {
+            element(
+                0x67c8,
+                &[&element(0x45a3, &[name]), &element(0x4487, &[value])],
+            )
+        };
+        let tags = element(
+            TAGS,
+            &[&element(
+                0x7373,
+                &[
+                    &element(0x63c0, &[&element(0x68ca, &[&[30]])]),
+                    &simple(b"ARTIST", b"Cast"),
+                ],
+            )],
+        );
+        let cluster = element(CLUSTER, &[&[0; 16]]);
+        let body = [info, tracks, cluster, tags].concat();
+        // Muxers write the segment's size in eight bytes
+        let mut file = header;
+        file.extend(SEGMENT.to_be_bytes());
+        file.extend([0x01, 0, 0, 0, 0, 0, 0, body.len() as u8]);
+        file.extend(body);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clip.webm");
+        std::fs::write(&path, file).unwrap();
+        let info = media::read(&path).unwrap();
+
+        assert_eq!(info.duration, Some(Duration::from_millis(12500)));
+        assert_eq!(info.codec.as_deref(), Some("VP9, Opus"));
+        assert_eq!(info.resolution, Some((640, 360)));
+        assert_eq!(info.frame_rate, Some(25.0));
+        assert_eq!((info.sample_rate, info.channels), (Some(48000), Some(2)));
+        // After the cluster, found without a SeekHead
+        assert_eq!(info.tags.artist.as_deref(), Some("Cast"));
+    }
+}
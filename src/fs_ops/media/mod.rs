@@ -0,0 +1,306 @@
+// Audio and video metadata, read without an external player: stream
+// properties, tags and embedded cover art of MP3, FLAC, Ogg Vorbis/Opus,
+// WAV, MP4/M4A/MOV and Matroska/WebM files. Only headers and tag blocks are
+// read, never the media data, so it's cheap enough for a whole folder.
+mod flac;
+mod id3;
+mod matroska;
+mod mp4;
+mod mpeg;
+mod ogg;
+mod riff;
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+
+/// Tag blocks and boxes larger than this are skipped rather than read
+const MAX_BLOCK: u64 = 32 * 1024 * 1024;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MediaTags {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    /// Track number, "3" or "3/12"
+    pub track: Option<String>,
+}
+
+/// Embedded cover art
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cover {
+    pub mime: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MediaInfo {
+    pub duration: Option<Duration>,
+    /// Video codec first for files with both, e.g. "H.264, AAC"
+    pub codec: Option<String>,
+    /// Bits per second; the average over the file when the stream doesn't say
+    pub bitrate: Option<u64>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u16>,
+    pub resolution: Option<(u32, u32)>,
+    pub frame_rate: Option<f64>,
+    pub tags: MediaTags,
+    pub cover: Option<Cover>,
+}
+
+/// What the details view's columns show of a media file
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MediaSummary {
+    pub duration: Option<Duration>,
+    pub artist: Option<String>,
+}
+
+/// Whether `path` has the extension of a format `read` understands
+pub fn is_media(path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    matches!(
+        ext.as_str(),
+        "mp3"
+            | "mp2"
+            | "flac"
+            | "ogg"
+            | "oga"
+            | "ogv"
+            | "opus"
+            | "wav"
+            | "m4a"
+            | "m4b"
+            | "mp4"
+            | "m4v"
+            | "mov"
+            | "3gp"
+            | "mkv"
+            | "mka"
+            | "webm"
+    )
+}
+
+/// Reads the metadata of a media file, telling the format by its first bytes
+pub fn read(path: &Path) -> Result<MediaInfo, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+    let head = read_at(&mut file, 0, 12).map_err(|e| e.to_string())?;
+    if head.len() < 12 {
+        return Err("Not a media file".to_string());
+    }
+
+    let mut info = if head.starts_with(b"ID3") || mpeg::is_frame_header(&head) {
+        mpeg::read(&mut file, size)
+    } else if head.starts_with(b"fLaC") {
+        flac::read(&mut file)
+    } else if head.starts_with(b"OggS") {
+        ogg::read(&mut file, size)
+    } else if head.starts_with(b"RIFF") && &head[8..12] == b"WAVE" {
+        riff::read(&mut file)
+    } else if &head[4..8] == b"ftyp" {
+        mp4::read(&mut file, size)
+    } else if head.starts_with(&matroska::EBML_MAGIC) {
+        matroska::read(&mut file, size)
+    } else {
+        return Err("Not a supported media format".to_string());
+    }
+    .map_err(|e| e.to_string())?;
+
+    if info.bitrate.is_none() {
+        info.bitrate = info
+            .duration
+            .filter(|d| !d.is_zero())
+            .map(|d| (size as f64 * 8.0 / d.as_secs_f64()) as u64);
+    }
+    Ok(info)
+}
+
+/// Duration and artist of `path`; empty for files that can't be read, so
+/// they aren't tried again
+pub fn summary(path: &Path) -> MediaSummary {
+    read(path)
+        .map(|info| MediaSummary {
+            duration: info.duration,
+            artist: info.tags.artist,
+        })
+        .unwrap_or_default()
+}
+
+/// "3:07", or "1:02:03" from an hour up
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// Labelled values for display, in a fixed order, leaving out unknowns
+pub fn describe(info: &MediaInfo) -> Vec<(&'static str, String)> {
+    let tags = &info.tags;
+    let rows = [
+        ("Title", tags.title.clone()),
+        ("Artist", tags.artist.clone()),
+        ("Album", tags.album.clone()),
+        ("Track", tags.track.clone()),
+        ("Duration", info.duration.map(format_duration)),
+        ("Codec", info.codec.clone()),
+        (
+            "Bitrate",
+            info.bitrate.map(|b| format!("{} kb/s", b / 1000)),
+        ),
+        (
+            "Sample rate",
+            info.sample_rate
+                .map(|r| format!("{:.1} kHz", r as f64 / 1000.0)),
+        ),
+        (
+            "Channels",
+            info.channels.map(|c| match c {
+                1 => "Mono".to_string(),
+                2 => "Stereo".to_string(),
+                n => n.to_string(),
+            }),
+        ),
+        (
+            "Resolution",
+            info.resolution.map(|(w, h)| format!("{}x{}", w, h)),
+        ),
+        (
+            "Frame rate",
+            info.frame_rate.map(|fps| {
+                let fps = format!("{:.3}", fps);
+                format!("{} fps", fps.trim_end_matches('0').trim_end_matches('.'))
+            }),
+        ),
+    ];
+    rows.into_iter()
+        .filter_map(|(label, value)| Some((label, value?)))
+        .collect()
+}
+
+/// Up to `len` bytes from `offset`; fewer at the end of the file
+fn read_at(file: &mut File, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buf = Vec::new();
+    file.take(len).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// A mime type for image bytes, for covers stored without one
+fn image_mime(data: &[u8]) -> &'static str {
+    if data.starts_with(b"\x89PNG") {
+        "image/png"
+    } else {
+        "image/jpeg"
+    }
+}
+
+/// Joins the video and audio codecs of a container's tracks
+fn join_codecs(video: Option<String>, audio: Option<String>) -> Option<String> {
+    match (video, audio) {
+        (Some(video), Some(audio)) => Some(format!("{}, {}", video, audio)),
+        (video, audio) => video.or(audio),
+    }
+}
+
+/// Text with trailing NULs and spaces removed; `None` if nothing is left
+fn clean(text: String) -> Option<String> {
+    let trimmed = text.trim_end_matches(['\0', ' ']).trim_start();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+/// Bounds-checked reads from a byte slice; running off the end gives `None`.
+struct Bytes<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Bytes<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(n)?;
+        let bytes = self.data.get(self.pos..end)?;
+        self.pos = end;
+        Some(bytes)
+    }
+
+    fn skip(&mut self, n: usize) -> Option<()> {
+        self.take(n).map(|_| ())
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.data[self.pos.min(self.data.len())..];
+        self.pos = self.data.len();
+        rest
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn be_u16(&mut self) -> Option<u16> {
+        Some(u16::from_be_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    fn be_u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn be_u64(&mut self) -> Option<u64> {
+        Some(u64::from_be_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn le_u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    fn le_u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(format_duration(Duration::from_secs(187)), "3:07");
+        assert_eq!(format_duration(Duration::from_secs(3723)), "1:02:03");
+
+        let info = MediaInfo {
+            duration: Some(Duration::from_secs(65)),
+            codec: Some("H.264, AAC".to_string()),
+            sample_rate: Some(44100),
+            channels: Some(2),
+            resolution: Some((1920, 1080)),
+            frame_rate: Some(23.976),
+            tags: MediaTags {
+                artist: Some("Someone".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            describe(&info),
+            vec![
+                ("Artist", "Someone".to_string()),
+                ("Duration", "1:05".to_string()),
+                ("Codec", "H.264, AAC".to_string()),
+                ("Sample rate", "44.1 kHz".to_string()),
+                ("Channels", "Stereo".to_string()),
+                ("Resolution", "1920x1080".to_string()),
+                ("Frame rate", "23.976 fps".to_string()),
+            ]
+        );
+    }
+}
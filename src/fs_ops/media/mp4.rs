@@ -0,0 +1,310 @@
+// MP4, M4A and QuickTime: the movie header and tracks in the `moov` box, and
+// iTunes-style tags from its `udta/meta/ilst`. Boxes at the top level are
+// skipped by their sizes, so `moov` is found after the media data too.
+use super::{clean, image_mime, join_codecs, read_at, Bytes, Cover, MediaInfo, MAX_BLOCK};
+use std::fs::File;
+use std::io;
+use std::time::Duration;
+
+pub fn read(file: &mut File, size: u64) -> io::Result<MediaInfo> {
+    let mut offset = 0;
+    while offset < size {
+        let header = read_at(file, offset, 16)?;
+        let mut bytes = Bytes::new(&header);
+        let (Some(len), Some(kind)) = (bytes.be_u32(), bytes.take(4)) else {
+            break;
+        };
+        let (header_len, len) = match len {
+            0 => (8, size - offset),
+            1 => (16, bytes.be_u64().unwrap_or(0)),
+            len => (8, len as u64),
+        };
+        if len < header_len {
+            break;
+        }
+        if kind == b"moov" {
+            if len > MAX_BLOCK {
+                break;
+            }
+            let moov = read_at(file, offset + header_len, len - header_len)?;
+            return Ok(parse_moov(&moov));
+        }
+        offset += len;
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "No movie header",
+    ))
+}
+
+/// The child boxes in `data`, as type and contents
+fn boxes(data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut bytes = Bytes::new(data);
+    std::iter::from_fn(move || {
+        let len = bytes.be_u32()? as usize;
+        let kind = bytes.take(4)?;
+        let body = match len {
+            0 => bytes.rest(),
+            1 => {
+                let len = bytes.be_u64()? as usize;
+                bytes.take(len.checked_sub(16)?)?
+            }
+            len => bytes.take(len.checked_sub(8)?)?,
+        };
+        Some((kind, body))
+    })
+}
+
+/// The contents of the first child box of type `kind`
+fn child<'a>(data: &'a [u8], kind: &[u8]) -> Option<&'a [u8]> {
+    boxes(data).find(|(k, _)| *k == kind).map(|(_, body)| body)
+}
+
+/// Timescale and duration from an `mvhd` or `mdhd` box
+fn timing(body: &[u8]) -> Option<(u32, u64)> {
+    let mut bytes = Bytes::new(body);
+    let version = bytes.u8()?;
+    bytes.skip(3)?;
+    if version == 1 {
+        bytes.skip(16)?;
+        Some((bytes.be_u32()?, bytes.be_u64()?))
+    } else {
+        bytes.skip(8)?;
+        Some((bytes.be_u32()?, bytes.be_u32()? as u64))
+    }
+}
+
+fn parse_moov(moov: &[u8]) -> MediaInfo {
+    let mut info = MediaInfo::default();
+    if let Some((timescale, duration)) = child(moov, b"mvhd").and_then(timing) {
+        if timescale > 0 {
+            info.duration = Some(Duration::from_secs_f64(duration as f64 / timescale as f64));
+        }
+    }
+
+    let (mut video, mut audio) = (None, None);
+    for (_, trak) in boxes(moov).filter(|(kind, _)| *kind == b"trak") {
+        let Some(mdia) = child(trak, b"mdia") else {
+            continue;
+        };
+        let handler = child(mdia, b"hdlr").and_then(|hdlr| hdlr.get(8..12));
+        let timing = child(mdia, b"mdhd").and_then(timing);
+        let stbl = child(mdia, b"minf").and_then(|minf| child(minf, b"stbl"));
+        let Some(entry) = stbl
+            .and_then(|stbl| child(stbl, b"stsd"))
+            .and_then(|stsd| boxes(stsd.get(8..)?).next())
+        else {
+            continue;
+        };
+        let (format, sample_entry) = entry;
+        match handler {
+            Some(b"vide") if video.is_none() => {
+                video = Some(codec_name(format));
+                // Reserved fields, then width and height
+                let mut bytes = Bytes::new(sample_entry);
+                if bytes.skip(24).is_some() {
+                    let width = bytes.be_u16().unwrap_or(0) as u32;
+                    let height = bytes.be_u16().unwrap_or(0) as u32;
+                    info.resolution = Some((width, height)).filter(|&(w, h)| w > 0 && h > 0);
+                }
+                info.frame_rate = stbl
+                    .and_then(|stbl| child(stbl, b"stts"))
+                    .zip(timing)
+                    .and_then(|(stts, (timescale, _))| frame_rate(stts, timescale));
+            }
+            Some(b"soun") if audio.is_none() => {
+                audio = Some(codec_name(format));
+                // Reserved fields, then channels, sample size, two more
+                // reserved fields and the sample rate in 16.16
+                let mut bytes = Bytes::new(sample_entry);
+                if bytes.skip(16).is_some() {
+                    info.channels = bytes.be_u16();
+                    bytes.skip(4);
+                    info.sample_rate = bytes.be_u32().map(|rate| rate >> 16);
+                }
+                if info.duration.is_none() {
+                    info.duration = timing
+                        .filter(|&(timescale, _)| timescale > 0)
+                        .map(|(timescale, d)| Duration::from_secs_f64(d as f64 / timescale as f64));
+                }
+            }
+            _ => {}
+        }
+    }
+    info.codec = join_codecs(video, audio);
+
+    let ilst = child(moov, b"udta")
+        .and_then(|udta| child(udta, b"meta"))
+        .and_then(|meta| {
+            // iTunes writes `meta` as a full box, QuickTime doesn't
+            let meta = match meta.get(..4) {
+                Some([0, 0, 0, 0]) => &meta[4..],
+                _ => meta,
+            };
+            child(meta, b"ilst")
+        });
+    if let Some(ilst) = ilst {
+        tags(ilst, &mut info);
+    }
+    info
+}
+
+/// Frames per second from the sample durations in an `stts` box
+fn frame_rate(stts: &[u8], timescale: u32) -> Option<f64> {
+    let mut bytes = Bytes::new(stts);
+    bytes.skip(4)?;
+    let entries = bytes.be_u32()?;
+    let (mut samples, mut ticks) = (0u64, 0u64);
+    for _ in 0..entries {
+        let count = bytes.be_u32()? as u64;
+        let delta = bytes.be_u32()? as u64;
+        // Each product fits; only a malformed table's sum can overflow
+        samples = samples.checked_add(count)?;
+        ticks = ticks.checked_add(count * delta)?;
+    }
+    (ticks > 0).then(|| samples as f64 * timescale as f64 / ticks as f64)
+}
+
+fn tags(ilst: &[u8], info: &mut MediaInfo) {
+    for (kind, item) in boxes(ilst) {
+        // Each item holds a `data` box: type, locale, then the value
+        let Some(data) = child(item, b"data") else {
+            continue;
+        };
+        let Some(value) = data.get(8..) else {
+            continue;
+        };
+        let text = || clean(String::from_utf8_lossy(value).into_owned());
+        let tags = &mut info.tags;
+        match kind {
+            b"\xa9nam" => tags.title = tags.title.take().or_else(text),
+            b"\xa9ART" => tags.artist = tags.artist.take().or_else(text),
+            b"aART" => tags.artist = tags.artist.take().or_else(text),
+            b"\xa9alb" => tags.album = tags.album.take().or_else(text),
+            b"trkn" => {
+                let mut bytes = Bytes::new(value);
+                bytes.skip(2);
+                let (track, total) = (bytes.be_u16().unwrap_or(0), bytes.be_u16().unwrap_or(0));
+                if track > 0 && tags.track.is_none() {
+                    tags.track = Some(match total {
+                        0 => track.to_string(),
+                        total => format!("{}/{}", track, total),
+                    });
+                }
+            }
+            b"covr" if info.cover.is_none() && !value.is_empty() => {
+                let mime = match data.get(3) {
+                    Some(14) => "image/png",
+                    Some(13) => "image/jpeg",
+                    _ => image_mime(value),
+                };
+                info.cover = Some(Cover {
+                    mime: mime.to_string(),
+                    data: value.to_vec(),
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn codec_name(format: &[u8]) -> String {
+    match format {
+        b"avc1" | b"avc3" => "H.264",
+        b"hvc1" | b"hev1" => "H.265",
+        b"av01" => "AV1",
+        b"vp08" => "VP8",
+        b"vp09" => "VP9",
+        b"mp4v" => "MPEG-4 Visual",
+        b"apcn" | b"apch" | b"apcs" | b"apco" | b"ap4h" => "ProRes",
+        b"mp4a" => "AAC",
+        b"alac" => "ALAC",
+        b"ac-3" => "AC-3",
+        b"ec-3" => "E-AC-3",
+        b"Opus" => "Opus",
+        b"fLaC" => "FLAC",
+        b".mp3" => "MP3",
+        b"lpcm" | b"sowt" | b"twos" => "PCM",
+        other => return String::from_utf8_lossy(other).trim().to_string(),
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mp4_box(kind: &[u8], parts: &[&[u8]]) -> Vec<u8> {
+        let body = parts.concat();
+        let mut out = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        out.extend(kind);
+        out.extend(body);
+        out
+    }
+
+    #[test]
+    fn test_parse_moov() {
+        // 90 seconds at a timescale of 1000
+        let mvhd = mp4_box(
+            b"mvhd",
+            &[&[0; 12], &1000u32.to_be_bytes(), &90_000u32.to_be_bytes()],
+        );
+        let mut avc1 = vec![0; 24];
+        avc1.extend(1920u16.to_be_bytes());
+        avc1.extend(1080u16.to_be_bytes());
+        let stsd = mp4_box(
+            b"stsd",
+            &[&[0; 4], &1u32.to_be_bytes(), &mp4_box(b"avc1", &[&avc1])],
+        );
+        // 2400 frames of 1001 ticks at 24000 per second
+        let stts = mp4_box(
+            b"stts",
+            &[
+                &[0, 0, 0, 0, 0, 0, 0, 1],
+                &2400u32.to_be_bytes(),
+                &1001u32.to_be_bytes(),
+            ],
+        );
+        let mdhd = mp4_box(
+            b"mdhd",
+            &[&[0; 12], &24000u32.to_be_bytes(), &0u32.to_be_bytes()],
+        );
+        let hdlr = mp4_box(b"hdlr", &[&[0; 8], b"vide", &[0; 12]]);
+        let minf = mp4_box(b"minf", &[&mp4_box(b"stbl", &[&stsd, &stts])]);
+        let trak = mp4_box(b"trak", &[&mp4_box(b"mdia", &[&mdhd, &hdlr, &minf])]);
+
+        let data =
+            |kind: u32, value: &[u8]| mp4_box(b"data", &[&kind.to_be_bytes(), &[0; 4], value]);
+        let ilst = mp4_box(
+            b"ilst",
+            &[
+                &mp4_box(b"\xa9nam", &[&data(1, b"Clip")]),
+                &mp4_box(b"trkn", &[&data(0, &[0, 0, 0, 2, 0, 9, 0, 0])]),
+                &mp4_box(b"covr", &[&data(13, b"\xff\xd8jpeg")]),
+            ],
+        );
+        let meta = mp4_box(
+            b"meta",
+            &[&[0; 4], &mp4_box(b"hdlr", &[&[0; 8], b"mdir"]), &ilst],
+        );
+        let moov = [mvhd, trak, mp4_box(b"udta", &[&meta])].concat();
+
+        let info = parse_moov(&moov);
+        assert_eq!(info.duration, Some(Duration::from_secs(90)));
+        assert_eq!(info.codec.as_deref(), Some("H.264"));
+        assert_eq!(info.resolution, Some((1920, 1080)));
+        assert_eq!(format!("{:.3}", info.frame_rate.unwrap()), "23.976");
+        assert_eq!(info.tags.title.as_deref(), Some("Clip"));
+        assert_eq!(info.tags.track.as_deref(), Some("2/9"));
+        assert_eq!(info.cover.unwrap().mime, "image/jpeg");
+    }
+
+    #[test]
+    fn test_frame_rate_of_malformed_stts() {
+        let max = u32::MAX.to_be_bytes();
+        let stts = [&[0, 0, 0, 0, 0, 0, 0, 2][..], &max, &max, &max, &max].concat();
+        assert_eq!(frame_rate(&stts, 24000), None);
+        // Entries missing past the count
+        assert_eq!(frame_rate(&[0, 0, 0, 0, 0, 0, 0, 2], 24000), None);
+    }
+}
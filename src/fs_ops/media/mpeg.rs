@@ -0,0 +1,243 @@
+// MPEG audio (MP3 and MP2): the first frame header for the stream's
+// properties, and its Xing/Info or VBRI header for the duration of variable
+// bitrate files. Constant bitrate files are timed by their size.
+use super::{id3, read_at, Bytes, MediaInfo, MAX_BLOCK};
+use std::fs::File;
+use std::io;
+use std::time::Duration;
+
+/// How far past the ID3 tag the first frame is looked for
+const SEARCH_LEN: u64 = 64 * 1024;
+
+const BITRATES: [[u32; 15]; 5] = [
+    // MPEG-1 layers I, II and III
+    [
+        0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
+    ],
+    [
+        0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
+    ],
+    [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ],
+    // MPEG-2 and 2.5 layer I, then layers II and III
+    [
+        0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
+    ],
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct FrameHeader {
+    /// 1 for MPEG-1, 2 for MPEG-2 and MPEG-2.5
+    version: u8,
+    layer: u8,
+    /// Kilobits per second; 0 for free format
+    bitrate: u32,
+    sample_rate: u32,
+    mono: bool,
+    samples: u32,
+    length: usize,
+}
+
+pub fn is_frame_header(bytes: &[u8]) -> bool {
+    frame_header(bytes).is_some()
+}
+
+fn frame_header(bytes: &[u8]) -> Option<FrameHeader> {
+    let &[b0, b1, b2, b3, ..] = bytes else {
+        return None;
+    };
+    if b0 != 0xff || b1 & 0xe0 != 0xe0 {
+        return None;
+    }
+    let version_bits = (b1 >> 3) & 3;
+    let layer = match (b1 >> 1) & 3 {
+        1 => 3,
+        2 => 2,
+        3 => 1,
+        _ => return None,
+    };
+    let bitrate_index = (b2 >> 4) as usize;
+    let rate_index = ((b2 >> 2) & 3) as usize;
+    if version_bits == 1 || bitrate_index == 15 || rate_index == 3 {
+        return None;
+    }
+    let (version, base_rate) = match version_bits {
+        3 => (1, [44100, 48000, 32000][rate_index]),
+        2 => (2, [22050, 24000, 16000][rate_index]),
+        _ => (2, [11025, 12000, 8000][rate_index]),
+    };
+    let table = match (version, layer) {
+        (1, layer) => layer as usize - 1,
+        (_, 1) => 3,
+        _ => 4,
+    };
+    let bitrate = BITRATES[table][bitrate_index];
+    let samples = match (version, layer) {
+        (_, 1) => 384,
+        (1, _) | (_, 2) => 1152,
+        _ => 576,
+    };
+    let padding = ((b2 >> 1) & 1) as usize;
+    let length = if layer == 1 {
+        (12 * bitrate as usize * 1000 / base_rate as usize + padding) * 4
+    } else {
+        samples as usize / 8 * bitrate as usize * 1000 / base_rate as usize + padding
+    };
+    Some(FrameHeader {
+        version,
+        layer,
+        bitrate,
+        sample_rate: base_rate,
+        mono: b3 >> 6 == 3,
+        samples,
+        length,
+    })
+}
+
+pub fn read(file: &mut File, size: u64) -> io::Result<MediaInfo> {
+    let mut info = MediaInfo::default();
+    let mut start = 0;
+    let head = read_at(file, 0, 10)?;
+    if let Some(tag_size) = id3::tag_size(&head) {
+        if tag_size <= MAX_BLOCK {
+            id3::parse(&read_at(file, 0, tag_size)?, &mut info);
+        }
+        start = tag_size;
+    }
+    let mut end = size;
+    if size >= start + 128 {
+        let v1 = read_at(file, size - 128, 128)?;
+        if v1.starts_with(b"TAG") {
+            id3::parse_v1(&v1, &mut info);
+            end -= 128;
+        }
+    }
+
+    let buf = read_at(file, start, SEARCH_LEN)?;
+    // A sync word followed by another frame where this one says it ends, to
+    // skip stray 0xFF bytes in leftover data
+    let found = (0..buf.len().saturating_sub(4)).find_map(|i| {
+        let header = frame_header(&buf[i..])?;
+        let next = i + header.length;
+        let confirmed = header.length > 0
+            && match buf.get(next..next + 4) {
+                Some(next) => is_frame_header(next),
+                None => true,
+            };
+        confirmed.then_some((i, header))
+    });
+    let Some((offset, header)) = found else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "No MPEG audio frames",
+        ));
+    };
+
+    info.codec = Some(format!("MPEG-{} Layer {}", header.version, header.layer));
+    info.sample_rate = Some(header.sample_rate);
+    info.channels = Some(if header.mono { 1 } else { 2 });
+
+    let frame = &buf[offset..];
+    if let Some((frames, bytes)) = vbr_header(frame, &header) {
+        let duration = Duration::from_secs_f64(
+            frames as f64 * header.samples as f64 / header.sample_rate as f64,
+        );
+        if !duration.is_zero() {
+            let bytes = bytes.unwrap_or(end.saturating_sub(start + offset as u64));
+            info.bitrate = Some((bytes as f64 * 8.0 / duration.as_secs_f64()) as u64);
+        }
+        info.duration = Some(duration);
+    } else if header.bitrate > 0 {
+        let bits = end.saturating_sub(start + offset as u64) * 8;
+        info.bitrate = Some(header.bitrate as u64 * 1000);
+        info.duration = Some(Duration::from_secs_f64(
+            bits as f64 / (header.bitrate as f64 * 1000.0),
+        ));
+    }
+    Ok(info)
+}
+
+/// Frame count and stream size from a Xing/Info or VBRI header in the
+/// first frame
+fn vbr_header(frame: &[u8], header: &FrameHeader) -> Option<(u32, Option<u64>)> {
+    // Xing follows the side information, whose size depends on the mode
+    let side_info = match (header.version, header.mono) {
+        (1, false) => 32,
+        (1, true) | (_, false) => 17,
+        (_, true) => 9,
+    };
+    let mut xing = Bytes::new(frame.get(4 + side_info..)?);
+    if matches!(xing.take(4), Some(b"Xing" | b"Info")) {
+        let flags = xing.be_u32()?;
+        if flags & 1 == 0 {
+            return None;
+        }
+        let frames = xing.be_u32()?;
+        let bytes = if flags & 2 != 0 { xing.be_u32() } else { None };
+        return Some((frames, bytes.map(u64::from)));
+    }
+
+    let mut vbri = Bytes::new(frame.get(36..)?);
+    if vbri.take(4)? == b"VBRI" {
+        vbri.skip(6)?;
+        let bytes = vbri.be_u32()?;
+        let frames = vbri.be_u32()?;
+        return Some((frames, Some(bytes as u64)));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fs_ops::media;
+
+    #[test]
+    fn test_read_mp3() {
+        // ID3v2.3 tag with a title, a UTF-16 artist and a track
+        let mut frames = Vec::new();
+        let mut frame = |id: &[u8], data: &[u8]| {
+            frames.extend_from_slice(id);
+            frames.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            frames.extend_from_slice(&[0, 0]);
+            frames.extend_from_slice(data);
+        };
+        frame(b"TIT2", b"\x00Song\x00");
+        let mut artist = vec![1, 0xff, 0xfe];
+        artist.extend("Ünï".encode_utf16().flat_map(u16::to_le_bytes));
+        frame(b"TPE1", &artist);
+        frame(b"TRCK", b"\x033/12");
+        frame(b"APIC", b"\x00image/png\x00\x03\x00\x89PNGdata");
+        let mut file = b"ID3\x03\x00\x00".to_vec();
+        let size = frames.len() + 16;
+        file.extend((0..4).rev().map(|i| ((size >> (7 * i)) & 0x7f) as u8));
+        file.extend(frames);
+        file.extend([0; 16]);
+
+        // 128 kb/s 44.1 kHz stereo frames, 417 bytes each, the first with
+        // a Xing header counting 100 frames
+        let mut first = vec![0xff, 0xfb, 0x90, 0x00];
+        first.extend([0; 32]);
+        first.extend(b"Xing\x00\x00\x00\x01\x00\x00\x00\x64");
+        first.resize(417, 0);
+        file.extend(&first);
+        file.extend([0xff, 0xfb, 0x90, 0x00]);
+        file.resize(file.len() + 413, 0);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("song.mp3");
+        std::fs::write(&path, file).unwrap();
+        let info = media::read(&path).unwrap();
+
+        assert_eq!(info.tags.title.as_deref(), Some("Song"));
+        assert_eq!(info.tags.artist.as_deref(), Some("Ünï"));
+        assert_eq!(info.tags.track.as_deref(), Some("3/12"));
+        assert_eq!(info.cover.as_ref().unwrap().mime, "image/png");
+        assert_eq!(info.cover.as_ref().unwrap().data, b"\x89PNGdata");
+        assert_eq!(info.codec.as_deref(), Some("MPEG-1 Layer 3"));
+        assert_eq!((info.sample_rate, info.channels), (Some(44100), Some(2)));
+        // 100 frames of 1152 samples
+        assert_eq!(info.duration.unwrap().as_millis(), 2612);
+    }
+}
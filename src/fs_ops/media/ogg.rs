@@ -0,0 +1,160 @@
+// Ogg Vorbis, Opus and Theora: the identification and comment packets of
+// the first logical stream, and the duration from the granule position of
+// its last page.
+use super::{flac, read_at, Bytes, MediaInfo, MAX_BLOCK};
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::time::Duration;
+
+/// How much of the end of the file is searched for the last page
+const TAIL_LEN: u64 = 64 * 1024;
+
+struct Page {
+    serial: u32,
+    /// Segment sizes, then the data they divide
+    segments: Vec<u8>,
+    data: Vec<u8>,
+}
+
+fn read_page(reader: &mut impl Read) -> io::Result<Option<Page>> {
+    let mut header = [0; 27];
+    match reader.read_exact(&mut header) {
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        result => result?,
+    }
+    if &header[..4] != b"OggS" {
+        return Ok(None);
+    }
+    let mut segments = vec![0; header[26] as usize];
+    reader.read_exact(&mut segments)?;
+    let mut data = vec![0; segments.iter().map(|&s| s as usize).sum()];
+    reader.read_exact(&mut data)?;
+    Ok(Some(Page {
+        serial: u32::from_le_bytes(header[14..18].try_into().unwrap_or_default()),
+        segments,
+        data,
+    }))
+}
+
+/// The serial number of the first stream and its first two packets,
+/// identification and comments
+fn header_packets(file: &mut File) -> io::Result<(u32, Vec<Vec<u8>>)> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut reader = BufReader::new(file);
+    let mut packets = Vec::new();
+    let mut packet = Vec::new();
+    let mut serial = None;
+    while let Some(page) = read_page(&mut reader)? {
+        if *serial.get_or_insert(page.serial) != page.serial {
+            continue;
+        }
+        let mut offset = 0;
+        for &segment in &page.segments {
+            packet.extend_from_slice(&page.data[offset..offset + segment as usize]);
+            offset += segment as usize;
+            // A segment shorter than 255 bytes ends the packet
+            if segment < 255 {
+                packets.push(std::mem::take(&mut packet));
+                if packets.len() == 2 {
+                    return Ok((page.serial, packets));
+                }
+            }
+        }
+        if packet.len() as u64 > MAX_BLOCK {
+            break;
+        }
+    }
+    Ok((serial.unwrap_or_default(), packets))
+}
+
+/// Granule position of the stream's last page
+fn last_granule(file: &mut File, size: u64, serial: u32) -> io::Result<Option<u64>> {
+    let start = size.saturating_sub(TAIL_LEN);
+    let tail = read_at(file, start, TAIL_LEN)?;
+    let last = (0..tail.len().saturating_sub(27))
+        .rev()
+        .filter(|&i| &tail[i..i + 4] == b"OggS")
+        .map(|i| {
+            let granule = u64::from_le_bytes(tail[i + 6..i + 14].try_into().unwrap_or_default());
+            let page_serial =
+                u32::from_le_bytes(tail[i + 14..i + 18].try_into().unwrap_or_default());
+            (granule, page_serial)
+        })
+        // -1 marks a page on which no packet ends
+        .find(|&(granule, page_serial)| page_serial == serial && granule != u64::MAX);
+    Ok(last.map(|(granule, _)| granule))
+}
+
+pub fn read(file: &mut File, size: u64) -> io::Result<MediaInfo> {
+    let mut info = MediaInfo::default();
+    let (serial, packets) = header_packets(file)?;
+    let (Some(ident), comment) = (packets.first(), packets.get(1)) else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "No Ogg packets"));
+    };
+
+    // Samples per second of the granule position, and samples to skip
+    let (granule_rate, pre_skip, comment_body) = if ident.starts_with(b"\x01vorbis") {
+        let mut bytes = Bytes::new(&ident[7..]);
+        bytes.skip(4);
+        let channels = bytes.u8();
+        let sample_rate = bytes.le_u32();
+        bytes.skip(4);
+        let nominal = bytes.le_u32().filter(|&b| b > 0 && b < i32::MAX as u32);
+        info.codec = Some("Vorbis".to_string());
+        info.channels = channels.map(u16::from);
+        info.sample_rate = sample_rate;
+        info.bitrate = nominal.map(u64::from);
+        let body = comment.and_then(|c| c.strip_prefix(b"\x03vorbis"));
+        (sample_rate.unwrap_or(0), 0, body)
+    } else if ident.starts_with(b"OpusHead") {
+        let mut bytes = Bytes::new(&ident[8..]);
+        bytes.skip(1);
+        let channels = bytes.u8();
+        let pre_skip = bytes.le_u16().unwrap_or(0);
+        info.codec = Some("Opus".to_string());
+        info.channels = channels.map(u16::from);
+        // Opus always decodes at 48 kHz; the header keeps the input's rate
+        info.sample_rate = bytes.le_u32().filter(|&r| r > 0).or(Some(48000));
+        let body = comment.and_then(|c| c.strip_prefix(b"OpusTags"));
+        (48000, pre_skip as u64, body)
+    } else if ident.starts_with(b"\x80theora") {
+        info.codec = Some("Theora".to_string());
+        let mut bytes = Bytes::new(&ident[7..]);
+        bytes.skip(7);
+        let mut u24 = || {
+            bytes
+                .take(3)
+                .map(|b| u32::from_be_bytes([0, b[0], b[1], b[2]]))
+        };
+        let (width, height) = (u24(), u24());
+        bytes.skip(2);
+        let (num, den) = (bytes.be_u32(), bytes.be_u32());
+        info.resolution = width.zip(height);
+        info.frame_rate = num
+            .zip(den)
+            .filter(|&(_, den)| den > 0)
+            .map(|(num, den)| num as f64 / den as f64);
+        let body = comment.and_then(|c| c.strip_prefix(b"\x81theora"));
+        (0, 0, body)
+    } else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Unknown Ogg codec",
+        ));
+    };
+
+    if let Some(body) = comment_body {
+        let mut covers = Vec::new();
+        flac::comments(body, &mut info, &mut covers);
+        flac::set_cover(&mut info, covers);
+    }
+    if granule_rate > 0 {
+        if let Some(granule) = last_granule(file, size, serial)? {
+            let samples = granule.saturating_sub(pre_skip);
+            info.duration = Some(Duration::from_secs_f64(
+                samples as f64 / granule_rate as f64,
+            ));
+        }
+    }
+    Ok(info)
+}
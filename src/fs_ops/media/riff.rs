@@ -0,0 +1,97 @@
+// WAV files: the format chunk, the size of the data chunk for the duration,
+// and tags from a LIST/INFO or an embedded ID3 chunk.
+use super::{clean, id3, read_at, Bytes, MediaInfo, MAX_BLOCK};
+use std::fs::File;
+use std::io;
+use std::time::Duration;
+
+pub fn read(file: &mut File) -> io::Result<MediaInfo> {
+    let mut info = MediaInfo::default();
+    let mut byte_rate = 0;
+    let mut data_size = None;
+    let mut offset = 12;
+    loop {
+        let header = read_at(file, offset, 8)?;
+        let mut bytes = Bytes::new(&header);
+        let (Some(id), Some(size)) = (bytes.take(4), bytes.le_u32()) else {
+            break;
+        };
+        let size = size as u64;
+        match id {
+            b"fmt " => {
+                let chunk = read_at(file, offset + 8, size.min(64))?;
+                byte_rate = format(&chunk, &mut info);
+            }
+            b"data" => data_size = Some(size),
+            b"LIST" if size <= MAX_BLOCK => {
+                let chunk = read_at(file, offset + 8, size)?;
+                if let Some(list) = chunk.strip_prefix(b"INFO") {
+                    list_info(list, &mut info);
+                }
+            }
+            b"id3 " | b"ID3 " if size <= MAX_BLOCK => {
+                id3::parse(&read_at(file, offset + 8, size)?, &mut info);
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even size
+        offset += 8 + size + (size & 1);
+    }
+
+    if byte_rate > 0 {
+        info.bitrate = Some(byte_rate as u64 * 8);
+        if let Some(size) = data_size {
+            info.duration = Some(Duration::from_secs_f64(size as f64 / byte_rate as f64));
+        }
+    }
+    Ok(info)
+}
+
+/// Reads the format chunk, returning its bytes per second
+fn format(chunk: &[u8], info: &mut MediaInfo) -> u32 {
+    let mut bytes = Bytes::new(chunk);
+    let tag = bytes.le_u16().unwrap_or(0);
+    info.channels = bytes.le_u16();
+    info.sample_rate = bytes.le_u32();
+    let byte_rate = bytes.le_u32().unwrap_or(0);
+    bytes.skip(2);
+    let bits = bytes.le_u16().unwrap_or(0);
+    // Extensible formats keep the real tag at the start of the GUID
+    let tag = match tag {
+        0xfffe => {
+            bytes.skip(8);
+            bytes.le_u16().unwrap_or(tag)
+        }
+        tag => tag,
+    };
+    info.codec = Some(match tag {
+        1 => format!("PCM {}-bit", bits),
+        3 => format!("IEEE float {}-bit", bits),
+        6 => "A-law".to_string(),
+        7 => "μ-law".to_string(),
+        0x11 => "IMA ADPCM".to_string(),
+        0x55 => "MP3".to_string(),
+        tag => format!("Format 0x{:04x}", tag),
+    });
+    byte_rate
+}
+
+/// Tags in a LIST chunk of type INFO
+fn list_info(list: &[u8], info: &mut MediaInfo) {
+    let mut bytes = Bytes::new(list);
+    while let (Some(id), Some(size)) = (bytes.take(4), bytes.le_u32()) {
+        let Some(value) = bytes.take(size as usize) else {
+            break;
+        };
+        bytes.skip(size as usize & 1);
+        let value = clean(String::from_utf8_lossy(value).into_owned());
+        let tags = &mut info.tags;
+        match id {
+            b"INAM" => tags.title = tags.title.take().or(value),
+            b"IART" => tags.artist = tags.artist.take().or(value),
+            b"IPRD" => tags.album = tags.album.take().or(value),
+            b"ITRK" | b"IPRT" => tags.track = tags.track.take().or(value),
+            _ => {}
+        }
+    }
+}
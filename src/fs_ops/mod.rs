@@ -1,6 +1,17 @@
 // File System Operations Module
 pub mod applications;
+pub mod archive;
+pub mod file_info;
+pub mod file_signature;
+pub mod git_status;
+pub mod hex_dump;
+pub mod image_metadata;
+pub mod media;
+pub mod names;
 pub mod portal;
 pub mod provider;
 pub mod scanner;
+pub mod sort;
+pub mod tags;
+pub mod text_preview;
 pub mod watcher;
@@ -0,0 +1,204 @@
+// Checks on names typed for files and folders, against the rules of the
+// filesystem they will live on.
+use nix::sys::statfs::statfs;
+use nix::unistd::{pathconf, PathconfVar};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+/// Filesystems that refuse the characters Windows reserves: vfat, exfat,
+/// ntfs (kernel and ntfs3), cifs and smb2
+const WINDOWS_FILESYSTEMS: [u32; 6] = [
+    0x4d44,
+    0x2011_bab0,
+    0x5346_544e,
+    0x7366_746e,
+    0xff53_4d42,
+    0xfe53_4d42,
+];
+const WINDOWS_RESERVED: &[char] = &['<', '>', ':', '"', '\\', '|', '?', '*'];
+
+/// Double extensions kept together when pre-selecting a base name
+const COMPOUND_EXTENSIONS: [&str; 5] = [".tar.gz", ".tar.bz2", ".tar.xz", ".tar.zst", ".tar.lz"];
+
+/// What a directory's filesystem accepts as names.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NameLimits {
+    /// Longest name in bytes
+    pub name_max: usize,
+    /// Longest full path in bytes
+    pub path_max: usize,
+    /// Names must also be valid on Windows
+    pub windows_compatible: bool,
+}
+
+impl Default for NameLimits {
+    fn default() -> Self {
+        Self {
+            name_max: 255,
+            path_max: 4096,
+            windows_compatible: false,
+        }
+    }
+}
+
+impl NameLimits {
+    /// Limits of the filesystem holding `dir`, with the usual Linux values
+    /// where it can't be queried.
+    pub fn for_dir(dir: &Path) -> Self {
+        let default = Self::default();
+        let query = |var| {
+            pathconf(dir, var)
+                .ok()
+                .flatten()
+                .and_then(|value| usize::try_from(value).ok())
+        };
+        Self {
+            name_max: query(PathconfVar::NAME_MAX).unwrap_or(default.name_max),
+            path_max: query(PathconfVar::PATH_MAX).unwrap_or(default.path_max),
+            windows_compatible: statfs(dir).is_ok_and(|stat| {
+                // Magic numbers are 32 bits whatever the width of f_type
+                let magic = stat.filesystem_type().0 as u32;
+                WINDOWS_FILESYSTEMS.contains(&magic)
+            }),
+        }
+    }
+}
+
+/// Why `name` can't be used in `dir`, if it can't. Only looks at the name
+/// itself, see `validate_rename` for clashes with existing entries.
+pub fn check_name(name: &str, dir: &Path, limits: &NameLimits) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Name can't be empty".to_string());
+    }
+    if name == "." || name == ".." {
+        return Err(format!("\"{}\" is reserved", name));
+    }
+    if name.contains('/') {
+        return Err("Name can't contain \"/\"".to_string());
+    }
+    if name.contains('\0') {
+        return Err("Name can't contain NUL characters".to_string());
+    }
+    if name.len() > limits.name_max {
+        return Err(format!(
+            "Name is too long ({} of {} bytes)",
+            name.len(),
+            limits.name_max
+        ));
+    }
+    if dir.join(name).as_os_str().len() >= limits.path_max {
+        return Err("Path is too long for this filesystem".to_string());
+    }
+    if limits.windows_compatible {
+        if let Some(c) = name
+            .chars()
+            .find(|c| WINDOWS_RESERVED.contains(c) || c.is_ascii_control())
+        {
+            return Err(format!("This filesystem doesn't allow {:?} in names", c));
+        }
+        if name.ends_with('.') || name.ends_with(' ') {
+            return Err(
+                "This filesystem doesn't allow names ending in \".\" or a space".to_string(),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Why `path` can't be renamed to `name`: an invalid name or another entry
+/// already using it. Changing only the case on a case-insensitive
+/// filesystem finds `path` itself and is allowed.
+pub fn validate_rename(path: &Path, name: &str) -> Result<(), String> {
+    let Some(dir) = path.parent() else {
+        return Err("Can't rename this folder".to_string());
+    };
+    check_name(name, dir, &NameLimits::for_dir(dir))?;
+
+    let target = dir.join(name);
+    if let Ok(existing) = target.symlink_metadata() {
+        let same_file = path
+            .symlink_metadata()
+            .is_ok_and(|source| source.dev() == existing.dev() && source.ino() == existing.ino());
+        if !same_file {
+            return Err(format!("\"{}\" already exists", name));
+        }
+    }
+    Ok(())
+}
+
+/// Length in bytes of `name` without its extension, the part selected when
+/// renaming starts. Folders and dot files without another dot have none.
+pub fn base_name_len(name: &str, is_dir: bool) -> usize {
+    if is_dir {
+        return name.len();
+    }
+    let lower = name.to_lowercase();
+    if let Some(ext) = COMPOUND_EXTENSIONS
+        .iter()
+        .find(|ext| lower.len() > ext.len() && lower.ends_with(*ext))
+    {
+        return name.len() - ext.len();
+    }
+    match name.rfind('.') {
+        Some(dot) if dot > 0 => dot,
+        _ => name.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_name() {
+        let dir = Path::new("/tmp");
+        let limits = NameLimits::default();
+        assert!(check_name("notes.txt", dir, &limits).is_ok());
+        assert!(check_name("a: b?", dir, &limits).is_ok());
+        assert!(check_name("", dir, &limits).is_err());
+        assert!(check_name("   ", dir, &limits).is_err());
+        // Spaces around a name are kept as typed where they're allowed
+        assert!(check_name(" padded ", dir, &limits).is_ok());
+        assert!(check_name("..", dir, &limits).is_err());
+        assert!(check_name("a/b", dir, &limits).is_err());
+        assert!(check_name("a\0b", dir, &limits).is_err());
+        assert!(check_name(&"x".repeat(255), dir, &limits).is_ok());
+        assert!(check_name(&"x".repeat(256), dir, &limits).is_err());
+        // Counted in bytes, not characters
+        assert!(check_name(&"é".repeat(128), dir, &limits).is_err());
+
+        let fat = NameLimits {
+            windows_compatible: true,
+            ..limits
+        };
+        assert!(check_name("notes.txt", dir, &fat).is_ok());
+        assert!(check_name("a: b?", dir, &fat).is_err());
+        assert!(check_name("trailing.", dir, &fat).is_err());
+        assert!(check_name("trailing ", dir, &fat).is_err());
+    }
+
+    #[test]
+    fn test_validate_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        std::fs::write(&file, "a").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "b").unwrap();
+
+        assert!(validate_rename(&file, "c.txt").is_ok());
+        assert!(validate_rename(&file, "a.txt").is_ok());
+        assert_eq!(
+            validate_rename(&file, "b.txt"),
+            Err("\"b.txt\" already exists".to_string())
+        );
+    }
+
+    #[test]
+    fn test_base_name_len() {
+        assert_eq!(base_name_len("photo.jpg", false), 5);
+        assert_eq!(base_name_len("backup.tar.gz", false), 6);
+        assert_eq!(base_name_len("archive.v2.zip", false), 10);
+        assert_eq!(base_name_len(".bashrc", false), 7);
+        assert_eq!(base_name_len("README", false), 6);
+        assert_eq!(base_name_len("my.folder", true), 9);
+    }
+}
@@ -1,9 +1,11 @@
 #![allow(dead_code)]
-use crate::fs_ops::scanner;
+use crate::fs_ops::file_info::FileAttributes;
+use crate::fs_ops::media::MediaSummary;
+use crate::fs_ops::{scanner, sort};
 use async_trait::async_trait;
 use gpui::Result;
 use gpui::*;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, Local};
 use humansize::{format_size, DECIMAL};
@@ -17,6 +19,83 @@ pub struct FileEntry {
     pub modified: std::time::SystemTime,
     pub formatted_size: String,
     pub formatted_date: String,
+    /// Excluded by .gitignore/.ignore. Only set when the listing asked for it.
+    pub is_ignored: bool,
+    /// Tags from the `user.xdg.tags` xattr
+    pub tags: Vec<String>,
+    pub attributes: FileAttributes,
+    /// Entries in a folder; filled in lazily for the details view
+    pub child_count: Option<usize>,
+    /// Duration and artist of audio and video files; filled in lazily for
+    /// the details view
+    pub media: Option<MediaSummary>,
+    /// Nesting level in the list view's tree; 0 for the current directory
+    pub depth: usize,
+}
+
+impl FileEntry {
+    pub fn from_scanned(f: scanner::ScannedFile) -> Self {
+        let name = f
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        // Pre-compute formatted strings
+        let formatted_date = DateTime::<Local>::from(f.modified)
+            .format("%Y-%m-%d %H:%M")
+            .to_string();
+
+        let formatted_size = if f.is_dir {
+            "--".to_string()
+        } else {
+            format_size(f.size, DECIMAL)
+        };
+
+        FileEntry {
+            path: f.path,
+            name,
+            is_dir: f.is_dir,
+            size: f.size,
+            modified: f.modified,
+            formatted_size,
+            formatted_date,
+            is_ignored: f.is_ignored,
+            tags: f.tags,
+            attributes: f.attributes,
+            child_count: None,
+            media: None,
+            depth: 0,
+        }
+    }
+}
+
+impl sort::SortEntry for FileEntry {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+    fn size(&self) -> u64 {
+        self.size
+    }
+    fn modified(&self) -> std::time::SystemTime {
+        self.modified
+    }
+    fn attributes(&self) -> &FileAttributes {
+        &self.attributes
+    }
+    fn child_count(&self) -> Option<usize> {
+        self.child_count
+    }
+    fn media(&self) -> Option<&MediaSummary> {
+        self.media.as_ref()
+    }
 }
 
 #[async_trait]
@@ -26,6 +105,7 @@ pub trait FileSystemProvider: Send + Sync {
         executor: BackgroundExecutor,
         path: PathBuf,
         show_hidden: bool,
+        mark_ignored: bool,
     ) -> Result<Vec<FileEntry>>;
     async fn open(
         &self,
@@ -34,6 +114,8 @@ pub trait FileSystemProvider: Send + Sync {
         command: Option<String>,
     ) -> Result<()>;
     async fn delete(&self, executor: BackgroundExecutor, path: PathBuf) -> Result<()>;
+    /// Removes `path` (recursively for folders) without using the trash
+    async fn delete_permanently(&self, executor: BackgroundExecutor, path: PathBuf) -> Result<()>;
     async fn rename(&self, executor: BackgroundExecutor, from: PathBuf, to: PathBuf) -> Result<()>;
     async fn copy(&self, executor: BackgroundExecutor, from: PathBuf, to: PathBuf) -> Result<()>;
     async fn create_dir(&self, executor: BackgroundExecutor, path: PathBuf) -> Result<()>;
@@ -55,45 +137,17 @@ impl FileSystemProvider for LocalFs {
         executor: BackgroundExecutor,
         path: PathBuf,
         show_hidden: bool,
+        mark_ignored: bool,
     ) -> Result<Vec<FileEntry>> {
         let scan_result = executor
-            .spawn(async move { scanner::scan_dir(path, show_hidden) })
+            .spawn(async move { scanner::scan_dir(path, show_hidden, mark_ignored) })
             .await;
 
-        let entries = scan_result
+        Ok(scan_result
             .files
             .into_iter()
-            .map(|f| {
-                let name = f
-                    .path
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_string();
-
-                // Pre-compute formatted strings
-                let formatted_date = DateTime::<Local>::from(f.modified)
-                    .format("%Y-%m-%d %H:%M")
-                    .to_string();
-
-                let formatted_size = if f.is_dir {
-                    "--".to_string()
-                } else {
-                    format_size(f.size, DECIMAL)
-                };
-
-                FileEntry {
-                    path: f.path,
-                    name,
-                    is_dir: f.is_dir,
-                    size: f.size,
-                    modified: f.modified,
-                    formatted_size,
-                    formatted_date,
-                }
-            })
-            .collect();
-        Ok(entries)
+            .map(FileEntry::from_scanned)
+            .collect())
     }
 
     async fn open(
@@ -121,6 +175,20 @@ impl FileSystemProvider for LocalFs {
             .map_err(|e| anyhow::anyhow!(e).into())
     }
 
+    async fn delete_permanently(&self, executor: BackgroundExecutor, path: PathBuf) -> Result<()> {
+        executor
+            .spawn(async move {
+                // Links are removed themselves, never what they point to
+                if path.is_dir() && !path.is_symlink() {
+                    std::fs::remove_dir_all(&path).map_err(|e| anyhow::anyhow!(e))
+                } else {
+                    std::fs::remove_file(&path).map_err(|e| anyhow::anyhow!(e))
+                }
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!(e).into())
+    }
+
     async fn rename(&self, executor: BackgroundExecutor, from: PathBuf, to: PathBuf) -> Result<()> {
         executor
             .spawn(async move { std::fs::rename(from, to).map_err(|e| anyhow::anyhow!(e)) })
@@ -136,12 +204,12 @@ impl FileSystemProvider for LocalFs {
                     options.copy_inside = true;
                     // fs_extra dir copy logic
                     fs_extra::dir::copy(&from, &to, &options).map_err(|e| anyhow::anyhow!(e))?;
-                    Ok(())
                 } else {
-                    std::fs::copy(from, to)
-                        .map(|_| ())
-                        .map_err(|e| anyhow::anyhow!(e))
+                    std::fs::copy(&from, &to).map_err(|e| anyhow::anyhow!(e))?;
                 }
+                // Neither copy carries xattrs, so bring the tags over explicitly
+                crate::fs_ops::tags::copy_tags(&from, &to);
+                Ok::<(), anyhow::Error>(())
             })
             .await
             .map_err(|e| anyhow::anyhow!(e).into())
@@ -165,39 +233,10 @@ impl FileSystemProvider for LocalFs {
             .spawn(async move { scanner::scan_recursive(path, query, options) })
             .await;
 
-        let entries = scan_result
+        Ok(scan_result
             .files
             .into_iter()
-            .map(|f| {
-                let name = f
-                    .path
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_string();
-
-                // Pre-compute formatted strings
-                let formatted_date = DateTime::<Local>::from(f.modified)
-                    .format("%Y-%m-%d %H:%M")
-                    .to_string();
-
-                let formatted_size = if f.is_dir {
-                    "--".to_string()
-                } else {
-                    format_size(f.size, DECIMAL)
-                };
-
-                FileEntry {
-                    path: f.path,
-                    name,
-                    is_dir: f.is_dir,
-                    size: f.size,
-                    modified: f.modified,
-                    formatted_size,
-                    formatted_date,
-                }
-            })
-            .collect();
-        Ok(entries)
+            .map(FileEntry::from_scanned)
+            .collect())
     }
 }
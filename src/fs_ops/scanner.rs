@@ -1,22 +1,91 @@
 #![allow(dead_code)]
+use ignore::WalkBuilder;
 use jwalk::WalkDir;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
 use std::io::BufRead;
 use std::time::SystemTime;
 
+use super::file_info::FileAttributes;
+
 #[derive(Clone, Debug)]
 pub struct ScannedFile {
     pub path: PathBuf,
     pub is_dir: bool,
     pub size: u64,
     pub modified: SystemTime,
+    pub is_ignored: bool,
+    pub tags: Vec<String>,
+    pub attributes: FileAttributes,
+}
+
+/// A search query split into its name/content text and `tag:` filters.
+/// `tag:work report` finds files named like "report" tagged "work";
+/// quote tags containing spaces: `tag:"to do"`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchQuery {
+    /// Remaining free text, lowercased
+    pub text: String,
+    pub tags: Vec<String>,
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> Self {
+        let mut text_terms = Vec::new();
+        let mut tags = Vec::new();
+
+        let mut chars = query.chars().peekable();
+        while chars.peek().is_some() {
+            // Skip separators
+            while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                chars.next();
+            }
+
+            let mut token = String::new();
+            let mut in_quotes = false;
+            while let Some(&c) = chars.peek() {
+                if c == '"' {
+                    in_quotes = !in_quotes;
+                } else if c.is_whitespace() && !in_quotes {
+                    break;
+                } else {
+                    token.push(c);
+                }
+                chars.next();
+            }
+
+            if let Some(tag) = token.strip_prefix("tag:") {
+                if !tag.is_empty() {
+                    tags.push(tag.to_string());
+                }
+            } else if !token.is_empty() {
+                text_terms.push(token);
+            }
+        }
+
+        Self {
+            text: text_terms.join(" ").to_lowercase(),
+            tags,
+        }
+    }
+
+    /// True when every requested tag is present (case-insensitive).
+    pub fn matches_tags(&self, file_tags: &[String]) -> bool {
+        self.tags
+            .iter()
+            .all(|wanted| file_tags.iter().any(|t| t.eq_ignore_ascii_case(wanted)))
+    }
 }
 
-#[derive(Clone, Debug, Copy)] // Added Copy/Clone for easy passing
+#[derive(Clone, Debug, Copy, PartialEq, Serialize, Deserialize)] // Added Copy/Clone for easy passing
+#[serde(default)]
 pub struct SearchOptions {
     pub recursive: bool,
     pub content_search: bool,
+    /// Skip anything excluded by .gitignore, .ignore or the global git excludes.
+    pub respect_ignore: bool,
 }
 
 impl Default for SearchOptions {
@@ -24,6 +93,7 @@ impl Default for SearchOptions {
         Self {
             recursive: true,
             content_search: false,
+            respect_ignore: false,
         }
     }
 }
@@ -35,13 +105,48 @@ pub struct ScanResult {
     pub files: Vec<ScannedFile>,
 }
 
-pub fn scan_dir(path: PathBuf, show_hidden: bool) -> ScanResult {
+/// Builds a walker that honours .gitignore, .ignore and global git excludes.
+/// Hidden entries are still yielded (hidden filtering is handled separately),
+/// but the `.git` directory itself is never descended into.
+fn ignore_walker(path: &Path, max_depth: usize) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(path);
+    builder
+        .hidden(false)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .ignore(true)
+        .parents(true)
+        .max_depth(Some(max_depth))
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|entry| entry.file_name() != ".git");
+    builder
+}
+
+/// Returns the direct children of `dir` that are NOT excluded by ignore rules.
+/// Anything in the directory missing from this set is considered ignored.
+fn unignored_children(dir: &Path) -> HashSet<PathBuf> {
+    ignore_walker(dir, 1)
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.depth() == 1)
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+pub fn scan_dir(path: PathBuf, show_hidden: bool, mark_ignored: bool) -> ScanResult {
     let mut files = Vec::new();
     eprintln!(
         "[DEBUG] scan_dir() - path={:?}, show_hidden={}",
         path, show_hidden
     );
 
+    let unignored = if mark_ignored {
+        Some(unignored_children(&path))
+    } else {
+        None
+    };
+
     // Depth 1 for current view.
     // CRITICAL: jwalk skips hidden files by default on Unix!
     // We must set skip_hidden(false) to see ALL files, then filter ourselves
@@ -68,6 +173,7 @@ pub fn scan_dir(path: PathBuf, show_hidden: bool) -> ScanResult {
             let mut size = 0;
             let mut modified = SystemTime::UNIX_EPOCH;
             let mut is_dir = false;
+            let mut attributes = FileAttributes::default();
 
             if let Ok(metadata) = std::fs::metadata(&path) {
                 size = metadata.len();
@@ -75,13 +181,24 @@ pub fn scan_dir(path: PathBuf, show_hidden: bool) -> ScanResult {
                     modified = m;
                 }
                 is_dir = metadata.is_dir();
+                attributes =
+                    FileAttributes::from_metadata(&path, &metadata, entry.file_type().is_symlink());
             }
 
+            let is_ignored = unignored
+                .as_ref()
+                .is_some_and(|visible| !visible.contains(&path) && file_name != ".git");
+
+            let tags = super::tags::read_tags(&path);
+
             files.push(ScannedFile {
                 path,
                 is_dir,
                 size,
                 modified,
+                is_ignored,
+                tags,
+                attributes,
             });
         }
     }
@@ -92,11 +209,27 @@ pub fn scan_dir(path: PathBuf, show_hidden: bool) -> ScanResult {
 
 pub fn scan_recursive(path: PathBuf, query: String, options: SearchOptions) -> ScanResult {
     let mut files = Vec::new();
-    let query_lower = query.to_lowercase();
+    let query = SearchQuery::parse(&query);
 
     // Configure recursion depth
     let max_depth = if options.recursive { usize::MAX } else { 1 };
 
+    if options.respect_ignore {
+        for entry in ignore_walker(&path, max_depth).build().flatten() {
+            // Skip the root directory itself
+            if entry.depth() == 0 {
+                continue;
+            }
+
+            let is_file = entry.file_type().is_some_and(|t| t.is_file());
+            let entry_path = entry.into_path();
+            if entry_matches(&entry_path, is_file, &query, &options) {
+                files.push(scanned_file(entry_path));
+            }
+        }
+        return ScanResult { dir: path, files };
+    }
+
     for entry in WalkDir::new(&path)
         .sort(true)
         .max_depth(max_depth)
@@ -110,110 +243,101 @@ pub fn scan_recursive(path: PathBuf, query: String, options: SearchOptions) -> S
             }
 
             let entry_path = entry.path();
-            let file_name = entry.file_name().to_string_lossy();
-            let mut matched = false;
-
-            // 1. Name Match (Always check first)
-            if file_name.to_lowercase().contains(&query_lower) {
-                matched = true;
+            if entry_matches(&entry_path, entry.file_type().is_file(), &query, &options) {
+                files.push(scanned_file(entry_path));
             }
+        }
+    }
+    ScanResult { dir: path, files }
+}
 
-            // 2. Content Search (Only if requested, not already matched, and is a file)
-            // Implementation Constraint: Memory Safety (BufReader) & Binary Check (Null Byte)
-            if !matched && options.content_search && entry.file_type().is_file() {
-                if let Ok(file) = std::fs::File::open(&entry_path) {
-                    let reader = std::io::BufReader::new(file);
-
-                    // Helper to check for binary without consuming the reader entirely
-                    // Actually, we need to peek or read the first chunk.
-                    // Since we are searching, we can just read the first chunk into a buffer.
-                    let mut buffer = [0; 1024];
-                    // Use std::io::Read trait
-                    use std::io::Read;
-
-                    // We need to re-open or seek?
-                    // Simpler: Read first chunk. If binary, stop. If text, scan it + continue scan.
-
-                    // Let's implement robust check.
-                    // But wait, if we read the first 1024 bytes, we consume them.
-                    // We'd have to handle searching IN that buffer, then continuing.
-
-                    // Re-opening is safer/easier logic wise unless performance is critical (open syscall).
-                    // For a search tool, open is fine.
-
-                    let mut is_binary = false;
-                    // Scope for binary check
-                    {
-                        if let Ok(mut f_check) = std::fs::File::open(&entry_path) {
-                            if let Ok(n) = f_check.read(&mut buffer) {
-                                if buffer[..n].iter().any(|&b| b == 0) {
-                                    is_binary = true;
-                                }
-                            }
-                        }
-                    }
-
-                    if !is_binary {
-                        // It's likely text. Search content line by line.
-                        // We must re-open or seek to 0. Since we opened a new file for check, `reader` (not used yet) is still at 0?
-                        // Ah, I created `reader` before. It's safe if I didn't read from it.
-                        // Wait, I didn't verify `reader` *creation* doesn't read. It buffers.
-                        // But I didn't call read on `reader` yet.
-                        // Actually, I opened `file` and made `reader`.
-                        // Then I opened `f_check`.
-                        // So `reader` is fresh at pos 0? Yes.
-
-                        // Note: `BufReader` might pre-fill buffer? No, only on read.
-
-                        // Search Loop
-                        for line in reader.lines() {
-                            if let Ok(l) = line {
-                                if l.to_lowercase().contains(&query_lower) {
-                                    matched = true;
-                                    break; // Early exit
-                                }
-                            } else {
-                                // Encoding error or read error -> treat as binary/skip
-                                break;
-                            }
-                        }
-                    }
-                }
-            }
+fn entry_matches(path: &Path, is_file: bool, query: &SearchQuery, options: &SearchOptions) -> bool {
+    // Tag filters must all match before looking at the name
+    if !query.tags.is_empty() && !query.matches_tags(&super::tags::read_tags(path)) {
+        return false;
+    }
+    // A pure `tag:` query matches every tagged entry
+    if query.text.is_empty() {
+        return !query.tags.is_empty();
+    }
 
-            if !matched {
-                continue;
-            }
+    let query_lower = query.text.as_str();
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
 
-            let mut size = 0;
-            let mut modified = SystemTime::UNIX_EPOCH;
-            let mut is_dir = false;
+    // 1. Name Match (Always check first)
+    if file_name.contains(query_lower) {
+        return true;
+    }
 
-            if let Ok(metadata) = entry.metadata() {
-                size = metadata.len();
-                if let Ok(m) = metadata.modified() {
-                    modified = m;
-                }
-                is_dir = metadata.is_dir();
-            } else {
-                if let Ok(metadata) = std::fs::metadata(&entry_path) {
-                    size = metadata.len();
-                    if let Ok(m) = metadata.modified() {
-                        modified = m;
-                    }
-                    is_dir = metadata.is_dir();
+    // 2. Content Search (Only if requested, not already matched, and is a file)
+    options.content_search && is_file && content_matches(path, query_lower)
+}
+
+// Implementation Constraint: Memory Safety (BufReader) & Binary Check (Null Byte)
+fn content_matches(path: &Path, query_lower: &str) -> bool {
+    use std::io::Read;
+
+    // Read the first chunk in a separate handle to detect binaries, so the
+    // line reader below still starts at offset 0.
+    let mut buffer = [0; 1024];
+    if let Ok(mut f_check) = std::fs::File::open(path) {
+        if let Ok(n) = f_check.read(&mut buffer) {
+            if buffer[..n].contains(&0) {
+                return false;
+            }
+        }
+    }
+
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let reader = std::io::BufReader::new(file);
+
+    // Search Loop
+    for line in reader.lines() {
+        match line {
+            Ok(l) => {
+                if l.to_lowercase().contains(query_lower) {
+                    return true; // Early exit
                 }
             }
+            // Encoding error or read error -> treat as binary/skip
+            Err(_) => return false,
+        }
+    }
+    false
+}
 
-            files.push(ScannedFile {
-                path: entry_path,
-                is_dir,
-                size,
-                modified,
-            });
+fn scanned_file(path: PathBuf) -> ScannedFile {
+    let mut size = 0;
+    let mut modified = SystemTime::UNIX_EPOCH;
+    let mut is_dir = false;
+    let mut attributes = FileAttributes::default();
+
+    if let Ok(metadata) = std::fs::metadata(&path) {
+        size = metadata.len();
+        if let Ok(m) = metadata.modified() {
+            modified = m;
         }
+        is_dir = metadata.is_dir();
+        let is_symlink = path.is_symlink();
+        attributes = FileAttributes::from_metadata(&path, &metadata, is_symlink);
+    }
+
+    let tags = super::tags::read_tags(&path);
+
+    ScannedFile {
+        path,
+        is_dir,
+        size,
+        modified,
+        is_ignored: false,
+        tags,
+        attributes,
     }
-    ScanResult { dir: path, files }
 }
 
 pub fn calculate_recursive_size(path: &Path) -> u64 {
@@ -248,6 +372,7 @@ mod tests {
         let opts_rec = SearchOptions {
             recursive: true,
             content_search: false,
+            respect_ignore: false,
         };
         let res_rec = scan_recursive(root.to_path_buf(), "target".to_string(), opts_rec);
         assert_eq!(res_rec.files.len(), 2, "Should find both files recursively");
@@ -256,6 +381,7 @@ mod tests {
         let opts_flat = SearchOptions {
             recursive: false,
             content_search: false,
+            respect_ignore: false,
         };
         let res_flat = scan_recursive(root.to_path_buf(), "target".to_string(), opts_flat);
         assert_eq!(
@@ -281,6 +407,7 @@ mod tests {
         let opts = SearchOptions {
             recursive: true,
             content_search: true,
+            respect_ignore: false,
         };
         let res = scan_recursive(root.to_path_buf(), "secret".to_string(), opts);
 
@@ -308,6 +435,7 @@ mod tests {
         let opts = SearchOptions {
             recursive: true,
             content_search: true,
+            respect_ignore: false,
         };
         let res = scan_recursive(root.to_path_buf(), "secret".to_string(), opts);
 
@@ -327,6 +455,7 @@ mod tests {
         let opts = SearchOptions {
             recursive: true,
             content_search: false,
+            respect_ignore: false,
         };
         let res = scan_recursive(root.to_path_buf(), "findme".to_string(), opts);
 
@@ -336,4 +465,77 @@ mod tests {
             "Should not find file by content calls if disabled"
         );
     }
+
+    #[test]
+    fn test_search_respects_gitignore() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        // The ignore crate only applies .gitignore inside a git repository
+        std::fs::create_dir(root.join(".git")).unwrap();
+        std::fs::write(root.join(".gitignore"), "build/\n*.log\n").unwrap();
+
+        let build = root.join("build");
+        std::fs::create_dir(&build).unwrap();
+        File::create(build.join("target_out.txt")).unwrap();
+        File::create(root.join("target.log")).unwrap();
+        File::create(root.join("target.rs")).unwrap();
+
+        let opts = SearchOptions {
+            recursive: true,
+            content_search: false,
+            respect_ignore: true,
+        };
+        let res = scan_recursive(root.to_path_buf(), "target".to_string(), opts);
+        assert_eq!(res.files.len(), 1, "Ignored files should be skipped");
+        assert!(res.files[0].path.ends_with("target.rs"));
+
+        let opts = SearchOptions {
+            respect_ignore: false,
+            ..opts
+        };
+        let res = scan_recursive(root.to_path_buf(), "target".to_string(), opts);
+        assert_eq!(res.files.len(), 3);
+    }
+
+    #[test]
+    fn test_scan_dir_marks_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        std::fs::create_dir(root.join(".git")).unwrap();
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        File::create(root.join("debug.log")).unwrap();
+        File::create(root.join("main.rs")).unwrap();
+
+        let res = scan_dir(root.to_path_buf(), true, true);
+        let ignored: Vec<_> = res
+            .files
+            .iter()
+            .filter(|f| f.is_ignored)
+            .map(|f| f.path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(ignored, vec!["debug.log".to_string()]);
+
+        let res = scan_dir(root.to_path_buf(), true, false);
+        assert!(res.files.iter().all(|f| !f.is_ignored));
+    }
+
+    #[test]
+    fn test_search_query_parse() {
+        let q = SearchQuery::parse("tag:work Report");
+        assert_eq!(q.text, "report");
+        assert_eq!(q.tags, vec!["work".to_string()]);
+
+        let q = SearchQuery::parse(r#"tag:"to do" tag:red  notes  2024"#);
+        assert_eq!(q.text, "notes 2024");
+        assert_eq!(q.tags, vec!["to do".to_string(), "red".to_string()]);
+
+        assert!(q.matches_tags(&["Red".to_string(), "to do".to_string()]));
+        assert!(!q.matches_tags(&["red".to_string()]));
+
+        let plain = SearchQuery::parse("hello");
+        assert!(plain.tags.is_empty());
+        assert!(plain.matches_tags(&[]));
+    }
 }
@@ -0,0 +1,409 @@
+// Ordering of directory listings, search results and groups.
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::file_info::FileAttributes;
+use super::media::MediaSummary;
+use crate::assets::mime_resolver::MimeResolver;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    #[default]
+    Name,
+    Size,
+    Modified,
+    /// MIME type, as the Type column shows it, then file extension
+    Type,
+    /// Category from `[categories]` in config.toml
+    Category,
+    Created,
+    Accessed,
+    Permissions,
+    Owner,
+    Group,
+    LinkTarget,
+    /// Entries in a folder
+    ItemCount,
+    /// Length of audio and video files
+    Duration,
+    /// Artist tag of audio and video files
+    Artist,
+}
+
+impl SortKey {
+    /// Keys the toolbar cycles through; the details view's columns can sort
+    /// by the others too
+    pub const TOOLBAR: [SortKey; 5] = [
+        SortKey::Name,
+        SortKey::Size,
+        SortKey::Modified,
+        SortKey::Type,
+        SortKey::Category,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortKey::Name => "Name",
+            SortKey::Size => "Size",
+            SortKey::Modified => "Modified",
+            SortKey::Type => "Type",
+            SortKey::Category => "Category",
+            SortKey::Created => "Created",
+            SortKey::Accessed => "Accessed",
+            SortKey::Permissions => "Permissions",
+            SortKey::Owner => "Owner",
+            SortKey::Group => "Group",
+            SortKey::LinkTarget => "Link Target",
+            SortKey::ItemCount => "Items",
+            SortKey::Duration => "Duration",
+            SortKey::Artist => "Artist",
+        }
+    }
+
+    pub fn next(&self) -> SortKey {
+        match Self::TOOLBAR.iter().position(|k| k == self) {
+            Some(index) => Self::TOOLBAR[(index + 1) % Self::TOOLBAR.len()],
+            None => SortKey::Name,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SortOptions {
+    pub key: SortKey,
+    pub descending: bool,
+    /// Keep folders above files regardless of key and direction
+    pub folders_first: bool,
+}
+
+impl Default for SortOptions {
+    fn default() -> Self {
+        Self {
+            key: SortKey::Name,
+            descending: false,
+            folders_first: true,
+        }
+    }
+}
+
+impl SortOptions {
+    /// Clicking a column header: same key flips the direction, a new key
+    /// starts ascending.
+    pub fn toggled(&self, key: SortKey) -> Self {
+        Self {
+            key,
+            descending: self.key == key && !self.descending,
+            folders_first: self.folders_first,
+        }
+    }
+}
+
+/// What sorting needs to know about an entry.
+pub trait SortEntry {
+    fn path(&self) -> &Path;
+    fn name(&self) -> &str;
+    fn is_dir(&self) -> bool;
+    fn size(&self) -> u64;
+    fn modified(&self) -> SystemTime;
+    fn attributes(&self) -> &FileAttributes;
+    fn child_count(&self) -> Option<usize>;
+    fn media(&self) -> Option<&MediaSummary>;
+}
+
+/// Compare names the way people count: case-insensitive, with digit runs
+/// compared by value so `file9` sorts before `file10`.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    // Names differing only in case still need a stable order
+    natural_cmp_folded(a, b).then_with(|| a.cmp(b))
+}
+
+fn natural_cmp_folded(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ca), Some(cb)) if ca.is_ascii_digit() && cb.is_ascii_digit() => {
+                let a_num = take_digits(&mut a_chars);
+                let b_num = take_digits(&mut b_chars);
+                let a_value = a_num.trim_start_matches('0');
+                let b_value = b_num.trim_start_matches('0');
+                let ordering = a_value
+                    .len()
+                    .cmp(&b_value.len())
+                    .then_with(|| a_value.cmp(b_value))
+                    // "1" before "01"
+                    .then_with(|| a_num.len().cmp(&b_num.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(ca), Some(cb)) => {
+                let ordering = ca.to_lowercase().cmp(cb.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.peek().copied().filter(|c| c.is_ascii_digit()) {
+        digits.push(c);
+        chars.next();
+    }
+    digits
+}
+
+fn extension(entry: &impl SortEntry) -> String {
+    if entry.is_dir() {
+        return String::new();
+    }
+    entry
+        .path()
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn artist(entry: &impl SortEntry) -> &str {
+    entry
+        .media()
+        .and_then(|media| media.artist.as_deref())
+        .unwrap_or_default()
+}
+
+/// Sort in place. `category` is only consulted for `SortKey::Category`.
+pub fn sort_entries<T: SortEntry>(
+    entries: &mut [T],
+    options: &SortOptions,
+    category: impl Fn(&Path) -> Option<String>,
+) {
+    let categories: HashMap<PathBuf, Option<String>> = if options.key == SortKey::Category {
+        entries
+            .iter()
+            .map(|e| (e.path().to_path_buf(), category(e.path())))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+    let types: HashMap<PathBuf, String> = if options.key == SortKey::Type {
+        entries
+            .iter()
+            .map(|e| {
+                (
+                    e.path().to_path_buf(),
+                    MimeResolver::type_label(e.path(), e.is_dir()),
+                )
+            })
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    entries.sort_by(|a, b| {
+        if options.folders_first && a.is_dir() != b.is_dir() {
+            return b.is_dir().cmp(&a.is_dir());
+        }
+        let by_name = || natural_cmp(a.name(), b.name());
+        let ordering = match options.key {
+            SortKey::Name => by_name(),
+            SortKey::Size => a.size().cmp(&b.size()).then_with(by_name),
+            SortKey::Modified => a.modified().cmp(&b.modified()).then_with(by_name),
+            SortKey::Type => types
+                .get(a.path())
+                .cmp(&types.get(b.path()))
+                .then_with(|| extension(a).cmp(&extension(b)))
+                .then_with(by_name),
+            SortKey::Category => categories
+                .get(a.path())
+                .cmp(&categories.get(b.path()))
+                .then_with(by_name),
+            SortKey::Created => a
+                .attributes()
+                .created
+                .cmp(&b.attributes().created)
+                .then_with(by_name),
+            SortKey::Accessed => a
+                .attributes()
+                .accessed
+                .cmp(&b.attributes().accessed)
+                .then_with(by_name),
+            SortKey::Permissions => (a.attributes().mode & 0o7777)
+                .cmp(&(b.attributes().mode & 0o7777))
+                .then_with(by_name),
+            SortKey::Owner => natural_cmp(a.attributes().owner_name(), b.attributes().owner_name())
+                .then_with(by_name),
+            SortKey::Group => natural_cmp(a.attributes().group_name(), b.attributes().group_name())
+                .then_with(by_name),
+            SortKey::LinkTarget => a
+                .attributes()
+                .link_target
+                .cmp(&b.attributes().link_target)
+                .then_with(by_name),
+            SortKey::ItemCount => a.child_count().cmp(&b.child_count()).then_with(by_name),
+            SortKey::Duration => a
+                .media()
+                .and_then(|media| media.duration)
+                .cmp(&b.media().and_then(|media| media.duration))
+                .then_with(by_name),
+            SortKey::Artist => natural_cmp(artist(a), artist(b)).then_with(by_name),
+        };
+        if options.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    struct Entry {
+        path: PathBuf,
+        name: String,
+        is_dir: bool,
+        size: u64,
+        modified: SystemTime,
+        attributes: FileAttributes,
+    }
+
+    impl SortEntry for Entry {
+        fn path(&self) -> &Path {
+            &self.path
+        }
+        fn name(&self) -> &str {
+            &self.name
+        }
+        fn is_dir(&self) -> bool {
+            self.is_dir
+        }
+        fn size(&self) -> u64 {
+            self.size
+        }
+        fn modified(&self) -> SystemTime {
+            self.modified
+        }
+        fn attributes(&self) -> &FileAttributes {
+            &self.attributes
+        }
+        fn child_count(&self) -> Option<usize> {
+            None
+        }
+        fn media(&self) -> Option<&MediaSummary> {
+            None
+        }
+    }
+
+    fn entry(name: &str, is_dir: bool, size: u64, age: u64) -> Entry {
+        Entry {
+            path: PathBuf::from("/d").join(name),
+            name: name.to_string(),
+            is_dir,
+            size,
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(1000 - age),
+            attributes: FileAttributes {
+                mode: 0o644 + size as u32,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn names(entries: &[Entry]) -> Vec<&str> {
+        entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        let mut list = vec!["file10", "File2", "file9", "file1", "a", "file01"];
+        list.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            list,
+            vec!["a", "file1", "file01", "File2", "file9", "file10"]
+        );
+        assert_eq!(
+            natural_cmp("abc", "ABC").reverse(),
+            natural_cmp("ABC", "abc")
+        );
+        assert_eq!(natural_cmp("x", "x"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_sort_entries() {
+        let mut entries = vec![
+            entry("b.txt", false, 30, 1),
+            entry("docs", true, 0, 5),
+            entry("a.rs", false, 10, 3),
+            entry("c.jpg", false, 20, 2),
+        ];
+        let no_category = |_: &Path| None;
+
+        let mut options = SortOptions::default();
+        sort_entries(&mut entries, &options, no_category);
+        assert_eq!(names(&entries), vec!["docs", "a.rs", "b.txt", "c.jpg"]);
+
+        // Folders stay on top when the direction flips
+        options = options.toggled(SortKey::Size);
+        options = options.toggled(SortKey::Size);
+        assert!(options.descending);
+        sort_entries(&mut entries, &options, no_category);
+        assert_eq!(names(&entries), vec!["docs", "b.txt", "c.jpg", "a.rs"]);
+
+        options.folders_first = false;
+        options = options.toggled(SortKey::Modified);
+        sort_entries(&mut entries, &options, no_category);
+        assert_eq!(names(&entries), vec!["docs", "a.rs", "c.jpg", "b.txt"]);
+
+        options = options.toggled(SortKey::Type);
+        options.folders_first = true;
+        sort_entries(&mut entries, &options, no_category);
+        assert_eq!(names(&entries), vec!["docs", "c.jpg", "b.txt", "a.rs"]);
+
+        options = options.toggled(SortKey::Category);
+        sort_entries(&mut entries, &options, |p: &Path| {
+            match p.extension()?.to_str()? {
+                "rs" | "txt" => Some("Documents".to_string()),
+                _ => Some("Images".to_string()),
+            }
+        });
+        assert_eq!(names(&entries), vec!["docs", "a.rs", "b.txt", "c.jpg"]);
+
+        options = options.toggled(SortKey::Permissions);
+        sort_entries(&mut entries, &options, no_category);
+        assert_eq!(names(&entries), vec!["docs", "a.rs", "c.jpg", "b.txt"]);
+        assert_eq!(SortKey::Permissions.next(), SortKey::Name);
+    }
+
+    #[test]
+    fn test_sort_by_type() {
+        let mut entries = vec![
+            entry("a.jpg", false, 0, 0),
+            entry("b.txt", false, 0, 0),
+            entry("c.jpeg", false, 0, 0),
+            entry("d.png", false, 0, 0),
+            entry("e.JPG", false, 0, 0),
+        ];
+        let options = SortOptions::default().toggled(SortKey::Type);
+        sort_entries(&mut entries, &options, |_: &Path| None);
+        // By MIME type, then extension for the same type
+        assert_eq!(
+            names(&entries),
+            vec!["c.jpeg", "a.jpg", "e.JPG", "d.png", "b.txt"]
+        );
+    }
+}
@@ -0,0 +1,142 @@
+// File tags stored in the `user.xdg.tags` extended attribute.
+// Format follows the freedesktop CommonExtendedAttributes spec: a comma
+// separated list of tag names, so tags set by other tools show up here too.
+use jwalk::WalkDir;
+use std::io;
+use std::path::Path;
+
+pub const TAGS_XATTR: &str = "user.xdg.tags";
+
+pub fn parse_tags(raw: &[u8]) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in String::from_utf8_lossy(raw).split(',') {
+        let tag = tag.trim().trim_end_matches('\0');
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+pub fn format_tags(tags: &[String]) -> String {
+    tags.join(",")
+}
+
+/// Tags of a file. Missing attribute or unsupported filesystem reads as no tags.
+pub fn read_tags(path: &Path) -> Vec<String> {
+    match xattr::get(path, TAGS_XATTR) {
+        Ok(Some(raw)) => parse_tags(&raw),
+        _ => Vec::new(),
+    }
+}
+
+/// Replace the tags of a file. An empty list removes the attribute.
+pub fn write_tags(path: &Path, tags: &[String]) -> io::Result<()> {
+    if tags.is_empty() {
+        // Removing an attribute that was never set is not an error for us
+        if xattr::get(path, TAGS_XATTR)?.is_some() {
+            xattr::remove(path, TAGS_XATTR)?;
+        }
+        return Ok(());
+    }
+    xattr::set(path, TAGS_XATTR, format_tags(tags).as_bytes())
+}
+
+/// Add the tag if missing, remove it otherwise. Returns the new tag list.
+pub fn toggle_tag(path: &Path, tag: &str) -> io::Result<Vec<String>> {
+    let mut tags = read_tags(path);
+    if let Some(pos) = tags.iter().position(|t| t == tag) {
+        tags.remove(pos);
+    } else {
+        tags.push(tag.to_string());
+    }
+    write_tags(path, &tags)?;
+    Ok(tags)
+}
+
+/// Copy tags from `from` to `to` after a copy/move that doesn't carry xattrs
+/// along (std::fs::copy, fs_extra). Directories are walked so nested entries
+/// keep their tags too. Best effort: failures are logged, not returned.
+pub fn copy_tags(from: &Path, to: &Path) {
+    if !from.is_dir() {
+        copy_entry_tags(from, to);
+        return;
+    }
+
+    for entry in WalkDir::new(from).skip_hidden(false).into_iter().flatten() {
+        let source = entry.path();
+        if let Ok(relative) = source.strip_prefix(from) {
+            copy_entry_tags(&source, &to.join(relative));
+        }
+    }
+}
+
+fn copy_entry_tags(from: &Path, to: &Path) {
+    let tags = read_tags(from);
+    if tags.is_empty() {
+        return;
+    }
+    if let Err(e) = write_tags(to, &tags) {
+        eprintln!("[DEBUG] copy_tags() - failed to tag {:?}: {}", to, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_and_format() {
+        assert_eq!(
+            parse_tags(b"work, urgent,,work"),
+            vec!["work".to_string(), "urgent".to_string()]
+        );
+        assert!(parse_tags(b"").is_empty());
+        assert_eq!(
+            format_tags(&["a".to_string(), "b c".to_string()]),
+            "a,b c".to_string()
+        );
+    }
+
+    #[test]
+    fn test_toggle_and_copy() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let file = root.join("tagged.txt");
+        std::fs::write(&file, "x").unwrap();
+
+        // Some filesystems (and sandboxes) don't support user xattrs
+        if xattr::set(&file, TAGS_XATTR, b"probe").is_err() {
+            return;
+        }
+        write_tags(&file, &[]).unwrap();
+        assert!(read_tags(&file).is_empty());
+
+        assert_eq!(toggle_tag(&file, "red").unwrap(), vec!["red".to_string()]);
+        assert_eq!(
+            toggle_tag(&file, "blue").unwrap(),
+            vec!["red".to_string(), "blue".to_string()]
+        );
+
+        let copy = root.join("copy.txt");
+        std::fs::copy(&file, &copy).unwrap();
+        copy_tags(&file, &copy);
+        assert_eq!(read_tags(&copy), read_tags(&file));
+
+        // Nested entries keep their tags when a directory is copied
+        let dir = root.join("dir");
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(dir.join("inner.txt"), "y").unwrap();
+        toggle_tag(&dir.join("inner.txt"), "green").unwrap();
+        let dir_copy = root.join("dir_copy");
+        std::fs::create_dir(&dir_copy).unwrap();
+        std::fs::write(dir_copy.join("inner.txt"), "y").unwrap();
+        copy_tags(&dir, &dir_copy);
+        assert_eq!(read_tags(&dir_copy.join("inner.txt")), vec!["green"]);
+
+        assert_eq!(toggle_tag(&file, "red").unwrap(), vec!["blue".to_string()]);
+        toggle_tag(&file, "blue").unwrap();
+        assert!(read_tags(&file).is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+// Reading the start of a text file for the preview sidebar. Only the first
+// few hundred lines are read, the encoding is worked out from a byte order
+// mark or the bytes themselves, and binary files are told apart by the NUL
+// and control bytes text doesn't have.
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Files larger than this aren't previewed at all
+pub const MAX_FILE_SIZE: u64 = 8 * 1024 * 1024;
+/// Bytes read from the start of a file
+const MAX_READ: u64 = 256 * 1024;
+/// Lines kept for the preview
+pub const MAX_LINES: usize = 400;
+/// Bytes looked at to tell text from binary
+const SNIFF_LEN: usize = 8192;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextPreview {
+    pub lines: Vec<String>,
+    /// Name of the encoding it was decoded from
+    pub encoding: &'static str,
+    /// More of the file wasn't shown
+    pub truncated: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextPreviewError {
+    TooLarge(u64),
+    Binary,
+    Io(String),
+}
+
+impl fmt::Display for TextPreviewError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextPreviewError::TooLarge(size) => write!(
+                f,
+                "Too large to preview ({})",
+                humansize::format_size(*size, humansize::DECIMAL)
+            ),
+            TextPreviewError::Binary => write!(f, "Binary file"),
+            TextPreviewError::Io(e) => write!(f, "Can't read file: {}", e),
+        }
+    }
+}
+
+/// Whether `path` is worth trying as text: a text MIME type, a file in the
+/// "Code" or "Documents" categories, or a file without an extension such
+/// as README or LICENSE. Binary files among them are caught when loading.
+pub fn is_text_like(path: &Path, config: &crate::app_state::config::Config) -> bool {
+    if path.extension().is_none() {
+        return true;
+    }
+    let mime = mime_guess::from_path(path).first_or_octet_stream();
+    let textual_application = matches!(
+        mime.subtype().as_str(),
+        "json" | "xml" | "javascript" | "x-sh" | "toml" | "x-yaml" | "sql"
+    );
+    mime.type_() == mime_guess::mime::TEXT
+        || textual_application
+        || matches!(
+            config.get_file_category(path).as_deref(),
+            Some("Code" | "Documents")
+        )
+}
+
+/// The first `MAX_LINES` lines of `path`
+pub fn load(path: &Path) -> Result<TextPreview, TextPreviewError> {
+    load_lines(path, MAX_LINES)
+}
+
+pub fn load_lines(path: &Path, max_lines: usize) -> Result<TextPreview, TextPreviewError> {
+    let io_error = |e: std::io::Error| TextPreviewError::Io(e.to_string());
+    let file = File::open(path).map_err(io_error)?;
+    let size = file.metadata().map_err(io_error)?.len();
+    if size > MAX_FILE_SIZE {
+        return Err(TextPreviewError::TooLarge(size));
+    }
+    let mut bytes = Vec::new();
+    file.take(MAX_READ)
+        .read_to_end(&mut bytes)
+        .map_err(io_error)?;
+
+    let (text, encoding) = decode(&bytes)?;
+    let mut lines: Vec<String> = text
+        .lines()
+        .take(max_lines + 1)
+        .map(|line| line.replace('\t', "    "))
+        .collect();
+    let truncated = lines.len() > max_lines || size > MAX_READ;
+    lines.truncate(max_lines);
+    Ok(TextPreview {
+        lines,
+        encoding: encoding.name(),
+        truncated,
+    })
+}
+
+/// Decodes the start of a file, working out its encoding: a byte order
+/// mark, then UTF-16 by its zero bytes, then UTF-8 if it's valid (a
+/// character cut off at the end is fine), and Windows-1252 otherwise.
+pub fn decode(bytes: &[u8]) -> Result<(String, &'static Encoding), TextPreviewError> {
+    let (encoding, bom_len) = match Encoding::for_bom(bytes) {
+        Some(found) => found,
+        None => (guess_encoding(bytes)?, 0),
+    };
+    let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+    Ok((text.into_owned(), encoding))
+}
+
+fn guess_encoding(bytes: &[u8]) -> Result<&'static Encoding, TextPreviewError> {
+    let sniff = &bytes[..bytes.len().min(SNIFF_LEN)];
+    if let Some(utf16) = guess_utf16(sniff) {
+        return Ok(utf16);
+    }
+    if sniff.contains(&0) {
+        return Err(TextPreviewError::Binary);
+    }
+    // Text has few control characters besides whitespace and escapes
+    let controls = sniff
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b))
+        .count();
+    if controls * 10 > sniff.len() {
+        return Err(TextPreviewError::Binary);
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => Ok(UTF_8),
+        Err(e) if e.error_len().is_none() => Ok(UTF_8),
+        Err(_) => Ok(WINDOWS_1252),
+    }
+}
+
+/// UTF-16 without a byte order mark: mostly ASCII text has a zero in every
+/// other byte, odd ones for little endian and even ones for big endian.
+fn guess_utf16(sniff: &[u8]) -> Option<&'static Encoding> {
+    if sniff.len() < 4 {
+        return None;
+    }
+    let pairs = sniff.len() / 2;
+    let zeros_at = |parity: usize| {
+        sniff
+            .iter()
+            .skip(parity)
+            .step_by(2)
+            .filter(|&&b| b == 0)
+            .count()
+    };
+    let (even, odd) = (zeros_at(0), zeros_at(1));
+    if odd * 10 > pairs * 7 && even * 10 < pairs {
+        Some(UTF_16LE)
+    } else if even * 10 > pairs * 7 && odd * 10 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let (text, encoding) = decode("héllo\n".as_bytes()).unwrap();
+        assert_eq!((text.as_str(), encoding), ("héllo\n", UTF_8));
+
+        let latin1 = [b'h', 0xe9, b'l', b'l', b'o'];
+        assert_eq!(
+            decode(&latin1).unwrap(),
+            ("héllo".to_string(), WINDOWS_1252)
+        );
+
+        let utf16: Vec<u8> = "hello world"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        assert_eq!(
+            decode(&utf16).unwrap(),
+            ("hello world".to_string(), UTF_16LE)
+        );
+        let mut with_bom = vec![0xfe, 0xff];
+        with_bom.extend("hi".encode_utf16().flat_map(u16::to_be_bytes));
+        assert_eq!(decode(&with_bom).unwrap(), ("hi".to_string(), UTF_16BE));
+
+        // Cut off in the middle of "é"
+        assert_eq!(decode(&"abé".as_bytes()[..3]).unwrap().1, UTF_8);
+        assert_eq!(
+            decode(b"\x7fELF\x02\x01\x01\x00\x00\x00"),
+            Err(TextPreviewError::Binary)
+        );
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("long.txt");
+        let text: String = (0..1000).map(|i| format!("line\t{}\n", i)).collect();
+        std::fs::write(&path, text).unwrap();
+
+        let preview = load(&path).unwrap();
+        assert_eq!(preview.lines.len(), MAX_LINES);
+        assert_eq!(preview.lines[1], "line    1");
+        assert!(preview.truncated);
+        assert_eq!(preview.encoding, "UTF-8");
+    }
+}
@@ -1,10 +1,13 @@
 use flume::Sender;
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 pub struct FsWatcher {
     watcher: RecommendedWatcher,
     current_path: Option<PathBuf>,
+    /// Additional non-recursive watches, grouped by the feature that owns them
+    extra_paths: HashMap<&'static str, Vec<PathBuf>>,
 }
 
 impl FsWatcher {
@@ -19,16 +22,55 @@ impl FsWatcher {
         Ok(Self {
             watcher,
             current_path: None,
+            extra_paths: HashMap::new(),
         })
     }
 
     pub fn watch(&mut self, path: &Path) {
-        if let Some(old_path) = &self.current_path {
-            let _ = self.watcher.unwatch(old_path);
+        self.watch_with_mode(path, RecursiveMode::NonRecursive);
+    }
+
+    /// Watch a whole tree, e.g. the root of a recursive smart folder search.
+    pub fn watch_recursive(&mut self, path: &Path) {
+        self.watch_with_mode(path, RecursiveMode::Recursive);
+    }
+
+    fn watch_with_mode(&mut self, path: &Path, mode: RecursiveMode) {
+        if let Some(old_path) = self.current_path.take() {
+            if !self.is_watched(&old_path) {
+                let _ = self.watcher.unwatch(&old_path);
+            }
         }
-        if let Err(e) = self.watcher.watch(path, RecursiveMode::NonRecursive) {
+        if let Err(e) = self.watcher.watch(path, mode) {
             eprintln!("Failed to watch {:?}: {:?}", path, e);
         }
         self.current_path = Some(path.to_path_buf());
     }
+
+    /// Replace the extra watches owned by `key`, e.g. the repository's .git
+    /// directory for status badges. They live alongside the main watch.
+    pub fn set_extra(&mut self, key: &'static str, paths: Vec<PathBuf>) {
+        if self.extra_paths.get(key) == Some(&paths) {
+            return;
+        }
+        for old_path in self.extra_paths.remove(key).unwrap_or_default() {
+            if !self.is_watched(&old_path) {
+                let _ = self.watcher.unwatch(&old_path);
+            }
+        }
+        for path in &paths {
+            if self.is_watched(path) {
+                continue;
+            }
+            if let Err(e) = self.watcher.watch(path, RecursiveMode::NonRecursive) {
+                eprintln!("Failed to watch {:?}: {:?}", path, e);
+            }
+        }
+        self.extra_paths.insert(key, paths);
+    }
+
+    fn is_watched(&self, path: &Path) -> bool {
+        self.current_path.as_deref() == Some(path)
+            || self.extra_paths.values().flatten().any(|p| p == path)
+    }
 }
@@ -10,14 +10,18 @@ use gpui::*;
 use std::env;
 use std::path::PathBuf;
 
-use crate::app_state::config::ConfigManager;
-use crate::app_state::workspace::{Workspace, WorkspaceEvent};
+use crate::app_state::config::{ConfigContext, ConfigManager};
+use crate::app_state::keybindings;
+use crate::app_state::panes;
+use crate::app_state::workspace::{ClipboardOp, PaneAction, Workspace, WorkspaceEvent};
 use crate::assets::app_cache::AppCache;
 use crate::assets::fonts;
 use crate::assets::icon_cache::IconCache;
+use crate::assets::thumbnail_pool::ThumbnailPool;
 use crate::theme_engine::theme::{Theme, ThemeContext};
 use crate::ui_components::settings_window::events::SettingsEvent;
 use crate::ui_components::{
+    command_palette::{self, CommandKind, CommandPalette, CommandPaletteEvent},
     dashboard::{Dashboard, DashboardEvent},
     file_list::FileList,
     navigation_toolbar::NavigationToolbar,
@@ -30,14 +34,33 @@ use crate::ui_components::{
 #[derive(Clone)]
 pub enum TabContent {
     Workspace {
+        // The active pane
         model: Entity<Workspace>,
         file_list: Entity<FileList>,
         dashboard: Entity<Dashboard>,
         preview_sidebar: Entity<PreviewSidebar>,
+        // Dual-pane mode
+        split: Option<Split>,
     },
     Settings(Entity<crate::ui_components::settings_window::SettingsWindow>),
 }
 
+// A workspace and the views showing it
+#[derive(Clone)]
+pub struct Pane {
+    model: Entity<Workspace>,
+    file_list: Entity<FileList>,
+    preview_sidebar: Entity<PreviewSidebar>,
+}
+
+// The inactive pane of a tab in dual-pane mode; switching panes swaps it
+// with the tab's active one
+#[derive(Clone)]
+pub struct Split {
+    pane: Pane,
+    active_right: bool,
+}
+
 impl Render for MainWindow {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl gpui::IntoElement {
         let palette = cx.theme().palette.clone();
@@ -50,12 +73,13 @@ impl Render for MainWindow {
         // Pattern match on the active tab to determine what to render
         let active_tab = &self.tabs[self.active_tab_index];
 
-        match active_tab {
+        let content = match active_tab {
             TabContent::Workspace {
                 model,
                 file_list,
                 dashboard,
                 preview_sidebar,
+                split,
             } => {
                 // Render workspace tab (file browser)
                 self.render_workspace_tab(
@@ -63,6 +87,7 @@ impl Render for MainWindow {
                     file_list.clone(),
                     dashboard.clone(),
                     preview_sidebar.clone(),
+                    split.clone(),
                     palette,
                     window,
                     cx,
@@ -72,7 +97,61 @@ impl Render for MainWindow {
                 // Render settings tab
                 self.render_settings_tab(settings_entity.clone(), palette, cx)
             }
-        }
+        };
+
+        // Window-wide key bindings, see `keybindings`
+        div()
+            .size_full()
+            .on_action(cx.listener(|this, _: &keybindings::GoBack, _, cx| {
+                this.update_active_workspace(cx, |ws, cx| ws.go_back(cx));
+            }))
+            .on_action(cx.listener(|this, _: &keybindings::GoForward, _, cx| {
+                this.update_active_workspace(cx, |ws, cx| ws.go_forward(cx));
+            }))
+            .on_action(cx.listener(|this, _: &keybindings::FocusLocation, window, cx| {
+                this.update_active_workspace(cx, |ws, cx| ws.begin_location_edit(cx));
+                window.focus(&this.location_focus_handle, cx);
+            }))
+            .on_action(cx.listener(|this, _: &keybindings::Search, window, cx| {
+                window.focus(&this.search_focus_handle, cx);
+            }))
+            .on_action(cx.listener(|this, _: &keybindings::NewTab, _, cx| this.new_tab(cx)))
+            .on_action(cx.listener(|this, _: &keybindings::CloseTab, _, cx| {
+                this.close_tab(this.active_tab_index, cx);
+            }))
+            .on_action(cx.listener(|this, _: &keybindings::ToggleHidden, _, cx| {
+                this.update_active_workspace(cx, |ws, cx| ws.toggle_hidden(cx));
+            }))
+            .on_action(cx.listener(|this, _: &keybindings::ToggleGrouping, _, cx| {
+                this.update_active_workspace(cx, |ws, cx| ws.toggle_grouping(cx));
+            }))
+            .on_action(cx.listener(|this, _: &keybindings::Reload, _, cx| {
+                this.update_active_workspace(cx, |ws, cx| ws.reload(cx));
+            }))
+            .on_action(cx.listener(|this, _: &keybindings::OpenSettings, _, cx| {
+                this.new_settings_tab(cx);
+            }))
+            .on_action(cx.listener(|this, _: &keybindings::ToggleSplit, _, cx| this.toggle_split(cx)))
+            // Midnight Commander style pane keys; without a split, Tab goes on
+            .on_action(cx.listener(|this, _: &keybindings::SwitchPane, _, cx| {
+                if this.has_split() {
+                    this.switch_pane(cx);
+                } else {
+                    cx.propagate();
+                }
+            }))
+            .on_action(cx.listener(|this, _: &keybindings::CopyToOtherPane, _, cx| {
+                this.transfer_to_other_pane(ClipboardOp::Copy, cx);
+            }))
+            .on_action(cx.listener(|this, _: &keybindings::MoveToOtherPane, _, cx| {
+                this.transfer_to_other_pane(ClipboardOp::Cut, cx);
+            }))
+            .on_action(cx.listener(|this, _: &keybindings::SyncPanes, _, cx| this.sync_panes(cx)))
+            .on_action(cx.listener(|this, _: &keybindings::CommandPalette, window, cx| {
+                this.toggle_command_palette(window, cx);
+            }))
+            .child(content)
+            .children(self.command_palette.clone())
     }
 }
 
@@ -83,6 +162,7 @@ impl MainWindow {
         file_list: Entity<FileList>,
         dashboard: Entity<Dashboard>,
         preview_sidebar: Entity<PreviewSidebar>,
+        split: Option<Split>,
         palette: crate::theme_engine::palette::M3Palette,
         window: &mut Window,
         cx: &mut Context<Self>,
@@ -97,6 +177,9 @@ impl MainWindow {
             details_modified,
             details_mime,
             details_dim,
+            details_tags,
+            details_media,
+            details_photo,
             is_loading,
             toasts,
         ) = {
@@ -142,6 +225,19 @@ impl MainWindow {
                     (0, std::time::SystemTime::now(), None, None)
                 }
             };
+            let details_tags = if let Some(m) = &workspace.details_metadata {
+                m.tags.clone()
+            } else {
+                details_file.map(|f| f.tags).unwrap_or_default()
+            };
+            let details_media = workspace
+                .details_metadata
+                .as_ref()
+                .and_then(|m| m.media.clone());
+            let details_photo = workspace
+                .details_metadata
+                .as_ref()
+                .and_then(|m| m.photo.clone());
             let toasts = workspace.toasts.clone();
             (
                 active_overlay,
@@ -152,48 +248,28 @@ impl MainWindow {
                 details_modified,
                 details_mime,
                 details_dim,
+                details_tags,
+                details_media,
+                details_photo,
                 is_loading,
                 toasts,
             )
         };
 
-        let search_focus = self.search_focus_handle.clone();
-        let ws_entity_key = workspace_entity.clone();
         let ws_entity_click = workspace_entity.clone();
 
+        // Keys go to the file list unless something else took focus
+        let is_dashboard = workspace_entity.read(cx).is_dashboard;
+        if !is_dashboard && (self.focus_file_list || window.focused(cx).is_none()) {
+            self.focus_file_list = false;
+            window.focus(&file_list.focus_handle(cx), cx);
+        }
+
         div()
             .id("root")
             .size_full()
             .flex()
             .font_family("Inter")
-            .on_key_down(move |event, window, cx| {
-                let search_focus = search_focus.clone();
-                let workspace_entity = ws_entity_key.clone();
-                if event.keystroke.modifiers.control && event.keystroke.key == "f" {
-                    window.focus(&search_focus, cx);
-                } else if event.keystroke.modifiers.control && event.keystroke.key == "r" {
-                    workspace_entity.update(cx, |ws, cx| {
-                        ws.reload(cx);
-                    });
-                } else if event.keystroke.modifiers.alt && event.keystroke.key == "arrow left" {
-                    workspace_entity.update(cx, |ws, cx| ws.go_back(cx));
-                } else if event.keystroke.modifiers.alt && event.keystroke.key == "arrow right" {
-                    workspace_entity.update(cx, |ws, cx| ws.go_forward(cx));
-                } else if event.keystroke.modifiers.control && event.keystroke.key == "," {
-                    workspace_entity.update(cx, |ws, cx| {
-                        ws.active_overlay = Some(crate::app_state::workspace::ActiveOverlay::Settings);
-                        cx.notify();
-                    });
-                } else if event.keystroke.modifiers.control && event.keystroke.key == "g" {
-                    workspace_entity.update(cx, |ws, cx| {
-                        ws.toggle_grouping(cx);
-                    });
-                } else if event.keystroke.key == "backspace" {
-                    if !search_focus.is_focused(window) {
-                        workspace_entity.update(cx, |ws, cx| ws.go_back(cx));
-                    }
-                }
-            })
             .bg(palette.background)
             .child(
                 div()
@@ -217,6 +293,7 @@ impl MainWindow {
                                 .child(NavigationToolbar::render(
                                     workspace_entity.clone(),
                                     self.search_focus_handle.clone(),
+                                    self.location_focus_handle.clone(),
                                     cx,
                                     window,
                                 ))
@@ -231,22 +308,30 @@ impl MainWindow {
                                         .flex()
                                         .flex_1()
                                         .child(
-                                            div()
-                                                .flex()
-                                                .flex_1()
-                                                .h_full()
-                                                .min_w_0()
-                                                .m_2()
-                                                .bg(palette.surface_container_low)
-                                                .rounded_xl()
-                                                .overflow_hidden()
-                                                .child(
-                                                    if workspace_entity.read(cx).is_dashboard {
-                                                        dashboard.clone().into_any_element()
-                                                    } else {
-                                                        file_list.clone().into_any_element()
-                                                    }
-                                                )
+                                            if is_dashboard {
+                                                self.render_pane(dashboard.clone().into_any_element(), None, &palette, cx)
+                                            } else if let Some(split) = &split {
+                                                let active = self.render_pane(
+                                                    file_list.clone().into_any_element(),
+                                                    Some((workspace_entity.clone(), true)),
+                                                    &palette,
+                                                    cx,
+                                                );
+                                                let inactive = self.render_pane(
+                                                    split.pane.file_list.clone().into_any_element(),
+                                                    Some((split.pane.model.clone(), false)),
+                                                    &palette,
+                                                    cx,
+                                                );
+                                                let (left, right) = if split.active_right {
+                                                    (inactive, active)
+                                                } else {
+                                                    (active, inactive)
+                                                };
+                                                div().flex().flex_1().min_w_0().child(left).child(right)
+                                            } else {
+                                                self.render_pane(file_list.clone().into_any_element(), None, &palette, cx)
+                                            }
                                         )
                                         .child(preview_sidebar.into_any_element())
                                 ),
@@ -286,6 +371,9 @@ impl MainWindow {
                                 details_modified,
                                 details_mime,
                                 details_dim,
+                                details_tags,
+                                details_media,
+                                details_photo,
                                 workspace_entity.clone(),
                                 cx,
                             ))
@@ -360,6 +448,37 @@ impl MainWindow {
                         } else {
                             None
                         },
+                    )
+                    .children(
+                        if let Some(crate::app_state::workspace::ActiveOverlay::MarkdownPreview(
+                            _,
+                        )) = &active_overlay
+                        {
+                            let ws = workspace_entity.read(cx);
+                            ws.markdown_preview.clone().map(|preview| preview.into_any_element())
+                        } else {
+                            None
+                        },
+                    )
+                    .children(
+                        if let Some(crate::app_state::workspace::ActiveOverlay::HexPreview(_)) =
+                            &active_overlay
+                        {
+                            let ws = workspace_entity.read(cx);
+                            ws.hex_preview.clone().map(|preview| preview.into_any_element())
+                        } else {
+                            None
+                        },
+                    )
+                    .children(
+                        if let Some(crate::app_state::workspace::ActiveOverlay::NameDialog) =
+                            &active_overlay
+                        {
+                            let ws = workspace_entity.read(cx);
+                            ws.name_dialog.clone().map(|dialog| dialog.into_any_element())
+                        } else {
+                            None
+                        },
                     ),
             )
             .child(
@@ -375,6 +494,64 @@ impl MainWindow {
             .into_any_element()
     }
 
+    // One file browser area. In dual-pane mode `pane` is the pane's workspace
+    // and whether it is the active one: panes show their path, the active one
+    // is outlined and clicking the other activates it.
+    fn render_pane(
+        &self,
+        content: AnyElement,
+        pane: Option<(Entity<Workspace>, bool)>,
+        palette: &crate::theme_engine::palette::M3Palette,
+        cx: &mut Context<Self>,
+    ) -> Div {
+        let frame = div()
+            .flex()
+            .flex_col()
+            .flex_1()
+            .h_full()
+            .min_w_0()
+            .m_2()
+            .bg(palette.surface_container_low)
+            .rounded_xl()
+            .overflow_hidden();
+        let Some((model, is_active)) = pane else {
+            return frame.child(content);
+        };
+
+        let path = model.read(cx).current_path.to_string_lossy().to_string();
+        frame
+            .border_2()
+            .border_color(if is_active {
+                palette.primary
+            } else {
+                palette.outline_variant
+            })
+            // Items stop mouse down propagation, so switch before they see it
+            .when(!is_active, |d| {
+                d.capture_any_mouse_down(cx.listener(|this, _, _, cx| this.switch_pane(cx)))
+            })
+            .child(
+                div()
+                    .flex_none()
+                    .px_3()
+                    .py_1()
+                    .text_xs()
+                    .text_ellipsis()
+                    .bg(if is_active {
+                        palette.secondary_container
+                    } else {
+                        palette.surface_container
+                    })
+                    .text_color(if is_active {
+                        palette.on_secondary_container
+                    } else {
+                        palette.on_surface_variant
+                    })
+                    .child(path),
+            )
+            .child(div().flex().flex_1().min_h_0().child(content))
+    }
+
     fn render_settings_tab(
         &mut self,
         settings_entity: Entity<crate::ui_components::settings_window::SettingsWindow>,
@@ -404,15 +581,36 @@ struct MainWindow {
     tabs: Vec<TabContent>,
     active_tab_index: usize,
     icon_cache: Entity<IconCache>,
+    thumbnail_pool: Entity<ThumbnailPool>,
     search_focus_handle: FocusHandle,
+    location_focus_handle: FocusHandle,
+    // Move keyboard focus to the active file list on the next render
+    focus_file_list: bool,
     app_cache: Entity<AppCache>,
+    command_palette: Option<Entity<CommandPalette>>,
+}
+
+/// Toasts the problems found in the [keybindings] config, if any
+fn show_keybinding_problems(workspace: &Entity<Workspace>, problems: &[String], cx: &mut App) {
+    if problems.is_empty() {
+        return;
+    }
+    workspace.update(cx, |ws, cx| {
+        ws.show_toast(
+            format!("Problems in [keybindings]: {}", problems.join("; ")),
+            crate::ui_components::toast::ToastKind::Error,
+            cx,
+        );
+    });
 }
 
 impl MainWindow {
     fn new(
         cx: &mut Context<Self>,
         icon_cache: Entity<IconCache>,
+        thumbnail_pool: Entity<ThumbnailPool>,
         app_cache: Entity<AppCache>,
+        keybinding_problems: Vec<String>,
     ) -> Self {
         let initial_path = env::var("HOME")
             .ok()
@@ -424,9 +622,34 @@ impl MainWindow {
         let workspace = Workspace::new(cx, initial_path, app_cache.clone());
         cx.subscribe(&workspace, Self::handle_workspace_event)
             .detach();
+        show_keybinding_problems(&workspace, &keybinding_problems, cx);
+        // Reloads report theirs through the config manager
+        cx.observe_global::<ConfigManager>(|this: &mut Self, cx| {
+            if cx.global::<ConfigManager>().keybinding_problems.is_empty() {
+                return;
+            }
+            let config = cx.global_mut::<ConfigManager>();
+            let problems = std::mem::take(&mut config.keybinding_problems);
+            // The active tab, or the first workspace when it's the settings
+            let workspace = this
+                .tabs
+                .get(this.active_tab_index)
+                .into_iter()
+                .chain(&this.tabs)
+                .find_map(|tab| match tab {
+                    TabContent::Workspace { model, .. } => Some(model.clone()),
+                    TabContent::Settings(_) => None,
+                });
+            if let Some(workspace) = workspace {
+                show_keybinding_problems(&workspace, &problems, cx);
+            }
+        })
+        .detach();
 
         // FileList, Dashboard, Sidebar need cx.new() wrapper
-        let file_list = cx.new(|cx| FileList::new(workspace.clone(), icon_cache.clone(), cx));
+        let file_list = cx.new(|cx| {
+            FileList::new(workspace.clone(), icon_cache.clone(), thumbnail_pool.clone(), cx)
+        });
         let dashboard = cx.new(|cx| Dashboard::new(cx));
         cx.subscribe(&dashboard, Self::handle_dashboard_event)
             .detach();
@@ -439,6 +662,7 @@ impl MainWindow {
             file_list,
             dashboard,
             preview_sidebar: cx.new(|cx| PreviewSidebar::new(workspace.clone(), cx)),
+            split: None,
         }];
         let tab_bar = cx.new(|_cx| TabBar::new(vec![home_dir.clone()], 0));
 
@@ -450,8 +674,12 @@ impl MainWindow {
             tabs,
             active_tab_index: 0,
             icon_cache: icon_cache.clone(),
+            thumbnail_pool,
             search_focus_handle: cx.focus_handle(),
+            location_focus_handle: cx.focus_handle(),
+            focus_file_list: true,
             app_cache,
+            command_palette: None,
         };
         this.update_tab_bar(cx);
         this
@@ -473,6 +701,25 @@ impl MainWindow {
                     self.update_tab_titles(cx);
                 }
             }
+            SidebarEvent::OpenSmartFolder(folder) => {
+                if let TabContent::Workspace { model, .. } = &self.tabs[self.active_tab_index] {
+                    model.update(cx, |ws, cx| {
+                        ws.is_dashboard = false;
+                        ws.open_smart_folder(folder.clone(), cx);
+                    });
+                    self.update_tab_titles(cx);
+                }
+            }
+            SidebarEvent::RenameSmartFolder(name) => {
+                if let TabContent::Workspace { model, .. } = &self.tabs[self.active_tab_index] {
+                    model.update(cx, |ws, cx| ws.rename_smart_folder(name.clone(), cx));
+                }
+            }
+            SidebarEvent::RemoveSmartFolder(name) => {
+                if let TabContent::Workspace { model, .. } = &self.tabs[self.active_tab_index] {
+                    model.update(cx, |ws, cx| ws.remove_smart_folder(name, cx));
+                }
+            }
             SidebarEvent::OpenDashboard => {
                 if let TabContent::Workspace { model, .. } = &self.tabs[self.active_tab_index] {
                     model.update(cx, |ws, cx| {
@@ -504,6 +751,15 @@ impl MainWindow {
                     self.update_tab_bar(cx);
                 }
             }
+            DashboardEvent::OpenSmartFolder(folder) => {
+                if let TabContent::Workspace { model, .. } = &self.tabs[self.active_tab_index] {
+                    model.update(cx, |ws, cx| {
+                        ws.is_dashboard = false;
+                        ws.open_smart_folder(folder.clone(), cx);
+                    });
+                    self.update_tab_bar(cx);
+                }
+            }
             DashboardEvent::ShowAddPinned => {
                 if let TabContent::Workspace { model, .. } = &self.tabs[self.active_tab_index] {
                     model.update(cx, |ws, cx| {
@@ -525,6 +781,7 @@ impl MainWindow {
         match event {
             TabEvent::Activate(ix) => {
                 self.active_tab_index = *ix;
+                self.focus_file_list = true;
                 self.update_tab_bar(cx);
             }
             TabEvent::Close(ix) => {
@@ -538,7 +795,7 @@ impl MainWindow {
 
     fn handle_workspace_event(
         &mut self,
-        _workspace: Entity<Workspace>,
+        workspace: Entity<Workspace>,
         event: &WorkspaceEvent,
         cx: &mut Context<Self>,
     ) {
@@ -546,6 +803,138 @@ impl MainWindow {
             WorkspaceEvent::PathChanged(_path) => {
                 self.update_tab_bar(cx);
             }
+            WorkspaceEvent::Pane(action) => {
+                // Only the active tab's active pane acts; a click in the other
+                // pane has already made it active
+                let is_active_pane = matches!(
+                    self.tabs.get(self.active_tab_index),
+                    Some(TabContent::Workspace { model, .. }) if *model == workspace
+                );
+                if !is_active_pane {
+                    return;
+                }
+                match action {
+                    PaneAction::ToggleSplit => self.toggle_split(cx),
+                    PaneAction::SwitchPane => self.switch_pane(cx),
+                    PaneAction::TransferToOther(op) => self.transfer_to_other_pane(*op, cx),
+                    PaneAction::SyncPanes => self.sync_panes(cx),
+                }
+            }
+        }
+    }
+
+    fn new_pane(&mut self, path: PathBuf, cx: &mut Context<Self>) -> Pane {
+        let model = Workspace::new(cx, path, self.app_cache.clone());
+        cx.subscribe(&model, Self::handle_workspace_event).detach();
+        let icon_cache = self.icon_cache.clone();
+        let thumbnail_pool = self.thumbnail_pool.clone();
+        let file_list =
+            cx.new(|cx| FileList::new(model.clone(), icon_cache, thumbnail_pool, cx));
+        let preview_sidebar = cx.new(|cx| PreviewSidebar::new(model.clone(), cx));
+        Pane {
+            model,
+            file_list,
+            preview_sidebar,
+        }
+    }
+
+    // Open a second pane on the same directory, or close the inactive one
+    fn toggle_split(&mut self, cx: &mut Context<Self>) {
+        let Some(TabContent::Workspace { model, split, .. }) =
+            self.tabs.get(self.active_tab_index).cloned()
+        else {
+            return;
+        };
+        let new_split = match split {
+            Some(split) => {
+                split.pane.model.update(cx, |ws, _cx| ws.in_split = false);
+                None
+            }
+            None => {
+                let path = model.read(cx).current_path.clone();
+                let pane = self.new_pane(path, cx);
+                pane.model.update(cx, |ws, _cx| ws.in_split = true);
+                Some(Split {
+                    pane,
+                    active_right: false,
+                })
+            }
+        };
+        let in_split = new_split.is_some();
+        model.update(cx, |ws, cx| {
+            ws.in_split = in_split;
+            cx.notify();
+        });
+        if let Some(TabContent::Workspace { split, .. }) = self.tabs.get_mut(self.active_tab_index) {
+            *split = new_split;
+        }
+        cx.notify();
+    }
+
+    fn switch_pane(&mut self, cx: &mut Context<Self>) {
+        if let Some(TabContent::Workspace {
+            model,
+            file_list,
+            preview_sidebar,
+            split: Some(split),
+            ..
+        }) = self.tabs.get_mut(self.active_tab_index)
+        {
+            std::mem::swap(model, &mut split.pane.model);
+            std::mem::swap(file_list, &mut split.pane.file_list);
+            std::mem::swap(preview_sidebar, &mut split.pane.preview_sidebar);
+            split.active_right = !split.active_right;
+        }
+        self.focus_file_list = true;
+        self.update_tab_bar(cx);
+    }
+
+    fn has_split(&self) -> bool {
+        matches!(
+            self.tabs.get(self.active_tab_index),
+            Some(TabContent::Workspace { split: Some(_), .. })
+        )
+    }
+
+    fn update_active_workspace(
+        &mut self,
+        cx: &mut Context<Self>,
+        update: impl FnOnce(&mut Workspace, &mut Context<Workspace>),
+    ) {
+        if let Some(TabContent::Workspace { model, .. }) = self.tabs.get(self.active_tab_index) {
+            model.update(cx, update);
+        }
+    }
+
+    // F5/F6: copy or move the active pane's selection into the other pane's directory
+    fn transfer_to_other_pane(&mut self, op: ClipboardOp, cx: &mut Context<Self>) {
+        if let Some(TabContent::Workspace {
+            model,
+            split: Some(split),
+            ..
+        }) = self.tabs.get(self.active_tab_index).cloned()
+        {
+            let target_dir = split.pane.model.read(cx).current_path.clone();
+            model.update(cx, |ws, cx| {
+                match panes::transfer_sources(&ws.current_path, &target_dir, &ws.selection) {
+                    Ok(sources) => ws.transfer(op, sources, target_dir, cx),
+                    Err(e) => {
+                        ws.show_toast(e, crate::ui_components::toast::ToastKind::Info, cx)
+                    }
+                }
+            });
+        }
+    }
+
+    fn sync_panes(&mut self, cx: &mut Context<Self>) {
+        if let Some(TabContent::Workspace {
+            model,
+            split: Some(split),
+            ..
+        }) = self.tabs.get(self.active_tab_index).cloned()
+        {
+            let path = model.read(cx).current_path.clone();
+            split.pane.model.update(cx, |ws, cx| ws.navigate(path, cx));
         }
     }
 
@@ -566,15 +955,10 @@ impl MainWindow {
             SettingsEvent::ConfigChanged => {
                 // Refresh ALL workspace tabs to apply new settings
                 // (can't use active_tab_index since that points to the settings tab itself)
-                for tab in &self.tabs {
-                    if let TabContent::Workspace { model, .. } = tab {
-                        model.update(cx, |ws, cx| {
-                            // Re-navigate to current path to reload with new settings
-                            let current_path = ws.current_path.clone();
-                            ws.navigate(current_path, cx);
-                        });
-                    }
-                }
+                self.reload_workspaces(cx);
+            }
+            SettingsEvent::ThumbnailCacheCleared => {
+                self.thumbnail_pool.update(cx, |pool, cx| pool.reset(cx));
             }
             SettingsEvent::ShowToast(message) => {
                 for tab in &self.tabs {
@@ -593,6 +977,19 @@ impl MainWindow {
         }
     }
 
+    // Reload every pane of every tab with new settings (re-runs smart folder
+    // searches too)
+    fn reload_workspaces(&mut self, cx: &mut Context<Self>) {
+        for tab in &self.tabs {
+            if let TabContent::Workspace { model, split, .. } = tab {
+                let panes = std::iter::once(model).chain(split.as_ref().map(|s| &s.pane.model));
+                for model in panes {
+                    model.update(cx, |ws, cx| ws.reload(cx));
+                }
+            }
+        }
+    }
+
     fn new_tab(&mut self, cx: &mut Context<Self>) {
         let initial_path = env::var("HOME")
             .ok()
@@ -604,7 +1001,10 @@ impl MainWindow {
         cx.subscribe(&workspace, Self::handle_workspace_event)
             .detach();
 
-        let file_list = cx.new(|cx| FileList::new(workspace.clone(), self.icon_cache.clone(), cx));
+        let icon_cache = self.icon_cache.clone();
+        let thumbnail_pool = self.thumbnail_pool.clone();
+        let file_list =
+            cx.new(|cx| FileList::new(workspace.clone(), icon_cache, thumbnail_pool, cx));
         let dashboard = cx.new(|cx| Dashboard::new(cx));
         cx.subscribe(&dashboard, Self::handle_dashboard_event)
             .detach();
@@ -616,8 +1016,10 @@ impl MainWindow {
             file_list,
             dashboard,
             preview_sidebar,
+            split: None,
         });
         self.active_tab_index = self.tabs.len() - 1;
+        self.focus_file_list = true;
 
         self.update_tab_bar(cx);
         self.update_sidebar(cx);
@@ -654,13 +1056,108 @@ impl MainWindow {
             } else if self.active_tab_index > ix {
                 self.active_tab_index = self.active_tab_index.saturating_sub(1);
             }
+            self.focus_file_list = true;
             self.update_tab_bar(cx);
             self.update_sidebar(cx);
         }
     }
 
-    fn update_tab_bar(&mut self, cx: &mut Context<Self>) {
-        let titles: Vec<String> = self
+    fn toggle_command_palette(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.command_palette.take().is_some() {
+            self.focus_file_list = true;
+            cx.notify();
+            return;
+        }
+        let workspace = match self.tabs.get(self.active_tab_index) {
+            Some(TabContent::Workspace { model, .. }) => Some(model.read(cx)),
+            _ => None,
+        };
+        let config = cx.config();
+        let commands = command_palette::commands(
+            workspace,
+            config,
+            &self.tab_titles(cx),
+            self.active_tab_index,
+        );
+        let recent = config.recent_commands.iter().cloned().collect();
+
+        let palette = cx.new(|cx| CommandPalette::new(commands, recent, cx));
+        cx.subscribe_in(&palette, window, Self::handle_command_palette_event)
+            .detach();
+        window.focus(&palette.focus_handle(cx), cx);
+        self.command_palette = Some(palette);
+        cx.notify();
+    }
+
+    fn handle_command_palette_event(
+        &mut self,
+        _palette: &Entity<CommandPalette>,
+        event: &CommandPaletteEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.command_palette = None;
+        cx.notify();
+        let CommandPaletteEvent::Run(command) = event else {
+            self.focus_file_list = true;
+            return;
+        };
+        cx.update_global::<ConfigManager, _>(|manager, _cx| {
+            manager.config.record_command(&command.id);
+            manager.save_config();
+        });
+
+        match &command.kind {
+            CommandKind::Action(name) => {
+                // Dispatched from the file list so its actions apply too
+                if let Some(TabContent::Workspace { file_list, .. }) =
+                    self.tabs.get(self.active_tab_index)
+                {
+                    window.focus(&file_list.focus_handle(cx), cx);
+                }
+                match cx.build_action(&format!("file_manager::{}", name), None) {
+                    Ok(action) => window.dispatch_action(action, cx),
+                    Err(e) => eprintln!("[DEBUG] Command palette: {}", e),
+                }
+            }
+            CommandKind::Workspace(run) => {
+                self.focus_file_list = true;
+                self.update_active_workspace(cx, |ws, cx| run(ws, cx));
+            }
+            CommandKind::Setting(apply) => {
+                self.focus_file_list = true;
+                cx.update_global::<ConfigManager, _>(|manager, cx| {
+                    apply(&mut manager.config);
+                    manager.save_config();
+                    cx.refresh_windows();
+                });
+                self.reload_workspaces(cx);
+            }
+            CommandKind::OpenFolder(path) => {
+                let in_workspace = matches!(
+                    self.tabs.get(self.active_tab_index),
+                    Some(TabContent::Workspace { .. })
+                );
+                if !in_workspace {
+                    self.new_tab(cx);
+                }
+                self.focus_file_list = true;
+                self.update_active_workspace(cx, |ws, cx| {
+                    ws.is_dashboard = false;
+                    ws.navigate(path.clone(), cx);
+                });
+                self.update_tab_bar(cx);
+            }
+            CommandKind::ActivateTab(ix) => {
+                self.active_tab_index = *ix;
+                self.focus_file_list = true;
+                self.update_tab_bar(cx);
+            }
+        }
+    }
+
+    fn tab_titles(&self, cx: &App) -> Vec<String> {
+        self
             .tabs
             .iter()
             .map(|tab| match tab {
@@ -668,6 +1165,8 @@ impl MainWindow {
                     let workspace = model.read(cx);
                     if workspace.is_dashboard {
                         "Dashboard".to_string()
+                    } else if let Some(folder) = &workspace.active_smart_folder {
+                        folder.name.clone()
                     } else {
                         workspace
                             .current_path
@@ -686,11 +1185,33 @@ impl MainWindow {
                 }
                 TabContent::Settings(_) => "Settings".to_string(),
             })
-            .collect();
+            .collect()
+    }
+
+    /// Folder each tab shows, for drops onto the tab
+    fn tab_folders(&self, cx: &App) -> Vec<Option<PathBuf>> {
+        self.tabs
+            .iter()
+            .map(|tab| match tab {
+                TabContent::Workspace { model, .. } => {
+                    let workspace = model.read(cx);
+                    let browsing =
+                        !workspace.is_dashboard && workspace.active_smart_folder.is_none();
+                    browsing.then(|| workspace.current_path.clone())
+                }
+                TabContent::Settings(_) => None,
+            })
+            .collect()
+    }
+
+    fn update_tab_bar(&mut self, cx: &mut Context<Self>) {
+        let titles = self.tab_titles(cx);
+        let folders = self.tab_folders(cx);
         let active_index = self.active_tab_index;
 
         self.tab_bar.update(cx, |bar, cx| {
             bar.tabs = titles;
+            bar.folders = folders;
             bar.active_index = active_index;
             cx.notify();
         });
@@ -706,9 +1227,13 @@ impl MainWindow {
                 let workspace = model.read(cx);
                 let current_path = workspace.current_path.clone();
                 let is_dashboard = workspace.is_dashboard;
+                let smart_folder = workspace
+                    .active_smart_folder
+                    .as_ref()
+                    .map(|folder| folder.name.clone());
 
                 self.sidebar.update(cx, |sidebar, cx| {
-                    sidebar.set_state(current_path, is_dashboard, cx);
+                    sidebar.set_state(current_path, is_dashboard, smart_folder, cx);
                 });
             }
         }
@@ -731,11 +1256,25 @@ fn main() {
         Theme::watch(cx);
         fonts::load_fonts(cx);
         ConfigManager::init(cx);
+        crate::app_state::view_settings::ViewSettingsStore::init(cx);
+        let keybinding_problems = keybindings::apply(cx);
 
         let icon_cache = IconCache::new(cx);
+        let thumbnail_pool = ThumbnailPool::new(cx);
+        crate::assets::thumbnail_cleanup::spawn_startup_cleanup(
+            cx.global::<ConfigManager>().config.thumbnails.limits(),
+        );
         let app_cache = AppCache::new(cx);
         let _ = cx.open_window(WindowOptions::default(), move |_, cx| {
-            cx.new(|cx| MainWindow::new(cx, icon_cache.clone(), app_cache.clone()))
+            cx.new(|cx| {
+                MainWindow::new(
+                    cx,
+                    icon_cache.clone(),
+                    thumbnail_pool.clone(),
+                    app_cache.clone(),
+                    keybinding_problems.clone(),
+                )
+            })
         });
     });
 }
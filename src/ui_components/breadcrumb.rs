@@ -1,6 +1,7 @@
 use crate::app_state::workspace::Workspace;
 use crate::assets::icons;
 use crate::theme_engine::theme::ThemeContext;
+use crate::ui_components::drag_drop::accept_dragged_files;
 use gpui::prelude::*;
 use gpui::*;
 use std::path::PathBuf;
@@ -45,6 +46,9 @@ impl Breadcrumb {
         let menu_item = |label: &str, target: PathBuf, is_last: bool, cx: &mut Context<V>| {
             let theme = cx.theme();
             let workspace = workspace.clone();
+            // Entries dragged onto a segment move into that folder
+            let drop_dir = target.clone();
+            let drop_highlight = theme.palette.primary_container.into();
 
             let base_div = div()
                 .flex()
@@ -54,7 +58,7 @@ impl Breadcrumb {
                 .rounded_md()
                 .text_sm();
 
-            if is_last {
+            let crumb = if is_last {
                 base_div
                     .bg(theme.palette.surface_variant)
                     .text_color(theme.palette.on_surface)
@@ -76,7 +80,8 @@ impl Breadcrumb {
                         });
                     })
                     .child(label.to_string())
-            }
+            };
+            accept_dragged_files(crumb, drop_dir, drop_highlight)
         };
 
         div()
@@ -108,4 +113,63 @@ impl Breadcrumb {
                     })),
             )
     }
+
+    /// Editable path shown in place of the segments (Ctrl+L). Enter opens
+    /// the folder, Escape cancels.
+    pub fn render_location_input<V: 'static>(
+        text: String,
+        focus: &FocusHandle,
+        workspace: Entity<Workspace>,
+        cx: &mut Context<V>,
+    ) -> impl IntoElement {
+        let palette = cx.theme().palette.clone();
+
+        div().w_full().h_10().flex().items_center().child(
+            div()
+                .flex_grow()
+                .h_8()
+                .flex()
+                .items_center()
+                .px_3()
+                .rounded_md()
+                .bg(palette.surface_container_high)
+                .border_1()
+                .border_color(palette.primary)
+                .text_sm()
+                .text_color(palette.on_surface)
+                .track_focus(focus)
+                .on_key_down(move |event: &KeyDownEvent, window, cx| {
+                    let keystroke = &event.keystroke;
+                    let key = keystroke.key.as_str();
+                    workspace.update(cx, |ws, cx| match key {
+                        "enter" => ws.commit_location(cx),
+                        "escape" => ws.cancel_location_edit(cx),
+                        "backspace" => {
+                            let mut text = ws.location_input.clone().unwrap_or_default();
+                            text.pop();
+                            ws.set_location_input(text, cx);
+                        }
+                        _ => {
+                            let typed = keystroke.key_char.as_ref().filter(|typed| {
+                                !typed.chars().any(char::is_control)
+                                    && !keystroke.modifiers.control
+                                    && !keystroke.modifiers.alt
+                            });
+                            if let Some(typed) = typed {
+                                let mut text = ws.location_input.clone().unwrap_or_default();
+                                text.push_str(typed);
+                                ws.set_location_input(text, cx);
+                            }
+                        }
+                    });
+                    // Hand focus back to the file list
+                    if key == "enter" || key == "escape" {
+                        window.blur();
+                    }
+                    cx.stop_propagation();
+                })
+                .child(text)
+                .child(div().flex_none().w(px(1.0)).h_4().bg(palette.primary)),
+        )
+    }
 }
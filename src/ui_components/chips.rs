@@ -1,8 +1,8 @@
 use crate::theme_engine::theme::Theme;
 use gpui::prelude::*;
 use gpui::{
-    div, App, ClickEvent, ElementId, InteractiveElement, IntoElement, RenderOnce, SharedString,
-    Styled, Window,
+    div, px, App, ClickEvent, ElementId, Hsla, InteractiveElement, IntoElement, RenderOnce,
+    SharedString, Styled, Window,
 };
 
 #[derive(Clone, PartialEq)]
@@ -18,6 +18,10 @@ pub struct Chip {
     icon: Option<SharedString>,
     selected: bool,
     chip_type: ChipType,
+    /// Colored dot before the label (tags)
+    color: Option<Hsla>,
+    /// Compact variant for use inside file items
+    dense: bool,
     on_click: Option<Box<dyn Fn(&ClickEvent, &mut Window, &mut App) + Send + Sync + 'static>>,
 }
 
@@ -29,6 +33,8 @@ impl Chip {
             icon: None,
             selected: false,
             chip_type: ChipType::Action,
+            color: None,
+            dense: false,
             on_click: None,
         }
     }
@@ -43,6 +49,16 @@ impl Chip {
         self
     }
 
+    pub fn color(mut self, color: impl Into<Hsla>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    pub fn dense(mut self) -> Self {
+        self.dense = true;
+        self
+    }
+
     pub fn selected(mut self, selected: bool) -> Self {
         self.selected = selected;
         self
@@ -81,6 +97,7 @@ impl RenderOnce for Chip {
             .gap_2()
             .px_3()
             .h_8()
+            .when(self.dense, |d| d.gap_1().px_2().h(px(18.0)).rounded_md())
             .rounded_lg()
             .border_1()
             .border_color(border)
@@ -115,6 +132,14 @@ impl RenderOnce for Chip {
                 div().into_any_element()
             },
         )
-        .child(div().text_sm().child(self.label))
+        .children(
+            self.color
+                .map(|color| div().size_2().flex_none().rounded_full().bg(color)),
+        )
+        .child(if self.dense {
+            div().text_xs().child(self.label)
+        } else {
+            div().text_sm().child(self.label)
+        })
     }
 }
@@ -0,0 +1,229 @@
+// Column (Miller) view: the current directory in the first column, each
+// highlighted folder opening in the next one and a preview of the
+// highlighted file at the end. State lives in `Workspace::column_path`.
+use crate::app_state::workspace::Workspace;
+use crate::fs_ops::provider::FileEntry;
+use crate::ui_components::drag_drop::with_drag_and_drop;
+use crate::ui_components::file_list::{icon_name_for, render_git_badge, ItemStyle};
+use crate::ui_components::preview_sidebar::render_preview;
+use gpui::prelude::*;
+use gpui::*;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const COLUMN_WIDTH: f32 = 240.0;
+
+fn render_column_item(
+    item: &FileEntry,
+    column: usize,
+    ix: usize,
+    highlighted: bool,
+    is_selected: bool,
+    style: &ItemStyle,
+    workspace: &Entity<Workspace>,
+) -> AnyElement {
+    let palette = &style.palette;
+    // The selection is the highlighted entry of the last column; the
+    // entries leading to it stay marked in the columns before
+    let bg_color = if is_selected {
+        Hsla::from(palette.secondary_container)
+    } else if highlighted {
+        Hsla::from(palette.surface_container_highest)
+    } else {
+        gpui::hsla(0., 0., 0., 0.)
+    };
+    let text_color = if is_selected {
+        palette.on_secondary_container
+    } else {
+        palette.on_surface
+    };
+
+    let ws_click = workspace.clone();
+    let entry = item.clone();
+    let ws_dbl = workspace.clone();
+    let path_dbl = item.path.clone();
+    let ws_right = workspace.clone();
+    let path_right = item.path.clone();
+
+    let element = div()
+        .id(ElementId::Name(format!("column-{}-{}", column, ix).into()))
+        .h_8()
+        .flex()
+        .items_center()
+        .gap_2()
+        .w_full()
+        .px_3()
+        .bg(bg_color)
+        .text_color(text_color)
+        .text_sm()
+        .when(style.dim_ignored && item.is_ignored, |d| d.opacity(0.5))
+        .hover(|s| s.bg(palette.surface_container_highest));
+
+    with_drag_and_drop(
+        element,
+        item,
+        Hsla::from(palette.primary_container),
+        workspace,
+    )
+    .on_click(move |event, _, cx| {
+        if event.click_count() >= 2 {
+            ws_dbl.update(cx, |ws, cx| ws.open(path_dbl.clone(), cx));
+        }
+    })
+    .on_mouse_down(MouseButton::Left, move |event, _, cx| {
+        cx.stop_propagation();
+        ws_click.update(cx, |ws, cx| {
+            if event.modifiers.control {
+                ws.toggle_selection(entry.path.clone(), cx);
+            } else {
+                ws.select_in_column(column, entry.clone(), cx);
+            }
+        });
+    })
+    .on_mouse_down(MouseButton::Right, move |event, _, cx| {
+        cx.stop_propagation();
+        ws_right.update(cx, |ws, cx| {
+            if !ws.selection.contains(&path_right) {
+                ws.set_selection(path_right.clone(), cx);
+            }
+            ws.open_context_menu(event.position, Some(path_right.clone()), cx);
+        });
+    })
+    .child(
+        crate::assets::icons::icon(icon_name_for(item))
+            .size_4()
+            .flex_none(),
+    )
+    .child(
+        div()
+            .flex_grow()
+            .min_w_0()
+            .text_ellipsis()
+            .child(item.name.clone()),
+    )
+    .children(render_git_badge(item, style))
+    .when(item.is_dir, |d| {
+        d.child(
+            crate::assets::icons::icon("chevron_right")
+                .size_4()
+                .flex_none()
+                .text_color(palette.on_surface_variant),
+        )
+    })
+    .into_any_element()
+}
+
+fn render_column(
+    column: usize,
+    items: Option<Arc<Vec<FileEntry>>>,
+    highlighted: Option<PathBuf>,
+    selection: &HashSet<PathBuf>,
+    style: &ItemStyle,
+    workspace: &Entity<Workspace>,
+) -> AnyElement {
+    let palette = &style.palette;
+    let frame = div()
+        .w(px(COLUMN_WIDTH))
+        .h_full()
+        .flex_none()
+        .flex()
+        .flex_col()
+        .border_r_1()
+        .border_color(palette.outline_variant);
+
+    let Some(items) = items else {
+        return frame.into_any_element();
+    };
+    if items.is_empty() {
+        return frame
+            .items_center()
+            .justify_center()
+            .text_sm()
+            .text_color(palette.on_surface_variant)
+            .child("Empty folder")
+            .into_any_element();
+    }
+
+    let style = style.clone();
+    let selection = selection.clone();
+    let workspace = workspace.clone();
+    frame
+        .child(
+            uniform_list(
+                ElementId::Name(format!("column_list_{}", column).into()),
+                items.len(),
+                move |range, _window, _cx| {
+                    range
+                        .map(|ix| {
+                            let item = &items[ix];
+                            render_column_item(
+                                item,
+                                column,
+                                ix,
+                                highlighted.as_ref() == Some(&item.path),
+                                selection.contains(&item.path),
+                                &style,
+                                &workspace,
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                },
+            )
+            .w_full()
+            .flex_1()
+            .min_h_0(),
+        )
+        .into_any_element()
+}
+
+/// All columns side by side, scrolled horizontally with `scroll_handle`.
+pub fn render_column_view(
+    ws: &Workspace,
+    style: &ItemStyle,
+    workspace: &Entity<Workspace>,
+    scroll_handle: &ScrollHandle,
+) -> Stateful<Div> {
+    let mut columns = vec![render_column(
+        0,
+        ws.column_items(0),
+        ws.column_path.first().map(|e| e.path.clone()),
+        &ws.selection,
+        style,
+        workspace,
+    )];
+    for (index, entry) in ws.column_path.iter().enumerate() {
+        let column = index + 1;
+        if entry.is_dir {
+            columns.push(render_column(
+                column,
+                ws.column_items(column),
+                ws.column_path.get(column).map(|e| e.path.clone()),
+                &ws.selection,
+                style,
+                workspace,
+            ));
+        } else {
+            columns.push(
+                div()
+                    .id("column_preview")
+                    .w(px(COLUMN_WIDTH + 40.0))
+                    .h_full()
+                    .flex_none()
+                    .overflow_y_scroll()
+                    .child(render_preview(entry, &style.palette))
+                    .into_any_element(),
+            );
+        }
+    }
+
+    div()
+        .id("column_view")
+        .flex()
+        .w_full()
+        .flex_1()
+        .min_h_0()
+        .overflow_x_scroll()
+        .track_scroll(scroll_handle)
+        .children(columns)
+}
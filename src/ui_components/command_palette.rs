@@ -0,0 +1,774 @@
+// Command palette (Ctrl+Shift+P): everything the app can do in one fuzzy
+// searchable list. The commands are collected when the palette opens and
+// `MainWindow` runs the chosen one.
+use crate::app_state::config::Config;
+use crate::app_state::keybindings::{self, ResolvedBinding};
+use crate::app_state::workspace::{PickerAction, Workspace};
+use crate::fs_ops::sort::{SortKey, SortOptions};
+use crate::theme_engine::palette::M3Palette;
+use crate::theme_engine::theme::ThemeContext;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use gpui::prelude::*;
+use gpui::*;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+const ROW_HEIGHT: f32 = 36.0;
+const MAX_VISIBLE_ROWS: usize = 10;
+/// Ranking bonus per place in the recent commands, see `rank`
+const RECENT_STEP: i64 = 10;
+
+pub type WorkspaceCommand = Rc<dyn Fn(&mut Workspace, &mut Context<Workspace>)>;
+
+/// What running a command does
+#[derive(Clone)]
+pub enum CommandKind {
+    /// A key binding action, dispatched as if its keys were pressed
+    Action(&'static str),
+    /// An operation on the active pane's workspace
+    Workspace(WorkspaceCommand),
+    /// A settings switch, saved to config.toml
+    Setting(fn(&mut Config)),
+    OpenFolder(PathBuf),
+    ActivateTab(usize),
+}
+
+#[derive(Clone)]
+pub struct Command {
+    /// Identifies the command in `Config::recent_commands`
+    pub id: String,
+    pub category: &'static str,
+    pub label: String,
+    /// Keys of the action, as shown to users
+    pub keys: Option<String>,
+    /// Secondary text, such as a folder's path
+    pub detail: Option<String>,
+    /// State of a switch
+    pub checked: Option<bool>,
+    pub kind: CommandKind,
+}
+
+impl Command {
+    fn new(
+        id: String,
+        category: &'static str,
+        label: impl Into<String>,
+        kind: CommandKind,
+    ) -> Self {
+        Self {
+            id,
+            category,
+            label: label.into(),
+            keys: None,
+            detail: None,
+            checked: None,
+            kind,
+        }
+    }
+
+    fn workspace(
+        id: &str,
+        category: &'static str,
+        label: impl Into<String>,
+        run: impl Fn(&mut Workspace, &mut Context<Workspace>) + 'static,
+    ) -> Self {
+        Self::new(
+            format!("workspace:{}", id),
+            category,
+            label,
+            CommandKind::Workspace(Rc::new(run)),
+        )
+    }
+
+    fn setting(id: &str, label: &str, checked: bool, apply: fn(&mut Config)) -> Self {
+        let mut command = Self::new(
+            format!("setting:{}", id),
+            "Settings",
+            label,
+            CommandKind::Setting(apply),
+        );
+        command.checked = Some(checked);
+        command
+    }
+
+    fn folder(category: &'static str, path: &Path) -> Self {
+        let label = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string_lossy().to_string());
+        let mut command = Self::new(
+            format!("{}:{}", category.to_lowercase(), path.display()),
+            category,
+            label,
+            CommandKind::OpenFolder(path.to_path_buf()),
+        );
+        command.detail = Some(path.to_string_lossy().to_string());
+        command
+    }
+
+    fn checked(mut self, checked: bool) -> Self {
+        self.checked = Some(checked);
+        self
+    }
+
+    fn search_text(&self) -> String {
+        format!("{}: {}", self.category, self.label)
+    }
+}
+
+fn action_category(name: &str) -> &'static str {
+    match name {
+        "NewTab" | "CloseTab" => "Tab",
+        "ToggleSplit" | "SwitchPane" | "SyncPanes" | "CopyToOtherPane" | "MoveToOtherPane" => {
+            "Pane"
+        }
+        "Rename" | "Delete" | "DeletePermanently" | "SelectAll" | "Copy" | "Cut" | "Paste" => {
+            "Edit"
+        }
+        "ToggleHidden" | "ToggleGrouping" => "View",
+        "OpenSettings" => "Settings",
+        "Search" => "Search",
+        _ => "Go",
+    }
+}
+
+fn category_icon(category: &str) -> &'static str {
+    match category {
+        "Tab" => "add",
+        "Pane" => "view_column",
+        "Edit" | "Selection" => "description",
+        "View" => "grid",
+        "Search" => "search",
+        "Settings" => "settings",
+        "Pinned" => "star",
+        "Recent" => "schedule",
+        _ => "arrow_right",
+    }
+}
+
+/// Every command available right now. `workspace` is the active pane's,
+/// None while the settings tab is shown; `tabs` are the tab titles.
+pub fn commands(
+    workspace: Option<&Workspace>,
+    config: &Config,
+    tabs: &[String],
+    active_tab: usize,
+) -> Vec<Command> {
+    let (bindings, _) = keybindings::resolve(&config.keybindings);
+    let has_selection = workspace.is_some_and(|ws| !ws.selection.is_empty());
+    let in_split = workspace.is_some_and(|ws| ws.in_split);
+    let mut commands = Vec::new();
+
+    for action in keybindings::ACTIONS {
+        let needs_selection = matches!(
+            action.name,
+            "Open"
+                | "Rename"
+                | "Delete"
+                | "DeletePermanently"
+                | "Copy"
+                | "Cut"
+                | "CopyToOtherPane"
+                | "MoveToOtherPane"
+        );
+        let needs_split = matches!(
+            action.name,
+            "SwitchPane" | "SyncPanes" | "CopyToOtherPane" | "MoveToOtherPane"
+        );
+        // Cursor keys make no sense as commands, nor does the palette itself
+        let skip = action.name.starts_with("Move") && action.name != "MoveToOtherPane"
+            || action.name == "CommandPalette";
+        if skip || (needs_selection && !has_selection) || (needs_split && !in_split) {
+            continue;
+        }
+        if action.context.is_some() && workspace.is_none() {
+            continue;
+        }
+        let mut command = Command::new(
+            format!("action:{}", action.name),
+            action_category(action.name),
+            action.label,
+            CommandKind::Action(action.name),
+        );
+        command.keys = bindings
+            .iter()
+            .find(|binding: &&ResolvedBinding| binding.action == action.name)
+            .map(|binding| keybindings::display_keystroke(&binding.keystroke));
+        command.checked = workspace.and_then(|ws| match action.name {
+            "ToggleHidden" => Some(ws.view.show_hidden),
+            "ToggleGrouping" => Some(ws.view.group_by_type),
+            "ToggleSplit" => Some(ws.in_split),
+            _ => None,
+        });
+        commands.push(command);
+    }
+
+    if let Some(ws) = workspace {
+        commands.extend(workspace_commands(ws));
+    }
+    commands.extend(setting_commands(config));
+
+    for (ix, title) in tabs.iter().enumerate() {
+        if ix != active_tab {
+            commands.push(Command::new(
+                format!("tab:{}", ix),
+                "Tab",
+                format!("Switch to {}", title),
+                CommandKind::ActivateTab(ix),
+            ));
+        }
+    }
+    commands.extend(
+        config
+            .pinned_folders
+            .iter()
+            .map(|path| Command::folder("Pinned", path)),
+    );
+    let current_path = workspace.map(|ws| &ws.current_path);
+    commands.extend(
+        config
+            .recent_folders
+            .iter()
+            .filter(|path| Some(*path) != current_path)
+            .map(|path| Command::folder("Recent", path)),
+    );
+    commands
+}
+
+fn workspace_commands(ws: &Workspace) -> Vec<Command> {
+    let mut commands = Vec::new();
+
+    for (mode, label) in [
+        ("grid", "Grid"),
+        ("list", "List"),
+        ("details", "Details"),
+        ("columns", "Columns"),
+    ] {
+        commands.push(
+            Command::workspace(mode, "View", label, move |ws, cx| {
+                ws.set_view_mode(mode, cx)
+            })
+            .checked(ws.view.view_mode == mode),
+        );
+    }
+    commands.push(Command::workspace(
+        "zoom_in",
+        "View",
+        "Zoom In",
+        |ws, cx| ws.zoom(1, cx),
+    ));
+    commands.push(Command::workspace(
+        "zoom_out",
+        "View",
+        "Zoom Out",
+        |ws, cx| ws.zoom(-1, cx),
+    ));
+    for key in SortKey::TOOLBAR {
+        commands.push(
+            Command::workspace(
+                &format!("sort_{}", key.label()),
+                "View",
+                format!("Sort by {}", key.label()),
+                move |ws, cx| {
+                    let options = SortOptions {
+                        key,
+                        ..ws.view.sort
+                    };
+                    ws.set_sort(options, cx)
+                },
+            )
+            .checked(ws.view.sort.key == key),
+        );
+    }
+    commands.push(
+        Command::workspace("sort_descending", "View", "Sort Descending", |ws, cx| {
+            let mut options = ws.view.sort;
+            options.descending = !options.descending;
+            ws.set_sort(options, cx)
+        })
+        .checked(ws.view.sort.descending),
+    );
+    commands.push(
+        Command::workspace("folders_first", "View", "Folders First", |ws, cx| {
+            let mut options = ws.view.sort;
+            options.folders_first = !options.folders_first;
+            ws.set_sort(options, cx)
+        })
+        .checked(ws.view.sort.folders_first),
+    );
+    commands.push(Command::workspace(
+        "inherit_view",
+        "View",
+        "Apply View to Subfolders",
+        |ws, cx| ws.toggle_view_inheritance(cx),
+    ));
+    commands.push(Command::workspace(
+        "reset_view",
+        "View",
+        "Reset View to Defaults",
+        |ws, cx| ws.reset_view_settings(cx),
+    ));
+    commands.push(Command::workspace(
+        "view_everywhere",
+        "View",
+        "Use This View Everywhere",
+        |ws, cx| ws.use_view_settings_everywhere(cx),
+    ));
+
+    commands.push(
+        Command::workspace(
+            "search_recursive",
+            "Search",
+            "Search Subfolders",
+            |ws, cx| ws.toggle_search_recursive(cx),
+        )
+        .checked(ws.search_options.recursive),
+    );
+    commands.push(
+        Command::workspace(
+            "search_content",
+            "Search",
+            "Search File Contents",
+            |ws, cx| ws.toggle_search_content(cx),
+        )
+        .checked(ws.search_options.content_search),
+    );
+    commands.push(
+        Command::workspace("search_ignore", "Search", "Respect .gitignore", |ws, cx| {
+            ws.toggle_search_ignore(cx)
+        })
+        .checked(ws.search_options.respect_ignore),
+    );
+    if !ws.filter_query.is_empty() {
+        commands.push(Command::workspace(
+            "save_search",
+            "Search",
+            "Save Search as Smart Folder",
+            |ws, cx| ws.save_current_search(cx),
+        ));
+    }
+
+    // Context menu entries for the selection, or for the folder itself
+    let target = ws
+        .last_selected
+        .clone()
+        .filter(|path| ws.selection.contains(path));
+    let terminal_path = target.clone().unwrap_or_else(|| ws.current_path.clone());
+    commands.push(Command::workspace(
+        "open_in_terminal",
+        "Selection",
+        "Open in Terminal",
+        move |ws, cx| ws.open_in_terminal(terminal_path.clone(), cx),
+    ));
+    if let Some(path) = target {
+        let open_with = path.clone();
+        commands.push(Command::workspace(
+            "open_with",
+            "Selection",
+            "Open With...",
+            move |ws, cx| ws.open_with(open_with.clone(), cx),
+        ));
+        let copy_path = path.clone();
+        commands.push(Command::workspace(
+            "copy_path",
+            "Selection",
+            "Copy Path",
+            move |ws, cx| {
+                ws.copy_text_to_clipboard(copy_path.to_string_lossy().to_string(), cx);
+                ws.show_toast(
+                    "Path copied to clipboard".to_string(),
+                    crate::ui_components::toast::ToastKind::Info,
+                    cx,
+                );
+            },
+        ));
+        commands.push(Command::workspace(
+            "properties",
+            "Selection",
+            "Properties",
+            move |ws, cx| ws.show_properties(path.clone(), cx),
+        ));
+    }
+    if !ws.selection.is_empty() {
+        commands.push(Command::workspace(
+            "move_to",
+            "Selection",
+            "Move To...",
+            |ws, cx| ws.open_folder_picker(PickerAction::MoveSelection, cx),
+        ));
+        commands.push(Command::workspace(
+            "copy_to",
+            "Selection",
+            "Copy To...",
+            |ws, cx| ws.open_folder_picker(PickerAction::CopySelection, cx),
+        ));
+    }
+    commands
+}
+
+fn setting_commands(config: &Config) -> Vec<Command> {
+    let ui = &config.ui;
+    vec![
+        Command::setting(
+            "show_hidden",
+            "Show Hidden Files by Default",
+            ui.show_hidden,
+            |config| config.ui.show_hidden = !config.ui.show_hidden,
+        ),
+        Command::setting(
+            "group_by_type",
+            "Group by Type by Default",
+            config.group_files_by_type,
+            |config| config.group_files_by_type = !config.group_files_by_type,
+        ),
+        Command::setting(
+            "view_grid",
+            "Default View: Grid",
+            ui.view_mode == "grid",
+            |config| config.ui.view_mode = "grid".to_string(),
+        ),
+        Command::setting(
+            "view_list",
+            "Default View: List",
+            ui.view_mode == "list",
+            |config| config.ui.view_mode = "list".to_string(),
+        ),
+        Command::setting(
+            "view_details",
+            "Default View: Details",
+            ui.view_mode == "details",
+            |config| config.ui.view_mode = "details".to_string(),
+        ),
+        Command::setting(
+            "view_columns",
+            "Default View: Columns",
+            ui.view_mode == "columns",
+            |config| config.ui.view_mode = "columns".to_string(),
+        ),
+        Command::setting(
+            "ignored_show",
+            "Ignored Files: Show",
+            ui.ignored_files == "show",
+            |config| config.ui.ignored_files = "show".to_string(),
+        ),
+        Command::setting(
+            "ignored_dim",
+            "Ignored Files: Dim",
+            ui.ignored_files == "dim",
+            |config| config.ui.ignored_files = "dim".to_string(),
+        ),
+        Command::setting(
+            "ignored_hide",
+            "Ignored Files: Hide",
+            ui.ignored_files == "hide",
+            |config| config.ui.ignored_files = "hide".to_string(),
+        ),
+        Command::setting("use_dms", "Use DMS Open", config.use_dms, |config| {
+            config.use_dms = !config.use_dms
+        }),
+    ]
+}
+
+/// Indices of the commands matching `query`, best first. Recently run
+/// commands (`recent`, most recent first) get a bonus shrinking with age,
+/// so with an empty query they come first in the order they were run.
+pub fn rank(commands: &[Command], query: &str, recent: &[String]) -> Vec<usize> {
+    let matcher = SkimMatcherV2::default();
+    let query = query.trim();
+    let mut ranked: Vec<(i64, usize)> = commands
+        .iter()
+        .enumerate()
+        .filter_map(|(ix, command)| {
+            let score = if query.is_empty() {
+                0
+            } else {
+                matcher.fuzzy_match(&command.search_text(), query)?
+            };
+            let bonus = recent
+                .iter()
+                .position(|id| *id == command.id)
+                .map_or(0, |age| (recent.len() - age) as i64 * RECENT_STEP);
+            Some((score + bonus, ix))
+        })
+        .collect();
+    // Stable, so equal scores keep the listing order
+    ranked.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    ranked.into_iter().map(|(_, ix)| ix).collect()
+}
+
+pub enum CommandPaletteEvent {
+    Run(Command),
+    Dismiss,
+}
+
+pub struct CommandPalette {
+    commands: Vec<Command>,
+    recent: Vec<String>,
+    query: String,
+    /// Indices into `commands`, best match first
+    matches: Vec<usize>,
+    selected: usize,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+}
+
+impl EventEmitter<CommandPaletteEvent> for CommandPalette {}
+
+impl Focusable for CommandPalette {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl CommandPalette {
+    pub fn new(commands: Vec<Command>, recent: Vec<String>, cx: &mut Context<Self>) -> Self {
+        let matches = rank(&commands, "", &recent);
+        Self {
+            commands,
+            recent,
+            query: String::new(),
+            matches,
+            selected: 0,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+        }
+    }
+
+    fn set_query(&mut self, query: String, cx: &mut Context<Self>) {
+        self.matches = rank(&self.commands, &query, &self.recent);
+        self.query = query;
+        self.select(0, cx);
+    }
+
+    fn select(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.selected = ix;
+        self.scroll_handle.scroll_to_item(ix, ScrollStrategy::Top);
+        cx.notify();
+    }
+
+    fn run(&mut self, ix: usize, cx: &mut Context<Self>) {
+        if let Some(command) = self.matches.get(ix).map(|&i| self.commands[i].clone()) {
+            cx.emit(CommandPaletteEvent::Run(command));
+        }
+    }
+
+    fn handle_key_down(&mut self, event: &KeyDownEvent, cx: &mut Context<Self>) {
+        let keystroke = &event.keystroke;
+        let count = self.matches.len();
+        match keystroke.key.as_str() {
+            "escape" => cx.emit(CommandPaletteEvent::Dismiss),
+            "enter" => self.run(self.selected, cx),
+            "up" if count > 0 => self.select((self.selected + count - 1) % count, cx),
+            "down" if count > 0 => self.select((self.selected + 1) % count, cx),
+            "backspace" => {
+                let mut query = self.query.clone();
+                query.pop();
+                self.set_query(query, cx);
+            }
+            _ => {
+                let typed = keystroke.key_char.as_ref().filter(|typed| {
+                    !typed.chars().any(char::is_control)
+                        && !keystroke.modifiers.control
+                        && !keystroke.modifiers.alt
+                });
+                if let Some(typed) = typed {
+                    let query = format!("{}{}", self.query, typed);
+                    self.set_query(query, cx);
+                }
+            }
+        }
+        cx.stop_propagation();
+    }
+
+    fn render_row(&self, ix: usize, palette: &M3Palette, cx: &mut Context<Self>) -> AnyElement {
+        let command = &self.commands[self.matches[ix]];
+        let is_selected = ix == self.selected;
+        let text_color = if is_selected {
+            palette.on_secondary_container
+        } else {
+            palette.on_surface
+        };
+
+        div()
+            .id(ix)
+            .h(px(ROW_HEIGHT))
+            .flex()
+            .items_center()
+            .gap_3()
+            .px_4()
+            .when(is_selected, |d| d.bg(palette.secondary_container))
+            .hover(|s| s.bg(palette.surface_container_highest))
+            .cursor_pointer()
+            .text_sm()
+            .text_color(text_color)
+            .on_click(cx.listener(move |this, _, _, cx| this.run(ix, cx)))
+            .child(
+                crate::assets::icons::icon(category_icon(command.category))
+                    .size_4()
+                    .flex_none()
+                    .text_color(palette.on_surface_variant),
+            )
+            .child(
+                div()
+                    .flex_none()
+                    .text_color(palette.on_surface_variant)
+                    .child(format!("{}:", command.category)),
+            )
+            .child(
+                div()
+                    .flex_none()
+                    .max_w(px(280.0))
+                    .text_ellipsis()
+                    .child(command.label.clone()),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .min_w_0()
+                    .text_xs()
+                    .text_color(palette.on_surface_variant)
+                    .text_ellipsis()
+                    .children(command.detail.clone()),
+            )
+            .when(command.checked == Some(true), |d| {
+                d.child(div().flex_none().text_color(palette.primary).child("✓"))
+            })
+            .children(command.keys.clone().map(|keys| {
+                div()
+                    .flex_none()
+                    .px_2()
+                    .rounded_md()
+                    .bg(palette.surface_container_highest)
+                    .text_xs()
+                    .text_color(palette.on_surface_variant)
+                    .child(keys)
+            }))
+            .into_any_element()
+    }
+}
+
+impl Render for CommandPalette {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let palette = cx.theme().palette.clone();
+        let rows = self.matches.len().min(MAX_VISIBLE_ROWS);
+
+        let list = if self.matches.is_empty() {
+            div()
+                .p_4()
+                .text_sm()
+                .text_color(palette.on_surface_variant)
+                .child("No matching commands")
+                .into_any_element()
+        } else {
+            let row_palette = palette.clone();
+            let this = cx.entity();
+            uniform_list(
+                "command_palette_list",
+                self.matches.len(),
+                move |range, _window, cx| {
+                    this.update(cx, |this, cx| {
+                        range
+                            .map(|ix| this.render_row(ix, &row_palette, cx))
+                            .collect::<Vec<_>>()
+                    })
+                },
+            )
+            .track_scroll(self.scroll_handle.clone())
+            .h(px(ROW_HEIGHT * rows as f32))
+            .into_any_element()
+        };
+
+        div()
+            .id("command_palette_scrim")
+            .absolute()
+            .top_0()
+            .left_0()
+            .size_full()
+            .flex()
+            .flex_col()
+            .items_center()
+            .pt(px(96.0))
+            .bg(Hsla::from(palette.scrim).opacity(0.32))
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|_, _, _, cx| cx.emit(CommandPaletteEvent::Dismiss)),
+            )
+            .child(
+                div()
+                    .id("command_palette")
+                    .w(px(640.0))
+                    .flex()
+                    .flex_col()
+                    .rounded_xl()
+                    .overflow_hidden()
+                    .bg(palette.surface_container_high)
+                    .shadow_xl()
+                    .track_focus(&self.focus_handle)
+                    .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                    .on_key_down(cx.listener(|this, event: &KeyDownEvent, _, cx| {
+                        this.handle_key_down(event, cx)
+                    }))
+                    .child(
+                        div()
+                            .h_12()
+                            .flex()
+                            .items_center()
+                            .gap_3()
+                            .px_4()
+                            .border_b_1()
+                            .border_color(palette.outline_variant)
+                            .child(
+                                crate::assets::icons::icon("search")
+                                    .size_5()
+                                    .text_color(palette.on_surface_variant),
+                            )
+                            .child(if self.query.is_empty() {
+                                div()
+                                    .text_color(palette.on_surface_variant)
+                                    .child("Type a command or folder")
+                            } else {
+                                div()
+                                    .text_color(palette.on_surface)
+                                    .child(self.query.clone())
+                            })
+                            .child(div().flex_none().w(px(1.0)).h_5().bg(palette.primary)),
+                    )
+                    .child(list),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Not `super::*`: gpui's glob export would shadow `#[test]`
+    use super::{rank, Command, CommandKind};
+
+    fn command(id: &str, label: &str) -> Command {
+        Command::new(id.to_string(), "Go", label, CommandKind::Setting(|_| {}))
+    }
+
+    #[test]
+    fn test_rank_empty_query_puts_recent_first() {
+        let commands = vec![
+            command("a", "Alpha"),
+            command("b", "Beta"),
+            command("c", "Gamma"),
+        ];
+        let recent = vec!["c".to_string(), "b".to_string()];
+        assert_eq!(rank(&commands, "", &recent), vec![2, 1, 0]);
+        assert_eq!(rank(&commands, "", &[]), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_rank_filters_and_boosts_recent() {
+        let commands = vec![
+            command("tab", "New Tab"),
+            command("reload", "Reload"),
+            command("tab2", "Close Tab"),
+        ];
+        assert_eq!(rank(&commands, "tab", &[]), vec![0, 2]);
+        assert_eq!(rank(&commands, "tab", &["tab2".to_string()]), vec![2, 0]);
+        assert!(rank(&commands, "xyz", &[]).is_empty());
+    }
+}
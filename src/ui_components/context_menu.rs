@@ -1,4 +1,6 @@
-use crate::app_state::workspace::{ActiveOverlay, Workspace};
+use crate::app_state::view_settings::ViewSettingsStore;
+use crate::app_state::workspace::{ActiveOverlay, ClipboardOp, PaneAction, Workspace};
+use crate::fs_ops::image_metadata::{self, StripMode};
 use crate::theme_engine::theme::ThemeContext;
 use gpui::prelude::*;
 use gpui::*;
@@ -20,16 +22,52 @@ impl ContextMenuOverlay {
         let path_open_with = path.clone();
         let path_copy = path.clone();
         let path_delete = path.clone();
+        let path_rename = path.clone();
         let path_props = path.clone();
+        let is_photo = image_metadata::is_supported(&path);
 
-        // Helper to create menu items
-        let menu_item = |label: &str,
-                         action: Box<
-            dyn Fn(&mut Workspace, &mut Context<Workspace>) + Send + Sync + 'static,
-        >,
-                         cx: &Context<V>| {
+        // The selected photos, or just this one when it isn't part of the
+        // selection
+        let photo_paths = {
+            let path = path.clone();
+            move |ws: &Workspace| -> Vec<PathBuf> {
+                if ws.selection.contains(&path) {
+                    ws.selection
+                        .iter()
+                        .filter(|p| image_metadata::is_supported(p))
+                        .cloned()
+                        .collect()
+                } else {
+                    vec![path.clone()]
+                }
+            }
+        };
+        let remove_location = {
+            let photo_paths = photo_paths.clone();
+            Box::new(move |ws: &mut Workspace, cx: &mut Context<Workspace>| {
+                let paths = photo_paths(ws);
+                ws.strip_metadata(paths, StripMode::Location, cx);
+            })
+        };
+
+        // Opened on the background rather than an item: offer view settings
+        let (on_background, show_hidden, inherits, in_split) = {
+            let ws = workspace.read(cx);
+            let inherits = cx
+                .global::<ViewSettingsStore>()
+                .get(&ws.current_path)
+                .is_some_and(|v| v.inherit);
+            (
+                ws.context_menu_path.is_none(),
+                ws.view.show_hidden,
+                inherits,
+                ws.in_split,
+            )
+        };
+
+        // Helpers to create menu items
+        let menu_row = |label: &str, cx: &Context<V>| {
             let theme = cx.theme();
-            let workspace = workspace.clone();
             div()
                 .id(label.to_string()) // Add ID for interaction (owned string)
                 .w_full()
@@ -39,17 +77,24 @@ impl ContextMenuOverlay {
                 .hover(|s| s.bg(theme.palette.surface_variant))
                 .cursor_pointer()
                 .child(label.to_string())
-                .on_click(move |_, _, cx| {
-                    cx.stop_propagation();
-                    workspace.update(cx, |ws, cx| {
-                        action(ws, cx);
-                        // Only dismiss if it's still the context menu
-                        // (Actions like "Properties" might have changed it to a dialog)
-                        if let Some(ActiveOverlay::ContextMenu) = ws.active_overlay {
-                            ws.dismiss_overlay(cx);
-                        }
-                    });
-                })
+        };
+        let menu_item = |label: &str,
+                         action: Box<
+            dyn Fn(&mut Workspace, &mut Context<Workspace>) + Send + Sync + 'static,
+        >,
+                         cx: &Context<V>| {
+            let workspace = workspace.clone();
+            menu_row(label, cx).on_click(move |_, _, cx| {
+                cx.stop_propagation();
+                workspace.update(cx, |ws, cx| {
+                    action(ws, cx);
+                    // Only dismiss if it's still the context menu
+                    // (Actions like "Properties" might have changed it to a dialog)
+                    if let Some(ActiveOverlay::ContextMenu) = ws.active_overlay {
+                        ws.dismiss_overlay(cx);
+                    }
+                });
+            })
         };
 
         div()
@@ -146,6 +191,17 @@ impl ContextMenuOverlay {
                 }),
                 cx,
             ))
+            .when(!on_background, |menu| {
+                let path = path_rename.clone();
+                menu.child(menu_item(
+                    "Rename",
+                    Box::new(move |ws, cx| {
+                        ws.set_selection(path.clone(), cx);
+                        ws.begin_rename(cx);
+                    }),
+                    cx,
+                ))
+            })
             // Delete
             .child(menu_item(
                 "Delete",
@@ -154,17 +210,131 @@ impl ContextMenuOverlay {
                 }),
                 cx,
             ))
+            // Dual-pane transfers
+            .when(in_split && !on_background, |menu| {
+                menu.child(div().h_px().bg(theme.palette.outline_variant))
+                    .child(menu_item(
+                        "Copy to Other Pane",
+                        Box::new(|ws, cx| {
+                            ws.pane_action(PaneAction::TransferToOther(ClipboardOp::Copy), cx)
+                        }),
+                        cx,
+                    ))
+                    .child(menu_item(
+                        "Move to Other Pane",
+                        Box::new(|ws, cx| {
+                            ws.pane_action(PaneAction::TransferToOther(ClipboardOp::Cut), cx)
+                        }),
+                        cx,
+                    ))
+            })
+            .when(on_background, |menu| {
+                menu.child(div().h_px().bg(theme.palette.outline_variant))
+                    .child(menu_item(
+                        if in_split {
+                            "Close Split View"
+                        } else {
+                            "Split View"
+                        },
+                        Box::new(|ws, cx| ws.pane_action(PaneAction::ToggleSplit, cx)),
+                        cx,
+                    ))
+                    .when(in_split, |menu| {
+                        menu.child(menu_item(
+                            "Sync Panes",
+                            Box::new(|ws, cx| ws.pane_action(PaneAction::SyncPanes, cx)),
+                            cx,
+                        ))
+                    })
+                    .child(div().h_px().bg(theme.palette.outline_variant))
+                    .child(menu_item("Zoom In", Box::new(|ws, cx| ws.zoom(1, cx)), cx))
+                    .child(menu_item(
+                        "Zoom Out",
+                        Box::new(|ws, cx| ws.zoom(-1, cx)),
+                        cx,
+                    ))
+                    .child(menu_item(
+                        if show_hidden {
+                            "Hide Hidden Files"
+                        } else {
+                            "Show Hidden Files"
+                        },
+                        Box::new(|ws, cx| ws.toggle_hidden(cx)),
+                        cx,
+                    ))
+                    .child(menu_item(
+                        if inherits {
+                            "Stop Applying View to Subfolders"
+                        } else {
+                            "Apply View to Subfolders"
+                        },
+                        Box::new(|ws, cx| ws.toggle_view_inheritance(cx)),
+                        cx,
+                    ))
+                    .child(menu_item(
+                        "Reset View to Defaults",
+                        Box::new(|ws, cx| ws.reset_view_settings(cx)),
+                        cx,
+                    ))
+                    .child(menu_item(
+                        "Use This View Everywhere",
+                        Box::new(|ws, cx| ws.use_view_settings_everywhere(cx)),
+                        cx,
+                    ))
+            })
+            .when(is_photo && !on_background, |menu| {
+                menu.child(div().h_px().bg(theme.palette.outline_variant))
+                    .child(menu_item("Remove Location", remove_location, cx))
+                    .child(menu_row("Remove All Metadata", cx).on_click({
+                        let workspace = workspace.clone();
+                        move |_, window, cx| {
+                            cx.stop_propagation();
+                            let paths = workspace.update(cx, |ws, cx| {
+                                ws.dismiss_overlay(cx);
+                                photo_paths(ws)
+                            });
+                            confirm_strip_all(paths, workspace.clone(), window, cx);
+                        }
+                    }))
+            })
             // Properties
             .child(div().h_px().bg(theme.palette.outline_variant))
             .child(menu_item(
                 "Properties",
-                Box::new(move |ws, cx| {
-                    ws.active_overlay = Some(ActiveOverlay::DetailsDialog(path_props.clone()));
-                    ws.details_metadata = None;
-                    ws.load_details_metadata(path_props.clone(), cx);
-                    cx.notify();
-                }),
+                Box::new(move |ws, cx| ws.show_properties(path_props.clone(), cx)),
                 cx,
             ))
     }
 }
+
+/// Removing all metadata can't be undone, so it asks first
+fn confirm_strip_all(
+    paths: Vec<PathBuf>,
+    workspace: Entity<Workspace>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let message = if paths.len() == 1 {
+        "Remove all metadata from the selected photo?".to_string()
+    } else {
+        format!("Remove all metadata from {} selected photos?", paths.len())
+    };
+    let answer = window.prompt(
+        PromptLevel::Warning,
+        &message,
+        Some("Camera details, dates and location are removed for good."),
+        &["Remove", "Cancel"],
+        cx,
+    );
+    cx.spawn(move |cx: &mut AsyncApp| {
+        let cx = cx.clone();
+        async move {
+            if matches!(answer.await, Ok(0)) {
+                let _ = cx.update(|cx| {
+                    workspace.update(cx, |ws, cx| ws.strip_metadata(paths, StripMode::All, cx));
+                });
+            }
+        }
+    })
+    .detach();
+}
@@ -1,4 +1,4 @@
-use crate::app_state::config::ConfigContext;
+use crate::app_state::config::{ConfigContext, SmartFolder};
 use crate::theme_engine::theme::ThemeContext;
 use chrono::Timelike;
 use gpui::prelude::*;
@@ -239,6 +239,85 @@ impl Dashboard {
             )
     }
 
+    fn render_smart_folders_section(
+        &self,
+        palette: &crate::theme_engine::palette::M3Palette,
+        smart_folders: &[SmartFolder],
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        div()
+            .flex()
+            .flex_col()
+            .gap_3()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(crate::assets::icons::icon("search").size_5())
+                    .child(
+                        div()
+                            .text_base()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(palette.on_surface)
+                            .child("Smart Folders"),
+                    ),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_wrap()
+                    .gap_3()
+                    .children(smart_folders.iter().map(|folder| {
+                        let folder_clone = folder.clone();
+                        let scope = format!("\"{}\" in {}", folder.query, folder.root.display());
+
+                        div()
+                            .id(SharedString::from(format!("smart_{}", folder.name)))
+                            .w(px(220.0))
+                            .p_3()
+                            .bg(palette.surface_container)
+                            .rounded_xl()
+                            .border_1()
+                            .border_color(palette.outline_variant)
+                            .hover(|s| s.bg(palette.surface_container_high))
+                            .cursor_pointer()
+                            .on_click(cx.listener(move |_, _, _, cx| {
+                                cx.emit(DashboardEvent::OpenSmartFolder(folder_clone.clone()));
+                            }))
+                            .flex()
+                            .items_center()
+                            .gap_3()
+                            .child(
+                                crate::assets::icons::icon("search")
+                                    .size_6()
+                                    .text_color(palette.primary),
+                            )
+                            .child(
+                                div()
+                                    .flex()
+                                    .flex_col()
+                                    .min_w_0()
+                                    .gap_1()
+                                    .child(
+                                        div()
+                                            .text_sm()
+                                            .text_color(palette.on_surface)
+                                            .text_ellipsis()
+                                            .child(folder.name.clone()),
+                                    )
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(palette.on_surface_variant)
+                                            .text_ellipsis()
+                                            .child(scope),
+                                    ),
+                            )
+                    })),
+            )
+    }
+
     fn render_recent_section(
         &self,
         palette: &crate::theme_engine::palette::M3Palette,
@@ -316,6 +395,7 @@ impl Render for Dashboard {
         let config = cx.config();
         let pinned_folders = config.pinned_folders.clone();
         let recent_folders = config.recent_folders.clone();
+        let smart_folders = config.smart_folders.clone();
 
         div()
             .id("dashboard")
@@ -361,13 +441,31 @@ impl Render for Dashboard {
                                     .border_color(palette.outline_variant)
                                     .child(self.render_recent_section(&palette, &recent_folders)),
                             ),
-                    ),
+                    )
+                    .children(if smart_folders.is_empty() {
+                        None
+                    } else {
+                        Some(
+                            div()
+                                .p_5()
+                                .bg(palette.surface_container_low)
+                                .rounded_2xl()
+                                .border_1()
+                                .border_color(palette.outline_variant)
+                                .child(self.render_smart_folders_section(
+                                    &palette,
+                                    &smart_folders,
+                                    cx,
+                                )),
+                        )
+                    }),
             )
     }
 }
 
 pub enum DashboardEvent {
     OpenPath(PathBuf),
+    OpenSmartFolder(SmartFolder),
     ShowAddPinned,
 }
 
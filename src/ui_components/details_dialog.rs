@@ -1,19 +1,28 @@
+use crate::app_state::config::ConfigContext;
 use crate::app_state::workspace::Workspace;
+use crate::fs_ops::image_metadata::{self, PhotoMetadata};
+use crate::fs_ops::media::{self, MediaInfo};
 use crate::theme_engine::theme::ThemeContext;
+use crate::ui_components::chips::Chip;
 use gpui::prelude::*;
 use gpui::*;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::SystemTime;
 
 pub struct DetailsDialog;
 
 impl DetailsDialog {
+    #[allow(clippy::too_many_arguments)]
     pub fn render<V: 'static>(
         path: &PathBuf,
         size: u64,
         modified: SystemTime,
         mime_type: Option<String>,
         image_dimensions: Option<(u32, u32)>,
+        tags: Vec<String>,
+        media_info: Option<Arc<MediaInfo>>,
+        photo: Option<PhotoMetadata>,
         workspace: Entity<Workspace>,
         cx: &Context<V>,
     ) -> impl IntoElement {
@@ -48,6 +57,39 @@ impl DetailsDialog {
             }
         };
 
+        // Every configured tag is offered; tags set by other tools are shown too
+        let mut tag_chips: Vec<(String, Option<u32>)> = cx
+            .config()
+            .tags
+            .iter()
+            .map(|t| (t.name.clone(), Some(t.rgb())))
+            .collect();
+        for tag in &tags {
+            if !tag_chips.iter().any(|(name, _)| name == tag) {
+                tag_chips.push((tag.clone(), None));
+            }
+        }
+
+        // Stream properties and tags, and what the embedded cover art is
+        let mut media_rows = media_info
+            .as_deref()
+            .map(media::describe)
+            .unwrap_or_default();
+        if let Some(cover) = media_info.as_ref().and_then(|info| info.cover.as_ref()) {
+            media_rows.push((
+                "Cover art",
+                format!(
+                    "{}, {}",
+                    cover.mime,
+                    human_bytes::human_bytes(cover.data.len() as f64)
+                ),
+            ));
+        }
+        // Camera, exposure and where a photo was taken
+        if let Some(photo) = &photo {
+            media_rows.extend(image_metadata::describe(photo));
+        }
+
         let workspace_scrim = workspace.clone();
         let workspace_close = workspace.clone();
 
@@ -112,6 +154,14 @@ impl DetailsDialog {
                                     .child("Dimensions:")
                                     .child(format!("{}x{}", w, h))
                             }))
+                            .children(media_rows.into_iter().map(|(label, value)| {
+                                div()
+                                    .flex()
+                                    .justify_between()
+                                    .gap_4()
+                                    .child(format!("{}:", label))
+                                    .child(div().text_ellipsis().overflow_hidden().child(value))
+                            }))
                             .child(
                                 div()
                                     .flex()
@@ -134,7 +184,42 @@ impl DetailsDialog {
                                         .format("%Y-%m-%d %H:%M")
                                         .to_string(),
                                 ),
-                            ),
+                            )
+                            .when(!tag_chips.is_empty(), |d| {
+                                d.child(
+                                    div().flex().justify_between().gap_4().child("Tags:").child(
+                                        div().flex().flex_wrap().justify_end().gap_1().children(
+                                            tag_chips.into_iter().map(|(name, color)| {
+                                                let workspace = workspace.clone();
+                                                let path = path.clone();
+                                                let tag = name.clone();
+                                                Chip::new(
+                                                    SharedString::from(format!(
+                                                        "details-tag-{}",
+                                                        name
+                                                    )),
+                                                    name.clone(),
+                                                )
+                                                .filter()
+                                                .dense()
+                                                .selected(tags.contains(&name))
+                                                .color(
+                                                    color
+                                                        .map(|c| Hsla::from(gpui::rgb(c)))
+                                                        .unwrap_or_else(|| {
+                                                            theme.palette.outline.into()
+                                                        }),
+                                                )
+                                                .on_click(move |_, _, cx| {
+                                                    workspace.update(cx, |ws, cx| {
+                                                        ws.toggle_tag(path.clone(), tag.clone(), cx)
+                                                    });
+                                                })
+                                            }),
+                                        ),
+                                    ),
+                                )
+                            }),
                     )
                     .child(
                         div().flex().justify_end().child(
@@ -0,0 +1,344 @@
+// Details view: one row per entry with user-chosen columns. Headers sort on
+// click, reorder by dragging and resize from their right edge; the layout is
+// kept in `[ui] details_columns` in config.toml.
+use crate::app_state::config::{ConfigManager, DetailsColumn};
+use crate::app_state::workspace::Workspace;
+use crate::assets::mime_resolver::MimeResolver;
+use crate::fs_ops::media;
+use crate::fs_ops::provider::FileEntry;
+use crate::fs_ops::sort::{SortKey, SortOptions};
+use crate::theme_engine::palette::M3Palette;
+use crate::ui_components::chips::Chip;
+use crate::ui_components::file_list::{
+    icon_name_for, render_git_badge, render_name, render_tag_chips, with_item_handlers, ItemStyle,
+};
+use gpui::prelude::*;
+use gpui::*;
+use std::time::SystemTime;
+
+/// Horizontal padding of header and rows, matching list mode
+const ROW_PADDING: f32 = 12.0;
+
+/// A header cell being dragged to a new position.
+#[derive(Clone)]
+pub struct ColumnDrag {
+    index: usize,
+    label: &'static str,
+    palette: M3Palette,
+}
+
+impl Render for ColumnDrag {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .px_3()
+            .py_1()
+            .rounded_md()
+            .bg(self.palette.secondary_container)
+            .text_color(self.palette.on_secondary_container)
+            .text_xs()
+            .font_weight(FontWeight::SEMIBOLD)
+            .shadow_md()
+            .child(self.label)
+    }
+}
+
+/// The resize handle of a column being dragged.
+#[derive(Clone)]
+pub struct ColumnResize {
+    index: usize,
+}
+
+fn format_time(time: Option<SystemTime>) -> String {
+    time.map(|t| {
+        chrono::DateTime::<chrono::Local>::from(t)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    })
+    .unwrap_or_else(|| "--".to_string())
+}
+
+fn cell_text(item: &FileEntry, key: SortKey) -> String {
+    match key {
+        SortKey::Name => item.name.clone(),
+        SortKey::Size => item.formatted_size.clone(),
+        SortKey::Modified => item.formatted_date.clone(),
+        SortKey::Created => format_time(item.attributes.created),
+        SortKey::Accessed => format_time(item.attributes.accessed),
+        SortKey::Type | SortKey::Category => MimeResolver::type_label(&item.path, item.is_dir),
+        SortKey::Permissions => item.attributes.permissions_string(item.is_dir),
+        SortKey::Owner => item.attributes.owner_name().to_string(),
+        SortKey::Group => item.attributes.group_name().to_string(),
+        SortKey::LinkTarget => item
+            .attributes
+            .link_target
+            .as_ref()
+            .map(|target| target.display().to_string())
+            .unwrap_or_default(),
+        SortKey::ItemCount => match item.child_count {
+            Some(1) => "1 item".to_string(),
+            Some(count) => format!("{} items", count),
+            None => "--".to_string(),
+        },
+        SortKey::Duration => item
+            .media
+            .as_ref()
+            .and_then(|media| media.duration)
+            .map(media::format_duration)
+            .unwrap_or_else(|| "--".to_string()),
+        SortKey::Artist => item
+            .media
+            .as_ref()
+            .and_then(|media| media.artist.clone())
+            .unwrap_or_default(),
+    }
+}
+
+fn is_numeric(key: SortKey) -> bool {
+    matches!(key, SortKey::Size | SortKey::ItemCount | SortKey::Duration)
+}
+
+pub fn render_details_row(
+    item: &FileEntry,
+    id: ElementId,
+    is_selected: bool,
+    columns: &[DetailsColumn],
+    style: &ItemStyle,
+    workspace: &Entity<Workspace>,
+) -> AnyElement {
+    let palette = &style.palette;
+    let bg_color = if is_selected {
+        Hsla::from(palette.secondary_container)
+    } else {
+        gpui::hsla(0., 0., 0., 0.)
+    };
+    let text_color = if is_selected {
+        palette.on_secondary_container
+    } else {
+        palette.on_surface
+    };
+    let sub_text_color = if is_selected {
+        palette.on_secondary_container
+    } else {
+        palette.on_surface_variant
+    };
+
+    let element = div()
+        .id(id)
+        .h_8()
+        .flex()
+        .items_center()
+        .w_full()
+        .px(px(ROW_PADDING))
+        .border_b_1()
+        .border_color(Hsla::from(palette.outline_variant).opacity(0.1))
+        .bg(bg_color)
+        .text_color(text_color)
+        .when(style.dim_ignored && item.is_ignored, |d| d.opacity(0.5))
+        .hover(|s| s.bg(palette.surface_container_highest));
+
+    with_item_handlers(element, item, style, workspace)
+        .children(columns.iter().map(|column| {
+            let cell = div()
+                .w(px(column.width))
+                .flex_none()
+                .flex()
+                .items_center()
+                .gap_2()
+                .pr_2()
+                .overflow_hidden();
+            if column.key == SortKey::Name {
+                cell.child(
+                    crate::assets::icons::icon(icon_name_for(item))
+                        .size_4()
+                        .flex_none(),
+                )
+                .child(render_name(item, style).min_w_0())
+                .children(render_git_badge(item, style))
+                .when(!item.tags.is_empty(), |d| {
+                    d.child(render_tag_chips(item, style, 2).flex_none())
+                })
+            } else {
+                cell.text_sm()
+                    .text_color(sub_text_color)
+                    .when(is_numeric(column.key), |d| d.justify_end())
+                    .child(div().text_ellipsis().child(cell_text(item, column.key)))
+            }
+        }))
+        .into_any_element()
+}
+
+/// Sortable, reorderable and resizable column headers. `on_choose_columns`
+/// opens the column chooser.
+pub fn render_details_header(
+    columns: &[DetailsColumn],
+    sort: SortOptions,
+    palette: &M3Palette,
+    workspace: &Entity<Workspace>,
+    on_choose_columns: impl Fn(&ClickEvent, &mut Window, &mut App) + 'static,
+) -> Stateful<Div> {
+    let widths: Vec<f32> = columns.iter().map(|c| c.width).collect();
+
+    let cells = columns.iter().enumerate().map(|(index, column)| {
+        let key = column.key;
+        let active = sort.key == key;
+        let workspace = workspace.clone();
+        let drag = ColumnDrag {
+            index,
+            label: key.label(),
+            palette: palette.clone(),
+        };
+        let drop_highlight = palette.surface_container_highest;
+
+        div()
+            .id(SharedString::from(format!(
+                "details_header_{}",
+                key.label()
+            )))
+            .relative()
+            .w(px(column.width))
+            .h_full()
+            .flex_none()
+            .flex()
+            .items_center()
+            .gap_1()
+            .pr_2()
+            .overflow_hidden()
+            .cursor_pointer()
+            .when(active, |d| d.text_color(palette.primary))
+            .hover(|s| s.text_color(palette.on_surface))
+            .on_click(move |_, _, cx| {
+                workspace.update(cx, |ws, cx| ws.set_sort(ws.view.sort.toggled(key), cx));
+            })
+            .on_drag(drag, |drag, _offset, _window, cx| cx.new(|_| drag.clone()))
+            .drag_over::<ColumnDrag>(move |style, _, _, _| style.bg(drop_highlight))
+            .on_drop(move |drag: &ColumnDrag, _window, cx| {
+                cx.update_global::<ConfigManager, _>(|manager, cx| {
+                    manager.config.ui.move_details_column(drag.index, index);
+                    manager.save_config();
+                    cx.refresh_windows();
+                });
+            })
+            .when(is_numeric(key), |d| d.justify_end())
+            .child(div().text_ellipsis().child(key.label()))
+            .when(active, |d| {
+                d.child(if sort.descending { "↓" } else { "↑" })
+            })
+            // Resize handle on the right edge
+            .child(
+                div()
+                    .id(SharedString::from(format!(
+                        "details_resize_{}",
+                        key.label()
+                    )))
+                    .absolute()
+                    .top_0()
+                    .bottom_0()
+                    .right_0()
+                    .w(px(6.0))
+                    .cursor_col_resize()
+                    .hover(|s| s.bg(palette.outline_variant))
+                    .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                    .on_click(|_, _, cx| cx.stop_propagation())
+                    .on_drag(ColumnResize { index }, |_, _, _, cx| cx.new(|_| EmptyView)),
+            )
+    });
+
+    div()
+        .id("details_header")
+        .h_8()
+        .flex()
+        .flex_none()
+        .items_center()
+        .w_full()
+        .px(px(ROW_PADDING))
+        .border_b_1()
+        .border_color(palette.outline_variant)
+        .text_xs()
+        .font_weight(FontWeight::SEMIBOLD)
+        .text_color(palette.on_surface_variant)
+        .on_drag_move(move |event: &DragMoveEvent<ColumnResize>, _window, cx| {
+            let index = event.drag(cx).index;
+            let start = f32::from(event.bounds.left())
+                + ROW_PADDING
+                + widths.iter().take(index).sum::<f32>();
+            let width = f32::from(event.event.position.x) - start;
+            // Saved once the drag ends, see `FileList`
+            cx.update_global::<ConfigManager, _>(|manager, cx| {
+                manager.config.ui.resize_details_column(index, width);
+                cx.refresh_windows();
+            });
+        })
+        .children(cells)
+        .child(div().flex_grow())
+        .child(
+            div()
+                .id("details_columns_btn")
+                .flex_none()
+                .p_1()
+                .rounded_full()
+                .cursor_pointer()
+                .hover(|s| s.bg(palette.surface_container_highest))
+                .on_click(on_choose_columns)
+                .child(
+                    crate::assets::icons::icon("settings")
+                        .size_4()
+                        .text_color(palette.on_surface_variant),
+                ),
+        )
+}
+
+/// Chips to show or hide columns; the name column is always shown.
+pub fn render_column_chooser(
+    columns: &[DetailsColumn],
+    palette: &M3Palette,
+    workspace: &Entity<Workspace>,
+) -> Div {
+    div()
+        .absolute()
+        .top(px(36.0))
+        .right_2()
+        .w(px(320.0))
+        .p_3()
+        .flex()
+        .flex_col()
+        .gap_2()
+        .rounded_lg()
+        .bg(palette.surface_container_high)
+        .border_1()
+        .border_color(palette.outline_variant)
+        .shadow_lg()
+        .text_color(palette.on_surface)
+        .child(
+            div()
+                .text_sm()
+                .font_weight(FontWeight::SEMIBOLD)
+                .child("Columns"),
+        )
+        .child(
+            div().flex().flex_wrap().gap_2().children(
+                DetailsColumn::AVAILABLE
+                    .iter()
+                    .filter(|key| **key != SortKey::Name)
+                    .map(|&key| {
+                        let workspace = workspace.clone();
+                        Chip::new(
+                            SharedString::from(format!("details_column_{}", key.label())),
+                            key.label(),
+                        )
+                        .filter()
+                        .selected(columns.iter().any(|c| c.key == key))
+                        .on_click(move |_, _, cx| {
+                            cx.update_global::<ConfigManager, _>(|manager, cx| {
+                                manager.config.ui.toggle_details_column(key);
+                                manager.save_config();
+                                cx.refresh_windows();
+                            });
+                            workspace.update(cx, |ws, cx| {
+                                ws.load_child_counts(cx);
+                                ws.load_media_summaries(cx);
+                            });
+                        })
+                    }),
+            ),
+        )
+}
@@ -0,0 +1,232 @@
+// Dragging entries onto folders, sidebar places, breadcrumb segments, tabs
+// and the other pane, and dropping files from other applications.
+//
+// Drags inside the window carry a `DraggedFiles`; files dragged in from
+// other applications arrive as gpui's `ExternalPaths`, which the platform
+// layer reads from their text/uri-list.
+//
+// Not done yet: dragging entries out to other applications as file URIs.
+// gpui's X11 and Wayland backends only receive drags and can't start one,
+// so this waits on a drag source in gpui. Copy Path is the stand-in.
+use crate::app_state::workspace::Workspace;
+use crate::fs_ops::provider::FileEntry;
+use crate::theme_engine::theme::ThemeContext;
+use gpui::prelude::*;
+use gpui::*;
+use std::path::{Path, PathBuf};
+
+/// What a drop does with the dragged entries
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropOp {
+    Move,
+    Copy,
+    Link,
+}
+
+impl DropOp {
+    /// Ctrl copies, Ctrl+Shift or Alt links and Shift moves, as in other
+    /// file managers; without modifiers it's `default`.
+    pub fn from_modifiers(modifiers: &Modifiers, default: DropOp) -> Self {
+        if modifiers.alt || (modifiers.control && modifiers.shift) {
+            DropOp::Link
+        } else if modifiers.control {
+            DropOp::Copy
+        } else if modifiers.shift {
+            DropOp::Move
+        } else {
+            default
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DropOp::Move => "Move",
+            DropOp::Copy => "Copy",
+            DropOp::Link => "Link",
+        }
+    }
+}
+
+/// Entries being dragged from a workspace's file view.
+#[derive(Clone)]
+pub struct DraggedFiles {
+    /// Entry the drag started on
+    pub path: PathBuf,
+    /// Workspace the drag started in; it carries out the drop
+    pub workspace: Entity<Workspace>,
+}
+
+impl DraggedFiles {
+    /// Dragging a selected entry takes the rest of the selection along
+    pub fn paths(&self, cx: &App) -> Vec<PathBuf> {
+        let selection = &self.workspace.read(cx).selection;
+        if selection.contains(&self.path) {
+            let mut paths: Vec<_> = selection.iter().cloned().collect();
+            paths.sort();
+            paths
+        } else {
+            vec![self.path.clone()]
+        }
+    }
+}
+
+impl Render for DraggedFiles {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let palette = cx.theme().palette.clone();
+        let paths = self.paths(cx);
+        let (icon, label) = match paths.as_slice() {
+            [path] => (
+                if path.is_dir() { "folder" } else { "file" },
+                path.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            ),
+            _ => ("file", format!("{} items", paths.len())),
+        };
+        let op = DropOp::from_modifiers(&window.modifiers(), DropOp::Move);
+
+        div()
+            .flex()
+            .items_center()
+            .gap_2()
+            .px_3()
+            .py_1()
+            .rounded_lg()
+            .shadow_md()
+            .bg(palette.secondary_container)
+            .text_color(palette.on_secondary_container)
+            .text_sm()
+            .child(crate::assets::icons::icon(icon).size_4())
+            .child(label)
+            .when(op != DropOp::Move, |d| {
+                d.child(div().text_xs().opacity(0.8).child(op.label()))
+            })
+    }
+}
+
+/// Whether `paths` can be dropped into `target_dir`: not into themselves
+/// or their own subfolders, and not all back where they already are.
+pub fn can_drop(paths: &[PathBuf], target_dir: &Path) -> bool {
+    !paths.is_empty()
+        && paths.iter().all(|path| !target_dir.starts_with(path))
+        && paths.iter().any(|path| path.parent() != Some(target_dir))
+}
+
+/// Makes an entry draggable and, for folders, a place to drop others.
+pub(crate) fn with_drag_and_drop(
+    element: Stateful<Div>,
+    item: &FileEntry,
+    highlight: Hsla,
+    workspace: &Entity<Workspace>,
+) -> Stateful<Div> {
+    let dragged = DraggedFiles {
+        path: item.path.clone(),
+        workspace: workspace.clone(),
+    };
+    let element = element.on_drag(dragged, |dragged, _offset, _window, cx| {
+        cx.new(|_| dragged.clone())
+    });
+    if !item.is_dir {
+        return element;
+    }
+    let element = accept_dragged_files(element, item.path.clone(), highlight);
+    accept_external_paths(element, item.path.clone(), highlight, workspace)
+}
+
+/// Moves (or copies or links) entries dragged inside the window into
+/// `target_dir` when they are dropped on `element`.
+pub(crate) fn accept_dragged_files<E: InteractiveElement>(
+    element: E,
+    target_dir: PathBuf,
+    highlight: Hsla,
+) -> E {
+    let target = target_dir.clone();
+    element
+        .can_drop(
+            move |value, _window, cx| match value.downcast_ref::<DraggedFiles>() {
+                Some(dragged) => can_drop(&dragged.paths(cx), &target),
+                None => true,
+            },
+        )
+        .drag_over::<DraggedFiles>(move |style, _, _, _| style.bg(highlight))
+        .on_drop(move |dragged: &DraggedFiles, window, cx| {
+            let paths = dragged.paths(cx);
+            let op = DropOp::from_modifiers(&window.modifiers(), DropOp::Move);
+            let target = target_dir.clone();
+            dragged
+                .workspace
+                .update(cx, |ws, cx| ws.drop_paths(paths, target, op, cx));
+        })
+}
+
+/// Copies files dropped from other applications into `target_dir`.
+pub(crate) fn accept_external_paths<E: InteractiveElement>(
+    element: E,
+    target_dir: PathBuf,
+    highlight: Hsla,
+    workspace: &Entity<Workspace>,
+) -> E {
+    let workspace = workspace.clone();
+    element
+        .drag_over::<ExternalPaths>(move |style, _, _, _| style.bg(highlight))
+        .on_drop(move |external: &ExternalPaths, window, cx| {
+            let paths = external.paths().to_vec();
+            let op = DropOp::from_modifiers(&window.modifiers(), DropOp::Copy);
+            let target = target_dir.clone();
+            workspace.update(cx, |ws, cx| ws.drop_paths(paths, target, op, cx));
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    // Not `super::*`: gpui's glob export would shadow `#[test]`
+    use super::{can_drop, DropOp};
+    use gpui::Modifiers;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_drop_op_from_modifiers() {
+        let plain = Modifiers::default();
+        let ctrl = Modifiers {
+            control: true,
+            ..Default::default()
+        };
+        let ctrl_shift = Modifiers {
+            control: true,
+            shift: true,
+            ..Default::default()
+        };
+        let alt = Modifiers {
+            alt: true,
+            ..Default::default()
+        };
+        let shift = Modifiers {
+            shift: true,
+            ..Default::default()
+        };
+        assert_eq!(DropOp::from_modifiers(&plain, DropOp::Move), DropOp::Move);
+        assert_eq!(DropOp::from_modifiers(&plain, DropOp::Copy), DropOp::Copy);
+        assert_eq!(DropOp::from_modifiers(&ctrl, DropOp::Move), DropOp::Copy);
+        assert_eq!(
+            DropOp::from_modifiers(&ctrl_shift, DropOp::Move),
+            DropOp::Link
+        );
+        assert_eq!(DropOp::from_modifiers(&alt, DropOp::Move), DropOp::Link);
+        assert_eq!(DropOp::from_modifiers(&shift, DropOp::Copy), DropOp::Move);
+    }
+
+    #[test]
+    fn test_can_drop() {
+        let paths = vec![
+            PathBuf::from("/home/a/docs"),
+            PathBuf::from("/home/a/x.txt"),
+        ];
+        assert!(can_drop(&paths, Path::new("/home/b")));
+        // Into itself or below it
+        assert!(!can_drop(&paths, Path::new("/home/a/docs")));
+        assert!(!can_drop(&paths, Path::new("/home/a/docs/sub")));
+        // Back into the folder they're in
+        assert!(!can_drop(&paths, Path::new("/home/a")));
+        assert!(!can_drop(&[], Path::new("/home/b")));
+    }
+}
@@ -1,80 +1,374 @@
-use crate::app_state::config::ConfigManager;
-use crate::app_state::workspace::Workspace;
+use crate::app_state::config::{ConfigManager, TagDef};
+use crate::app_state::keybindings;
+use crate::app_state::workspace::{RenameState, Workspace};
+use crate::fs_ops::git_status::{GitFileStatus, GitStatus};
+use crate::fs_ops::provider::FileEntry;
+use crate::fs_ops::sort::{SortKey, SortOptions};
+use crate::theme_engine::palette::M3Palette;
 use crate::theme_engine::theme::ThemeContext;
-use fuzzy_matcher::skim::SkimMatcherV2;
-use fuzzy_matcher::FuzzyMatcher;
 use gpui::prelude::*;
 use gpui::*;
+use std::cell::RefCell;
 use std::collections::HashSet;
 use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use crate::assets::icon_cache::IconCache;
+use crate::assets::thumbnail_cache::ThumbnailSize;
+use crate::assets::thumbnail_pool::{ThumbnailKey, ThumbnailPool};
+use crate::assets::thumbnail_worker::ThumbnailWorker;
+use crate::ui_components::chips::Chip;
+use crate::ui_components::column_view;
+use crate::ui_components::details_view::{self, ColumnResize};
+use crate::ui_components::drag_drop::{
+    accept_dragged_files, accept_external_paths, with_drag_and_drop,
+};
 use crate::ui_components::loader::ShapeShifterLoader;
+use crate::ui_components::marquee::{self, ItemLayout, Marquee, MarqueeMode};
 
 pub struct FileList {
     workspace: Entity<Workspace>,
     icon_cache: Entity<IconCache>,
+    thumbnail_pool: Entity<ThumbnailPool>,
     focus_handle: FocusHandle,
-    _subscription: Subscription,
+    _subscriptions: Vec<Subscription>,
     collapsed_categories: std::collections::HashSet<String>,
-    pending_thumbnails: HashSet<PathBuf>,
     loader: Entity<ShapeShifterLoader>,
+    /// Details view column chooser
+    show_column_menu: bool,
+    /// Column view: horizontal scroll, and the column count it was last
+    /// scrolled for so new columns are brought into view
+    column_scroll: ScrollHandle,
+    scrolled_columns: usize,
+    list_scroll: UniformListScrollHandle,
+    /// Items per grid row as last rendered, for up/down movement
+    grid_cols: usize,
+    /// List width the grid rows were laid out for
+    list_width: Pixels,
+    /// Where entries sit in the list as last rendered, for the rubber band
+    item_layout: Option<ItemLayout>,
+    /// The grouped view's scroll position, and where each group's entries
+    /// were laid out in its content, for the rubber band
+    grouped_scroll: ScrollHandle,
+    group_bounds: Rc<RefCell<Vec<Vec<Bounds<Pixels>>>>>,
+    /// Rubber-band selection being dragged out over empty space
+    marquee: Option<Marquee>,
+    /// Scrolls the list while the band is held near its edges
+    _marquee_scroll: Option<Task<()>>,
+    /// Type-ahead find: letters typed so far and when the last one was
+    type_ahead: String,
+    type_ahead_at: Option<Instant>,
+}
+
+/// Pause after which type-ahead starts a new search
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_secs(1);
+/// Space around each grid item
+const GRID_ITEM_MARGIN: f32 = 4.0;
+/// Rubber-band auto-scroll interval
+const AUTOSCROLL_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Per-render settings shared by every item in the list.
+#[derive(Clone)]
+pub(crate) struct ItemStyle {
+    pub(crate) palette: M3Palette,
+    /// Render entries excluded by .gitignore/.ignore at reduced opacity
+    pub(crate) dim_ignored: bool,
+    /// Configured tags, for chip colors
+    pub(crate) tag_defs: Vec<TagDef>,
+    /// Status of the repository being browsed, for badges
+    pub(crate) git_status: Option<Arc<GitStatus>>,
+    /// Grid preview size in pixels (the directory's zoom level)
+    pub(crate) icon_size: f32,
+    /// Entry being renamed in place
+    pub(crate) rename: Option<RenameState>,
+}
+
+impl ItemStyle {
+    fn grid_item_width(&self) -> f32 {
+        self.icon_size + 56.0
+    }
+
+    /// Width taken by a grid item with its margins
+    fn grid_slot_width(&self) -> f32 {
+        self.grid_item_width() + 2.0 * GRID_ITEM_MARGIN
+    }
+
+    fn grid_row_height(&self) -> f32 {
+        self.icon_size + 66.0
+    }
+
+    /// The rename editor's state if `item` is being renamed
+    pub(crate) fn rename_for(&self, item: &FileEntry) -> Option<&RenameState> {
+        self.rename
+            .as_ref()
+            .filter(|rename| rename.path == item.path)
+    }
+
+    fn tag_color(&self, name: &str) -> Hsla {
+        self.tag_defs
+            .iter()
+            .find(|t| t.name == name)
+            .map(|t| Hsla::from(gpui::rgb(t.rgb())))
+            .unwrap_or_else(|| Hsla::from(self.palette.outline))
+    }
+}
+
+/// One-letter git status badge; folders show the roll-up of their contents.
+pub(crate) fn render_git_badge(item: &FileEntry, style: &ItemStyle) -> Option<Div> {
+    let status = style.git_status.as_ref()?.status_for(&item.path)?;
+    let palette = &style.palette;
+    let color = match status {
+        GitFileStatus::Conflicted => palette.error,
+        GitFileStatus::Modified => palette.tertiary,
+        GitFileStatus::Staged => palette.primary,
+        GitFileStatus::Untracked => palette.secondary,
+        GitFileStatus::Ignored => palette.outline,
+    };
+    Some(
+        div()
+            .flex_none()
+            .w(px(16.0))
+            .h(px(16.0))
+            .flex()
+            .items_center()
+            .justify_center()
+            .rounded_sm()
+            .border_1()
+            .border_color(color)
+            .bg(palette.surface)
+            .text_color(color)
+            .text_xs()
+            .font_weight(FontWeight::BOLD)
+            .child(status.label()),
+    )
+}
+
+pub(crate) type NameEdit = Box<dyn FnOnce(&mut RenameState)>;
+
+/// The edit a key makes to a name being typed: typing, pasting, deleting,
+/// moving the caret and selecting all. Enter, Escape and copying are up to
+/// the field.
+pub(crate) fn name_field_edit(
+    keystroke: &Keystroke,
+    cx: &App,
+) -> Option<NameEdit> {
+    let extend = keystroke.modifiers.shift;
+    let command = keystroke.modifiers.control || keystroke.modifiers.platform;
+    let edit: NameEdit = match keystroke.key.as_str() {
+        "v" if command => {
+            // Pasted text is a single line
+            let text = cx.read_from_clipboard().and_then(|item| item.text())?;
+            let line = text.lines().next().unwrap_or_default().to_string();
+            Box::new(move |r| r.insert(&line))
+        }
+        "a" if command => Box::new(|r| r.select_all()),
+        "backspace" => Box::new(|r| r.backspace()),
+        "delete" => Box::new(|r| r.delete()),
+        "left" => Box::new(move |r| r.move_left(extend)),
+        "right" => Box::new(move |r| r.move_right(extend)),
+        "home" => Box::new(move |r| r.move_to(0, extend)),
+        "end" => Box::new(move |r| r.move_to(r.text.len(), extend)),
+        _ => {
+            let typed = keystroke
+                .key_char
+                .clone()
+                .filter(|text| !text.chars().any(char::is_control))
+                .filter(|_| !command && !keystroke.modifiers.alt)?;
+            Box::new(move |r| r.insert(&typed))
+        }
+    };
+    Some(edit)
+}
+
+/// Copies the field's selected text, if any
+pub(crate) fn copy_name_selection(field: &RenameState, cx: &mut App) {
+    let selected = field.selected_text();
+    if !selected.is_empty() {
+        cx.write_to_clipboard(ClipboardItem::new_string(selected.to_string()));
+    }
+}
+
+/// In-place name editor showing the selection or caret, with the reason
+/// the name can't be used in a bubble underneath.
+pub(crate) fn render_rename_field(rename: &RenameState, palette: &M3Palette) -> Div {
+    let range = rename.selection();
+    let text = &rename.text;
+    let border = if rename.error.is_some() {
+        palette.error
+    } else {
+        palette.primary
+    };
+    div()
+        .relative()
+        .flex()
+        .items_center()
+        .min_w_0()
+        .px_1()
+        .rounded_sm()
+        .border_1()
+        .border_color(border)
+        .bg(palette.surface)
+        .text_color(palette.on_surface)
+        .whitespace_nowrap()
+        .overflow_hidden()
+        .child(text[..range.start].to_string())
+        .map(|field| {
+            if range.is_empty() {
+                field.child(div().flex_none().w(px(1.0)).h_4().bg(palette.primary))
+            } else {
+                field.child(
+                    div()
+                        .flex_none()
+                        .bg(palette.primary)
+                        .text_color(palette.on_primary)
+                        .child(text[range.clone()].to_string()),
+                )
+            }
+        })
+        .child(text[range.end..].to_string())
+        .children(rename.error.clone().map(|error| {
+            deferred(
+                div()
+                    .absolute()
+                    .top_full()
+                    .left_0()
+                    .mt_1()
+                    .w_56()
+                    .px_2()
+                    .py_1()
+                    .rounded_md()
+                    .shadow_md()
+                    .bg(palette.error_container)
+                    .text_color(palette.on_error_container)
+                    .text_xs()
+                    .whitespace_normal()
+                    .child(error),
+            )
+            .with_priority(1)
+        }))
+}
+
+/// An item's name, or the rename editor while it is being renamed.
+pub(crate) fn render_name(item: &FileEntry, style: &ItemStyle) -> Div {
+    match style.rename_for(item) {
+        Some(rename) => render_rename_field(rename, &style.palette),
+        None => div().text_ellipsis().child(item.name.clone()),
+    }
+}
+
+/// Small colored chips for the first `limit` tags of an item.
+pub(crate) fn render_tag_chips(item: &FileEntry, style: &ItemStyle, limit: usize) -> Div {
+    div()
+        .flex()
+        .gap_1()
+        .overflow_hidden()
+        .children(item.tags.iter().take(limit).map(|tag| {
+            Chip::new(
+                SharedString::from(format!("tag-{}-{}", item.path.display(), tag)),
+                tag.clone(),
+            )
+            .dense()
+            .color(style.tag_color(tag))
+        }))
 }
 
 impl FileList {
     pub fn new(
         workspace: Entity<Workspace>,
         icon_cache: Entity<IconCache>,
+        thumbnail_pool: Entity<ThumbnailPool>,
         cx: &mut Context<Self>,
     ) -> Self {
-        let subscription = cx.observe(&icon_cache, |_, _, cx| cx.notify());
+        let subscriptions = vec![
+            cx.observe(&icon_cache, |_, _, cx| cx.notify()),
+            cx.observe(&thumbnail_pool, |_, _, cx| cx.notify()),
+        ];
         let loader = cx.new(|cx| ShapeShifterLoader::new(cx));
         Self {
             workspace,
             icon_cache,
+            thumbnail_pool,
             focus_handle: cx.focus_handle(),
-            _subscription: subscription,
+            _subscriptions: subscriptions,
             collapsed_categories: std::collections::HashSet::new(),
-            pending_thumbnails: HashSet::new(),
             loader,
+            show_column_menu: false,
+            column_scroll: ScrollHandle::new(),
+            scrolled_columns: 0,
+            list_scroll: UniformListScrollHandle::new(),
+            grid_cols: 1,
+            list_width: px(0.0),
+            item_layout: None,
+            grouped_scroll: ScrollHandle::new(),
+            group_bounds: Rc::default(),
+            marquee: None,
+            _marquee_scroll: None,
+            type_ahead: String::new(),
+            type_ahead_at: None,
         }
     }
 
     fn render_grouped_view(
-        &self,
-        grouped_files: std::collections::HashMap<String, Vec<crate::fs_ops::provider::FileEntry>>,
+        &mut self,
+        grouped_files: std::collections::HashMap<String, Vec<FileEntry>>,
         selection: HashSet<PathBuf>,
-        palette: &crate::theme_engine::palette::M3Palette,
+        style: &ItemStyle,
         is_grid: bool,
         cx: &mut Context<Self>,
     ) -> AnyElement {
-        let mut groups: Vec<_> = grouped_files.keys().cloned().collect();
-        groups.sort_by(|a, b| {
-            if *a == "Folders" {
-                std::cmp::Ordering::Less
-            } else if *b == "Folders" {
-                std::cmp::Ordering::Greater
-            } else if *a == "Other" {
-                std::cmp::Ordering::Greater
-            } else if *b == "Other" {
-                std::cmp::Ordering::Less
-            } else {
-                a.cmp(b)
-            }
-        });
+        let groups = self.workspace.read(cx).group_names();
+        let (bounds, offset) = self.list_geometry(cx);
+        self.list_width = bounds.size.width;
+        *self.group_bounds.borrow_mut() = vec![Vec::new(); groups.len()];
 
-        let file_list_entity = cx.entity().clone();
-        let workspace_handle = self.workspace.clone();
+        // One request for every group, as each request replaces this
+        // view's earlier ones
+        let mut thumbnails = if is_grid {
+            let items: Vec<&FileEntry> = groups
+                .iter()
+                .flat_map(|group_name| &grouped_files[group_name])
+                .collect();
+            request_thumbnails(&self.thumbnail_pool, cx.entity_id(), &items, style, cx)
+        } else {
+            Vec::new()
+        }
+        .into_iter();
 
-        div()
-            .flex()
-            .flex_col()
-            .size_full()
-            // .overflow_y_scroll() // Removed due to compilation error
-            .children(groups.into_iter().map(|group_name| {
-                let items = &grouped_files[&group_name];
-                let item_count = items.len();
+        let palette = &style.palette;
+        let mut sections = Vec::new();
+        for (group_ix, group_name) in groups.into_iter().enumerate() {
+            let items = &grouped_files[&group_name];
 
+            let mut children = Vec::new();
+            for (idx, item) in items.iter().enumerate() {
+                let id = ElementId::Name(format!("{}-{}", group_name, idx).into());
+                let is_selected = selection.contains(&item.path);
+                children.push(if is_grid {
+                    let thumbnail = thumbnails.next().flatten();
+                    render_grid_item(item, id, is_selected, thumbnail, style, &self.workspace)
+                } else {
+                    render_list_item(item, id, is_selected, None, style, &self.workspace)
+                });
+            }
+
+            let body = if is_grid {
+                div().flex().flex_wrap().children(children)
+            } else {
+                div().flex().flex_col().children(children)
+            };
+            let group_bounds = self.group_bounds.clone();
+            let scroll = self.grouped_scroll.clone();
+            let body = body.on_children_prepainted(move |items, _window, _cx| {
+                let (origin, offset) = (scroll.bounds().origin, scroll.offset());
+                group_bounds.borrow_mut()[group_ix] = items
+                    .into_iter()
+                    .map(|item| Bounds::new(item.origin - origin - offset, item.size))
+                    .collect();
+            });
+
+            sections.push(
                 div()
                     .flex()
                     .flex_col()
@@ -85,278 +379,864 @@ impl FileList {
                             .p_2()
                             .font_weight(FontWeight::BOLD)
                             .text_color(palette.primary)
-                            .child(format!("{} ({})", group_name, item_count)),
+                            .child(format!("{} ({})", group_name, items.len())),
                     )
-                    .child(
-                        if is_grid {
-                            div()
-                                .flex()
-                                .flex_wrap()
-                                .children(items.iter().enumerate().map(|(idx, item)| {
-                                    // INLINE RENDER GRID ITEM
-                                    let is_selected = selection.contains(&item.path);
-                                    let item_path = item.path.clone();
-                                    let is_dir = item.is_dir;
-
-                                    let bg_color = if is_selected {
-                                        Hsla::from(palette.secondary_container)
-                                    } else {
-                                        gpui::hsla(0., 0., 0., 0.)
-                                    };
-                                    let text_color = if is_selected {
-                                        palette.on_secondary_container
-                                    } else {
-                                        palette.on_surface
-                                    };
-
-                                    let ext = item_path
-                                        .extension()
-                                        .and_then(|e: &std::ffi::OsStr| e.to_str())
-                                        .unwrap_or("")
-                                        .to_lowercase();
-
-                                    let is_image = !is_dir && matches!(ext.as_str(), "png" | "jpg" | "jpeg" | "webp");
-
-                                    let thumbnail_path = if is_image {
-                                        crate::assets::thumbnail_worker::ThumbnailWorker::get_cached_path(&item_path)
-                                    } else {
-                                        None
-                                    };
-
-                                    if is_image && thumbnail_path.is_none() {
-                                        let is_pending = file_list_entity
-                                            .read(cx)
-                                            .pending_thumbnails
-                                            .contains(&item_path);
-                                        if !is_pending {
-                                            let path_for_task = item_path.clone();
-                                            cx.background_executor()
-                                                .spawn(async move {
-                                                    crate::assets::thumbnail_worker::ThumbnailWorker::generate_thumbnail(
-                                                        path_for_task.clone(),
-                                                    );
-                                                })
-                                                .detach();
-                                        }
-                                    }
-                                    let icon_name = if is_dir {
-                                        "folder"
-                                    } else {
-                                        match ext.as_str() {
-                                            "png" | "jpg" | "jpeg" | "webp" => "image",
-                                            "mp4" | "mkv" | "webm" => "video",
-                                            "mp3" | "wav" | "ogg" => "audio",
-                                            _ => "file",
-                                        }
-                                    };
-
-                                    let ws_click = workspace_handle.clone();
-                                    let path_click = item_path.clone();
-                                    let ws_dbl = workspace_handle.clone();
-                                    let path_dbl = item_path.clone();
-                                    let ws_right = workspace_handle.clone();
-                                    let path_right = item_path.clone();
-
-                                    div()
-                                        .id(idx)
-                                        .w(px(120.0))
-                                        .h_full()
-                                        .flex()
-                                        .flex_col()
-                                        .items_center()
-                                        .p_2()
-                                        .m_1()
-                                        .rounded_md()
-                                        .bg(bg_color)
-                                        .text_color(text_color)
-                                        .hover(|s| s.bg(palette.surface_container_highest))
-                                        .on_click(move |event, _, cx| {
-                                            if event.click_count() >= 2 {
-                                                ws_dbl.update(cx, |ws, cx| {
-                                                    ws.open(path_dbl.clone(), cx);
-                                                });
-                                            }
-                                        })
-                                        .on_mouse_down(MouseButton::Left, move |event, _, cx| {
-                                            cx.stop_propagation();
-                                            ws_click.update(cx, |ws, cx| {
-                                                if event.modifiers.control {
-                                                    ws.toggle_selection(path_click.clone(), cx);
-                                                } else if event.modifiers.shift {
-                                                    ws.select_range(path_click.clone(), cx);
-                                                } else {
-                                                    ws.set_selection(path_click.clone(), cx);
-                                                }
-                                            });
-                                        })
-                                        .on_mouse_down(MouseButton::Right, move |event, _, cx| {
-                                            cx.stop_propagation();
-                                            ws_right.update(cx, |ws, cx| {
-                                                if !ws.selection.contains(&path_right) {
-                                                    ws.set_selection(path_right.clone(), cx);
-                                                }
-                                                ws.open_context_menu(event.position, Some(path_right.clone()), cx);
-                                            });
-                                        })
-                                        .child(if let Some(thumb) = thumbnail_path {
-                                            let path_str = format!("file://{}", thumb.to_string_lossy());
-                                            div()
-                                                .flex()
-                                                .items_center()
-                                                .justify_center()
-                                                .w_full()
-                                                .h(px(64.0))
-                                                .child(
-                                                    img(path_str)
-                                                        .w(px(64.0))
-                                                        .h(px(64.0))
-                                                        .object_fit(ObjectFit::Cover)
-                                                        .rounded_md(),
-                                                )
-                                        } else {
-                                            div()
-                                                .flex()
-                                                .items_center()
-                                                .justify_center()
-                                                .w_full()
-                                                .h(px(64.0))
-                                                .child(crate::assets::icons::icon(icon_name).size_12())
-                                        })
-                                        .child(
-                                            div()
-                                                .mt_2()
-                                                .text_sm()
-                                                .text_center()
-                                                .text_ellipsis()
-                                                .max_w_full()
-                                                .child(item.name.clone()),
-                                        )
-                                        .into_any_element()
-                                }))
-                        } else {
-                             div()
-                                .flex()
-                                .flex_col()
-                                .children(items.iter().enumerate().map(|(idx, item)| {
-                                    // INLINE RENDER LIST ITEM
-                                    let is_selected = selection.contains(&item.path);
-                                    let item_path = item.path.clone();
-                                    let is_dir = item.is_dir;
-
-                                    let bg_color = if is_selected {
-                                        Hsla::from(palette.secondary_container)
-                                    } else {
-                                        gpui::hsla(0., 0., 0., 0.)
-                                    };
-                                    let text_color = if is_selected {
-                                        palette.on_secondary_container
-                                    } else {
-                                        palette.on_surface
-                                    };
-                                    let sub_text_color = if is_selected {
-                                        palette.on_secondary_container
-                                    } else {
-                                        palette.on_surface_variant
-                                    };
-                                    let ext = item_path
-                                        .extension()
-                                        .and_then(|e: &std::ffi::OsStr| e.to_str())
-                                        .unwrap_or("")
-                                        .to_lowercase();
-
-                                    let icon_name = if is_dir {
-                                        "folder"
-                                    } else {
-                                        match ext.as_str() {
-                                            "png" | "jpg" | "jpeg" | "webp" => "image",
-                                            "mp4" | "mkv" | "webm" => "video",
-                                            "mp3" | "wav" | "ogg" => "audio",
-                                            _ => "file",
-                                        }
-                                    };
-
-                                    let ws_click = workspace_handle.clone();
-                                    let path_click = item_path.clone();
-                                    let ws_dbl = workspace_handle.clone();
-                                    let path_dbl = item_path.clone();
-                                    let ws_right = workspace_handle.clone();
-                                    let path_right = item_path.clone();
-
-                                    div()
-                                        .id(idx)
-                                        .h_10()
-                                        .flex()
-                                        .items_center()
-                                        .w_full()
-                                        .px_3()
-                                        .border_b_1()
-                                        .border_color(Hsla::from(palette.outline_variant).opacity(0.1))
-                                        .bg(bg_color)
-                                        .text_color(text_color)
-                                        .hover(|s| s.bg(palette.surface_container_highest))
-                                        .on_click(move |event, _, cx| {
-                                            if event.click_count() >= 2 {
-                                                ws_dbl.update(cx, |ws, cx| {
-                                                    ws.open(path_dbl.clone(), cx);
-                                                });
-                                            }
-                                        })
-                                        .on_mouse_down(MouseButton::Left, move |event, _, cx| {
-                                            cx.stop_propagation();
-                                            ws_click.update(cx, |ws, cx| {
-                                                if event.modifiers.control {
-                                                    ws.toggle_selection(path_click.clone(), cx);
-                                                } else if event.modifiers.shift {
-                                                    ws.select_range(path_click.clone(), cx);
-                                                } else {
-                                                    ws.set_selection(path_click.clone(), cx);
-                                                }
-                                            });
-                                        })
-                                        .on_mouse_down(MouseButton::Right, move |event, _, cx| {
-                                            cx.stop_propagation();
-                                            ws_right.update(cx, |ws, cx| {
-                                                if !ws.selection.contains(&path_right) {
-                                                    ws.set_selection(path_right.clone(), cx);
-                                                }
-                                                ws.open_context_menu(event.position, Some(path_right.clone()), cx);
-                                            });
-                                        })
-                                        .child(
-                                            div()
-                                                .w(px(24.0))
-                                                .flex()
-                                                .justify_center()
-                                                .child(crate::assets::icons::icon(icon_name).size_5()),
-                                        )
-                                        .child(
-                                            div()
-                                                .ml_3()
-                                                .flex_grow()
-                                                .min_w_0()
-                                                .child(div().text_ellipsis().child(item.name.clone())),
-                                        )
-                                        .child(
-                                            div()
-                                                .w_24()
-                                                .text_sm()
-                                                .text_color(sub_text_color)
-                                                .child(item.formatted_date.clone()),
-                                        )
-                                        .child(
-                                            div()
-                                                .w_20()
-                                                .text_sm()
-                                                .text_right()
-                                                .text_color(sub_text_color)
-                                                .child(item.formatted_size.clone()),
-                                        )
-                                        .into_any_element()
-                                }))
+                    .child(body),
+            );
+        }
+
+        let grouped = div()
+            .id("file_list_grouped")
+            .flex()
+            .flex_col()
+            .size_full()
+            .overflow_y_scroll()
+            .track_scroll(&self.grouped_scroll)
+            .children(sections);
+        let band = self.marquee.as_ref().map(|marquee| {
+            marquee::render_band(
+                marquee.start + offset,
+                marquee.pointer - bounds.origin,
+                palette.primary.into(),
+            )
+        });
+        div()
+            .relative()
+            .size_full()
+            // Presses that get here missed every item
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|this, event: &MouseDownEvent, _, cx| this.begin_marquee(event, cx)),
+            )
+            .child(self.accept_drops(grouped, cx))
+            .child(self.render_marquee_tracker(cx))
+            .children(band)
+            .into_any_element()
+    }
+}
+
+pub(crate) fn icon_name_for(item: &FileEntry) -> &'static str {
+    if item.is_dir {
+        return "folder";
+    }
+    let ext = item
+        .path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match ext.as_str() {
+        "png" | "jpg" | "jpeg" | "webp" => "image",
+        "mp4" | "mkv" | "webm" => "video",
+        "mp3" | "wav" | "ogg" => "audio",
+        _ => "file",
+    }
+}
+
+/// Attaches the shared click / selection / context menu / drag and drop
+/// handlers to an item.
+pub(crate) fn with_item_handlers(
+    element: Stateful<Div>,
+    item: &FileEntry,
+    style: &ItemStyle,
+    workspace: &Entity<Workspace>,
+) -> Stateful<Div> {
+    let path = item.path.as_path();
+    let ws_click = workspace.clone();
+    let path_click = path.to_path_buf();
+    let ws_release = workspace.clone();
+    let path_release = path.to_path_buf();
+    let ws_right = workspace.clone();
+    let path_right = path.to_path_buf();
+
+    let drop_highlight = Hsla::from(style.palette.primary_container);
+    with_drag_and_drop(element, item, drop_highlight, workspace)
+        .on_click(move |event, _, cx| {
+            ws_release.update(cx, |ws, cx| {
+                ws.click_released(&path_release, event.click_count(), cx);
+                if event.click_count() >= 2 {
+                    ws.open(path_release.clone(), cx);
+                }
+            });
+        })
+        .on_mouse_down(MouseButton::Left, move |event, _, cx| {
+            cx.stop_propagation();
+            ws_click.update(cx, |ws, cx| {
+                // Clicks inside the editor leave it alone
+                if ws.renaming.as_ref().is_some_and(|r| r.path == path_click) {
+                    return;
+                }
+                if ws.renaming.is_some() {
+                    ws.commit_rename(cx);
+                }
+                if event.modifiers.control {
+                    ws.toggle_selection(path_click.clone(), cx);
+                } else if event.modifiers.shift {
+                    ws.select_range(path_click.clone(), cx);
+                } else {
+                    ws.arm_click_rename(&path_click);
+                    ws.set_selection(path_click.clone(), cx);
+                }
+            });
+        })
+        .on_mouse_down(MouseButton::Right, move |event, _, cx| {
+            cx.stop_propagation();
+            ws_right.update(cx, |ws, cx| {
+                if !ws.selection.contains(&path_right) {
+                    ws.set_selection(path_right.clone(), cx);
+                }
+                ws.open_context_menu(event.position, Some(path_right.clone()), cx);
+            });
+        })
+}
+
+/// Thumbnails known for `items`, in order, for the grid. The pool is asked
+/// for the rest, ahead of anything queued for rows no longer shown.
+fn request_thumbnails(
+    pool: &Entity<ThumbnailPool>,
+    owner: EntityId,
+    items: &[&FileEntry],
+    style: &ItemStyle,
+    cx: &mut App,
+) -> Vec<Option<PathBuf>> {
+    let size = ThumbnailSize::for_pixels(style.icon_size);
+    let (indices, keys): (Vec<usize>, Vec<ThumbnailKey>) = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| !item.is_dir && ThumbnailWorker::can_thumbnail(&item.path))
+        .map(|(ix, item)| {
+            let key = ThumbnailKey {
+                path: item.path.clone(),
+                size,
+                modified: item.modified,
+            };
+            (ix, key)
+        })
+        .unzip();
+    let found = pool.update(cx, |pool, cx| pool.request(owner.as_u64(), &keys, cx));
+
+    let mut thumbnails = vec![None; items.len()];
+    for (ix, thumbnail) in indices.into_iter().zip(found) {
+        thumbnails[ix] = thumbnail;
+    }
+    thumbnails
+}
+
+fn render_grid_item(
+    item: &FileEntry,
+    id: ElementId,
+    is_selected: bool,
+    thumbnail: Option<PathBuf>,
+    style: &ItemStyle,
+    workspace: &Entity<Workspace>,
+) -> AnyElement {
+    let palette = &style.palette;
+    let bg_color = if is_selected {
+        Hsla::from(palette.secondary_container)
+    } else {
+        gpui::hsla(0., 0., 0., 0.)
+    };
+    let text_color = if is_selected {
+        palette.on_secondary_container
+    } else {
+        palette.on_surface
+    };
+
+    let icon_name = icon_name_for(item);
+
+    let element = div()
+        .id(id)
+        .w(px(style.grid_item_width()))
+        .h_full()
+        .flex()
+        .flex_col()
+        .items_center()
+        .p_2()
+        .m(px(GRID_ITEM_MARGIN))
+        .rounded_md()
+        .bg(bg_color)
+        .text_color(text_color)
+        .when(style.dim_ignored && item.is_ignored, |d| d.opacity(0.5))
+        .hover(|s| s.bg(palette.surface_container_highest));
+
+    let preview = if let Some(thumb) = thumbnail {
+        let path_str = format!("file://{}", thumb.to_string_lossy());
+        div()
+            .flex()
+            .items_center()
+            .justify_center()
+            .w_full()
+            .h(px(style.icon_size))
+            .child(
+                img(path_str)
+                    .w(px(style.icon_size))
+                    .h(px(style.icon_size))
+                    .object_fit(ObjectFit::Cover)
+                    .rounded_md(),
+            )
+    } else {
+        div()
+            .flex()
+            .items_center()
+            .justify_center()
+            .w_full()
+            .h(px(style.icon_size))
+            .child(crate::assets::icons::icon(icon_name).size(px(style.icon_size * 0.75)))
+    };
+
+    with_item_handlers(element, item, style, workspace)
+        .child(preview.relative().children(
+            render_git_badge(item, style).map(|badge| badge.absolute().top_0().right(px(16.0))),
+        ))
+        .child(
+            div()
+                .mt_2()
+                .text_sm()
+                .text_center()
+                .text_ellipsis()
+                .max_w_full()
+                .child(render_name(item, style)),
+        )
+        .when(!item.tags.is_empty(), |d| {
+            d.child(render_tag_chips(item, style, 2).mt_1().max_w_full())
+        })
+        .into_any_element()
+}
+
+/// Indentation and expand/collapse triangle of a tree row.
+fn render_disclosure(item: &FileEntry, is_expanded: bool, workspace: &Entity<Workspace>) -> Div {
+    let disclosure = div()
+        .flex_none()
+        .w(px(20.0))
+        .ml(px(item.depth as f32 * 16.0))
+        .flex()
+        .justify_center();
+    if !item.is_dir {
+        return disclosure;
+    }
+
+    let workspace = workspace.clone();
+    let path = item.path.clone();
+    let angle = if is_expanded {
+        std::f32::consts::FRAC_PI_2
+    } else {
+        0.0
+    };
+    disclosure
+        .cursor_pointer()
+        .on_mouse_down(MouseButton::Left, move |_, _, cx| {
+            cx.stop_propagation();
+            workspace.update(cx, |ws, cx| ws.toggle_expanded(path.clone(), cx));
+        })
+        .child(
+            crate::assets::icons::icon("chevron_right")
+                .size_4()
+                .with_transformation(Transformation::rotate(radians(angle))),
+        )
+}
+
+/// `expanded` is set for rows of the list view's tree: whether the row's
+/// folder is expanded.
+fn render_list_item(
+    item: &FileEntry,
+    id: ElementId,
+    is_selected: bool,
+    expanded: Option<bool>,
+    style: &ItemStyle,
+    workspace: &Entity<Workspace>,
+) -> AnyElement {
+    let palette = &style.palette;
+    let bg_color = if is_selected {
+        Hsla::from(palette.secondary_container)
+    } else {
+        gpui::hsla(0., 0., 0., 0.)
+    };
+    let text_color = if is_selected {
+        palette.on_secondary_container
+    } else {
+        palette.on_surface
+    };
+    let sub_text_color = if is_selected {
+        palette.on_secondary_container
+    } else {
+        palette.on_surface_variant
+    };
+
+    let element = div()
+        .id(id)
+        .h_10()
+        .flex()
+        .items_center()
+        .w_full()
+        .px_3()
+        .border_b_1()
+        .border_color(Hsla::from(palette.outline_variant).opacity(0.1))
+        .bg(bg_color)
+        .text_color(text_color)
+        .when(style.dim_ignored && item.is_ignored, |d| d.opacity(0.5))
+        .hover(|s| s.bg(palette.surface_container_highest));
+
+    with_item_handlers(element, item, style, workspace)
+        .when_some(expanded, |d, is_expanded| {
+            d.child(render_disclosure(item, is_expanded, workspace))
+        })
+        .child(
+            div()
+                .w(px(24.0))
+                .flex()
+                .justify_center()
+                .child(crate::assets::icons::icon(icon_name_for(item)).size_5()),
+        )
+        .child(
+            div()
+                .ml_3()
+                .flex_grow()
+                .min_w_0()
+                .child(render_name(item, style)),
+        )
+        .children(render_git_badge(item, style).map(|badge| badge.mr_3()))
+        .when(!item.tags.is_empty(), |d| {
+            d.child(render_tag_chips(item, style, 3).mr_3().flex_none())
+        })
+        .child(
+            div()
+                .w_24()
+                .text_sm()
+                .text_color(sub_text_color)
+                .child(item.formatted_date.clone()),
+        )
+        .child(
+            div()
+                .w_20()
+                .text_sm()
+                .text_right()
+                .text_color(sub_text_color)
+                .child(item.formatted_size.clone()),
+        )
+        .into_any_element()
+}
+
+/// Column headers for list mode; clicking one sorts by it.
+fn render_list_header(
+    sort: SortOptions,
+    tree: bool,
+    palette: &M3Palette,
+    workspace: &Entity<Workspace>,
+) -> Stateful<Div> {
+    let header = |key: SortKey| {
+        let workspace = workspace.clone();
+        let active = sort.key == key;
+        div()
+            .id(SharedString::from(format!("list_header_{}", key.label())))
+            .flex()
+            .items_center()
+            .gap_1()
+            .cursor_pointer()
+            .when(active, |d| d.text_color(palette.primary))
+            .hover(|s| s.text_color(palette.on_surface))
+            .on_click(move |_, _, cx| {
+                workspace.update(cx, |ws, cx| ws.set_sort(ws.view.sort.toggled(key), cx));
+            })
+            .child(key.label())
+            .when(active, |d| {
+                d.child(if sort.descending { "↓" } else { "↑" })
+            })
+    };
+
+    div()
+        .id("list_header")
+        .h_8()
+        .flex()
+        .flex_none()
+        .items_center()
+        .w_full()
+        .px_3()
+        .border_b_1()
+        .border_color(palette.outline_variant)
+        .text_xs()
+        .font_weight(FontWeight::SEMIBOLD)
+        .text_color(palette.on_surface_variant)
+        .when(tree, |d| d.child(div().flex_none().w(px(20.0))))
+        .child(div().w(px(24.0)))
+        .child(div().ml_3().flex_grow().child(header(SortKey::Name)))
+        .child(div().w_24().child(header(SortKey::Modified)))
+        .child(
+            div()
+                .w_20()
+                .flex()
+                .justify_end()
+                .child(header(SortKey::Size)),
+        )
+}
+
+impl FileList {
+    fn render_columns(&mut self, style: &ItemStyle, cx: &mut Context<Self>) -> Div {
+        let ws = self.workspace.read(cx);
+        let column_count = ws.column_path.len();
+        if column_count > self.scrolled_columns {
+            self.column_scroll.scroll_to_item(column_count);
+        }
+        self.scrolled_columns = column_count;
+        let columns =
+            column_view::render_column_view(ws, style, &self.workspace, &self.column_scroll);
+
+        let ws_handle_click = self.workspace.clone();
+        let root = div()
+            .size_full()
+            .flex()
+            .flex_col()
+            .bg(style.palette.surface_container_low);
+        self.with_actions(root, cx)
+            .on_mouse_down(MouseButton::Right, move |event, _phase, cx| {
+                ws_handle_click.update(cx, |ws, cx| {
+                    ws.open_context_menu(event.position, None, cx);
+                });
+            })
+            .child(columns)
+    }
+}
+
+impl FileList {
+    /// The list's scroll handle: the grouped view's or the virtual list's
+    fn scroll_handle(&self, cx: &App) -> ScrollHandle {
+        if self.workspace.read(cx).shows_groups() {
+            self.grouped_scroll.clone()
+        } else {
+            self.list_scroll.0.borrow().base_handle.clone()
+        }
+    }
+
+    /// Viewport bounds and scroll offset of the list as last laid out
+    fn list_geometry(&self, cx: &App) -> (Bounds<Pixels>, Point<Pixels>) {
+        let handle = self.scroll_handle(cx);
+        (handle.bounds(), handle.offset())
+    }
+
+    /// A press on empty space starts a rubber band; Ctrl adds to the
+    /// selection and Shift extends it
+    fn begin_marquee(&mut self, event: &MouseDownEvent, cx: &mut Context<Self>) {
+        let (bounds, offset) = self.list_geometry(cx);
+        let mode = MarqueeMode::from_modifiers(&event.modifiers);
+        let (base, anchor) = {
+            let ws = self.workspace.read(cx);
+            let anchor = ws
+                .last_selected
+                .as_ref()
+                .and_then(|path| ws.visible_items().iter().position(|i| i.path == *path));
+            (ws.selection.clone(), anchor)
+        };
+        self.workspace.update(cx, |ws, cx| {
+            if ws.renaming.is_some() {
+                ws.commit_rename(cx);
+            }
+            if mode == MarqueeMode::Replace {
+                ws.select_paths(HashSet::new(), None, cx);
+            }
+        });
+        self.marquee = Some(Marquee {
+            start: event.position - bounds.origin - offset,
+            pointer: event.position,
+            mode,
+            base,
+            anchor,
+        });
+        self._marquee_scroll = Some(self.spawn_marquee_autoscroll(cx));
+        cx.notify();
+    }
+
+    fn drag_marquee(&mut self, position: Point<Pixels>, cx: &mut Context<Self>) {
+        if let Some(marquee) = &mut self.marquee {
+            marquee.pointer = position;
+            self.select_in_marquee(cx);
+            cx.notify();
+        }
+    }
+
+    fn end_marquee(&mut self, cx: &mut Context<Self>) {
+        if self.marquee.take().is_some() {
+            self._marquee_scroll = None;
+            cx.notify();
+        }
+    }
+
+    /// Selects the entries the band touches
+    fn select_in_marquee(&mut self, cx: &mut Context<Self>) {
+        let Some(marquee) = &self.marquee else {
+            return;
+        };
+        let (bounds, offset) = self.list_geometry(cx);
+        let (grouped, paths) = {
+            let ws = self.workspace.read(cx);
+            let paths: Vec<PathBuf> = ws.visible_items().iter().map(|i| i.path.clone()).collect();
+            (ws.shows_groups(), paths)
+        };
+        let (start, end) = (marquee.start, marquee.pointer - bounds.origin - offset);
+        let hits = if grouped {
+            // Every group's entries, in the order they're shown
+            let items = self.group_bounds.borrow().concat();
+            marquee::items_touching(&items, start, end)
+        } else if let Some(layout) = self.item_layout {
+            layout.items_in(start, end, paths.len())
+        } else {
+            return;
+        };
+        let selection = marquee.selection(&hits, |ix| paths.get(ix).cloned());
+        let cursor = hits.last().and_then(|&ix| paths.get(ix).cloned());
+        self.workspace
+            .update(cx, |ws, cx| ws.select_paths(selection, cursor, cx));
+    }
+
+    /// Scrolls the list while the band's pointer is near its top or bottom
+    fn spawn_marquee_autoscroll(&self, cx: &mut Context<Self>) -> Task<()> {
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                loop {
+                    cx.background_executor().timer(AUTOSCROLL_INTERVAL).await;
+                    let active = cx
+                        .update(|cx| this.update(cx, |this, cx| this.autoscroll_marquee(cx)))
+                        .ok()
+                        .and_then(Result::ok)
+                        .unwrap_or(false);
+                    if !active {
+                        break;
+                    }
+                }
+            }
+        })
+    }
+
+    /// One auto-scroll step; false once the band is gone
+    fn autoscroll_marquee(&mut self, cx: &mut Context<Self>) -> bool {
+        let Some(marquee) = &self.marquee else {
+            return false;
+        };
+        let (bounds, offset) = self.list_geometry(cx);
+        let step = marquee::autoscroll_step(
+            f32::from(marquee.pointer.y),
+            f32::from(bounds.top()),
+            f32::from(bounds.bottom()),
+        );
+        if step != 0.0 {
+            let handle = self.scroll_handle(cx);
+            let max = f32::from(handle.max_offset().height);
+            let y = (f32::from(offset.y) - step).clamp(-max, 0.0);
+            if px(y) != offset.y {
+                handle.set_offset(point(offset.x, px(y)));
+                self.select_in_marquee(cx);
+                cx.notify();
+            }
+        }
+        true
+    }
+
+    /// Notices when the list's width changes, and follows the pointer
+    /// anywhere in the window while a band is dragged
+    fn render_marquee_tracker(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let this = cx.entity();
+        let tracking = self.marquee.is_some();
+        canvas(
+            {
+                let this = this.clone();
+                move |bounds, _window, cx| {
+                    this.update(cx, |this, cx| {
+                        if bounds.size.width != this.list_width {
+                            cx.notify();
                         }
-                    )
+                    })
+                }
+            },
+            move |_bounds, (), window, _cx| {
+                if !tracking {
+                    return;
+                }
+                let on_move = this.clone();
+                window.on_mouse_event(move |event: &MouseMoveEvent, phase, _window, cx| {
+                    if phase == DispatchPhase::Bubble {
+                        on_move.update(cx, |this, cx| match event.pressed_button {
+                            Some(MouseButton::Left) => this.drag_marquee(event.position, cx),
+                            _ => this.end_marquee(cx),
+                        });
+                    }
+                });
+                window.on_mouse_event(move |event: &MouseUpEvent, phase, _window, cx| {
+                    if phase == DispatchPhase::Bubble && event.button == MouseButton::Left {
+                        this.update(cx, |this, cx| this.end_marquee(cx));
+                    }
+                });
+            },
+        )
+        .absolute()
+        .size_full()
+    }
+
+    /// Arrow keys: by one entry in lists, by rows and items in the grid;
+    /// in the list's tree right/left expand and collapse folders
+    fn move_cursor(&mut self, dx: isize, dy: isize, cx: &mut Context<Self>) {
+        let (view, shows_tree) = {
+            let ws = self.workspace.read(cx);
+            let shows_tree = ws.view.is_list() && !ws.shows_groups() && ws.search_results.is_none();
+            (ws.view.clone(), shows_tree)
+        };
+        if view.is_columns() {
+            self.workspace
+                .update(cx, |ws, cx| ws.move_in_columns(dx as i32, dy as i32, cx));
+            return;
+        }
+        if dx != 0 && shows_tree {
+            self.step_tree(dx > 0, cx);
+            return;
+        }
+
+        let delta = if view.is_grid() {
+            dx + dy * self.grid_cols as isize
+        } else {
+            dy
+        };
+        if delta != 0 {
+            let ix = self
+                .workspace
+                .update(cx, |ws, cx| ws.move_cursor(delta, cx));
+            self.scroll_to_index(ix, cx);
+        }
+    }
+
+    /// Right expands the folder under the cursor or enters it; left
+    /// collapses it or goes up to the folder containing it
+    fn step_tree(&mut self, expand: bool, cx: &mut Context<Self>) {
+        let Some(item) = self
+            .workspace
+            .read(cx)
+            .last_selected
+            .as_ref()
+            .and_then(|path| self.workspace.read(cx).find_entry(path).cloned())
+        else {
+            return;
+        };
+        let is_expanded = self
+            .workspace
+            .read(cx)
+            .expanded_folders
+            .contains(&item.path);
+        let ix = self.workspace.update(cx, |ws, cx| {
+            if item.is_dir && expand != is_expanded {
+                ws.toggle_expanded(item.path.clone(), cx);
+                None
+            } else if expand && item.is_dir {
+                ws.move_cursor(1, cx)
+            } else if !expand && item.depth > 0 {
+                ws.set_selection(item.path.parent()?.to_path_buf(), cx);
+                ws.cursor_index()
+            } else {
+                None
+            }
+        });
+        self.scroll_to_index(ix, cx);
+    }
+
+    /// Brings the entry at `ix` of the visible order into view
+    fn scroll_to_index(&self, ix: Option<usize>, cx: &mut Context<Self>) {
+        let ws = self.workspace.read(cx);
+        let Some(ix) = ix.filter(|_| !ws.shows_groups()) else {
+            return;
+        };
+        let row = if ws.view.is_grid() {
+            ix / self.grid_cols
+        } else {
+            ix
+        };
+        self.list_scroll.scroll_to_item(row, ScrollStrategy::Top);
+    }
+
+    /// Typing edits the name being renamed, or else finds entries by name
+    fn handle_key_down(&mut self, event: &KeyDownEvent, cx: &mut Context<Self>) {
+        if self.workspace.read(cx).renaming.is_some() {
+            self.handle_rename_key(event, cx);
+            cx.stop_propagation();
+            return;
+        }
+
+        let keystroke = &event.keystroke;
+        let modifiers = &keystroke.modifiers;
+        let typed = keystroke
+            .key_char
+            .clone()
+            .filter(|text| !text.chars().any(char::is_control))
+            .filter(|_| !modifiers.control && !modifiers.alt && !modifiers.platform);
+
+        let Some(typed) = typed else {
+            return;
+        };
+        if self.workspace.read(cx).view.is_columns() {
+            return;
+        }
+        let now = Instant::now();
+        if self
+            .type_ahead_at
+            .is_none_or(|at| now.duration_since(at) > TYPE_AHEAD_TIMEOUT)
+        {
+            self.type_ahead.clear();
+        }
+        self.type_ahead.push_str(&typed);
+        self.type_ahead_at = Some(now);
+
+        let prefix = self.type_ahead.clone();
+        let ix = self
+            .workspace
+            .update(cx, |ws, cx| ws.select_by_prefix(&prefix, cx));
+        self.scroll_to_index(ix, cx);
+        cx.stop_propagation();
+    }
+
+    /// Editing keys of the in-place rename field
+    fn handle_rename_key(&mut self, event: &KeyDownEvent, cx: &mut Context<Self>) {
+        let keystroke = &event.keystroke;
+        let command = keystroke.modifiers.control || keystroke.modifiers.platform;
+        match keystroke.key.as_str() {
+            "enter" => self.workspace.update(cx, |ws, cx| ws.commit_rename(cx)),
+            "escape" => self.workspace.update(cx, |ws, cx| ws.cancel_rename(cx)),
+            "c" if command => {
+                if let Some(rename) = self.workspace.read(cx).renaming.clone() {
+                    copy_name_selection(&rename, cx);
+                }
+            }
+            _ => {
+                if let Some(edit) = name_field_edit(keystroke, cx) {
+                    self.edit_rename(edit, cx);
+                }
+            }
+        }
+    }
+
+    fn edit_rename(&mut self, edit: impl FnOnce(&mut RenameState), cx: &mut Context<Self>) {
+        self.workspace.update(cx, |ws, cx| ws.edit_rename(edit, cx));
+    }
+
+    /// Shift+Delete asks before removing anything for good
+    fn delete_permanently(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let count = self.workspace.read(cx).selection.len();
+        if count == 0 {
+            return;
+        }
+        let message = if count == 1 {
+            "Permanently delete the selected item?".to_string()
+        } else {
+            format!("Permanently delete {} selected items?", count)
+        };
+        let answer = window.prompt(
+            PromptLevel::Warning,
+            &message,
+            Some("This can't be undone."),
+            &["Delete", "Cancel"],
+            cx,
+        );
+        let workspace = self.workspace.clone();
+        cx.spawn(move |_, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                if matches!(answer.await, Ok(0)) {
+                    let _ = cx.update(|cx| {
+                        workspace.update(cx, |ws, cx| ws.delete_selection(true, cx));
+                    });
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Drops on the background of the view land in the folder it shows
+    fn accept_drops<E: InteractiveElement>(&self, element: E, cx: &Context<Self>) -> E {
+        let current_path = self.workspace.read(cx).current_path.clone();
+        let highlight = Hsla::from(cx.theme().palette.surface_container_high);
+        let element = accept_dragged_files(element, current_path.clone(), highlight);
+        accept_external_paths(element, current_path, highlight, &self.workspace)
+    }
+
+    /// Focus tracking, the file view key bindings, type-ahead and drops
+    fn with_actions(&self, element: Div, cx: &mut Context<Self>) -> Div {
+        // While renaming, keys go to the editor instead of the bindings
+        let renaming = self.workspace.read(cx).renaming.is_some();
+        let workspace = &self.workspace;
+
+        self.accept_drops(element, cx)
+            .when(!renaming, |d| d.key_context(keybindings::FILE_LIST_CONTEXT))
+            .track_focus(&self.focus_handle)
+            // Items stop propagation, so take focus before they see the click
+            .capture_any_mouse_down({
+                let focus_handle = self.focus_handle.clone();
+                move |_, window, cx| window.focus(&focus_handle, cx)
+            })
+            .on_key_down(cx.listener(|this, event, _window, cx| this.handle_key_down(event, cx)))
+            .on_action(
+                cx.listener(|this, _: &keybindings::MoveUp, _, cx| this.move_cursor(0, -1, cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &keybindings::MoveDown, _, cx| this.move_cursor(0, 1, cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &keybindings::MoveLeft, _, cx| this.move_cursor(-1, 0, cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &keybindings::MoveRight, _, cx| this.move_cursor(1, 0, cx)),
+            )
+            .on_action(workspace_action::<keybindings::Open>(
+                workspace,
+                |ws, cx| match ws.column_path.last().filter(|_| ws.view.is_columns()) {
+                    Some(entry) => ws.open(entry.path.clone(), cx),
+                    None => ws.open_selection(cx),
+                },
+            ))
+            .on_action(workspace_action::<keybindings::GoToParent>(
+                workspace,
+                |ws, cx| ws.go_to_parent(cx),
+            ))
+            .on_action(workspace_action::<keybindings::Rename>(
+                workspace,
+                |ws, cx| {
+                    if !ws.view.is_columns() {
+                        ws.begin_rename(cx);
+                    }
+                },
+            ))
+            .on_action(workspace_action::<keybindings::Delete>(
+                workspace,
+                |ws, cx| ws.delete_selection(false, cx),
+            ))
+            .on_action(
+                cx.listener(|this, _: &keybindings::DeletePermanently, window, cx| {
+                    this.delete_permanently(window, cx)
+                }),
+            )
+            .on_action(workspace_action::<keybindings::SelectAll>(
+                workspace,
+                |ws, cx| ws.select_all(cx),
+            ))
+            .on_action(workspace_action::<keybindings::Copy>(
+                workspace,
+                |ws, cx| ws.copy_selection(cx),
+            ))
+            .on_action(workspace_action::<keybindings::Cut>(workspace, |ws, cx| {
+                ws.cut_selection(cx)
             }))
-            .into_any_element()
+            .on_action(workspace_action::<keybindings::Paste>(
+                workspace,
+                |ws, cx| ws.paste_clipboard(cx),
+            ))
+    }
+}
+
+/// An action listener that runs `f` on the workspace
+fn workspace_action<A: Action>(
+    workspace: &Entity<Workspace>,
+    f: fn(&mut Workspace, &mut Context<Workspace>),
+) -> impl Fn(&A, &mut Window, &mut App) + 'static {
+    let workspace = workspace.clone();
+    move |_, _, cx| workspace.update(cx, f)
+}
+
+impl Focusable for FileList {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
     }
 }
 
@@ -365,478 +1245,234 @@ impl Render for FileList {
         let palette = cx.theme().palette.clone();
 
         // Clone needed data to avoid immutable borrow of cx
-        let (item_count, is_loading, grouped_files, group_by_type, filtered_items, selection) = {
+        let (item_count, is_loading, grouped_files, filtered_items, selection, git_status, view) = {
             let ws = self.workspace.read(cx);
             (
                 ws.items.len(),
                 ws.is_loading,
                 ws.grouped_files.clone(), // Heavy clone but safe
-                ws.group_by_type,
                 ws.filtered_items.clone(),
                 ws.selection.clone(),
+                ws.git_status.clone(),
+                ws.view.clone(),
             )
         };
 
+        // List rows expand inline unless grouped or showing search results
+        let expanded = {
+            let ws = self.workspace.read(cx);
+            (view.is_list() && !view.group_by_type && ws.search_results.is_none())
+                .then(|| ws.expanded_folders.clone())
+        };
+
         let config = cx.global::<ConfigManager>().config.clone();
-        let view_mode = config.ui.view_mode.clone();
-        let is_grid = view_mode == "grid";
+        let is_grid = view.is_grid();
+        let is_details = view.is_details();
+        let columns = config.ui.details_columns();
+        let style = ItemStyle {
+            palette: palette.clone(),
+            dim_ignored: config.ui.ignored_files == "dim",
+            tag_defs: config.tags.clone(),
+            git_status,
+            icon_size: view.icon_size as f32,
+            rename: self.workspace.read(cx).renaming.clone(),
+        };
+
+        if view.is_columns() {
+            return self.render_columns(&style, cx).into_any_element();
+        }
 
         // Handle Grouped View
-        if group_by_type && !grouped_files.is_empty() {
-            return self.render_grouped_view(grouped_files, selection, &palette, is_grid, cx).into_any_element();
+        if self.workspace.read(cx).shows_groups() {
+            return self
+                .render_grouped_view(grouped_files, selection, &style, is_grid, cx)
+                .into_any_element();
         }
 
         let filtered_count = filtered_items.len();
 
         let workspace_handle = self.workspace.clone();
         let ws_handle_click = workspace_handle.clone();
-        let ws_handle_key = workspace_handle.clone();
+        let thumbnail_pool = self.thumbnail_pool.clone();
+        let owner = cx.entity_id();
 
-        let file_list_entity = cx.entity().clone();
         let list_id = ElementId::Name("file_list_virtual".into());
 
-        div()
+        // Grid items per row for the list's width as last laid out; the
+        // first frame lays out one column and corrects itself right after
+        let (list_bounds, list_offset) = self.list_geometry(cx);
+        self.list_width = list_bounds.size.width;
+        let slot_width = style.grid_slot_width();
+        let cols = if is_grid {
+            (f32::from(self.list_width) / slot_width).floor() as usize
+        } else {
+            1
+        };
+        let cols = std::cmp::max(1, cols);
+        self.grid_cols = cols;
+
+        let row_height = style.grid_row_height();
+        self.item_layout = Some(if is_grid {
+            ItemLayout {
+                cols,
+                row_height,
+                slot_width,
+                margin: GRID_ITEM_MARGIN,
+            }
+        } else {
+            let last_item_size = self.list_scroll.0.borrow().last_item_size;
+            ItemLayout {
+                cols: 1,
+                row_height: last_item_size.map_or(0.0, |size| f32::from(size.item.height)),
+                slot_width: f32::from(self.list_width),
+                margin: 0.0,
+            }
+        });
+        let band = self.marquee.as_ref().map(|marquee| {
+            marquee::render_band(
+                marquee.start + list_offset,
+                marquee.pointer - list_bounds.origin,
+                palette.primary.into(),
+            )
+        });
+        let marquee_tracker = self.render_marquee_tracker(cx);
+        let list_count = if is_grid {
+            filtered_count.div_ceil(cols)
+        } else {
+            filtered_count
+        };
+
+        let list_header = if is_details {
+            Some(details_view::render_details_header(
+                &columns,
+                view.sort,
+                &palette,
+                &self.workspace,
+                cx.listener(|this, _, _, cx| {
+                    this.show_column_menu = !this.show_column_menu;
+                    cx.notify();
+                }),
+            ))
+        } else if is_grid {
+            None
+        } else {
+            Some(render_list_header(
+                view.sort,
+                expanded.is_some(),
+                &palette,
+                &self.workspace,
+            ))
+        };
+        let column_menu = (is_details && self.show_column_menu)
+            .then(|| details_view::render_column_chooser(&columns, &palette, &self.workspace));
+
+        let root = div()
             .size_full()
-            .bg(palette.surface_container_low)
-            .track_focus(&self.focus_handle)
-            .on_key_down(move |event: &KeyDownEvent, _phase, cx| {
-                if event.keystroke.modifiers.control {
-                    ws_handle_key.update(cx, |ws, cx| match event.keystroke.key.as_str() {
-                        "c" => ws.copy_selection(cx),
-                        "x" => ws.cut_selection(cx),
-                        "v" => ws.paste_clipboard(cx),
-                        _ => {}
-                    });
-                }
-            })
+            .flex()
+            .flex_col()
+            .bg(palette.surface_container_low);
+        self.with_actions(root, cx)
             .on_mouse_down(MouseButton::Right, move |event, _phase, cx| {
                 ws_handle_click.update(cx, |ws, cx| {
                     ws.open_context_menu(event.position, None, cx);
                 });
             })
-            .child({
-                 let palette = palette.clone();
-                 // Estimate grid item width including margins/padding
-                 let item_width_px = 120.0f32;
-                 let viewport_width = 1000.0f32; // Fixed estimate for calc
-
-                 let cols = if is_grid {
-                     let w = viewport_width;
-                     (w / item_width_px).floor() as usize
-                 } else {
-                     1
-                 };
-                 let cols = std::cmp::max(1, cols);
-
-                 let list_count = if is_grid {
-                     (filtered_count + cols - 1) / cols
-                 } else {
-                     filtered_count
-                 };
-
-                 uniform_list(list_id, list_count, move |range, _window, cx| {
-                     let workspace = workspace_handle.clone();
-                     let selection = selection.clone();
-                     let file_list = file_list_entity.clone();
-
-                     if is_grid {
-                         range
-                             .map(|row_index| {
-                                 let start_index = row_index * cols;
-                                 let end_index = std::cmp::min(start_index + cols, filtered_count);
-                                 let row_items = &filtered_items[start_index..end_index];
-
-                                 div()
-                                     .id(row_index)
-                                     .flex()
-                                     .w_full()
-                                     .h(px(130.0)) // Row height for Grid
-                                     .items_start()
-                                     .children(row_items.iter().enumerate().map(
-                                         |(col_idx, item)| {
-                                             let item_idx = start_index + col_idx;
-
-                                             // INLINE RENDER GRID ITEM (Duplicate 2)
-                                    let is_selected = selection.contains(&item.path);
-                                    let item_path = item.path.clone();
-                                    let is_dir = item.is_dir;
-
-                                    let bg_color = if is_selected {
-                                        Hsla::from(palette.secondary_container)
-                                    } else {
-                                        gpui::hsla(0., 0., 0., 0.)
-                                    };
-                                    let text_color = if is_selected {
-                                        palette.on_secondary_container
-                                    } else {
-                                        palette.on_surface
-                                    };
-
-                                    let ext = item_path
-                                        .extension()
-                                        .and_then(|e: &std::ffi::OsStr| e.to_str())
-                                        .unwrap_or("")
-                                        .to_lowercase();
-
-                                    let is_image = !is_dir && matches!(ext.as_str(), "png" | "jpg" | "jpeg" | "webp");
-
-                                    let thumbnail_path = if is_image {
-                                        crate::assets::thumbnail_worker::ThumbnailWorker::get_cached_path(&item_path)
-                                    } else {
-                                        None
-                                    };
-
-                                    if is_image && thumbnail_path.is_none() {
-                                        let is_pending = file_list
-                                            .read(cx)
-                                            .pending_thumbnails
-                                            .contains(&item_path);
-                                        if !is_pending {
-                                            let path_for_task = item_path.clone();
-                                            cx.background_executor()
-                                                .spawn(async move {
-                                                    crate::assets::thumbnail_worker::ThumbnailWorker::generate_thumbnail(
-                                                        path_for_task.clone(),
-                                                    );
-                                                })
-                                                .detach();
-                                        }
-                                    }
-                                    let icon_name = if is_dir {
-                                        "folder"
-                                    } else {
-                                        match ext.as_str() {
-                                            "png" | "jpg" | "jpeg" | "webp" => "image",
-                                            "mp4" | "mkv" | "webm" => "video",
-                                            "mp3" | "wav" | "ogg" => "audio",
-                                            _ => "file",
-                                        }
-                                    };
-
-                                    let ws_click = workspace.clone();
-                                    let path_click = item_path.clone();
-                                    let ws_dbl = workspace.clone();
-                                    let path_dbl = item_path.clone();
-                                    let ws_right = workspace.clone();
-                                    let path_right = item_path.clone();
-
-                                    div()
-                                        .id(item_idx)
-                                        .w(px(120.0))
-                                        .h_full()
-                                        .flex()
-                                        .flex_col()
-                                        .items_center()
-                                        .p_2()
-                                        .m_1()
-                                        .rounded_md()
-                                        .bg(bg_color)
-                                        .text_color(text_color)
-                                        .hover(|s| s.bg(palette.surface_container_highest))
-                                        .on_click(move |event, _, cx| {
-                                            if event.click_count() >= 2 {
-                                                ws_dbl.update(cx, |ws, cx| {
-                                                    ws.open(path_dbl.clone(), cx);
-                                                });
-                                            }
-                                        })
-                                        .on_mouse_down(MouseButton::Left, move |event, _, cx| {
-                                            cx.stop_propagation();
-                                            ws_click.update(cx, |ws, cx| {
-                                                if event.modifiers.control {
-                                                    ws.toggle_selection(path_click.clone(), cx);
-                                                } else if event.modifiers.shift {
-                                                    ws.select_range(path_click.clone(), cx);
-                                                } else {
-                                                    ws.set_selection(path_click.clone(), cx);
-                                                }
-                                            });
-                                        })
-                                        .on_mouse_down(MouseButton::Right, move |event, _, cx| {
-                                            cx.stop_propagation();
-                                            ws_right.update(cx, |ws, cx| {
-                                                if !ws.selection.contains(&path_right) {
-                                                    ws.set_selection(path_right.clone(), cx);
-                                                }
-                                                ws.open_context_menu(event.position, Some(path_right.clone()), cx);
-                                            });
-                                        })
-                                        .child(if let Some(thumb) = thumbnail_path {
-                                            let path_str = format!("file://{}", thumb.to_string_lossy());
-                                            div()
-                                                .flex()
-                                                .items_center()
-                                                .p_2()
-                                                .m_1()
-                                                .rounded_md()
-                                                .bg(bg_color)
-                                                .text_color(text_color)
-                                                .hover(|s| s.bg(palette.surface_container_highest))
-                                                .on_click(move |event, _, cx| {
-                                                    if event.click_count() >= 2 {
-                                                        ws_dbl.update(cx, |ws, cx| {
-                                                            ws.open(path_dbl.clone(), cx);
-                                                        });
-                                                    }
-                                                })
-                                                .on_mouse_down(
-                                                    MouseButton::Left,
-                                                    move |event, _, cx| {
-                                                        cx.stop_propagation();
-                                                        ws_click.update(cx, |ws, cx| {
-                                                            if event.modifiers.control {
-                                                                ws.toggle_selection(
-                                                                    path_click.clone(),
-                                                                    cx,
-                                                                );
-                                                            } else if event.modifiers.shift {
-                                                                ws.select_range(
-                                                                    path_click.clone(),
-                                                                    cx,
-                                                                );
-                                                            } else {
-                                                                ws.set_selection(
-                                                                    path_click.clone(),
-                                                                    cx,
-                                                                );
-                                                            }
-                                                        });
-                                                    },
-                                                )
-                                                .on_mouse_down(
-                                                    MouseButton::Right,
-                                                    move |event, _, cx| {
-                                                        cx.stop_propagation();
-                                                        ws_right.update(cx, |ws, cx| {
-                                                            if !ws.selection.contains(&path_right) {
-                                                                ws.set_selection(
-                                                                    path_right.clone(),
-                                                                    cx,
-                                                                );
-                                                            }
-                                                            ws.open_context_menu(
-                                                                event.position,
-                                                                Some(path_right.clone()),
-                                                                cx,
-                                                            );
-                                                        });
-                                                    },
-                                                )
-                                                .child(
-                                                    if let Some(thumb) = thumbnail_path {
-                                                        let path_str = format!("file://{}", thumb.to_string_lossy());
-                                                        div().flex().children(vec![
-                                                            div().w(px(4.0)).h(px(4.0)).bg(gpui::blue()).rounded_full().into_any_element(),
-                                                            img(path_str)
-                                                                .w(px(64.0))
-                                                                .h(px(64.0))
-                                                                .object_fit(ObjectFit::Cover)
-                                                                .rounded_md()
-                                                                .into_any_element()
-                                                        ]).into_any_element()
-                                                    } else {
-                                                        div().flex().items_center().justify_center().children(vec![
-                                                            // Revert to svg(), explicit size
-                                                            icon(icon_name).size_12().into_any_element()
-                                                        ]).into_any_element()
-                                                    }
-                                                )
-                                                .child(
-                                                    img(path_str)
-                                                        .w(px(64.0))
-                                                        .h(px(64.0))
-                                                        .object_fit(ObjectFit::Cover)
-                                                        .rounded_md(),
-                                                )
-                                        } else {
-                                            div()
-                                                .flex()
-                                                .items_center()
-                                                .justify_center()
-                                                .w_full()
-                                                .h(px(64.0))
-                                                .child(crate::assets::icons::icon(icon_name).size_12())
-                                        })
-                                        .child(
-                                            div()
-                                                .mt_2()
-                                                .text_sm()
-                                                .text_center()
-                                                .text_ellipsis()
-                                                .max_w_full()
-                                                .child(item.name.clone()),
-                                        )
-                                        .into_any_element()
-                                         },
-                                     ))
-                                     .into_any_element()
-                             })
-                             .collect::<Vec<_>>()
-                     } else {
-                         // LIST VIEW
-                         let items_slice = &filtered_items[range];
-                         items_slice
-                             .iter()
-                             .enumerate()
-                             .map(|(i, item)| {
-                                 // INLINE RENDER LIST ITEM (Duplicate 3)
-                                    let is_selected = selection.contains(&item.path);
-                                    let item_path = item.path.clone();
-                                    let is_dir = item.is_dir;
-
-                                    let bg_color = if is_selected {
-                                        Hsla::from(palette.secondary_container)
-                                    } else {
-                                        gpui::hsla(0., 0., 0., 0.)
-                                    };
-                                    let text_color = if is_selected {
-                                        palette.on_secondary_container
-                                    } else {
-                                        palette.on_surface
-                                    };
-                                    let sub_text_color = if is_selected {
-                                        palette.on_secondary_container
-                                    } else {
-                                        palette.on_surface_variant
-                                    };
-                                    let ext = item_path
-                                        .extension()
-                                        .and_then(|e: &std::ffi::OsStr| e.to_str())
-                                        .unwrap_or("")
-                                        .to_lowercase();
-
-                                    let icon_name = if is_dir {
-                                        "folder"
-                                    } else {
-                                        match ext.as_str() {
-                                            "png" | "jpg" | "jpeg" | "webp" => "image",
-                                            "mp4" | "mkv" | "webm" => "video",
-                                            "mp3" | "wav" | "ogg" => "audio",
-                                            _ => "file",
+            // Column widths update live while dragging; persist when released
+            .on_drop(|_: &ColumnResize, _window, cx| {
+                cx.global::<ConfigManager>().save_config();
+            })
+            .children(list_header)
+            .child(
+                div()
+                    .relative()
+                    .w_full()
+                    .flex_1()
+                    .min_h_0()
+                    // Presses that get here missed every item
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|this, event: &MouseDownEvent, _, cx| {
+                            this.begin_marquee(event, cx)
+                        }),
+                    )
+                    .child(
+                        uniform_list(list_id, list_count, move |range, _window, cx| {
+                            if is_grid {
+                                // Only the rows in view ask for thumbnails
+                                let first = range.start * cols;
+                                let shown = first..(range.end * cols).min(filtered_count);
+                                let items: Vec<&FileEntry> = filtered_items[shown].iter().collect();
+                                let thumbnails =
+                                    request_thumbnails(&thumbnail_pool, owner, &items, &style, cx);
+                                range
+                                    .map(|row_index| {
+                                        let start_index = row_index * cols;
+                                        let end_index =
+                                            std::cmp::min(start_index + cols, filtered_count);
+
+                                        let mut row_children = Vec::new();
+                                        for item_idx in start_index..end_index {
+                                            let item = &filtered_items[item_idx];
+                                            row_children.push(render_grid_item(
+                                                item,
+                                                ElementId::from(item_idx),
+                                                selection.contains(&item.path),
+                                                thumbnails[item_idx - first].clone(),
+                                                &style,
+                                                &workspace_handle,
+                                            ));
                                         }
-                                    };
-
-                                    let ws_click = workspace.clone();
-                                    let path_click = item_path.clone();
-                                    let ws_dbl = workspace.clone();
-                                    let path_dbl = item_path.clone();
-                                    let ws_right = workspace.clone();
-                                    let path_right = item_path.clone();
-
-                                    div()
-                                        .id(i)
-                                        .h_10()
-                                        .flex()
-                                        .items_center()
-                                        .w_full()
-                                        .px_3()
-                                        .border_b_1()
-                                        .border_color(Hsla::from(palette.outline_variant).opacity(0.1))
-                                        .bg(bg_color)
-                                        .text_color(text_color)
-                                        .hover(|s| s.bg(palette.surface_container_highest))
-                                        .on_click(move |event, _, cx| {
-                                            if event.click_count() >= 2 {
-                                                ws_dbl.update(cx, |ws, cx| {
-                                                    ws.open(path_dbl.clone(), cx);
-                                                });
-                                            }
-                                            ws.open_context_menu(
-                                                event.position,
-                                                Some(path_right.clone()),
-                                                cx,
-                                            );
-                                        });
-                                    })
-                                    .child(
+
                                         div()
-                                            .w(px(24.0))
+                                            .id(row_index)
                                             .flex()
-                                            .justify_center()
-                                            .child(icon(icon_name).size_5()),
-                                    )
-                                    .child(div().ml_3().flex_grow().min_w_0().child(
-                                        div().text_ellipsis().child(item.name.clone()),
-                                    ))
-                                    .child(
-                                        div()
-                                            .w_24()
-                                            .text_sm()
-                                            .text_color(sub_text_color)
-                                            .child(item.formatted_date.clone()),
-                                    )
-                                    .child(
-                                        div()
-                                            .w_20()
-                                            .text_sm()
-                                            .text_right()
-                                            .text_color(sub_text_color)
-                                            .child(item.formatted_size.clone()),
-                                    )
-                                    .into_any_element()
-                            })
-                            .collect::<Vec<_>>()
-                    }
-                })
-                .size_full()
-                                        })
-                                        .on_mouse_down(MouseButton::Left, move |event, _, cx| {
-                                            cx.stop_propagation();
-                                            ws_click.update(cx, |ws, cx| {
-                                                if event.modifiers.control {
-                                                    ws.toggle_selection(path_click.clone(), cx);
-                                                } else if event.modifiers.shift {
-                                                    ws.select_range(path_click.clone(), cx);
-                                                } else {
-                                                    ws.set_selection(path_click.clone(), cx);
-                                                }
-                                            });
-                                        })
-                                        .on_mouse_down(MouseButton::Right, move |event, _, cx| {
-                                            cx.stop_propagation();
-                                            ws_right.update(cx, |ws, cx| {
-                                                if !ws.selection.contains(&path_right) {
-                                                    ws.set_selection(path_right.clone(), cx);
-                                                }
-                                                ws.open_context_menu(event.position, Some(path_right.clone()), cx);
-                                            });
-                                        })
-                                        .child(
-                                            div()
-                                                .w(px(24.0))
-                                                .flex()
-                                                .justify_center()
-                                                .child(crate::assets::icons::icon(icon_name).size_5()),
-                                        )
-                                        .child(
-                                            div()
-                                                .ml_3()
-                                                .flex_grow()
-                                                .min_w_0()
-                                                .child(div().text_ellipsis().child(item.name.clone())),
-                                        )
-                                        .child(
-                                            div()
-                                                .w_24()
-                                                .text_sm()
-                                                .text_color(sub_text_color)
-                                                .child(item.formatted_date.clone()),
+                                            .w_full()
+                                            .h(px(row_height)) // Row height for Grid
+                                            .items_start()
+                                            .children(row_children)
+                                            .into_any_element()
+                                    })
+                                    .collect::<Vec<_>>()
+                            } else if is_details {
+                                range
+                                    .map(|ix| {
+                                        let item = &filtered_items[ix];
+                                        details_view::render_details_row(
+                                            item,
+                                            ElementId::from(ix),
+                                            selection.contains(&item.path),
+                                            &columns,
+                                            &style,
+                                            &workspace_handle,
                                         )
-                                        .child(
-                                            div()
-                                                .w_20()
-                                                .text_sm()
-                                                .text_right()
-                                                .text_color(sub_text_color)
-                                                .child(item.formatted_size.clone()),
+                                    })
+                                    .collect::<Vec<_>>()
+                            } else {
+                                // LIST VIEW
+                                range
+                                    .map(|ix| {
+                                        let item = &filtered_items[ix];
+                                        render_list_item(
+                                            item,
+                                            ElementId::from(ix),
+                                            selection.contains(&item.path),
+                                            expanded
+                                                .as_ref()
+                                                .map(|expanded| expanded.contains(&item.path)),
+                                            &style,
+                                            &workspace_handle,
                                         )
-                                        .into_any_element()
-                             })
-                             .collect::<Vec<_>>()
-                     }
-                 })
-                 .size_full()
-            })
+                                    })
+                                    .collect::<Vec<_>>()
+                            }
+                        })
+                        .size_full()
+                        .track_scroll(self.list_scroll.clone()),
+                    )
+                    .child(marquee_tracker)
+                    .children(band),
+            )
+            .children(column_menu)
             .child(if is_loading {
                 div()
                     .absolute()
@@ -0,0 +1,310 @@
+// Hex dumps (see `hex_dump`) for the preview sidebar and the larger preview
+// overlay: offsets, hex bytes and their ASCII side by side, one page at a
+// time, with the file's detected signature above them.
+use crate::app_state::workspace::Workspace;
+use crate::fs_ops::file_signature::{self, Signature};
+use crate::fs_ops::hex_dump::{self, HexPage};
+use crate::theme_engine::palette::M3Palette;
+use crate::theme_engine::theme::ThemeContext;
+use crate::ui_components::chips::Chip;
+use crate::ui_components::syntax::CODE_FONT;
+use gpui::prelude::*;
+use gpui::*;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::path::PathBuf;
+
+/// Bytes per row and per page in the overlay
+const OVERLAY_WIDTH: NonZeroUsize = NonZeroUsize::new(16).unwrap();
+const OVERLAY_PAGE: NonZeroU64 = NonZeroU64::new(4096).unwrap();
+
+/// The rows of a page in columns: offset, hex and ASCII
+pub fn render_dump(
+    id: &'static str,
+    page: &HexPage,
+    width: NonZeroUsize,
+    palette: &M3Palette,
+) -> Stateful<Div> {
+    let rows = hex_dump::rows(page, width).into_iter().map(|row| {
+        div()
+            .flex()
+            .flex_row()
+            .gap_3()
+            .child(
+                div()
+                    .flex_none()
+                    .text_color(palette.outline)
+                    .child(row.offset),
+            )
+            .child(
+                div()
+                    .flex_none()
+                    .text_color(palette.on_surface)
+                    .child(row.hex),
+            )
+            .child(
+                div()
+                    .flex_none()
+                    .text_color(palette.tertiary)
+                    .child(row.ascii),
+            )
+    });
+    div()
+        .id(id)
+        .w_full()
+        .overflow_x_scroll()
+        .p_2()
+        .rounded_md()
+        .bg(palette.surface_container_low)
+        .font_family(CODE_FONT)
+        .text_xs()
+        .whitespace_nowrap()
+        .when(page.data.is_empty(), |dump| {
+            dump.text_color(palette.on_surface_variant)
+                .child("Empty file")
+        })
+        .children(rows)
+}
+
+/// "Page x of y" between buttons for the pages either side; `go_to` is
+/// called with the page asked for
+pub fn render_pager(
+    id: &'static str,
+    page: &HexPage,
+    palette: &M3Palette,
+    go_to: impl Fn(u64, &mut App) + Clone + Send + Sync + 'static,
+) -> Div {
+    let count = page.page_count();
+    let previous = page.number.checked_sub(1);
+    let next = Some(page.number + 1).filter(|&next| next < count);
+    let button = |suffix: &'static str, label: &'static str, target: Option<u64>| {
+        let go_to = go_to.clone();
+        target.map(|target| {
+            Chip::new(SharedString::from(format!("{}_{}", id, suffix)), label)
+                .dense()
+                .on_click(move |_, _, cx| go_to(target, cx))
+        })
+    };
+    div()
+        .flex()
+        .items_center()
+        .justify_between()
+        .gap_2()
+        .w_full()
+        .text_xs()
+        .text_color(palette.on_surface_variant)
+        .child(
+            div()
+                .flex()
+                .gap_1()
+                .children(button("first", "First", previous.map(|_| 0)))
+                .children(button("previous", "Previous", previous)),
+        )
+        .child(format!("Page {} of {}", page.number + 1, count))
+        .child(
+            div()
+                .flex()
+                .gap_1()
+                .children(button("next", "Next", next))
+                .children(button("last", "Last", next.map(|_| count - 1))),
+        )
+}
+
+/// What the file was detected to be, as label and value rows
+pub fn render_signature(signature: &Signature, palette: &M3Palette) -> Div {
+    let rows = file_signature::describe(signature)
+        .into_iter()
+        .map(|(label, value)| {
+            div()
+                .w_full()
+                .flex()
+                .justify_between()
+                .gap_4()
+                .child(div().flex_none().child(label))
+                .child(
+                    div()
+                        .text_right()
+                        .text_color(palette.on_surface)
+                        .child(value),
+                )
+        });
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .w_full()
+        .text_sm()
+        .text_color(palette.on_surface_variant)
+        .children(rows)
+}
+
+/// The larger preview overlay of a binary file.
+pub struct HexPreview {
+    workspace: Entity<Workspace>,
+    path: PathBuf,
+    /// `None` while loading
+    page: Option<Result<HexPage, String>>,
+    signature: Option<Signature>,
+}
+
+impl HexPreview {
+    pub fn new(workspace: Entity<Workspace>, path: PathBuf, cx: &mut Context<Self>) -> Self {
+        let executor = cx.background_executor().clone();
+        let task_path = path.clone();
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                let signature = executor
+                    .spawn(async move { file_signature::detect(&task_path).ok().flatten() })
+                    .await;
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |this, cx| {
+                        this.signature = signature;
+                        cx.notify();
+                    });
+                });
+            }
+        })
+        .detach();
+
+        let mut preview = Self {
+            workspace,
+            path,
+            page: None,
+            signature: None,
+        };
+        preview.go_to(0, cx);
+        preview
+    }
+
+    /// Reads page `number` of the file, keeping the current one shown until
+    /// it's in
+    pub fn go_to(&mut self, number: u64, cx: &mut Context<Self>) {
+        let executor = cx.background_executor().clone();
+        let path = self.path.clone();
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                let page = executor
+                    .spawn(async move {
+                        hex_dump::read_page(&path, number, OVERLAY_PAGE).map_err(|e| e.to_string())
+                    })
+                    .await;
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |this, cx| {
+                        this.page = Some(page);
+                        cx.notify();
+                    });
+                });
+            }
+        })
+        .detach();
+    }
+}
+
+impl Render for HexPreview {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let palette = cx.theme().palette.clone();
+        let name = self
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        let note = |message: String| {
+            div()
+                .text_sm()
+                .text_color(palette.on_surface_variant)
+                .child(message)
+        };
+        let content = match &self.page {
+            None => note("Loading preview…".to_string()),
+            Some(Ok(page)) => {
+                let this = cx.entity().downgrade();
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_4()
+                    .when_some(self.signature.as_ref(), |content, signature| {
+                        content.child(render_signature(signature, &palette))
+                    })
+                    .child(render_pager(
+                        "hex_preview_pager",
+                        page,
+                        &palette,
+                        move |number, cx| {
+                            let _ = this.update(cx, |this, cx| this.go_to(number, cx));
+                        },
+                    ))
+                    .child(render_dump(
+                        "hex_preview_dump",
+                        page,
+                        OVERLAY_WIDTH,
+                        &palette,
+                    ))
+            }
+            Some(Err(e)) => note(e.clone()),
+        };
+
+        let workspace_scrim = self.workspace.clone();
+        let workspace_close = self.workspace.clone();
+        div()
+            .id("hex_preview_scrim")
+            .absolute()
+            .size_full()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(gpui::rgba(0x00000080))
+            .on_click(move |_, _, cx| {
+                workspace_scrim.update(cx, |ws, cx| ws.dismiss_overlay(cx));
+            })
+            .child(
+                div()
+                    .id("hex_preview_card")
+                    .on_click(|_, _, cx| cx.stop_propagation())
+                    .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                    .on_mouse_down(MouseButton::Right, |_, _, cx| cx.stop_propagation())
+                    .w(relative(0.7))
+                    .h(relative(0.85))
+                    .flex()
+                    .flex_col()
+                    .bg(palette.surface)
+                    .rounded_3xl()
+                    .shadow_xl()
+                    .overflow_hidden()
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .justify_between()
+                            .px_6()
+                            .py_4()
+                            .border_b_1()
+                            .border_color(palette.outline_variant)
+                            .child(
+                                div()
+                                    .text_lg()
+                                    .font_weight(FontWeight::BOLD)
+                                    .text_color(palette.on_surface)
+                                    .child(name),
+                            )
+                            .child(Chip::new("hex_preview_close", "Close").on_click(
+                                move |_, _, cx| {
+                                    workspace_close.update(cx, |ws, cx| ws.dismiss_overlay(cx));
+                                },
+                            )),
+                    )
+                    .child(
+                        div()
+                            .id("hex_preview_body")
+                            .flex_1()
+                            .overflow_y_scroll()
+                            .px_6()
+                            .py_4()
+                            .text_color(palette.on_surface)
+                            .child(content),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,910 @@
+// Markdown for the preview sidebar and the preview overlay: the usual
+// CommonMark blocks plus GitHub's tables and task lists, parsed into a
+// small document model that `markdown_view` renders in theme colors.
+// Reference links and raw HTML aren't interpreted; HTML shows as text.
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Whether `path` is a Markdown file, by extension
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            matches!(
+                ext.to_lowercase().as_str(),
+                "md" | "markdown" | "mdown" | "mkd"
+            )
+        })
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InlineStyle {
+    pub strong: bool,
+    pub emphasis: bool,
+    pub code: bool,
+    pub strikethrough: bool,
+    pub link: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub alt: String,
+    pub src: String,
+}
+
+/// Text of a paragraph, heading or table cell with its styled runs and
+/// links, as byte ranges of `text`. Images are shown after the text.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Inlines {
+    pub text: String,
+    pub runs: Vec<(Range<usize>, InlineStyle)>,
+    pub links: Vec<(Range<usize>, String)>,
+    pub images: Vec<Image>,
+}
+
+impl Inlines {
+    fn push(&mut self, text: &str, style: InlineStyle) {
+        if text.is_empty() {
+            return;
+        }
+        let start = self.text.len();
+        self.text.push_str(text);
+        if style == InlineStyle::default() {
+            return;
+        }
+        match self.runs.last_mut() {
+            Some((range, last)) if *last == style && range.end == start => {
+                range.end = self.text.len()
+            }
+            _ => self.runs.push((start..self.text.len(), style)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListItem {
+    /// Task list checkbox, if it has one
+    pub checked: Option<bool>,
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Block {
+    Heading {
+        level: u8,
+        text: Inlines,
+    },
+    Paragraph(Inlines),
+    /// `start` is the first number of an ordered list
+    List {
+        start: Option<u64>,
+        items: Vec<ListItem>,
+    },
+    Code {
+        language: Option<String>,
+        lines: Vec<String>,
+    },
+    Quote(Vec<Block>),
+    Table {
+        alignments: Vec<Alignment>,
+        header: Vec<Inlines>,
+        rows: Vec<Vec<Inlines>>,
+    },
+    Rule,
+}
+
+pub fn parse(source: &str) -> Vec<Block> {
+    let lines: Vec<String> = source.lines().map(str::to_string).collect();
+    parse_blocks(&lines)
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn parse_blocks(lines: &[String]) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(parse_inlines(&paragraph.join("\n"))));
+            paragraph.clear();
+        }
+    };
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].as_str();
+        let trimmed = line.trim_start();
+        let indent = indent_of(line);
+        if trimmed.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+            i += 1;
+            continue;
+        }
+
+        // A paragraph underlined with = or - is a heading
+        let underline = trimmed.trim_end();
+        if !paragraph.is_empty()
+            && indent < 4
+            && (underline.bytes().all(|b| b == b'=') || underline.bytes().all(|b| b == b'-'))
+        {
+            let level = if underline.starts_with('=') { 1 } else { 2 };
+            let text = parse_inlines(&paragraph.join("\n"));
+            paragraph.clear();
+            blocks.push(Block::Heading { level, text });
+            i += 1;
+            continue;
+        }
+
+        if indent >= 4 && paragraph.is_empty() {
+            let mut code = Vec::new();
+            while i < lines.len() && (lines[i].trim().is_empty() || indent_of(&lines[i]) >= 4) {
+                code.push(lines[i].get(4..).unwrap_or("").to_string());
+                i += 1;
+            }
+            while code.last().is_some_and(|line| line.trim().is_empty()) {
+                code.pop();
+            }
+            blocks.push(Block::Code {
+                language: None,
+                lines: code,
+            });
+            continue;
+        }
+
+        if let Some((level, text)) = atx_heading(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Heading {
+                level,
+                text: parse_inlines(text),
+            });
+            i += 1;
+        } else if is_rule(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Rule);
+            i += 1;
+        } else if let Some((fence, language)) = fence_open(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            i += 1;
+            let mut code = Vec::new();
+            while i < lines.len() && !is_fence_close(&lines[i], fence) {
+                let strip = indent_of(&lines[i]).min(indent);
+                code.push(lines[i][strip..].to_string());
+                i += 1;
+            }
+            // The closing fence
+            i += 1;
+            blocks.push(Block::Code {
+                language,
+                lines: code,
+            });
+        } else if trimmed.starts_with('>') {
+            flush(&mut paragraph, &mut blocks);
+            let mut quoted = Vec::new();
+            while let Some(rest) = lines.get(i).and_then(|l| l.trim_start().strip_prefix('>')) {
+                quoted.push(rest.strip_prefix(' ').unwrap_or(rest).to_string());
+                i += 1;
+            }
+            blocks.push(Block::Quote(parse_blocks(&quoted)));
+        } else if list_marker(trimmed).is_some() {
+            flush(&mut paragraph, &mut blocks);
+            let (list, next) = parse_list(lines, i);
+            blocks.push(list);
+            i = next;
+        } else if let Some(alignments) = paragraph
+            .is_empty()
+            .then(|| lines.get(i + 1).and_then(|l| table_delimiter(l)))
+            .flatten()
+            .filter(|alignments| table_cells(trimmed).len() == alignments.len())
+        {
+            let (table, next) = parse_table(lines, i, alignments);
+            blocks.push(table);
+            i = next;
+        } else {
+            paragraph.push(trimmed);
+            i += 1;
+        }
+    }
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+/// Whether `trimmed` starts a block other than a paragraph, which ends a
+/// lazy continuation line
+fn starts_block(trimmed: &str) -> bool {
+    atx_heading(trimmed).is_some()
+        || is_rule(trimmed)
+        || fence_open(trimmed).is_some()
+        || trimmed.starts_with('>')
+        || list_marker(trimmed).is_some()
+}
+
+fn atx_heading(trimmed: &str) -> Option<(u8, &str)> {
+    let level = trimmed.bytes().take_while(|&b| b == b'#').count();
+    let rest = &trimmed[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    // An optional closing sequence of #s
+    let text = rest.trim();
+    let without_closing = text.trim_end_matches('#');
+    let text = if without_closing.is_empty() || without_closing.ends_with(' ') {
+        without_closing.trim_end()
+    } else {
+        text
+    };
+    Some((level as u8, text))
+}
+
+fn is_rule(trimmed: &str) -> bool {
+    let marks: Vec<char> = trimmed.chars().filter(|c| !c.is_whitespace()).collect();
+    marks.len() >= 3 && matches!(marks[0], '-' | '*' | '_') && marks.iter().all(|&c| c == marks[0])
+}
+
+/// The fence of a fenced code block, and its language
+fn fence_open(trimmed: &str) -> Option<(&str, Option<String>)> {
+    let c = trimmed.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let len = trimmed.chars().take_while(|&x| x == c).count();
+    let info = trimmed[len..].trim();
+    if len < 3 || (c == '`' && info.contains('`')) {
+        return None;
+    }
+    let language = info.split_whitespace().next().map(str::to_string);
+    Some((&trimmed[..len], language))
+}
+
+fn is_fence_close(line: &str, fence: &str) -> bool {
+    let trimmed = line.trim();
+    let c = fence.as_bytes()[0];
+    trimmed.len() >= fence.len() && trimmed.bytes().all(|b| b == c)
+}
+
+/// A list item marker at the start of `trimmed`: the number of an ordered
+/// item, the bullet or delimiter that has to match its siblings, and the
+/// width of the marker with the spaces after it
+fn list_marker(trimmed: &str) -> Option<(Option<u64>, char, usize)> {
+    let digits = trimmed.bytes().take_while(u8::is_ascii_digit).count();
+    let (number, kind, marker_len) = match trimmed.chars().next()? {
+        c @ ('-' | '*' | '+') => (None, c, 1),
+        _ if (1..=9).contains(&digits) => {
+            let kind = trimmed[digits..]
+                .chars()
+                .next()
+                .filter(|&c| c == '.' || c == ')')?;
+            (trimmed[..digits].parse().ok(), kind, digits + 1)
+        }
+        _ => return None,
+    };
+    let rest = &trimmed[marker_len..];
+    if rest.is_empty() {
+        return Some((number, kind, marker_len + 1));
+    }
+    let spaces = indent_of(rest);
+    if spaces == 0 {
+        return None;
+    }
+    // More than four spaces start indented code inside the item
+    let width = marker_len + if spaces > 4 { 1 } else { spaces };
+    Some((number, kind, width))
+}
+
+/// The list starting at `lines[start]`; returns it with the index of the
+/// line after it
+fn parse_list(lines: &[String], start: usize) -> (Block, usize) {
+    let first = lines[start].trim_start();
+    let (number, kind, _) = list_marker(first).unwrap_or((None, '-', 2));
+    let mut items = Vec::new();
+    let mut i = start;
+    while i < lines.len() {
+        let line = lines[i].as_str();
+        let trimmed = line.trim_start();
+        let Some((_, _, width)) = list_marker(trimmed).filter(|m| m.1 == kind) else {
+            break;
+        };
+        // `- - -` is a rule, not an item
+        if is_rule(trimmed) {
+            break;
+        }
+        let content_indent = indent_of(line) + width;
+        let mut item_lines = vec![trimmed.get(width..).unwrap_or("").to_string()];
+        i += 1;
+        while i < lines.len() {
+            let next = lines[i].as_str();
+            if next.trim().is_empty() {
+                item_lines.push(String::new());
+            } else if indent_of(next) >= content_indent {
+                item_lines.push(next[content_indent..].to_string());
+            } else if !item_lines.last().is_some_and(String::is_empty)
+                && !starts_block(next.trim_start())
+            {
+                // Lazy continuation of the item's paragraph
+                item_lines.push(next.trim_start().to_string());
+            } else {
+                break;
+            }
+            i += 1;
+        }
+        while item_lines.last().is_some_and(String::is_empty) {
+            item_lines.pop();
+        }
+
+        let checked = item_lines.first().and_then(|first| {
+            let checked = match first.get(..4)? {
+                "[ ] " => false,
+                "[x] " | "[X] " => true,
+                _ => return None,
+            };
+            Some(checked)
+        });
+        if checked.is_some() {
+            item_lines[0].drain(..4);
+        }
+        items.push(ListItem {
+            checked,
+            blocks: parse_blocks(&item_lines),
+        });
+    }
+    (
+        Block::List {
+            start: number,
+            items,
+        },
+        i,
+    )
+}
+
+/// Cells of a table row, split at unescaped pipes
+fn table_cells(line: &str) -> Vec<String> {
+    let trimmed = line.trim();
+    let trimmed = trimmed.strip_prefix('|').unwrap_or(trimmed);
+    let trimmed = match trimmed.strip_suffix('|') {
+        Some(rest) if !rest.ends_with('\\') => rest,
+        _ => trimmed,
+    };
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = trimmed.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('|') => cell.push('|'),
+                Some(next) => {
+                    cell.push('\\');
+                    cell.push(next);
+                }
+                None => cell.push('\\'),
+            },
+            '|' => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+/// The alignments of a table's delimiter row, like `| :-- | :-: | --: |`
+fn table_delimiter(line: &str) -> Option<Vec<Alignment>> {
+    if !line.contains('-') || !(line.contains('|') || line.contains(':')) {
+        return None;
+    }
+    table_cells(line)
+        .iter()
+        .map(|cell| {
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || !dashes.bytes().all(|b| b == b'-') {
+                return None;
+            }
+            Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => Alignment::Center,
+                (false, true) => Alignment::Right,
+                _ => Alignment::Left,
+            })
+        })
+        .collect()
+}
+
+fn parse_table(lines: &[String], start: usize, alignments: Vec<Alignment>) -> (Block, usize) {
+    let columns = alignments.len();
+    let row = |line: &str| {
+        let mut cells: Vec<Inlines> = table_cells(line)
+            .iter()
+            .map(|cell| parse_inlines(cell))
+            .collect();
+        cells.resize(columns, Inlines::default());
+        cells
+    };
+    let header = row(&lines[start]);
+    let mut rows = Vec::new();
+    let mut i = start + 2;
+    while let Some(line) = lines.get(i) {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || starts_block(trimmed) {
+            break;
+        }
+        rows.push(row(line));
+        i += 1;
+    }
+    (
+        Block::Table {
+            alignments,
+            header,
+            rows,
+        },
+        i,
+    )
+}
+
+pub fn parse_inlines(source: &str) -> Inlines {
+    let mut inlines = Inlines::default();
+    parse_inlines_into(&mut inlines, source, InlineStyle::default());
+    inlines
+}
+
+/// The label and destination of a `[label](destination "title")` link at
+/// the start of `s`, and its length
+fn link_parts(s: &str) -> Option<(&str, String, usize)> {
+    let label_end = matching_close(s, '[', ']')?;
+    let rest = &s[label_end + 1..];
+    if !rest.starts_with('(') {
+        return None;
+    }
+    let dest_end = matching_close(rest, '(', ')')?;
+    let inner = rest[1..dest_end].trim();
+    let destination = match inner.strip_prefix('<') {
+        Some(bracketed) => bracketed.split('>').next().unwrap_or(""),
+        None => inner.split_whitespace().next().unwrap_or(""),
+    };
+    Some((
+        &s[1..label_end],
+        destination.to_string(),
+        label_end + 1 + dest_end + 1,
+    ))
+}
+
+/// Index of the bracket closing the one `s` starts with
+fn matching_close(s: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+/// Where the emphasis that `delimiter` opens at the start of `s` closes: a
+/// run of exactly as many of the same character, not after whitespace
+fn closing_delimiter(s: &str, delimiter: &str) -> Option<usize> {
+    let c = delimiter.chars().next()?;
+    let mut i = delimiter.len();
+    while i < s.len() {
+        let rest = &s[i..];
+        let run = rest.chars().take_while(|&x| x == c).count();
+        if run == 0 {
+            // Skipping escaped characters along with the backslash
+            let skip = usize::from(rest.starts_with('\\'));
+            i += skip + rest[skip..].chars().next().map_or(0, char::len_utf8);
+            continue;
+        }
+        let after_space = s[..i].chars().next_back().is_some_and(char::is_whitespace);
+        let intraword = c == '_'
+            && rest[run..]
+                .chars()
+                .next()
+                .is_some_and(char::is_alphanumeric);
+        if run == delimiter.len() && i > delimiter.len() && !after_space && !intraword {
+            return Some(i);
+        }
+        i += run;
+    }
+    None
+}
+
+fn parse_inlines_into(out: &mut Inlines, s: &str, style: InlineStyle) {
+    let mut plain = String::new();
+    let mut i = 0;
+    while i < s.len() {
+        let rest = &s[i..];
+        let c = rest.chars().next().unwrap_or_default();
+        let prev = s[..i].chars().next_back();
+        match c {
+            '\\' => match rest[1..].chars().next() {
+                Some('\n') => {
+                    plain.push('\n');
+                    i += 2;
+                }
+                Some(next) if next.is_ascii_punctuation() => {
+                    plain.push(next);
+                    i += 1 + next.len_utf8();
+                }
+                _ => {
+                    plain.push('\\');
+                    i += 1;
+                }
+            },
+            '\n' => {
+                // Two trailing spaces make a hard break, otherwise a space
+                let hard = plain.ends_with("  ");
+                plain.truncate(plain.trim_end_matches(' ').len());
+                plain.push(if hard { '\n' } else { ' ' });
+                i += 1;
+            }
+            '`' => {
+                let run = rest.bytes().take_while(|&b| b == b'`').count();
+                match backtick_run(&rest[run..], run) {
+                    Some(at) => {
+                        let code = rest[run..run + at].replace('\n', " ");
+                        let code = match code.strip_prefix(' ').and_then(|c| c.strip_suffix(' ')) {
+                            Some(inner) if !inner.trim().is_empty() => inner.to_string(),
+                            _ => code,
+                        };
+                        out.push(&std::mem::take(&mut plain), style);
+                        let code_style = InlineStyle {
+                            code: true,
+                            ..style
+                        };
+                        out.push(&code, code_style);
+                        i += run + at + run;
+                    }
+                    None => {
+                        plain.push_str(&rest[..run]);
+                        i += run;
+                    }
+                }
+            }
+            '!' if rest.starts_with("![") => match link_parts(&rest[1..]) {
+                Some((alt, src, len)) => {
+                    let alt = parse_inlines(alt).text;
+                    out.images.push(Image { alt, src });
+                    i += 1 + len;
+                }
+                None => {
+                    plain.push('!');
+                    i += 1;
+                }
+            },
+            '[' => match link_parts(rest) {
+                Some((label, destination, len)) => {
+                    out.push(&std::mem::take(&mut plain), style);
+                    let start = out.text.len();
+                    let link_style = InlineStyle {
+                        link: true,
+                        ..style
+                    };
+                    parse_inlines_into(out, label, link_style);
+                    if out.text.len() > start {
+                        out.links.push((start..out.text.len(), destination));
+                    }
+                    i += len;
+                }
+                None => {
+                    plain.push('[');
+                    i += 1;
+                }
+            },
+            '<' => {
+                let autolink = rest[1..]
+                    .split_once('>')
+                    .map(|(inner, _)| inner)
+                    .filter(|inner| {
+                        !inner.contains(char::is_whitespace)
+                            && (inner.contains("://") || inner.starts_with("mailto:"))
+                    });
+                match autolink {
+                    Some(url) => {
+                        push_link(out, &mut plain, url, style);
+                        i += url.len() + 2;
+                    }
+                    None => {
+                        plain.push('<');
+                        i += 1;
+                    }
+                }
+            }
+            'h' if (rest.starts_with("https://") || rest.starts_with("http://"))
+                && !prev.is_some_and(char::is_alphanumeric) =>
+            {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '<')
+                    .unwrap_or(rest.len());
+                let url = rest[..end].trim_end_matches(['.', ',', ';', ':', '!', '?', ')']);
+                push_link(out, &mut plain, url, style);
+                i += url.len();
+            }
+            '*' | '_' | '~' => {
+                let run = rest.chars().take_while(|&x| x == c).count();
+                let delimiter = &rest[..run.min(if c == '~' { 2 } else { 3 })];
+                let opens = rest[delimiter.len()..]
+                    .chars()
+                    .next()
+                    .is_some_and(|next| !next.is_whitespace())
+                    && !(c == '_' && prev.is_some_and(char::is_alphanumeric))
+                    && !(c == '~' && run < 2)
+                    && run <= 3;
+                let close = opens.then(|| closing_delimiter(rest, delimiter)).flatten();
+                match close {
+                    Some(at) => {
+                        let inner_style = match (c, delimiter.len()) {
+                            ('~', _) => InlineStyle {
+                                strikethrough: true,
+                                ..style
+                            },
+                            (_, 1) => InlineStyle {
+                                emphasis: true,
+                                ..style
+                            },
+                            (_, 2) => InlineStyle {
+                                strong: true,
+                                ..style
+                            },
+                            _ => InlineStyle {
+                                strong: true,
+                                emphasis: true,
+                                ..style
+                            },
+                        };
+                        out.push(&std::mem::take(&mut plain), style);
+                        parse_inlines_into(out, &rest[delimiter.len()..at], inner_style);
+                        i += at + delimiter.len();
+                    }
+                    None => {
+                        plain.push_str(&rest[..run]);
+                        i += run;
+                    }
+                }
+            }
+            c => {
+                plain.push(c);
+                i += c.len_utf8();
+            }
+        }
+    }
+    out.push(&plain, style);
+}
+
+/// Start of the first run of exactly `len` backticks in `s`, which closes
+/// a code span opened by as many
+fn backtick_run(s: &str, len: usize) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let run = bytes[i..].iter().take_while(|&&b| b == b'`').count();
+        if run == len {
+            return Some(i);
+        }
+        i += run.max(1);
+    }
+    None
+}
+
+fn push_link(out: &mut Inlines, plain: &mut String, url: &str, style: InlineStyle) {
+    out.push(&std::mem::take(plain), style);
+    let start = out.text.len();
+    out.push(
+        url,
+        InlineStyle {
+            link: true,
+            ..style
+        },
+    );
+    out.links.push((start..out.text.len(), url.to_string()));
+}
+
+/// Where a link or image points.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinkTarget {
+    Web(String),
+    Local(PathBuf),
+    /// A heading in the same document
+    Anchor(String),
+}
+
+/// Resolves `href` from a document in `base_dir`. Relative paths are taken
+/// from that directory, with any query or fragment dropped.
+pub fn resolve_link(base_dir: &Path, href: &str) -> LinkTarget {
+    if let Some(anchor) = href.strip_prefix('#') {
+        return LinkTarget::Anchor(anchor.to_string());
+    }
+    match url::Url::parse(href) {
+        Ok(url) if url.scheme() == "file" => url
+            .to_file_path()
+            .map(LinkTarget::Local)
+            .unwrap_or_else(|_| LinkTarget::Web(href.to_string())),
+        Ok(_) => LinkTarget::Web(href.to_string()),
+        Err(_) => url::Url::from_directory_path(base_dir)
+            .ok()
+            .and_then(|base| base.join(href).ok())
+            .and_then(|url| url.to_file_path().ok())
+            .map(LinkTarget::Local)
+            .unwrap_or_else(|| LinkTarget::Local(base_dir.join(href))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn styled(inlines: &Inlines) -> Vec<(&str, InlineStyle)> {
+        inlines
+            .runs
+            .iter()
+            .map(|(range, style)| (&inlines.text[range.clone()], *style))
+            .collect()
+    }
+
+    #[test]
+    fn test_inlines() {
+        let inlines = parse_inlines(
+            "Some **bold *and* italic**, `code`, ~~gone~~ and a [link](docs/a.md \"A\")\n\
+             snake_case_name \\*",
+        );
+        assert_eq!(
+            inlines.text,
+            "Some bold and italic, code, gone and a link snake_case_name *"
+        );
+        let strong = InlineStyle {
+            strong: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            styled(&inlines),
+            vec![
+                ("bold ", strong),
+                (
+                    "and",
+                    InlineStyle {
+                        emphasis: true,
+                        ..strong
+                    }
+                ),
+                (" italic", strong),
+                (
+                    "code",
+                    InlineStyle {
+                        code: true,
+                        ..Default::default()
+                    }
+                ),
+                (
+                    "gone",
+                    InlineStyle {
+                        strikethrough: true,
+                        ..Default::default()
+                    }
+                ),
+                (
+                    "link",
+                    InlineStyle {
+                        link: true,
+                        ..Default::default()
+                    }
+                ),
+            ]
+        );
+        assert_eq!(inlines.links, vec![(39..43, "docs/a.md".to_string())]);
+
+        let image = parse_inlines("![The *logo*](img/logo.png) see https://example.com/x.");
+        assert_eq!(
+            image.images,
+            vec![Image {
+                alt: "The logo".to_string(),
+                src: "img/logo.png".to_string()
+            }]
+        );
+        assert_eq!(image.text, " see https://example.com/x.");
+        assert_eq!(image.links[0].1, "https://example.com/x");
+    }
+
+    #[test]
+    fn test_blocks() {
+        let blocks = parse(
+            "# Title #\n\
+             Intro\n\
+             text\n\
+             \n\
+             Setext\n\
+             ---\n\
+             - one\n\
+             - [x] done\n  \
+               1. nested\n\
+             \n\
+             > quoted\n\
+             \n\
+             ```rust\n\
+             fn main() {}\n\
+             ```\n\
+             | Name | Size |\n\
+             | :--- | ---: |\n\
+             | a \\| b | 1 |\n\
+             \n\
+             ***\n",
+        );
+        let text = |s: &str| parse_inlines(s);
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Heading {
+                    level: 1,
+                    text: text("Title")
+                },
+                Block::Paragraph(text("Intro text")),
+                Block::Heading {
+                    level: 2,
+                    text: text("Setext")
+                },
+                Block::List {
+                    start: None,
+                    items: vec![
+                        ListItem {
+                            checked: None,
+                            blocks: vec![Block::Paragraph(text("one"))]
+                        },
+                        ListItem {
+                            checked: Some(true),
+                            blocks: vec![
+                                Block::Paragraph(text("done")),
+                                Block::List {
+                                    start: Some(1),
+                                    items: vec![ListItem {
+                                        checked: None,
+                                        blocks: vec![Block::Paragraph(text("nested"))]
+                                    }]
+                                }
+                            ]
+                        },
+                    ]
+                },
+                Block::Quote(vec![Block::Paragraph(text("quoted"))]),
+                Block::Code {
+                    language: Some("rust".to_string()),
+                    lines: vec!["fn main() {}".to_string()]
+                },
+                Block::Table {
+                    alignments: vec![Alignment::Left, Alignment::Right],
+                    header: vec![text("Name"), text("Size")],
+                    rows: vec![vec![text("a | b"), text("1")]],
+                },
+                Block::Rule,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_link() {
+        let base = Path::new("/notes/projects");
+        assert_eq!(
+            resolve_link(base, "../todo%20list.md#today"),
+            LinkTarget::Local(PathBuf::from("/notes/todo list.md"))
+        );
+        assert_eq!(
+            resolve_link(base, "/etc/hosts"),
+            LinkTarget::Local(PathBuf::from("/etc/hosts"))
+        );
+        assert_eq!(
+            resolve_link(base, "https://example.com"),
+            LinkTarget::Web("https://example.com".to_string())
+        );
+        assert_eq!(
+            resolve_link(base, "#setup"),
+            LinkTarget::Anchor("setup".to_string())
+        );
+    }
+}
@@ -0,0 +1,503 @@
+// Parsed Markdown (see `markdown`) rendered in theme colors, for the
+// preview sidebar and the larger preview overlay. Links to local files
+// reveal them in the workspace and web links open in the browser. Remote
+// images aren't fetched; their alt text links to them instead.
+use crate::app_state::workspace::{ActiveOverlay, Workspace};
+use crate::fs_ops::text_preview::{self, TextPreviewError};
+use crate::theme_engine::palette::M3Palette;
+use crate::theme_engine::theme::ThemeContext;
+use crate::ui_components::chips::Chip;
+use crate::ui_components::markdown::{
+    self, Alignment, Block, Image, InlineStyle, Inlines, LinkTarget,
+};
+use crate::ui_components::syntax::{self, Highlighter, CODE_FONT};
+use crate::ui_components::toast::ToastKind;
+use gpui::prelude::*;
+use gpui::*;
+use std::path::{Path, PathBuf};
+
+/// Lines of a document read for the overlay
+pub const OVERLAY_LINES: usize = 5000;
+
+pub struct MarkdownDocument {
+    pub blocks: Vec<Block>,
+    /// More of the file wasn't read
+    pub truncated: bool,
+}
+
+/// The first `max_lines` lines of the document at `path`, parsed
+pub fn load(path: &Path, max_lines: usize) -> Result<MarkdownDocument, TextPreviewError> {
+    let preview = text_preview::load_lines(path, max_lines)?;
+    Ok(MarkdownDocument {
+        blocks: markdown::parse(&preview.lines.join("\n")),
+        truncated: preview.truncated,
+    })
+}
+
+/// Follows a clicked link: a local file is revealed in the workspace, a
+/// folder opened, and a web link opened in the browser.
+pub fn open_link(workspace: &Entity<Workspace>, target: &LinkTarget, cx: &mut App) {
+    match target {
+        LinkTarget::Local(path) => workspace.update(cx, |ws, cx| {
+            if !path.exists() {
+                ws.show_toast(
+                    format!("{} doesn't exist", path.display()),
+                    ToastKind::Error,
+                    cx,
+                );
+                return;
+            }
+            if matches!(ws.active_overlay, Some(ActiveOverlay::MarkdownPreview(_))) {
+                ws.dismiss_overlay(cx);
+            }
+            ws.reveal(path.clone(), cx);
+        }),
+        LinkTarget::Web(url) => {
+            if let Err(e) = open::that(url) {
+                workspace.update(cx, |ws, cx| {
+                    ws.show_toast(format!("Failed to open link: {}", e), ToastKind::Error, cx)
+                });
+            }
+        }
+        // Scrolling to a heading isn't supported
+        LinkTarget::Anchor(_) => {}
+    }
+}
+
+/// Renders a document's blocks. `id` keeps the element ids of documents
+/// shown at the same time apart.
+pub struct MarkdownRenderer<'a> {
+    id: &'static str,
+    palette: &'a M3Palette,
+    /// Directory of the document, for relative links and images
+    base_dir: &'a Path,
+    workspace: &'a Entity<Workspace>,
+    next_id: usize,
+}
+
+impl<'a> MarkdownRenderer<'a> {
+    pub fn new(
+        id: &'static str,
+        palette: &'a M3Palette,
+        base_dir: &'a Path,
+        workspace: &'a Entity<Workspace>,
+    ) -> Self {
+        Self {
+            id,
+            palette,
+            base_dir,
+            workspace,
+            next_id: 0,
+        }
+    }
+
+    fn element_id(&mut self) -> ElementId {
+        self.next_id += 1;
+        ElementId::named_usize(self.id, self.next_id)
+    }
+
+    pub fn render(&mut self, blocks: &[Block]) -> Div {
+        let children: Vec<AnyElement> = blocks
+            .iter()
+            .map(|block| self.render_block(block))
+            .collect();
+        div().flex().flex_col().gap_3().w_full().children(children)
+    }
+
+    fn render_block(&mut self, block: &Block) -> AnyElement {
+        let palette = self.palette;
+        match block {
+            Block::Heading { level, text } => {
+                let heading = div()
+                    .font_weight(FontWeight::BOLD)
+                    .child(self.render_inlines(text));
+                let heading = match level {
+                    1 => heading.text_2xl(),
+                    2 => heading.text_xl(),
+                    3 => heading.text_lg(),
+                    _ => heading.text_base(),
+                };
+                if *level <= 2 {
+                    heading
+                        .pb_1()
+                        .border_b_1()
+                        .border_color(palette.outline_variant)
+                        .into_any_element()
+                } else {
+                    heading.into_any_element()
+                }
+            }
+            Block::Paragraph(text) => self.render_inlines(text),
+            Block::List { start, items } => {
+                let rows: Vec<AnyElement> = items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        let marker = match (item.checked, start) {
+                            (Some(true), _) => "☑".to_string(),
+                            (Some(false), _) => "☐".to_string(),
+                            (None, Some(start)) => format!("{}.", start + i as u64),
+                            (None, None) => "•".to_string(),
+                        };
+                        div()
+                            .flex()
+                            .flex_row()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .flex_none()
+                                    .min_w(px(16.0))
+                                    .text_color(palette.on_surface_variant)
+                                    .child(marker),
+                            )
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .min_w(px(0.0))
+                                    .child(self.render(&item.blocks).gap_1()),
+                            )
+                            .into_any_element()
+                    })
+                    .collect();
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_1()
+                    .children(rows)
+                    .into_any_element()
+            }
+            Block::Code { language, lines } => self.render_code(language.as_deref(), lines),
+            Block::Quote(blocks) => div()
+                .pl_3()
+                .border_l_2()
+                .border_color(palette.primary)
+                .text_color(palette.on_surface_variant)
+                .child(self.render(blocks))
+                .into_any_element(),
+            Block::Table {
+                alignments,
+                header,
+                rows,
+            } => self.render_table(alignments, header, rows),
+            Block::Rule => div()
+                .h_px()
+                .w_full()
+                .bg(palette.outline_variant)
+                .into_any_element(),
+        }
+    }
+
+    fn inline_style(&self, style: InlineStyle) -> HighlightStyle {
+        let mut highlight = HighlightStyle::default();
+        if style.strong {
+            highlight.font_weight = Some(FontWeight::BOLD);
+        }
+        if style.emphasis {
+            highlight.font_style = Some(FontStyle::Italic);
+        }
+        if style.code {
+            highlight.color = Some(self.palette.tertiary.into());
+            highlight.background_color = Some(self.palette.surface_container_highest.into());
+        }
+        if style.strikethrough {
+            highlight.strikethrough = Some(StrikethroughStyle {
+                thickness: px(1.0),
+                color: None,
+            });
+        }
+        if style.link {
+            highlight.color = Some(self.palette.primary.into());
+            highlight.underline = Some(UnderlineStyle {
+                thickness: px(1.0),
+                color: None,
+                wavy: false,
+            });
+        }
+        highlight
+    }
+
+    fn render_inlines(&mut self, inlines: &Inlines) -> AnyElement {
+        let highlights: Vec<_> = inlines
+            .runs
+            .iter()
+            .map(|(range, style)| (range.clone(), self.inline_style(*style)))
+            .collect();
+        let styled = StyledText::new(inlines.text.clone()).with_highlights(highlights);
+        let text = if inlines.links.is_empty() {
+            styled.into_any_element()
+        } else {
+            let targets: Vec<LinkTarget> = inlines
+                .links
+                .iter()
+                .map(|(_, href)| markdown::resolve_link(self.base_dir, href))
+                .collect();
+            let ranges = inlines
+                .links
+                .iter()
+                .map(|(range, _)| range.clone())
+                .collect();
+            let workspace = self.workspace.clone();
+            InteractiveText::new(self.element_id(), styled)
+                .on_click(ranges, move |ix, _, cx| {
+                    if let Some(target) = targets.get(ix) {
+                        open_link(&workspace, target, cx);
+                    }
+                })
+                .into_any_element()
+        };
+        if inlines.images.is_empty() {
+            return text;
+        }
+
+        let images: Vec<AnyElement> = inlines
+            .images
+            .iter()
+            .map(|image| self.render_image(image))
+            .collect();
+        div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .when(!inlines.text.trim().is_empty(), |this| this.child(text))
+            .children(images)
+            .into_any_element()
+    }
+
+    fn render_image(&mut self, image: &Image) -> AnyElement {
+        if let LinkTarget::Local(path) = markdown::resolve_link(self.base_dir, &image.src) {
+            return img(path)
+                .max_w_full()
+                .object_fit(ObjectFit::Contain)
+                .into_any_element();
+        }
+        let label = if image.alt.is_empty() {
+            image.src.clone()
+        } else {
+            image.alt.clone()
+        };
+        let link = InlineStyle {
+            link: true,
+            ..Default::default()
+        };
+        self.render_inlines(&Inlines {
+            runs: vec![(0..label.len(), link)],
+            links: vec![(0..label.len(), image.src.clone())],
+            text: label,
+            images: Vec::new(),
+        })
+    }
+
+    fn render_code(&mut self, language: Option<&str>, lines: &[String]) -> AnyElement {
+        let palette = self.palette;
+        let mut highlighter = Highlighter::new(language.and_then(syntax::syntax_for_language));
+        let lines: Vec<StyledText> = lines
+            .iter()
+            .map(|line| {
+                let highlights: Vec<_> = highlighter
+                    .highlight(line)
+                    .into_iter()
+                    .map(|(range, kind)| (range, kind.style(palette)))
+                    .collect();
+                // An empty line would have no height
+                let text = if line.is_empty() { " " } else { line };
+                StyledText::new(text.to_string()).with_highlights(highlights)
+            })
+            .collect();
+        div()
+            .id(self.element_id())
+            .flex()
+            .flex_col()
+            .w_full()
+            .overflow_x_scroll()
+            .p_2()
+            .rounded_md()
+            .bg(palette.surface_container_low)
+            .font_family(CODE_FONT)
+            .text_xs()
+            .whitespace_nowrap()
+            .children(lines)
+            .into_any_element()
+    }
+
+    fn render_table(
+        &mut self,
+        alignments: &[Alignment],
+        header: &[Inlines],
+        rows: &[Vec<Inlines>],
+    ) -> AnyElement {
+        let palette = self.palette;
+        let mut row_elements = Vec::new();
+        for (i, cells) in std::iter::once(header)
+            .chain(rows.iter().map(Vec::as_slice))
+            .enumerate()
+        {
+            let cells: Vec<AnyElement> = cells
+                .iter()
+                .zip(alignments)
+                .map(|(cell, alignment)| {
+                    let cell = div()
+                        .flex_1()
+                        .min_w(px(0.0))
+                        .flex()
+                        .px_2()
+                        .py_1()
+                        .child(self.render_inlines(cell));
+                    match alignment {
+                        Alignment::Left => cell.justify_start(),
+                        Alignment::Center => cell.justify_center(),
+                        Alignment::Right => cell.justify_end(),
+                    }
+                    .into_any_element()
+                })
+                .collect();
+            let row = div().flex().flex_row().w_full().children(cells);
+            row_elements.push(if i == 0 {
+                row.font_weight(FontWeight::BOLD)
+                    .bg(palette.surface_container)
+            } else {
+                row.border_t_1().border_color(palette.outline_variant)
+            });
+        }
+        div()
+            .flex()
+            .flex_col()
+            .w_full()
+            .border_1()
+            .border_color(palette.outline_variant)
+            .rounded_md()
+            .overflow_hidden()
+            .children(row_elements)
+            .into_any_element()
+    }
+}
+
+/// The larger preview overlay of a Markdown file.
+pub struct MarkdownPreview {
+    workspace: Entity<Workspace>,
+    path: PathBuf,
+    /// `None` while loading
+    document: Option<Result<MarkdownDocument, TextPreviewError>>,
+}
+
+impl MarkdownPreview {
+    pub fn new(workspace: Entity<Workspace>, path: PathBuf, cx: &mut Context<Self>) -> Self {
+        let executor = cx.background_executor().clone();
+        let task_path = path.clone();
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                let document = executor
+                    .spawn(async move { load(&task_path, OVERLAY_LINES) })
+                    .await;
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |this, cx| {
+                        this.document = Some(document);
+                        cx.notify();
+                    });
+                });
+            }
+        })
+        .detach();
+
+        Self {
+            workspace,
+            path,
+            document: None,
+        }
+    }
+}
+
+impl Render for MarkdownPreview {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let palette = cx.theme().palette.clone();
+        let name = self
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let base_dir = self.path.parent().unwrap_or(Path::new("/"));
+
+        let note = |message: String| {
+            div()
+                .text_sm()
+                .text_color(palette.on_surface_variant)
+                .child(message)
+        };
+        let content = match &self.document {
+            None => note("Loading preview…".to_string()),
+            Some(Ok(document)) => {
+                let mut renderer =
+                    MarkdownRenderer::new("markdown_overlay", &palette, base_dir, &self.workspace);
+                let rendered = renderer.render(&document.blocks);
+                if document.truncated {
+                    rendered.child(note(format!("Showing the first {} lines", OVERLAY_LINES)))
+                } else {
+                    rendered
+                }
+            }
+            Some(Err(e)) => note(e.to_string()),
+        };
+
+        let workspace_scrim = self.workspace.clone();
+        let workspace_close = self.workspace.clone();
+        div()
+            .id("markdown_preview_scrim")
+            .absolute()
+            .size_full()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(gpui::rgba(0x00000080))
+            .on_click(move |_, _, cx| {
+                workspace_scrim.update(cx, |ws, cx| ws.dismiss_overlay(cx));
+            })
+            .child(
+                div()
+                    .id("markdown_preview_card")
+                    .on_click(|_, _, cx| cx.stop_propagation())
+                    .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                    .on_mouse_down(MouseButton::Right, |_, _, cx| cx.stop_propagation())
+                    .w(relative(0.7))
+                    .h(relative(0.85))
+                    .flex()
+                    .flex_col()
+                    .bg(palette.surface)
+                    .rounded_3xl()
+                    .shadow_xl()
+                    .overflow_hidden()
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .justify_between()
+                            .px_6()
+                            .py_4()
+                            .border_b_1()
+                            .border_color(palette.outline_variant)
+                            .child(
+                                div()
+                                    .text_lg()
+                                    .font_weight(FontWeight::BOLD)
+                                    .text_color(palette.on_surface)
+                                    .child(name),
+                            )
+                            .child(Chip::new("markdown_preview_close", "Close").on_click(
+                                move |_, _, cx| {
+                                    workspace_close.update(cx, |ws, cx| ws.dismiss_overlay(cx));
+                                },
+                            )),
+                    )
+                    .child(
+                        div()
+                            .id("markdown_preview_body")
+                            .flex_1()
+                            .overflow_y_scroll()
+                            .px_6()
+                            .py_4()
+                            .text_color(palette.on_surface)
+                            .child(content),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,291 @@
+// Rubber-band selection in the grid, list and details views. The band is
+// kept in list content coordinates so it stays anchored while the list
+// scrolls, and entries are hit-tested from the row geometry since the
+// virtualised list only renders the rows in view. The grouped view renders
+// every entry, so its entries are hit-tested where they were laid out.
+use gpui::prelude::*;
+use gpui::*;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Pointer distance from the top or bottom edge that scrolls the list
+pub const AUTOSCROLL_EDGE: f32 = 32.0;
+/// Fastest auto-scroll, in pixels per frame, with the pointer past the edge
+pub const AUTOSCROLL_MAX_STEP: f32 = 24.0;
+
+/// How the band combines with the selection it started from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarqueeMode {
+    /// Select only what the band covers
+    Replace,
+    /// Ctrl: add what the band covers
+    Add,
+    /// Shift: extend from the selection anchor to cover the band
+    Extend,
+}
+
+impl MarqueeMode {
+    pub fn from_modifiers(modifiers: &Modifiers) -> Self {
+        if modifiers.control || modifiers.platform {
+            MarqueeMode::Add
+        } else if modifiers.shift {
+            MarqueeMode::Extend
+        } else {
+            MarqueeMode::Replace
+        }
+    }
+}
+
+/// A band being dragged out over the list.
+pub struct Marquee {
+    /// Where the drag started, in content coordinates
+    pub start: Point<Pixels>,
+    /// Pointer position, in window coordinates
+    pub pointer: Point<Pixels>,
+    pub mode: MarqueeMode,
+    /// Selection when the drag started, kept in `Add` and `Extend` modes
+    pub base: HashSet<PathBuf>,
+    /// Index of the selection anchor, for `Extend`
+    pub anchor: Option<usize>,
+}
+
+impl Marquee {
+    /// Entries selected with the band covering `hits`, indices in list
+    /// order that `path` looks up.
+    pub fn selection(
+        &self,
+        hits: &[usize],
+        path: impl Fn(usize) -> Option<PathBuf>,
+    ) -> HashSet<PathBuf> {
+        let covered: Vec<usize> = match (self.mode, self.anchor, hits.first(), hits.last()) {
+            (MarqueeMode::Extend, Some(anchor), Some(&first), Some(&last)) => {
+                (anchor.min(first)..=anchor.max(last)).collect()
+            }
+            _ => hits.to_vec(),
+        };
+        let mut selection = match self.mode {
+            MarqueeMode::Replace => HashSet::new(),
+            MarqueeMode::Add | MarqueeMode::Extend => self.base.clone(),
+        };
+        selection.extend(covered.into_iter().filter_map(path));
+        selection
+    }
+}
+
+/// Where entries sit in the list's content: rows of `cols` items, each in
+/// a slot of `slot_width` with `margin` around the item.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ItemLayout {
+    pub cols: usize,
+    pub row_height: f32,
+    pub slot_width: f32,
+    pub margin: f32,
+}
+
+impl ItemLayout {
+    /// Left, top, right and bottom of item `ix`
+    fn item_edges(&self, ix: usize) -> (f32, f32, f32, f32) {
+        let row = (ix / self.cols) as f32;
+        let col = (ix % self.cols) as f32;
+        let left = col * self.slot_width + self.margin;
+        let top = row * self.row_height + self.margin;
+        (
+            left,
+            top,
+            left + self.slot_width - 2.0 * self.margin,
+            top + self.row_height - 2.0 * self.margin,
+        )
+    }
+
+    /// Items, out of `count`, touched by the rectangle between two corners
+    /// in content coordinates. Only the rows it spans are looked at.
+    pub fn items_in(&self, a: Point<Pixels>, b: Point<Pixels>, count: usize) -> Vec<usize> {
+        if self.cols == 0 || self.row_height <= 0.0 || count == 0 {
+            return Vec::new();
+        }
+        let (ax, ay) = (f32::from(a.x), f32::from(a.y));
+        let (bx, by) = (f32::from(b.x), f32::from(b.y));
+        let (left, right) = (ax.min(bx), ax.max(bx));
+        let (top, bottom) = (ay.min(by), ay.max(by));
+
+        let rows = count.div_ceil(self.cols);
+        let first_row = (top.max(0.0) / self.row_height) as usize;
+        let last_row = ((bottom.max(0.0) / self.row_height) as usize).min(rows - 1);
+        (first_row..=last_row)
+            .flat_map(|row| row * self.cols..((row + 1) * self.cols).min(count))
+            .filter(|&ix| {
+                let (l, t, r, b) = self.item_edges(ix);
+                l <= right && r >= left && t <= bottom && b >= top
+            })
+            .collect()
+    }
+}
+
+/// Items, by index into `items`, touched by the rectangle between two
+/// corners; `items` are their bounds in content coordinates as laid out.
+pub fn items_touching(items: &[Bounds<Pixels>], a: Point<Pixels>, b: Point<Pixels>) -> Vec<usize> {
+    let band = Bounds::from_corners(
+        point(a.x.min(b.x), a.y.min(b.y)),
+        point(a.x.max(b.x), a.y.max(b.y)),
+    );
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| {
+            item.left() <= band.right()
+                && item.right() >= band.left()
+                && item.top() <= band.bottom()
+                && item.bottom() >= band.top()
+        })
+        .map(|(ix, _)| ix)
+        .collect()
+}
+
+/// Scroll step for the pointer at `y` in a viewport from `top` to
+/// `bottom`: negative towards the top, zero away from the edges.
+pub fn autoscroll_step(y: f32, top: f32, bottom: f32) -> f32 {
+    let speed = |depth: f32| (depth / AUTOSCROLL_EDGE).min(1.0) * AUTOSCROLL_MAX_STEP;
+    if y < top + AUTOSCROLL_EDGE {
+        -speed(top + AUTOSCROLL_EDGE - y)
+    } else if y > bottom - AUTOSCROLL_EDGE {
+        speed(y - (bottom - AUTOSCROLL_EDGE))
+    } else {
+        0.0
+    }
+}
+
+/// The translucent band, between two corners in the list's own coordinates.
+pub fn render_band(a: Point<Pixels>, b: Point<Pixels>, color: Hsla) -> Div {
+    let origin = point(a.x.min(b.x), a.y.min(b.y));
+    let size = size((a.x - b.x).abs(), (a.y - b.y).abs());
+    div()
+        .absolute()
+        .left(origin.x)
+        .top(origin.y)
+        .w(size.width)
+        .h(size.height)
+        .border_1()
+        .border_color(color)
+        .bg(color.opacity(0.15))
+}
+
+#[cfg(test)]
+mod tests {
+    // Not `super::*`: gpui's glob export would shadow `#[test]`
+    use super::{
+        autoscroll_step, items_touching, ItemLayout, Marquee, MarqueeMode, AUTOSCROLL_MAX_STEP,
+    };
+    use gpui::{point, px, size, Bounds};
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_items_in_list() {
+        let list = ItemLayout {
+            cols: 1,
+            row_height: 40.0,
+            slot_width: 800.0,
+            margin: 0.0,
+        };
+        // Rows 1 to 3, from anywhere across the row
+        assert_eq!(
+            list.items_in(point(px(500.0), px(45.0)), point(px(20.0), px(130.0)), 10),
+            vec![1, 2, 3]
+        );
+        // Past the end of the list
+        assert_eq!(
+            list.items_in(point(px(0.0), px(330.0)), point(px(10.0), px(900.0)), 10),
+            vec![8, 9]
+        );
+        assert!(list
+            .items_in(point(px(0.0), px(0.0)), point(px(9.0), px(9.0)), 0)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_items_in_grid() {
+        let grid = ItemLayout {
+            cols: 4,
+            row_height: 100.0,
+            slot_width: 100.0,
+            margin: 4.0,
+        };
+        // Columns 1-2 of rows 0-1
+        assert_eq!(
+            grid.items_in(point(px(150.0), px(50.0)), point(px(250.0), px(150.0)), 10),
+            vec![1, 2, 5, 6]
+        );
+        // The gap between two items touches neither
+        assert!(grid
+            .items_in(point(px(97.0), px(10.0)), point(px(103.0), px(20.0)), 10)
+            .is_empty());
+        // Row 2 only has items 8 and 9
+        assert_eq!(
+            grid.items_in(point(px(0.0), px(250.0)), point(px(399.0), px(260.0)), 10),
+            vec![8, 9]
+        );
+    }
+
+    #[test]
+    fn test_items_touching_groups() {
+        // Two groups of a grid two items wide, each under a 40px header
+        let item = |x: f32, y: f32| Bounds::new(point(px(x), px(y)), size(px(90.0), px(90.0)));
+        let items = [
+            item(5.0, 45.0),
+            item(105.0, 45.0),
+            item(5.0, 145.0),
+            item(5.0, 285.0),
+            item(105.0, 285.0),
+        ];
+        // From the first group's second row into the next group
+        assert_eq!(
+            items_touching(
+                &items,
+                point(px(150.0), px(300.0)),
+                point(px(20.0), px(150.0))
+            ),
+            vec![2, 3, 4]
+        );
+        // Over a header only
+        assert!(items_touching(
+            &items,
+            point(px(0.0), px(245.0)),
+            point(px(200.0), px(280.0))
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_marquee_selection() {
+        let paths: Vec<PathBuf> = (0..6).map(|i| PathBuf::from(format!("/d/{}", i))).collect();
+        let mut marquee = Marquee {
+            start: point(px(0.0), px(0.0)),
+            pointer: point(px(0.0), px(0.0)),
+            mode: MarqueeMode::Replace,
+            base: HashSet::from([paths[0].clone()]),
+            anchor: Some(0),
+        };
+        let path = |ix: usize| paths.get(ix).cloned();
+        let pick = |ixs: &[usize]| {
+            ixs.iter()
+                .map(|&i| paths[i].clone())
+                .collect::<HashSet<_>>()
+        };
+
+        assert_eq!(marquee.selection(&[3, 4], path), pick(&[3, 4]));
+        marquee.mode = MarqueeMode::Add;
+        assert_eq!(marquee.selection(&[3, 4], path), pick(&[0, 3, 4]));
+        marquee.mode = MarqueeMode::Extend;
+        assert_eq!(marquee.selection(&[3, 4], path), pick(&[0, 1, 2, 3, 4]));
+        assert_eq!(marquee.selection(&[], path), pick(&[0]));
+    }
+
+    #[test]
+    fn test_autoscroll_step() {
+        assert_eq!(autoscroll_step(300.0, 100.0, 500.0), 0.0);
+        assert!(autoscroll_step(110.0, 100.0, 500.0) < 0.0);
+        assert!(autoscroll_step(490.0, 100.0, 500.0) > 0.0);
+        // Capped once the pointer leaves the viewport
+        assert_eq!(autoscroll_step(900.0, 100.0, 500.0), AUTOSCROLL_MAX_STEP);
+    }
+}
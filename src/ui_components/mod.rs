@@ -1,16 +1,26 @@
 pub mod breadcrumb;
 pub mod chips;
+pub mod column_view;
+pub mod command_palette;
 pub mod context_menu;
 pub mod dashboard;
 pub mod details_dialog;
+pub mod details_view;
+pub mod drag_drop;
 pub mod file_list;
+pub mod hex_view;
 pub mod loader;
+pub mod markdown;
+pub mod markdown_view;
+pub mod marquee;
+pub mod name_dialog;
 pub mod navigation_toolbar;
 pub mod open_with_dialog;
 pub mod preview_sidebar;
 pub mod progress;
 pub mod settings_window;
 pub mod sidebar;
+pub mod syntax;
 pub mod tab_bar;
 pub mod toast;
 pub mod universal_picker_modal;
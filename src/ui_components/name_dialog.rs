@@ -0,0 +1,147 @@
+// A small dialog asking for a name, such as a smart folder's. The name is
+// checked as it's typed, and the reason it can't be used shows under the
+// field, as when renaming a file in place.
+use crate::app_state::workspace::RenameState;
+use crate::theme_engine::theme::ThemeContext;
+use crate::ui_components::chips::Chip;
+use crate::ui_components::file_list::{copy_name_selection, name_field_edit, render_rename_field};
+use gpui::prelude::*;
+use gpui::*;
+
+/// Why a name can't be used, if it can't
+type Validate = Box<dyn Fn(&str, &App) -> Option<String>>;
+
+pub struct NameDialog {
+    title: SharedString,
+    confirm_label: SharedString,
+    field: RenameState,
+    validate: Validate,
+    focus_handle: FocusHandle,
+    should_focus: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NameDialogEvent {
+    Confirm(String),
+    Close,
+}
+
+impl EventEmitter<NameDialogEvent> for NameDialog {}
+
+impl NameDialog {
+    /// Starts out with `name`, all selected so typing replaces it
+    pub fn new(
+        title: impl Into<SharedString>,
+        confirm_label: impl Into<SharedString>,
+        name: String,
+        validate: impl Fn(&str, &App) -> Option<String> + 'static,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            confirm_label: confirm_label.into(),
+            field: RenameState::for_text(name),
+            validate: Box::new(validate),
+            focus_handle: cx.focus_handle(),
+            should_focus: true,
+        }
+    }
+
+    fn edit(&mut self, edit: impl FnOnce(&mut RenameState), cx: &mut Context<Self>) {
+        edit(&mut self.field);
+        self.field.error = (self.validate)(&self.field.text, cx);
+        cx.notify();
+    }
+
+    fn confirm(&mut self, cx: &mut Context<Self>) {
+        self.field.error = (self.validate)(&self.field.text, cx);
+        if self.field.error.is_none() {
+            cx.emit(NameDialogEvent::Confirm(self.field.text.trim().to_string()));
+        }
+        cx.notify();
+    }
+
+    fn cancel(&mut self, cx: &mut Context<Self>) {
+        cx.emit(NameDialogEvent::Close);
+    }
+
+    fn handle_key_down(&mut self, event: &KeyDownEvent, cx: &mut Context<Self>) {
+        let keystroke = &event.keystroke;
+        let command = keystroke.modifiers.control || keystroke.modifiers.platform;
+        match keystroke.key.as_str() {
+            "enter" => self.confirm(cx),
+            "escape" => self.cancel(cx),
+            "c" if command => copy_name_selection(&self.field, cx),
+            _ => {
+                if let Some(edit) = name_field_edit(keystroke, cx) {
+                    self.edit(edit, cx);
+                }
+            }
+        }
+        cx.stop_propagation();
+    }
+}
+
+impl Render for NameDialog {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if self.should_focus {
+            self.should_focus = false;
+            window.focus(&self.focus_handle, cx);
+        }
+        let palette = cx.theme().palette.clone();
+
+        div()
+            .id("name_dialog_overlay")
+            .absolute()
+            .size_full()
+            .bg(rgba(0x00000080))
+            .flex()
+            .items_center()
+            .justify_center()
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|this, _, _, cx| this.cancel(cx)),
+            )
+            .child(
+                div()
+                    .w(px(400.0))
+                    .p_6()
+                    .flex()
+                    .flex_col()
+                    .gap_4()
+                    .bg(palette.surface)
+                    .rounded_3xl()
+                    .shadow_xl()
+                    .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                    .track_focus(&self.focus_handle)
+                    .on_key_down(cx.listener(|this, event: &KeyDownEvent, _, cx| {
+                        this.handle_key_down(event, cx)
+                    }))
+                    .child(
+                        div()
+                            .text_lg()
+                            .font_weight(FontWeight::BOLD)
+                            .text_color(palette.on_surface)
+                            .child(self.title.clone()),
+                    )
+                    .child(render_rename_field(&self.field, &palette).h_8().text_sm())
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap_2()
+                            // Clear of the error bubble under the field
+                            .when(self.field.error.is_some(), |d| d.mt_12())
+                            .child(
+                                Chip::new("name_dialog_cancel", "Cancel")
+                                    .on_click(cx.listener(|this, _, _, cx| this.cancel(cx))),
+                            )
+                            .child(
+                                Chip::new("name_dialog_confirm", self.confirm_label.clone())
+                                    .selected(true)
+                                    .on_click(cx.listener(|this, _, _, cx| this.confirm(cx))),
+                            ),
+                    ),
+            )
+    }
+}
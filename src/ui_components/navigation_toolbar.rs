@@ -11,6 +11,7 @@ impl NavigationToolbar {
     pub fn render<V: 'static>(
         workspace: Entity<Workspace>,
         search_focus: FocusHandle,
+        location_focus: FocusHandle,
         cx: &mut Context<V>,
         window: &Window,
     ) -> impl IntoElement {
@@ -23,11 +24,38 @@ impl NavigationToolbar {
                 ws.can_go_forward(),
                 ws.current_path.clone(),
                 ws.filter_query.clone(),
-                ws.group_by_type,
+                ws.view.group_by_type,
                 ws.search_options.clone(),
             )
         };
         let is_searching = search_focus.is_focused(window);
+        // The location bar replaces the breadcrumb while it has focus
+        let location_input = workspace
+            .read(cx)
+            .location_input
+            .clone()
+            .filter(|_| location_focus.is_focused(window));
+        // Offer saving only for a finished search that isn't a smart folder yet
+        let can_save_search = {
+            let ws = workspace.read(cx);
+            ws.search_results.is_some() && ws.active_smart_folder.is_none()
+        };
+
+        // Branch and ahead/behind of the repository being browsed
+        let git_branch = workspace.read(cx).git_status.as_ref().map(|status| {
+            let info = &status.branch;
+            let mut label = info
+                .branch
+                .clone()
+                .unwrap_or_else(|| "detached".to_string());
+            if info.ahead > 0 {
+                label.push_str(&format!(" ↑{}", info.ahead));
+            }
+            if info.behind > 0 {
+                label.push_str(&format!(" ↓{}", info.behind));
+            }
+            label
+        });
 
         let ws_back = workspace.clone();
         let ws_fwd = workspace.clone();
@@ -35,6 +63,15 @@ impl NavigationToolbar {
 
         let ws_recursive = workspace.clone();
         let ws_content = workspace.clone();
+        let ws_ignore = workspace.clone();
+        let ws_save = workspace.clone();
+        let (sort_options, view_mode) = {
+            let view = &workspace.read(cx).view;
+            (view.sort, view.view_mode.clone())
+        };
+        let ws_sort_key = workspace.clone();
+        let ws_sort_dir = workspace.clone();
+        let ws_sort_folders = workspace.clone();
 
         div()
             .flex()
@@ -79,10 +116,33 @@ impl NavigationToolbar {
             })
             // Breadcrumb (Flex Grow)
             .child(
-                div()
-                    .flex_grow()
-                    .child(Breadcrumb::render(current_path, workspace.clone(), cx)),
+                div().flex_grow().child(match location_input {
+                    Some(text) => Breadcrumb::render_location_input(
+                        text,
+                        &location_focus,
+                        workspace.clone(),
+                        cx,
+                    )
+                    .into_any_element(),
+                    None => {
+                        Breadcrumb::render(current_path, workspace.clone(), cx).into_any_element()
+                    }
+                }),
             )
+            .children(git_branch.map(|label| {
+                div()
+                    .flex()
+                    .flex_none()
+                    .items_center()
+                    .h_8()
+                    .px_3()
+                    .rounded_lg()
+                    .border_1()
+                    .border_color(palette.outline)
+                    .text_sm()
+                    .text_color(palette.on_surface_variant)
+                    .child(label)
+            }))
             // Search Input Area
             .child(
                 div().flex().items_center().gap_2().child(
@@ -197,8 +257,99 @@ impl NavigationToolbar {
                             .on_click(cx.listener(move |_this, _, _, cx| {
                                 ws_content.update(cx, |ws, cx| ws.toggle_search_content(cx));
                             })),
+                    )
+                    .child(
+                        Chip::new("opt_ignore", "Gitignore")
+                            .filter()
+                            .selected(search_options.respect_ignore)
+                            .on_click(cx.listener(move |_this, _, _, cx| {
+                                ws_ignore.update(cx, |ws, cx| ws.toggle_search_ignore(cx));
+                            })),
+                    )
+                    .children(if can_save_search {
+                        Some(
+                            Chip::new("save_search", "Save")
+                                .icon("star")
+                                .on_click(cx.listener(move |_this, _, _, cx| {
+                                    ws_save.update(cx, |ws, cx| ws.save_current_search(cx));
+                                })),
+                        )
+                    } else {
+                        None
+                    }),
+            )
+            // Sort Order
+            .child(
+                div()
+                    .flex()
+                    .gap_1()
+                    .child(
+                        Chip::new("sort_key", format!("Sort: {}", sort_options.key.label()))
+                            .on_click(cx.listener(move |_this, _, _, cx| {
+                                ws_sort_key.update(cx, |ws, cx| {
+                                    let mut options = ws.view.sort;
+                                    options.key = options.key.next();
+                                    ws.set_sort(options, cx);
+                                });
+                            })),
+                    )
+                    .child(
+                        Chip::new(
+                            "sort_direction",
+                            if sort_options.descending {
+                                "↓"
+                            } else {
+                                "↑"
+                            },
+                        )
+                        .on_click(cx.listener(move |_this, _, _, cx| {
+                            ws_sort_dir.update(cx, |ws, cx| {
+                                let mut options = ws.view.sort;
+                                options.descending = !options.descending;
+                                ws.set_sort(options, cx);
+                            });
+                        })),
+                    )
+                    .child(
+                        Chip::new("sort_folders_first", "Folders first")
+                            .filter()
+                            .selected(sort_options.folders_first)
+                            .on_click(cx.listener(move |_this, _, _, cx| {
+                                ws_sort_folders.update(cx, |ws, cx| {
+                                    let mut options = ws.view.sort;
+                                    options.folders_first = !options.folders_first;
+                                    ws.set_sort(options, cx);
+                                });
+                            })),
                     ),
             )
+            // View Mode Toggle (per directory)
+            .child({
+                let ws_view = workspace.clone();
+                div()
+                    .id("view_mode_btn")
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .p_2()
+                    .rounded_full()
+                    .hover(|s| s.bg(palette.surface_container_highest))
+                    .cursor_pointer()
+                    .on_click(move |_event, _phase, cx| {
+                        ws_view.update(cx, |ws, cx| ws.toggle_view_mode(cx));
+                    })
+                    // Shows the mode a click switches to
+                    .child(
+                        crate::assets::icons::icon(match view_mode.as_str() {
+                            "list" => "description",
+                            "details" => "view_column",
+                            "columns" => "grid",
+                            _ => "list",
+                        })
+                        .size_5()
+                        .text_color(palette.on_surface_variant),
+                    )
+            })
             // Grouping Toggle
             .child({
                 // is_grouped is already defined above
@@ -1,114 +1,674 @@
+use crate::app_state::config::ConfigManager;
 use crate::app_state::workspace::Workspace;
+use crate::fs_ops::archive::{self, ArchiveSummary};
+use crate::fs_ops::file_signature::{self, Signature};
+use crate::fs_ops::hex_dump::{self, HexPage};
+use crate::fs_ops::image_metadata::{self, PhotoMetadata};
+use crate::fs_ops::media::{self, MediaInfo};
+use crate::fs_ops::provider::FileEntry;
+use crate::fs_ops::text_preview::{self, TextPreviewError};
+use crate::theme_engine::palette::M3Palette;
 use crate::theme_engine::theme::ThemeContext;
+use crate::ui_components::chips::Chip;
+use crate::ui_components::hex_view;
+use crate::ui_components::markdown;
+use crate::ui_components::markdown_view::{self, MarkdownDocument, MarkdownRenderer};
+use crate::ui_components::syntax::{self, Highlighter, TokenKind, CODE_FONT};
 use gpui::prelude::*;
 use gpui::*;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Archive entries beyond this many aren't listed
+const MAX_LISTED_ENTRIES: usize = 100;
+
+/// Bytes per row and per page of a binary file's hex dump
+const HEX_WIDTH: NonZeroUsize = NonZeroUsize::new(8).unwrap();
+const HEX_PAGE: NonZeroU64 = NonZeroU64::new(256).unwrap();
+
+/// A line and its highlighted byte ranges
+type HighlightedLine = (SharedString, Vec<(Range<usize>, TokenKind)>);
+
+/// A text file's first lines, highlighted.
+struct TextContent {
+    lines: Vec<HighlightedLine>,
+    encoding: &'static str,
+    language: Option<&'static str>,
+    truncated: bool,
+}
+
+/// An audio or video file's metadata, with its cover art ready to draw
+struct MediaContent {
+    info: MediaInfo,
+    cover: Option<Arc<Image>>,
+}
+
+/// A page of a binary file's hex dump and what the file was detected to be
+struct HexContent {
+    page: HexPage,
+    signature: Option<Signature>,
+}
+
+/// Text files are shown as highlighted lines, Markdown rendered, media
+/// files as their cover art and tags, photos with their EXIF details,
+/// archives as their top-level entries and anything else as a hex dump.
+enum Content {
+    Text(TextContent),
+    Markdown(MarkdownDocument),
+    Media(MediaContent),
+    Photo(PhotoMetadata),
+    Archive(ArchiveSummary),
+    Hex(HexContent),
+}
+
+/// The preview of one version of a file; `result` is `None` while it is
+/// loading.
+struct LoadedPreview {
+    path: PathBuf,
+    modified: SystemTime,
+    result: Option<Result<Content, TextPreviewError>>,
+}
 
 pub struct PreviewSidebar {
     workspace: Entity<Workspace>,
+    preview: Option<LoadedPreview>,
 }
 
 impl PreviewSidebar {
     pub fn new(workspace: Entity<Workspace>, _cx: &mut Context<Self>) -> Self {
-        Self { workspace }
+        Self {
+            workspace,
+            preview: None,
+        }
     }
-}
 
-impl Render for PreviewSidebar {
-    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let theme = cx.theme();
-        let ws = self.workspace.read(cx);
-        let selected_path = ws.last_selected.clone();
-        let palette = theme.palette.clone();
+    /// Starts reading `item` in the background unless it's already loaded.
+    /// Files that aren't `text_like`, or turn out to be binary, get a hex
+    /// dump.
+    fn load_preview(&mut self, item: &FileEntry, text_like: bool, cx: &mut Context<Self>) {
+        let loaded = self
+            .preview
+            .as_ref()
+            .is_some_and(|preview| preview.path == item.path && preview.modified == item.modified);
+        if loaded {
+            return;
+        }
+        self.preview = Some(LoadedPreview {
+            path: item.path.clone(),
+            modified: item.modified,
+            result: None,
+        });
 
-        // Check if selected file exists and get metadata
-        let file_info = if let Some(path) = &selected_path {
-            if let Some(item) = ws.items.iter().find(|i| i.path == *path) {
-                Some(item.clone())
-            } else {
-                None
+        let path = item.path.clone();
+        let executor = cx.background_executor().clone();
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                let task_path = path.clone();
+                let result = executor
+                    .spawn(async move {
+                        // Opening a pipe or device could block or never end
+                        let metadata = std::fs::metadata(&task_path)
+                            .map_err(|e| TextPreviewError::Io(e.to_string()))?;
+                        if !metadata.is_file() {
+                            return Err(TextPreviewError::Binary);
+                        }
+                        if media::is_media(&task_path) {
+                            let mut info = media::read(&task_path).map_err(TextPreviewError::Io)?;
+                            let cover = info.cover.take().and_then(|cover| {
+                                let format = ImageFormat::from_mime_type(&cover.mime)?;
+                                Some(Arc::new(Image::from_bytes(format, cover.data)))
+                            });
+                            return Ok(Content::Media(MediaContent { info, cover }));
+                        }
+                        if image_metadata::is_supported(&task_path) {
+                            return image_metadata::read(&task_path)
+                                .map(Content::Photo)
+                                .map_err(TextPreviewError::Io);
+                        }
+                        if archive::is_archive(&task_path) {
+                            return archive::read(&task_path)
+                                .map(Content::Archive)
+                                .map_err(TextPreviewError::Io);
+                        }
+                        if markdown::is_markdown(&task_path) {
+                            return markdown_view::load(&task_path, text_preview::MAX_LINES)
+                                .map(Content::Markdown);
+                        }
+                        if text_like {
+                            match text_preview::load(&task_path) {
+                                Ok(preview) => return Ok(highlight(&task_path, preview)),
+                                // Too large to tell, so maybe binary after all
+                                Err(TextPreviewError::Binary | TextPreviewError::TooLarge(_)) => {}
+                                Err(e) => return Err(e),
+                            }
+                        }
+                        let page = hex_dump::read_page(&task_path, 0, HEX_PAGE)
+                            .map_err(|e| TextPreviewError::Io(e.to_string()))?;
+                        let signature = file_signature::detect(&task_path).ok().flatten();
+                        Ok(Content::Hex(HexContent { page, signature }))
+                    })
+                    .await;
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |this, cx| {
+                        // Dropped if the selection moved on meanwhile
+                        let preview = this.preview.as_mut().filter(|p| p.path == path);
+                        if let Some(preview) = preview {
+                            preview.result = Some(result);
+                            cx.notify();
+                        }
+                    });
+                });
             }
-        } else {
-            None
+        })
+        .detach();
+    }
+
+    /// Replaces the hex dump shown with page `number`, reading only that
+    /// page
+    fn show_hex_page(&mut self, number: u64, cx: &mut Context<Self>) {
+        let Some(preview) = &self.preview else {
+            return;
         };
+        let path = preview.path.clone();
+        let executor = cx.background_executor().clone();
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                let task_path = path.clone();
+                let page = executor
+                    .spawn(async move { hex_dump::read_page(&task_path, number, HEX_PAGE) })
+                    .await;
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |this, cx| {
+                        let preview = this.preview.as_mut().filter(|p| p.path == path);
+                        match (preview.and_then(|p| p.result.as_mut()), page) {
+                            (Some(Ok(Content::Hex(content))), Ok(page)) => {
+                                content.page = page;
+                                cx.notify();
+                            }
+                            (Some(result), Err(e)) => {
+                                *result = Err(TextPreviewError::Io(e.to_string()));
+                                cx.notify();
+                            }
+                            _ => {}
+                        }
+                    });
+                });
+            }
+        })
+        .detach();
+    }
+}
 
-        let content = if let Some(item) = file_info {
-            let is_large = item.size > 10 * 1024 * 1024; // 10MB
-            let ext = item
-                .path
-                .extension()
-                .and_then(|e| e.to_str())
-                .unwrap_or("")
-                .to_lowercase();
-            let is_image =
-                matches!(ext.as_str(), "png" | "jpg" | "jpeg" | "webp" | "gif") && !is_large;
+/// A text file's lines, highlighted for its language
+fn highlight(path: &std::path::Path, preview: text_preview::TextPreview) -> Content {
+    let syntax = syntax::syntax_for(path);
+    let mut highlighter = Highlighter::new(syntax);
+    let lines = preview
+        .lines
+        .into_iter()
+        .map(|line| {
+            let spans = highlighter.highlight(&line);
+            (SharedString::from(line), spans)
+        })
+        .collect();
+    Content::Text(TextContent {
+        lines,
+        encoding: preview.encoding,
+        language: syntax.map(|syntax| syntax.name),
+        truncated: preview.truncated,
+    })
+}
+
+/// Preview image or icon plus basic metadata; also the last column of the
+/// column view.
+pub fn render_preview(item: &FileEntry, palette: &M3Palette) -> Div {
+    div()
+        .flex()
+        .flex_col()
+        .items_center()
+        .gap_4()
+        .p_4()
+        .w_full()
+        .child(render_visual(item, palette))
+        .child(render_metadata(item, palette))
+}
+
+/// The extension of `item`, lowercased
+fn extension(item: &FileEntry) -> String {
+    item.path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase()
+}
 
+/// Whether `item` is an image the preview draws
+fn is_image(item: &FileEntry) -> bool {
+    matches!(
+        extension(item).as_str(),
+        "png" | "jpg" | "jpeg" | "webp" | "gif"
+    )
+}
+
+fn render_visual(item: &FileEntry, palette: &M3Palette) -> AnyElement {
+    let is_large = item.size > 10 * 1024 * 1024; // 10MB
+    let ext = extension(item);
+
+    if is_image(item) && !is_large {
+        img(format!("file://{}", item.path.to_string_lossy()))
+            .w(px(200.0))
+            .h(px(200.0))
+            .object_fit(ObjectFit::Contain)
+            .into_any_element()
+    } else {
+        crate::assets::icons::icon(if item.is_dir {
+            "folder"
+        } else {
+            match ext.as_str() {
+                "mp4" | "mkv" | "webm" => "video",
+                "mp3" | "wav" | "ogg" => "audio",
+                "zip" | "tar" | "gz" | "7z" => "archive",
+                _ => "file",
+            }
+        })
+        .size(px(128.0))
+        .text_color(palette.primary)
+        .into_any_element()
+    }
+}
+
+fn render_metadata(item: &FileEntry, palette: &M3Palette) -> Div {
+    div()
+        .flex()
+        .flex_col()
+        .items_center()
+        .gap_2()
+        .w_full()
+        .child(
+            div()
+                .font_weight(FontWeight::BOLD)
+                .text_center()
+                .child(item.name.clone()),
+        )
+        .child(div().h_px().w_full().bg(palette.outline_variant))
+        .child(render_row("Size", item.formatted_size.clone(), palette))
+        .child(render_row("Modified", item.formatted_date.clone(), palette))
+}
+
+fn render_row(label: &'static str, value: impl Into<SharedString>, palette: &M3Palette) -> Div {
+    div()
+        .w_full()
+        .flex()
+        .justify_between()
+        .text_sm()
+        .text_color(palette.on_surface_variant)
+        .child(label)
+        .child(value.into())
+}
+
+/// The lines with a gutter of line numbers, scrolling both ways
+fn render_text(text: &TextContent, palette: &M3Palette) -> impl IntoElement {
+    let gutter_width = px(8.0 * text.lines.len().max(1).to_string().len() as f32 + 8.0);
+    let lines = text.lines.iter().enumerate().map(|(i, (line, spans))| {
+        let highlights = spans
+            .iter()
+            .map(|(range, kind)| (range.clone(), kind.style(palette)));
+        div()
+            .flex()
+            .flex_row()
+            .child(
+                div()
+                    .flex_none()
+                    .w(gutter_width)
+                    .pr_2()
+                    .flex()
+                    .justify_end()
+                    .text_color(palette.outline)
+                    .child((i + 1).to_string()),
+            )
+            .child(
+                div()
+                    .flex_none()
+                    .text_color(palette.on_surface)
+                    .child(StyledText::new(line.clone()).with_highlights(highlights)),
+            )
+    });
+
+    div()
+        .id("text-preview")
+        .w_full()
+        .max_h(px(360.0))
+        .overflow_scroll()
+        .p_2()
+        .rounded_md()
+        .bg(palette.surface_container_low)
+        .font_family(CODE_FONT)
+        .text_xs()
+        .whitespace_nowrap()
+        .children(lines)
+}
+
+/// Rendered Markdown, scrolling, with a button for the larger overlay
+fn render_markdown(
+    item: &FileEntry,
+    document: &MarkdownDocument,
+    palette: &M3Palette,
+    workspace: &Entity<Workspace>,
+) -> Div {
+    let base_dir = item.path.parent().unwrap_or(std::path::Path::new("/"));
+    let mut renderer = MarkdownRenderer::new("markdown_sidebar", palette, base_dir, workspace);
+    let workspace = workspace.clone();
+    let path = item.path.clone();
+    div()
+        .flex()
+        .flex_col()
+        .items_end()
+        .gap_2()
+        .w_full()
+        .child(
+            Chip::new("markdown_open_preview", "Open Preview").on_click(move |_, _, cx| {
+                workspace.update(cx, |ws, cx| ws.show_markdown_preview(path.clone(), cx));
+            }),
+        )
+        .child(
+            div()
+                .id("markdown-preview")
+                .w_full()
+                .max_h(px(480.0))
+                .overflow_y_scroll()
+                .p_2()
+                .rounded_md()
+                .bg(palette.surface_container_low)
+                .text_sm()
+                .text_color(palette.on_surface)
+                .child(renderer.render(&document.blocks)),
+        )
+}
+
+/// Cover art, or the file's icon, above the media tags and properties
+fn render_media(item: &FileEntry, content: &MediaContent, palette: &M3Palette) -> Div {
+    let visual = match &content.cover {
+        Some(cover) => img(cover.clone())
+            .w(px(200.0))
+            .h(px(200.0))
+            .object_fit(ObjectFit::Contain)
+            .rounded_md()
+            .into_any_element(),
+        None => render_visual(item, palette),
+    };
+    let rows = media::describe(&content.info)
+        .into_iter()
+        .map(|(label, value)| render_row(label, value, palette));
+    div()
+        .flex()
+        .flex_col()
+        .items_center()
+        .gap_4()
+        .p_4()
+        .w_full()
+        .child(visual)
+        .child(render_metadata(item, palette).children(rows))
+}
+
+/// The image above its camera and exposure details
+fn render_photo(item: &FileEntry, photo: &PhotoMetadata, palette: &M3Palette) -> Div {
+    let rows = image_metadata::describe(photo)
+        .into_iter()
+        .map(|(label, value)| render_row(label, value, palette));
+    div()
+        .flex()
+        .flex_col()
+        .items_center()
+        .gap_4()
+        .p_4()
+        .w_full()
+        .child(render_visual(item, palette))
+        .child(render_metadata(item, palette).children(rows))
+}
+
+/// The archive's totals above its top-level files and folders
+fn render_archive(item: &FileEntry, summary: &ArchiveSummary, palette: &M3Palette) -> Div {
+    let rows = archive::describe(summary)
+        .into_iter()
+        .map(|(label, value)| render_row(label, value, palette));
+    let entries = summary
+        .entries
+        .iter()
+        .take(MAX_LISTED_ENTRIES)
+        .map(|entry| {
+            let name = if entry.is_dir {
+                format!("{}/", entry.name)
+            } else {
+                entry.name.clone()
+            };
             div()
                 .flex()
-                .flex_col()
                 .items_center()
-                .gap_4()
-                .p_4()
-                .w_full()
-                .child(if is_image {
-                    // Reverting file:// prefix to see if raw path works better or if encoding was issue
-                    // Also adding debug print
-                    let path_str = format!("file://{}", item.path.to_string_lossy());
-                    println!("PreviewSidebar rendering image: {}", path_str);
-                    img(path_str)
-                        .w(px(200.0))
-                        .h(px(200.0))
-                        .object_fit(ObjectFit::Contain)
-                        .into_any_element()
-                } else {
-                    crate::assets::icons::icon(if item.is_dir {
-                        "folder"
-                    } else {
-                        match ext.as_str() {
-                            "mp4" | "mkv" | "webm" => "video",
-                            "mp3" | "wav" | "ogg" => "audio",
-                            "zip" | "tar" | "gz" | "7z" => "archive",
-                            _ => "file",
-                        }
-                    })
-                    .size(px(128.0))
-                    .text_color(palette.primary)
-                    .into_any_element()
+                .gap_2()
+                .child(
+                    crate::assets::icons::icon(if entry.is_dir { "folder" } else { "file" })
+                        .size(px(14.0))
+                        .flex_none()
+                        .text_color(palette.primary),
+                )
+                .child(
+                    div()
+                        .flex_1()
+                        .overflow_hidden()
+                        .text_ellipsis()
+                        .whitespace_nowrap()
+                        .text_color(palette.on_surface)
+                        .child(name),
+                )
+                .when(entry.encrypted, |row| {
+                    row.child(
+                        div()
+                            .flex_none()
+                            .text_color(palette.error)
+                            .child("Encrypted"),
+                    )
                 })
                 .child(
                     div()
-                        .flex()
-                        .flex_col()
-                        .items_center()
-                        .gap_2()
-                        .w_full()
-                        .child(
-                            div()
-                                .font_weight(FontWeight::BOLD)
-                                .text_center()
-                                .child(item.name.clone()),
-                        )
-                        .child(div().h_px().w_full().bg(palette.outline_variant))
-                        .child(
-                            div()
-                                .w_full()
-                                .flex()
-                                .justify_between()
-                                .text_sm()
-                                .text_color(palette.on_surface_variant)
-                                .child("Size")
-                                .child(item.formatted_size.clone()),
-                        )
-                        .child(
-                            div()
-                                .w_full()
-                                .flex()
-                                .justify_between()
-                                .text_sm()
-                                .text_color(palette.on_surface_variant)
-                                .child("Modified")
-                                .child(item.formatted_date.clone()),
-                        ),
+                        .flex_none()
+                        .text_color(palette.on_surface_variant)
+                        .child(human_bytes::human_bytes(entry.size as f64)),
+                )
+        });
+    let hidden = summary.entries.len().saturating_sub(MAX_LISTED_ENTRIES);
+    div()
+        .flex()
+        .flex_col()
+        .items_center()
+        .gap_4()
+        .p_4()
+        .w_full()
+        .child(render_visual(item, palette))
+        .child(render_metadata(item, palette).children(rows))
+        .child(
+            div()
+                .id("archive-entries")
+                .w_full()
+                .max_h(px(320.0))
+                .overflow_y_scroll()
+                .p_2()
+                .rounded_md()
+                .bg(palette.surface_container_low)
+                .flex()
+                .flex_col()
+                .gap_1()
+                .text_xs()
+                .children(entries)
+                .when(hidden > 0, |list| {
+                    list.child(
+                        div()
+                            .text_color(palette.on_surface_variant)
+                            .child(format!("and {} more", hidden)),
+                    )
+                }),
+        )
+}
+
+/// What the file was detected to be, above a page of its hex dump and a
+/// button for the larger overlay
+fn render_hex(
+    item: &FileEntry,
+    content: &HexContent,
+    palette: &M3Palette,
+    workspace: &Entity<Workspace>,
+    sidebar: &Entity<PreviewSidebar>,
+) -> Div {
+    let workspace = workspace.clone();
+    let sidebar = sidebar.downgrade();
+    let path = item.path.clone();
+    let mut metadata = render_metadata(item, palette);
+    if let Some(signature) = &content.signature {
+        metadata = metadata.child(hex_view::render_signature(signature, palette));
+    }
+    div()
+        .flex()
+        .flex_col()
+        .items_center()
+        .gap_4()
+        .p_4()
+        .w_full()
+        .child(metadata)
+        .child(
+            div()
+                .flex()
+                .flex_col()
+                .items_end()
+                .gap_2()
+                .w_full()
+                .child(
+                    Chip::new("hex_open_preview", "Open Hex View").on_click(move |_, _, cx| {
+                        workspace.update(cx, |ws, cx| ws.show_hex_preview(path.clone(), cx));
+                    }),
                 )
+                .child(hex_view::render_dump(
+                    "hex-preview",
+                    &content.page,
+                    HEX_WIDTH,
+                    palette,
+                ))
+                .child(hex_view::render_pager(
+                    "hex_sidebar_pager",
+                    &content.page,
+                    palette,
+                    move |number, cx| {
+                        let _ = sidebar.update(cx, |sidebar, cx| sidebar.show_hex_page(number, cx));
+                    },
+                )),
+        )
+}
+
+/// The text or media preview above the metadata; a note in place of the
+/// text when the file is too large or unreadable, and the usual preview for
+/// files that can't be read, such as pipes.
+fn render_file_preview(
+    item: &FileEntry,
+    preview: &LoadedPreview,
+    palette: &M3Palette,
+    workspace: &Entity<Workspace>,
+    sidebar: &Entity<PreviewSidebar>,
+) -> Div {
+    let container = div()
+        .flex()
+        .flex_col()
+        .items_center()
+        .gap_4()
+        .p_4()
+        .w_full();
+    let note = |message: String| {
+        div()
+            .text_xs()
+            .text_color(palette.on_surface_variant)
+            .child(message)
+    };
+    match &preview.result {
+        None => container
+            .child(note("Loading preview…".to_string()))
+            .child(render_metadata(item, palette)),
+        Some(Ok(Content::Markdown(document))) => {
+            let mut metadata = render_metadata(item, palette);
+            if document.truncated {
+                metadata = metadata.child(note(format!(
+                    "Showing the first {} lines",
+                    text_preview::MAX_LINES
+                )));
+            }
+            container
+                .child(render_markdown(item, document, palette, workspace))
+                .child(metadata)
+        }
+        Some(Ok(Content::Text(content))) => {
+            let mut metadata = render_metadata(item, palette);
+            if let Some(language) = content.language {
+                metadata = metadata.child(render_row("Language", language, palette));
+            }
+            metadata = metadata.child(render_row("Encoding", content.encoding, palette));
+            if content.truncated {
+                metadata = metadata.child(note(format!(
+                    "Showing the first {} lines",
+                    content.lines.len()
+                )));
+            }
+            container
+                .child(render_text(content, palette))
+                .child(metadata)
+        }
+        Some(Ok(Content::Media(content))) => render_media(item, content, palette),
+        Some(Ok(Content::Photo(photo))) => render_photo(item, photo, palette),
+        Some(Ok(Content::Archive(summary))) => render_archive(item, summary, palette),
+        Some(Ok(Content::Hex(content))) => render_hex(item, content, palette, workspace, sidebar),
+        Some(Err(TextPreviewError::Binary)) => render_preview(item, palette),
+        Some(Err(e)) => container
+            .child(render_visual(item, palette))
+            .child(note(e.to_string()))
+            .child(render_metadata(item, palette)),
+    }
+}
+
+impl Render for PreviewSidebar {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+        let ws = self.workspace.read(cx);
+        let selected_path = ws.last_selected.clone();
+        let palette = theme.palette.clone();
+
+        // Check if selected file exists and get metadata
+        let file_info = selected_path
+            .as_ref()
+            .and_then(|path| ws.find_entry(path))
+            .cloned();
+
+        // Images without photo details keep the usual preview; anything
+        // else unrecognised is shown as hex
+        let has_preview = file_info.as_ref().is_some_and(|item| {
+            !item.is_dir && (!is_image(item) || image_metadata::is_supported(&item.path))
+        });
+
+        let content = if let Some(item) = file_info {
+            if has_preview {
+                let config = &cx.global::<ConfigManager>().config;
+                let text_like = text_preview::is_text_like(&item.path, config);
+                self.load_preview(&item, text_like, cx);
+            }
+            match self
+                .preview
+                .as_ref()
+                .filter(|preview| has_preview && preview.path == item.path)
+            {
+                Some(preview) => {
+                    render_file_preview(&item, preview, &palette, &self.workspace, &cx.entity())
+                }
+                None => render_preview(&item, &palette),
+            }
         } else {
             div()
                 .flex()
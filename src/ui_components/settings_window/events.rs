@@ -5,6 +5,8 @@ pub enum SettingsEvent {
     Close,
     ConfigChanged, // Emitted when settings are changed
     ShowToast(String),
+    /// Thumbnails known to be in the cache are gone
+    ThumbnailCacheCleared,
 }
 
 impl EventEmitter<SettingsEvent> for super::SettingsWindow {}
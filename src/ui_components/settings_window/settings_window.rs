@@ -1,16 +1,25 @@
 #![allow(dead_code)]
 use super::events::SettingsEvent;
 use crate::app_state::config::ConfigManager;
+use crate::assets::thumbnail_cache::ThumbnailCache;
+use crate::assets::thumbnail_cleanup::{self, CacheUsage};
 use crate::theme_engine::palette::M3Palette;
 use crate::theme_engine::theme::ThemeContext;
 use gpui::prelude::*;
 use gpui::*;
+use humansize::{format_size, DECIMAL};
+
+/// Choices for the thumbnail cache size limit, in MB
+const THUMBNAIL_CACHE_SIZES: [u64; 4] = [256, 512, 1024, 4096];
+/// Choices for removing unused thumbnails, in days; 0 keeps them
+const THUMBNAIL_CACHE_AGES: [u64; 4] = [30, 90, 180, 0];
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum SettingsTab {
     General,
     Appearance,
     Advanced,
+    Tags,
 }
 
 pub struct SettingsWindow {
@@ -20,6 +29,10 @@ pub struct SettingsWindow {
     focus_handle: FocusHandle,
     terminal_focus: FocusHandle,
     editor_focus: FocusHandle,
+    tag_input: String,
+    tag_focus: FocusHandle,
+    /// Size of the thumbnail cache, once measured
+    thumbnail_usage: Option<CacheUsage>,
 }
 
 impl SettingsWindow {
@@ -36,14 +49,71 @@ impl SettingsWindow {
             .cloned()
             .unwrap_or_else(|| "code".to_string());
 
-        Self {
+        let mut this = Self {
             active_tab: SettingsTab::General,
             terminal_input: terminal,
             editor_input: editor,
             focus_handle: cx.focus_handle(),
             terminal_focus: cx.focus_handle(),
             editor_focus: cx.focus_handle(),
-        }
+            tag_input: String::new(),
+            tag_focus: cx.focus_handle(),
+            thumbnail_usage: None,
+        };
+        this.measure_thumbnail_cache(cx);
+        this
+    }
+
+    fn measure_thumbnail_cache(&mut self, cx: &mut Context<Self>) {
+        let executor = cx.background_executor().clone();
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                let usage = executor
+                    .spawn(async { thumbnail_cleanup::usage(&ThumbnailCache::user()) })
+                    .await;
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |this, cx| {
+                        this.thumbnail_usage = Some(usage);
+                        cx.notify();
+                    });
+                });
+            }
+        })
+        .detach();
+    }
+
+    fn clear_thumbnail_cache(&mut self, cx: &mut Context<Self>) {
+        self.thumbnail_usage = None;
+        cx.notify();
+        let executor = cx.background_executor().clone();
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                let (removed, usage) = executor
+                    .spawn(async {
+                        let cache = ThumbnailCache::user();
+                        (
+                            thumbnail_cleanup::clear(&cache),
+                            thumbnail_cleanup::usage(&cache),
+                        )
+                    })
+                    .await;
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |this, cx| {
+                        this.thumbnail_usage = Some(usage);
+                        cx.emit(SettingsEvent::ThumbnailCacheCleared);
+                        cx.emit(SettingsEvent::ShowToast(format!(
+                            "Removed {} thumbnails ({})",
+                            removed.files,
+                            format_size(removed.bytes, DECIMAL)
+                        )));
+                        cx.notify();
+                    });
+                });
+            }
+        })
+        .detach();
     }
 
     fn update_command(&mut self, key: &str, value: String, cx: &mut Context<Self>) {
@@ -54,6 +124,45 @@ impl SettingsWindow {
         });
     }
 
+    fn input_buffer(&mut self, key_name: &str) -> &mut String {
+        match key_name {
+            "terminal" => &mut self.terminal_input,
+            "tag" => &mut self.tag_input,
+            _ => &mut self.editor_input,
+        }
+    }
+
+    fn submit_input(&mut self, key_name: &'static str, cx: &mut Context<Self>) {
+        if key_name != "tag" {
+            let value = self.input_buffer(key_name).clone();
+            self.update_command(key_name, value, cx);
+            cx.emit(SettingsEvent::ShowToast(format!(
+                "{} command saved",
+                key_name
+            )));
+            return;
+        }
+
+        let name = self.tag_input.trim().to_string();
+        let added = cx.update_global::<ConfigManager, _>(|manager, cx| {
+            let added = manager.config.add_tag(&name);
+            if added {
+                manager.save_config();
+                cx.refresh_windows();
+            }
+            added
+        });
+        if added {
+            self.tag_input.clear();
+            cx.emit(SettingsEvent::ShowToast(format!("Added tag \"{}\"", name)));
+        } else {
+            cx.emit(SettingsEvent::ShowToast(format!(
+                "Invalid or duplicate tag name \"{}\"",
+                name
+            )));
+        }
+    }
+
     fn render_sidebar(&self, palette: &M3Palette, cx: &mut Context<Self>) -> impl IntoElement {
         div()
             .flex()
@@ -66,6 +175,7 @@ impl SettingsWindow {
             .child(self.render_tab_button("General", SettingsTab::General, palette, cx))
             .child(self.render_tab_button("Appearance", SettingsTab::Appearance, palette, cx))
             .child(self.render_tab_button("Advanced", SettingsTab::Advanced, palette, cx))
+            .child(self.render_tab_button("Tags", SettingsTab::Tags, palette, cx))
     }
 
     fn render_tab_button(
@@ -114,7 +224,7 @@ impl SettingsWindow {
         label: &str,
         value: &str,
         focus_handle: FocusHandle,
-        key_name: &'static str, // "terminal" or "editor"
+        key_name: &'static str, // "terminal", "editor" or "tag"
         palette: &M3Palette,
         cx: &mut Context<Self>,
     ) -> impl IntoElement {
@@ -153,48 +263,21 @@ impl SettingsWindow {
                         view.update(cx, |this, cx| {
                             let mut handled = false;
 
-                            if key_name == "terminal" {
-                                if key == "backspace" {
-                                    this.terminal_input.pop();
-                                    handled = true;
-                                } else if let Some(char_str) = &char_str {
-                                    if !modifiers.control
-                                        && !modifiers.alt
-                                        && !modifiers.platform
-                                        && char_str.len() == 1
-                                    {
-                                        this.terminal_input.push_str(char_str);
-                                        handled = true;
-                                    }
-                                } else if key == "enter" {
-                                    this.update_command(key_name, this.terminal_input.clone(), cx);
-                                    cx.emit(SettingsEvent::ShowToast(format!(
-                                        "{} command saved",
-                                        key_name
-                                    )));
-                                    handled = true;
-                                }
-                            } else {
-                                if key == "backspace" {
-                                    this.editor_input.pop();
-                                    handled = true;
-                                } else if let Some(char_str) = &char_str {
-                                    if !modifiers.control
-                                        && !modifiers.alt
-                                        && !modifiers.platform
-                                        && char_str.len() == 1
-                                    {
-                                        this.editor_input.push_str(char_str);
-                                        handled = true;
-                                    }
-                                } else if key == "enter" {
-                                    this.update_command(key_name, this.editor_input.clone(), cx);
-                                    cx.emit(SettingsEvent::ShowToast(format!(
-                                        "{} command saved",
-                                        key_name
-                                    )));
+                            if key == "backspace" {
+                                this.input_buffer(key_name).pop();
+                                handled = true;
+                            } else if let Some(char_str) = &char_str {
+                                if !modifiers.control
+                                    && !modifiers.alt
+                                    && !modifiers.platform
+                                    && char_str.len() == 1
+                                {
+                                    this.input_buffer(key_name).push_str(char_str);
                                     handled = true;
                                 }
+                            } else if key == "enter" {
+                                this.submit_input(key_name, cx);
+                                handled = true;
                             }
 
                             if handled {
@@ -205,7 +288,11 @@ impl SettingsWindow {
                     .child(if value.is_empty() {
                         div()
                             .text_color(palette.on_surface_variant)
-                            .child("Type command...")
+                            .child(if key_name == "tag" {
+                                "Type tag name..."
+                            } else {
+                                "Type command..."
+                            })
                     } else {
                         div()
                             .text_color(palette.on_surface)
@@ -216,7 +303,11 @@ impl SettingsWindow {
                 div()
                     .text_xs()
                     .text_color(palette.on_surface_variant)
-                    .child("Press Enter to save"),
+                    .child(if key_name == "tag" {
+                        "Press Enter to add"
+                    } else {
+                        "Press Enter to save"
+                    }),
             )
     }
 
@@ -226,6 +317,12 @@ impl SettingsWindow {
         palette: &M3Palette,
         cx: &mut Context<Self>,
     ) -> impl IntoElement {
+        // An unset value (older configs) behaves like "show"
+        let ignored_mode = match ui_config.ignored_files.as_str() {
+            "dim" | "hide" => ui_config.ignored_files.clone(),
+            _ => "show".to_string(),
+        };
+
         div()
             .flex()
             .flex_col()
@@ -262,37 +359,136 @@ impl SettingsWindow {
             // View Mode
             .child(div().h_px().bg(palette.outline_variant))
             .child(
-                div().flex().flex_col().gap_2().child("View Mode").child(
-                    div()
-                        .flex()
-                        .gap_2()
-                        .child(
-                            crate::ui_components::chips::Chip::new("view_grid_chip", "Grid")
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .child("Default View Mode")
+                    .child(
+                        div()
+                            .flex()
+                            .flex_wrap()
+                            .gap_2()
+                            .child(
+                                crate::ui_components::chips::Chip::new("view_grid_chip", "Grid")
+                                    .filter()
+                                    .icon("grid")
+                                    .selected(ui_config.view_mode == "grid")
+                                    .on_click(cx.listener(move |_this, _, _, cx| {
+                                        cx.update_global::<ConfigManager, _>(|manager, cx| {
+                                            manager.config.ui.view_mode = "grid".to_string();
+                                            manager.save_config();
+                                            cx.refresh_windows();
+                                        });
+                                        cx.emit(SettingsEvent::ConfigChanged);
+                                    })),
+                            )
+                            .child(
+                                crate::ui_components::chips::Chip::new("view_list_chip", "List")
+                                    .filter()
+                                    .icon("list")
+                                    .selected(ui_config.view_mode == "list")
+                                    .on_click(cx.listener(move |_this, _, _, cx| {
+                                        cx.update_global::<ConfigManager, _>(|manager, cx| {
+                                            manager.config.ui.view_mode = "list".to_string();
+                                            manager.save_config();
+                                            cx.refresh_windows();
+                                        });
+                                        cx.emit(SettingsEvent::ConfigChanged);
+                                    })),
+                            )
+                            .child(
+                                crate::ui_components::chips::Chip::new(
+                                    "view_details_chip",
+                                    "Details",
+                                )
                                 .filter()
-                                .icon("grid")
-                                .selected(ui_config.view_mode == "grid")
-                                .on_click(cx.listener(move |_this, _, _, cx| {
-                                    cx.update_global::<ConfigManager, _>(|manager, cx| {
-                                        manager.config.ui.view_mode = "grid".to_string();
-                                        manager.save_config();
-                                        cx.refresh_windows();
-                                    });
-                                })),
-                        )
-                        .child(
-                            crate::ui_components::chips::Chip::new("view_list_chip", "List")
+                                .icon("description")
+                                .selected(ui_config.view_mode == "details")
+                                .on_click(cx.listener(
+                                    move |_this, _, _, cx| {
+                                        cx.update_global::<ConfigManager, _>(|manager, cx| {
+                                            manager.config.ui.view_mode = "details".to_string();
+                                            manager.save_config();
+                                            cx.refresh_windows();
+                                        });
+                                        cx.emit(SettingsEvent::ConfigChanged);
+                                    },
+                                )),
+                            )
+                            .child(
+                                crate::ui_components::chips::Chip::new(
+                                    "view_columns_chip",
+                                    "Columns",
+                                )
                                 .filter()
-                                .icon("list")
-                                .selected(ui_config.view_mode == "list")
-                                .on_click(cx.listener(move |_this, _, _, cx| {
-                                    cx.update_global::<ConfigManager, _>(|manager, cx| {
-                                        manager.config.ui.view_mode = "list".to_string();
-                                        manager.save_config();
-                                        cx.refresh_windows();
-                                    });
-                                })),
-                        ),
-                ),
+                                .icon("view_column")
+                                .selected(ui_config.view_mode == "columns")
+                                .on_click(cx.listener(
+                                    move |_this, _, _, cx| {
+                                        cx.update_global::<ConfigManager, _>(|manager, cx| {
+                                            manager.config.ui.view_mode = "columns".to_string();
+                                            manager.save_config();
+                                            cx.refresh_windows();
+                                        });
+                                        cx.emit(SettingsEvent::ConfigChanged);
+                                    },
+                                )),
+                            ),
+                    ),
+            )
+            // Files excluded by .gitignore / .ignore
+            .child(div().h_px().bg(palette.outline_variant))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .child("Ignored Files")
+                    .child(
+                        div()
+                            .flex()
+                            .gap_2()
+                            .child(
+                                crate::ui_components::chips::Chip::new("ignored_show_chip", "Show")
+                                    .filter()
+                                    .selected(ignored_mode == "show")
+                                    .on_click(cx.listener(move |_this, _, _, cx| {
+                                        cx.update_global::<ConfigManager, _>(|manager, cx| {
+                                            manager.config.ui.ignored_files = "show".to_string();
+                                            manager.save_config();
+                                            cx.refresh_windows();
+                                        });
+                                        cx.emit(SettingsEvent::ConfigChanged);
+                                    })),
+                            )
+                            .child(
+                                crate::ui_components::chips::Chip::new("ignored_dim_chip", "Dim")
+                                    .filter()
+                                    .selected(ignored_mode == "dim")
+                                    .on_click(cx.listener(move |_this, _, _, cx| {
+                                        cx.update_global::<ConfigManager, _>(|manager, cx| {
+                                            manager.config.ui.ignored_files = "dim".to_string();
+                                            manager.save_config();
+                                            cx.refresh_windows();
+                                        });
+                                        cx.emit(SettingsEvent::ConfigChanged);
+                                    })),
+                            )
+                            .child(
+                                crate::ui_components::chips::Chip::new("ignored_hide_chip", "Hide")
+                                    .filter()
+                                    .selected(ignored_mode == "hide")
+                                    .on_click(cx.listener(move |_this, _, _, cx| {
+                                        cx.update_global::<ConfigManager, _>(|manager, cx| {
+                                            manager.config.ui.ignored_files = "hide".to_string();
+                                            manager.save_config();
+                                            cx.refresh_windows();
+                                        });
+                                        cx.emit(SettingsEvent::ConfigChanged);
+                                    })),
+                            ),
+                    ),
             )
     }
 
@@ -302,7 +498,7 @@ impl SettingsWindow {
         palette: &M3Palette,
         cx: &mut Context<Self>,
     ) -> impl IntoElement {
-        let icon_sizes = [48, 64, 80, 96];
+        let icon_sizes = crate::app_state::view_settings::ICON_SIZES;
 
         div()
             .flex()
@@ -320,7 +516,7 @@ impl SettingsWindow {
                             .items_center()
                             .gap_2()
                             .child(crate::assets::icons::icon("image").size_5())
-                            .child("Icon Size"),
+                            .child("Default Icon Size"),
                     )
                     .child(
                         div()
@@ -342,6 +538,7 @@ impl SettingsWindow {
                                             manager.save_config();
                                             cx.refresh_windows();
                                         });
+                                        cx.emit(SettingsEvent::ConfigChanged);
                                     },
                                 ))
                             })),
@@ -491,6 +688,239 @@ output_path = "~/.config/material_3_file_manager/theme.json""#;
                             })),
                     ),
             )
+            .child(div().h_px().bg(palette.outline_variant))
+            .child(self.render_thumbnail_cache_settings(config, palette, cx))
+            // Smart Folders
+            .children(if config.smart_folders.is_empty() {
+                None
+            } else {
+                Some(div().h_px().bg(palette.outline_variant))
+            })
+            .children(config.smart_folders.iter().map(|folder| {
+                let name = folder.name.clone();
+                div()
+                    .flex()
+                    .justify_between()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .min_w_0()
+                            .child(crate::assets::icons::icon("search").size_5())
+                            .child(div().text_ellipsis().child(folder.name.clone())),
+                    )
+                    .child(
+                        crate::ui_components::chips::Chip::new(
+                            SharedString::from(format!("remove_smart_{}", folder.name)),
+                            "Remove",
+                        )
+                        .icon("remove")
+                        .on_click(cx.listener(move |_this, _, _, cx| {
+                            let name = name.clone();
+                            cx.update_global::<ConfigManager, _>(|manager, cx| {
+                                manager.config.remove_smart_folder(&name);
+                                manager.save_config();
+                                cx.refresh_windows();
+                            });
+                            cx.emit(SettingsEvent::ShowToast(format!(
+                                "Removed smart folder \"{}\"",
+                                name
+                            )));
+                        })),
+                    )
+            }))
+    }
+
+    fn render_thumbnail_cache_settings(
+        &self,
+        config: &crate::app_state::config::Config,
+        palette: &M3Palette,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let limits = config.thumbnails.clone();
+        let usage = match self.thumbnail_usage {
+            Some(usage) => format!(
+                "{} in {} thumbnails",
+                format_size(usage.bytes, DECIMAL),
+                usage.files
+            ),
+            None => "Measuring...".to_string(),
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(
+                div()
+                    .flex()
+                    .justify_between()
+                    .items_center()
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(crate::assets::icons::icon("image").size_5())
+                            .child("Thumbnail Cache")
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(palette.on_surface_variant)
+                                    .child(usage),
+                            ),
+                    )
+                    .child(
+                        crate::ui_components::chips::Chip::new(
+                            "clear_thumbnails_chip",
+                            "Clear Thumbnail Cache",
+                        )
+                        .icon("remove")
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.clear_thumbnail_cache(cx);
+                        })),
+                    ),
+            )
+            .child(div().text_sm().child("Maximum size"))
+            .child(
+                div()
+                    .flex()
+                    .gap_2()
+                    .children(THUMBNAIL_CACHE_SIZES.iter().map(|&mb| {
+                        let label = if mb >= 1024 {
+                            format!("{} GB", mb / 1024)
+                        } else {
+                            format!("{} MB", mb)
+                        };
+                        crate::ui_components::chips::Chip::new(
+                            format!("thumbnail_size_{}", mb),
+                            label,
+                        )
+                        .filter()
+                        .selected(limits.max_size_mb == mb)
+                        .on_click(cx.listener(move |_this, _, _, cx| {
+                            cx.update_global::<ConfigManager, _>(move |manager, cx| {
+                                manager.config.thumbnails.max_size_mb = mb;
+                                manager.save_config();
+                                cx.refresh_windows();
+                            });
+                        }))
+                    })),
+            )
+            .child(div().text_sm().child("Remove thumbnails unused for"))
+            .child(
+                div()
+                    .flex()
+                    .gap_2()
+                    .children(THUMBNAIL_CACHE_AGES.iter().map(|&days| {
+                        let label = if days == 0 {
+                            "Never".to_string()
+                        } else {
+                            format!("{} days", days)
+                        };
+                        crate::ui_components::chips::Chip::new(
+                            format!("thumbnail_age_{}", days),
+                            label,
+                        )
+                        .filter()
+                        .selected(limits.max_age_days == days)
+                        .on_click(cx.listener(move |_this, _, _, cx| {
+                            cx.update_global::<ConfigManager, _>(move |manager, cx| {
+                                manager.config.thumbnails.max_age_days = days;
+                                manager.save_config();
+                                cx.refresh_windows();
+                            });
+                        }))
+                    })),
+            )
+    }
+
+    fn render_tag_settings(
+        &self,
+        config: &crate::app_state::config::Config,
+        palette: &M3Palette,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        div()
+            .flex()
+            .flex_col()
+            .gap_4()
+            .child(self.render_input_field(
+                "New Tag",
+                &self.tag_input,
+                self.tag_focus.clone(),
+                "tag",
+                palette,
+                cx,
+            ))
+            .child(div().h_px().bg(palette.outline_variant))
+            .children(if config.tags.is_empty() {
+                Some(
+                    div()
+                        .text_sm()
+                        .text_color(palette.on_surface_variant)
+                        .child("No tags yet"),
+                )
+            } else {
+                None
+            })
+            .children(config.tags.iter().map(|tag| {
+                let color_name = tag.name.clone();
+                let remove_name = tag.name.clone();
+                div()
+                    .flex()
+                    .justify_between()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_3()
+                            .min_w_0()
+                            .child(
+                                div()
+                                    .id(SharedString::from(format!("tag_color_{}", tag.name)))
+                                    .size_5()
+                                    .flex_none()
+                                    .rounded_full()
+                                    .bg(gpui::rgb(tag.rgb()))
+                                    .cursor_pointer()
+                                    .hover(|s| s.opacity(0.8))
+                                    // Cycle through the built-in palette
+                                    .on_click(cx.listener(move |_this, _, _, cx| {
+                                        cx.update_global::<ConfigManager, _>(|manager, cx| {
+                                            manager.config.cycle_tag_color(&color_name);
+                                            manager.save_config();
+                                            cx.refresh_windows();
+                                        });
+                                    })),
+                            )
+                            .child(div().text_ellipsis().child(tag.name.clone())),
+                    )
+                    .child(
+                        crate::ui_components::chips::Chip::new(
+                            SharedString::from(format!("remove_tag_{}", tag.name)),
+                            "Remove",
+                        )
+                        .icon("remove")
+                        .on_click(cx.listener(move |_this, _, _, cx| {
+                            let name = remove_name.clone();
+                            cx.update_global::<ConfigManager, _>(|manager, cx| {
+                                manager.config.remove_tag(&name);
+                                manager.save_config();
+                                cx.refresh_windows();
+                            });
+                            cx.emit(SettingsEvent::ShowToast(format!(
+                                "Removed tag \"{}\"",
+                                name
+                            )));
+                        })),
+                    )
+            }))
     }
 }
 
@@ -543,6 +973,9 @@ impl Render for SettingsWindow {
                                 SettingsTab::Advanced => self
                                     .render_advanced_settings(&config, &palette, cx)
                                     .into_any_element(),
+                                SettingsTab::Tags => self
+                                    .render_tag_settings(&config, &palette, cx)
+                                    .into_any_element(),
                             }),
                     ),
             )
@@ -1,4 +1,6 @@
+use crate::app_state::config::{ConfigContext, SmartFolder};
 use crate::theme_engine::theme::ThemeContext;
+use crate::ui_components::drag_drop::accept_dragged_files;
 use gpui::prelude::*;
 use gpui::*;
 use std::path::PathBuf;
@@ -7,10 +9,14 @@ pub struct Sidebar {
     active_path: PathBuf,
     is_dashboard: bool,
     drives: Vec<(String, PathBuf)>,
+    active_smart_folder: Option<String>,
 }
 
 pub enum SidebarEvent {
     Navigate(PathBuf),
+    OpenSmartFolder(SmartFolder),
+    RenameSmartFolder(String),
+    RemoveSmartFolder(String),
     OpenDashboard,
     OpenSettings,
 }
@@ -59,12 +65,20 @@ impl Sidebar {
             active_path: PathBuf::from(home),
             is_dashboard: true,
             drives: Vec::new(),
+            active_smart_folder: None,
         }
     }
 
-    pub fn set_state(&mut self, path: PathBuf, is_dashboard: bool, cx: &mut Context<Self>) {
+    pub fn set_state(
+        &mut self,
+        path: PathBuf,
+        is_dashboard: bool,
+        active_smart_folder: Option<String>,
+        cx: &mut Context<Self>,
+    ) {
         self.active_path = path;
         self.is_dashboard = is_dashboard;
+        self.active_smart_folder = active_smart_folder;
         cx.notify();
     }
 }
@@ -73,6 +87,8 @@ impl Render for Sidebar {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let theme = cx.theme();
         let home = std::env::var("HOME").unwrap_or_else(|_| "/home/user".into());
+        let smart_folders = cx.config().smart_folders.clone();
+        let tags = cx.config().tags.clone();
 
         div()
             .flex()
@@ -177,6 +193,70 @@ impl Render for Sidebar {
                 self.is_dashboard,
                 cx,
             ))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .children(if !smart_folders.is_empty() {
+                        Some(
+                            div()
+                                .text_xs()
+                                .font_weight(FontWeight::SEMIBOLD)
+                                .text_color(theme.palette.on_surface_variant)
+                                .px_4()
+                                .py_2()
+                                .mt_2()
+                                .child("Smart Folders"),
+                        )
+                    } else {
+                        None
+                    })
+                    .children(smart_folders.into_iter().map(|folder| {
+                        let active = !self.is_dashboard
+                            && self.active_smart_folder.as_ref() == Some(&folder.name);
+                        smart_folder_item(
+                            folder,
+                            active,
+                            true,
+                            crate::assets::icons::icon("search")
+                                .size_5()
+                                .into_any_element(),
+                            cx,
+                        )
+                    })),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .children(if !tags.is_empty() {
+                        Some(
+                            div()
+                                .text_xs()
+                                .font_weight(FontWeight::SEMIBOLD)
+                                .text_color(theme.palette.on_surface_variant)
+                                .px_4()
+                                .py_2()
+                                .mt_2()
+                                .child("Tags"),
+                        )
+                    } else {
+                        None
+                    })
+                    .children(tags.into_iter().map(|tag| {
+                        let folder = SmartFolder::for_tag(&tag.name, PathBuf::from(&home));
+                        let active = !self.is_dashboard
+                            && self.active_smart_folder.as_ref() == Some(&folder.name);
+                        let dot = div()
+                            .size_5()
+                            .flex()
+                            .items_center()
+                            .justify_center()
+                            .child(div().size_3().rounded_full().bg(gpui::rgb(tag.rgb())))
+                            .into_any_element();
+                        smart_folder_item(folder, active, false, dot, cx)
+                    })),
+            )
             .child(
                 div()
                     .flex()
@@ -251,7 +331,8 @@ fn sidebar_item(
         theme.palette.on_surface_variant
     };
 
-    div()
+    // Entries dragged onto a place move there
+    let element = div()
         .id(SharedString::from(format!("sidebar_item_{}", label)))
         .w_full()
         .flex()
@@ -275,8 +356,11 @@ fn sidebar_item(
                 s
             }
         })
-        .on_click(cx.listener(move |_, _, _, cx| {
-            cx.emit(SidebarEvent::Navigate(path_buf.clone()));
+        .on_click(cx.listener({
+            let path_buf = path_buf.clone();
+            move |_, _, _, cx| {
+                cx.emit(SidebarEvent::Navigate(path_buf.clone()));
+            }
         }))
         .child(
             crate::assets::icons::icon(match label {
@@ -290,5 +374,91 @@ fn sidebar_item(
             })
             .size_5(),
         )
-        .child(label.to_string())
+        .child(label.to_string());
+    accept_dragged_files(element, path_buf, theme.palette.primary_container.into())
+}
+
+/// Saved searches are `editable`, showing rename and remove buttons on hover;
+/// the tags' folders come and go with the tags.
+fn smart_folder_item(
+    folder: SmartFolder,
+    active: bool,
+    editable: bool,
+    leading: AnyElement,
+    cx: &Context<Sidebar>,
+) -> impl IntoElement {
+    let theme = cx.theme();
+    let label = folder.name.clone();
+    let group = SharedString::from(format!("sidebar_smart_{}", folder.name));
+    let action = |id: &str, icon: &str, event: fn(String) -> SidebarEvent| {
+        let name = label.clone();
+        div()
+            .id(SharedString::from(format!("{}_{}", id, label)))
+            .p_1()
+            .rounded_full()
+            .hover(|s| s.bg(theme.palette.surface_variant))
+            // Not opening the folder too
+            .on_click(cx.listener(move |_, _, _, cx| {
+                cx.stop_propagation();
+                cx.emit(event(name.clone()));
+            }))
+            .child(crate::assets::icons::icon(icon).size_4())
+    };
+    let actions = editable.then(|| {
+        div()
+            .ml_auto()
+            .flex()
+            .invisible()
+            .group_hover(group.clone(), |s| s.visible())
+            .child(action(
+                "sidebar_smart_rename",
+                "edit",
+                SidebarEvent::RenameSmartFolder,
+            ))
+            .child(action(
+                "sidebar_smart_remove",
+                "close",
+                SidebarEvent::RemoveSmartFolder,
+            ))
+    });
+
+    div()
+        .id(group.clone())
+        .group(group)
+        .w_full()
+        .flex()
+        .items_center()
+        .gap_3()
+        .px_4()
+        .py_3()
+        .rounded_full()
+        .bg(if active {
+            theme.palette.secondary_container
+        } else {
+            gpui::rgba(0x00000000)
+        })
+        .text_color(if active {
+            theme.palette.on_secondary_container
+        } else {
+            theme.palette.on_surface_variant
+        })
+        .font_weight(if active {
+            FontWeight::BOLD
+        } else {
+            FontWeight::MEDIUM
+        })
+        .cursor_pointer()
+        .hover(|s| {
+            if !active {
+                s.bg(theme.palette.surface_container_highest)
+            } else {
+                s
+            }
+        })
+        .on_click(cx.listener(move |_, _, _, cx| {
+            cx.emit(SidebarEvent::OpenSmartFolder(folder.clone()));
+        }))
+        .child(leading)
+        .child(div().text_ellipsis().child(label.clone()))
+        .children(actions)
 }
@@ -0,0 +1,434 @@
+// Syntax highlighting for the text preview. A line-by-line scanner that
+// knows each language's comments, strings and keywords is enough for a
+// few hundred lines in the sidebar; colors come from the theme palette so
+// the preview follows the wallpaper like the rest of the window.
+use crate::theme_engine::palette::M3Palette;
+use gpui::{FontStyle, FontWeight, HighlightStyle};
+use std::ops::Range;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Keyword,
+    Type,
+    Function,
+    String,
+    Number,
+    Comment,
+}
+
+impl TokenKind {
+    pub fn style(self, palette: &M3Palette) -> HighlightStyle {
+        let (color, weight, style) = match self {
+            TokenKind::Keyword => (palette.primary, Some(FontWeight::BOLD), None),
+            TokenKind::Type => (palette.tertiary, None, None),
+            TokenKind::Function => (palette.on_primary_container, None, None),
+            TokenKind::String => (palette.secondary, None, None),
+            TokenKind::Number => (palette.on_tertiary_container, None, None),
+            TokenKind::Comment => (palette.on_surface_variant, None, Some(FontStyle::Italic)),
+        };
+        HighlightStyle {
+            color: Some(color.into()),
+            font_weight: weight,
+            font_style: style,
+            ..Default::default()
+        }
+    }
+}
+
+/// What the scanner needs to know about a language.
+pub struct Syntax {
+    pub name: &'static str,
+    extensions: &'static [&'static str],
+    /// Whole file names, for files without an extension
+    file_names: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+    /// `'a` is a lifetime unless it closes like a char literal
+    lifetimes: bool,
+    /// Space separated
+    keywords: &'static str,
+}
+
+/// Font for code and other fixed-width previews
+pub const CODE_FONT: &str = "DejaVu Sans Mono";
+
+const C_FAMILY_QUOTES: &[char] = &['"', '\''];
+
+static SYNTAXES: &[Syntax] = &[
+    Syntax {
+        name: "Rust",
+        extensions: &["rs"],
+        file_names: &[],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: C_FAMILY_QUOTES,
+        lifetimes: true,
+        keywords: "\
+            as async await break const continue crate dyn else enum extern false fn for if \
+            impl in let loop match mod move mut pub ref return self Self static struct \
+            super trait true type unsafe use where while",
+    },
+    Syntax {
+        name: "C/C++",
+        extensions: &["c", "h", "cc", "cpp", "cxx", "hpp", "hh"],
+        file_names: &[],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: C_FAMILY_QUOTES,
+        lifetimes: false,
+        keywords: "\
+            auto bool break case char class const constexpr continue default delete do \
+            double else enum extern false float for goto if include inline int long \
+            namespace new nullptr private protected public return short signed sizeof \
+            static struct switch template this true typedef typename union unsigned using \
+            virtual void volatile while define ifdef ifndef endif pragma",
+    },
+    Syntax {
+        name: "Go",
+        extensions: &["go"],
+        file_names: &[],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\'', '`'],
+        lifetimes: false,
+        keywords: "\
+            break case chan const continue default defer else fallthrough false for func \
+            go goto if import interface map nil package range return select struct switch \
+            true type var",
+    },
+    Syntax {
+        name: "JavaScript",
+        extensions: &["js", "mjs", "cjs", "jsx", "ts", "tsx"],
+        file_names: &[],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\'', '`'],
+        lifetimes: false,
+        keywords: "\
+            async await break case catch class const continue default delete do else \
+            export extends false finally for from function if import in instanceof \
+            interface let new null of return static super switch this throw true try type \
+            typeof undefined var void while yield",
+    },
+    Syntax {
+        name: "Java/Kotlin",
+        extensions: &["java", "kt", "kts", "scala", "cs"],
+        file_names: &[],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: C_FAMILY_QUOTES,
+        lifetimes: false,
+        keywords: "\
+            abstract boolean break case catch class continue default do else enum extends \
+            false final finally for fun if implements import int interface is namespace \
+            new null object override package private protected public return static super \
+            switch this throw true try val var void when while",
+    },
+    Syntax {
+        name: "Python",
+        extensions: &["py", "pyi", "pyw"],
+        file_names: &[],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        lifetimes: false,
+        keywords: "\
+            and as assert async await break class continue def del elif else except False \
+            finally for from global if import in is lambda None nonlocal not or pass raise \
+            return self True try while with yield",
+    },
+    Syntax {
+        name: "Shell",
+        extensions: &["sh", "bash", "zsh", "fish", "mk"],
+        file_names: &[
+            "Makefile",
+            "makefile",
+            "Dockerfile",
+            "PKGBUILD",
+            ".bashrc",
+            ".zshrc",
+        ],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        lifetimes: false,
+        keywords: "\
+            case do done elif else esac export fi for function if in local return then \
+            while FROM RUN COPY CMD ENV",
+    },
+    Syntax {
+        name: "Lua",
+        extensions: &["lua"],
+        file_names: &[],
+        line_comments: &["--"],
+        block_comment: Some(("--[[", "]]")),
+        quotes: &['"', '\''],
+        lifetimes: false,
+        keywords: "\
+            and break do else elseif end false for function if in local nil not or repeat \
+            return then true until while",
+    },
+    Syntax {
+        name: "SQL",
+        extensions: &["sql"],
+        file_names: &[],
+        line_comments: &["--"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['\'', '"'],
+        lifetimes: false,
+        keywords: "\
+            and as by create delete from group insert into join not null on or order \
+            select set table update values where AND AS BY CREATE DELETE FROM GROUP INSERT \
+            INTO JOIN NOT NULL ON OR ORDER SELECT SET TABLE UPDATE VALUES WHERE",
+    },
+    Syntax {
+        name: "Config",
+        extensions: &[
+            "toml", "ini", "cfg", "conf", "desktop", "yaml", "yml", "env",
+        ],
+        file_names: &[".gitignore", ".editorconfig"],
+        line_comments: &["#", ";"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        lifetimes: false,
+        keywords: "true false yes no on off null",
+    },
+    Syntax {
+        name: "JSON",
+        extensions: &["json", "jsonc", "json5"],
+        file_names: &[],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"'],
+        lifetimes: false,
+        keywords: "true false null",
+    },
+    Syntax {
+        name: "Markup",
+        extensions: &["html", "htm", "xml", "svg", "xhtml", "vue"],
+        file_names: &[],
+        line_comments: &[],
+        block_comment: Some(("<!--", "-->")),
+        quotes: &['"', '\''],
+        lifetimes: false,
+        keywords: "\
+            ",
+    },
+    Syntax {
+        name: "CSS",
+        extensions: &["css", "scss", "sass", "less"],
+        file_names: &[],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        lifetimes: false,
+        keywords: "important media import from to",
+    },
+];
+
+/// The language of `path`, by extension or file name
+pub fn syntax_for(path: &Path) -> Option<&'static Syntax> {
+    let name = path.file_name()?.to_str()?;
+    if let Some(syntax) = SYNTAXES.iter().find(|s| s.file_names.contains(&name)) {
+        return Some(syntax);
+    }
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    SYNTAXES
+        .iter()
+        .find(|s| s.extensions.contains(&ext.as_str()))
+}
+
+/// The language named in a fenced code block, like `rust`, `py` or `c++`
+pub fn syntax_for_language(language: &str) -> Option<&'static Syntax> {
+    let language = language.to_lowercase();
+    SYNTAXES.iter().find(|s| {
+        s.extensions.contains(&language.as_str())
+            || s.name
+                .split('/')
+                .any(|name| name.to_lowercase() == language)
+    })
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Highlights lines in order, carrying block comments from one line to
+/// the next.
+pub struct Highlighter {
+    syntax: Option<&'static Syntax>,
+    in_block_comment: bool,
+}
+
+impl Highlighter {
+    pub fn new(syntax: Option<&'static Syntax>) -> Self {
+        Self {
+            syntax,
+            in_block_comment: false,
+        }
+    }
+
+    /// Highlighted byte ranges of `line`; the rest is plain text
+    pub fn highlight(&mut self, line: &str) -> Vec<(Range<usize>, TokenKind)> {
+        let Some(syntax) = self.syntax else {
+            return Vec::new();
+        };
+        let mut spans = Vec::new();
+        let mut i = 0;
+        while i < line.len() {
+            let rest = &line[i..];
+            if let Some((open, close)) = syntax.block_comment {
+                if self.in_block_comment || rest.starts_with(open) {
+                    let from = if self.in_block_comment {
+                        i
+                    } else {
+                        i + open.len()
+                    };
+                    let end = match line[from..].find(close) {
+                        Some(at) => {
+                            self.in_block_comment = false;
+                            from + at + close.len()
+                        }
+                        None => {
+                            self.in_block_comment = true;
+                            line.len()
+                        }
+                    };
+                    spans.push((i..end, TokenKind::Comment));
+                    i = end;
+                    continue;
+                }
+            }
+            if syntax.line_comments.iter().any(|c| rest.starts_with(c)) {
+                spans.push((i..line.len(), TokenKind::Comment));
+                break;
+            }
+
+            let c = rest.chars().next().unwrap_or_default();
+            let prev_is_ident = line[..i].chars().next_back().is_some_and(is_ident_char);
+            if syntax.quotes.contains(&c) {
+                if c == '\'' && syntax.lifetimes && !is_char_literal(rest) {
+                    i += 1;
+                    continue;
+                }
+                let end = string_end(line, i, c);
+                spans.push((i..end, TokenKind::String));
+                i = end;
+            } else if c.is_ascii_digit() && !prev_is_ident {
+                let len = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '.' || c == '_'))
+                    .unwrap_or(rest.len());
+                spans.push((i..i + len, TokenKind::Number));
+                i += len;
+            } else if is_ident_char(c) {
+                let len = rest.find(|c| !is_ident_char(c)).unwrap_or(rest.len());
+                let word = &rest[..len];
+                let kind = if syntax.keywords.split_whitespace().any(|k| k == word) {
+                    Some(TokenKind::Keyword)
+                } else if word.starts_with(|c: char| c.is_uppercase()) {
+                    Some(TokenKind::Type)
+                } else if rest[len..].trim_start().starts_with('(') {
+                    Some(TokenKind::Function)
+                } else {
+                    None
+                };
+                if let Some(kind) = kind {
+                    spans.push((i..i + len, kind));
+                }
+                i += len;
+            } else {
+                i += c.len_utf8();
+            }
+        }
+        spans
+    }
+}
+
+/// `'x'` or `'\n'`, as opposed to a lifetime like `'a`
+fn is_char_literal(rest: &str) -> bool {
+    let mut chars = rest.chars().skip(1);
+    match chars.next() {
+        Some('\\') => true,
+        Some(_) => chars.next() == Some('\''),
+        None => false,
+    }
+}
+
+/// End of the string opened by `quote` at `start`, past the closing quote
+/// or at the end of the line
+fn string_end(line: &str, start: usize, quote: char) -> usize {
+    let mut escaped = false;
+    for (at, c) in line[start + quote.len_utf8()..].char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return start + quote.len_utf8() + at + c.len_utf8();
+        }
+    }
+    line.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens<'a>(highlighter: &mut Highlighter, line: &'a str) -> Vec<(&'a str, TokenKind)> {
+        highlighter
+            .highlight(line)
+            .into_iter()
+            .map(|(range, kind)| (&line[range], kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_highlight_rust() {
+        let mut rust = Highlighter::new(syntax_for(Path::new("main.rs")));
+        assert_eq!(
+            tokens(&mut rust, "fn parse(s: &'a str) -> Option<u8> { // ok"),
+            vec![
+                ("fn", TokenKind::Keyword),
+                ("parse", TokenKind::Function),
+                ("Option", TokenKind::Type),
+                ("// ok", TokenKind::Comment),
+            ]
+        );
+        assert_eq!(
+            tokens(&mut rust, r#"let c = '\n'; let s = "a \" b"; x1 = 0x1f;"#),
+            vec![
+                ("let", TokenKind::Keyword),
+                (r"'\n'", TokenKind::String),
+                ("let", TokenKind::Keyword),
+                (r#""a \" b""#, TokenKind::String),
+                ("0x1f", TokenKind::Number),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_block_comments_span_lines() {
+        let mut c = Highlighter::new(syntax_for(Path::new("x.c")));
+        assert_eq!(
+            tokens(&mut c, "int x; /* start"),
+            vec![
+                ("int", TokenKind::Keyword),
+                ("/* start", TokenKind::Comment)
+            ]
+        );
+        assert_eq!(
+            tokens(&mut c, "still */ return"),
+            vec![
+                ("still */", TokenKind::Comment),
+                ("return", TokenKind::Keyword),
+            ]
+        );
+        assert!(tokens(&mut Highlighter::new(None), "fn x() {}").is_empty());
+        assert_eq!(
+            syntax_for(Path::new("/src/Makefile")).unwrap().name,
+            "Shell"
+        );
+    }
+}
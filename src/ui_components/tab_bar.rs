@@ -1,12 +1,22 @@
 use crate::assets::icons::icon;
 use crate::theme_engine::theme::ThemeContext;
+use crate::ui_components::drag_drop::{accept_dragged_files, DraggedFiles};
 use gpui::prelude::*;
 use gpui::*;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How long a drag has to rest on a tab before switching to it
+const DRAG_HOVER_DELAY: Duration = Duration::from_millis(500);
 
 pub struct TabBar {
     pub tabs: Vec<String>,
+    /// Folder each tab shows, where entries dropped on the tab go
+    pub folders: Vec<Option<PathBuf>>,
     pub active_index: usize,
     scroll_handle: ScrollHandle,
+    /// Tab a drag is resting on
+    drag_hover: Option<usize>,
 }
 
 pub enum TabEvent {
@@ -21,9 +31,42 @@ impl TabBar {
     pub fn new(tabs: Vec<String>, active_index: usize) -> Self {
         Self {
             tabs,
+            folders: Vec::new(),
             active_index,
             scroll_handle: ScrollHandle::new(),
+            drag_hover: None,
+        }
+    }
+
+    /// A drag moved on or off tab `ix`; resting on it switches to it
+    fn drag_hover(&mut self, ix: usize, hovered: bool, cx: &mut Context<Self>) {
+        if !hovered {
+            if self.drag_hover == Some(ix) {
+                self.drag_hover = None;
+            }
+            return;
         }
+        if self.drag_hover == Some(ix) || ix == self.active_index {
+            return;
+        }
+        self.drag_hover = Some(ix);
+        cx.spawn(move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let cx = cx.clone();
+            async move {
+                cx.background_executor().timer(DRAG_HOVER_DELAY).await;
+                let _ = cx.update(|cx| {
+                    let _ = this.update(cx, |bar, cx| {
+                        if bar.drag_hover == Some(ix) {
+                            bar.drag_hover = None;
+                            if cx.has_active_drag() {
+                                cx.emit(TabEvent::Activate(ix));
+                            }
+                        }
+                    });
+                });
+            }
+        })
+        .detach();
     }
 }
 
@@ -50,13 +93,12 @@ impl Render for TabBar {
                     .flex_grow()
                     .overflow_x_scroll()
                     .track_scroll(&self.scroll_handle)
-                    .child(
-                        div().flex().flex_row().gap_2().children(
-                            self.tabs.iter().enumerate().map(|(ix, title)| {
-                                render_tab(ix, title, ix == self.active_index, cx)
-                            }),
-                        ),
-                    ),
+                    .child(div().flex().flex_row().gap_2().children(
+                        self.tabs.iter().enumerate().map(|(ix, title)| {
+                            let folder = self.folders.get(ix).cloned().flatten();
+                            render_tab(ix, title, folder, ix == self.active_index, cx)
+                        }),
+                    )),
             )
             .child(
                 div()
@@ -76,7 +118,13 @@ impl Render for TabBar {
     }
 }
 
-fn render_tab(ix: usize, title: &str, active: bool, cx: &mut Context<TabBar>) -> impl IntoElement {
+fn render_tab(
+    ix: usize,
+    title: &str,
+    folder: Option<PathBuf>,
+    active: bool,
+    cx: &mut Context<TabBar>,
+) -> impl IntoElement {
     let theme = cx.theme();
     let entity = cx.entity().clone();
     let entity_close = entity.clone();
@@ -115,6 +163,16 @@ fn render_tab(ix: usize, title: &str, active: bool, cx: &mut Context<TabBar>) ->
         .on_click(move |_, _, cx| {
             entity.update(cx, |_, cx| cx.emit(TabEvent::Activate(ix)));
         })
+        .on_drag_move(
+            cx.listener(move |bar, event: &DragMoveEvent<DraggedFiles>, _, cx| {
+                let hovered = event.bounds.contains(&event.event.position);
+                bar.drag_hover(ix, hovered, cx);
+            }),
+        )
+        // Dropping on a tab moves the entries into its folder
+        .when_some(folder, |tab, folder| {
+            accept_dragged_files(tab, folder, theme.palette.primary_container.into())
+        })
         .child(
             div()
                 .mr_2()